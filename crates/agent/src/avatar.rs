@@ -1,12 +1,14 @@
 use actix::Message;
 use benthic_protocol::skeleton::{JointName, Skeleton};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 use std::time::SystemTime;
 
 use benthic_asset_pipeline::generated::DEFAULT_SKELETON;
 use glam::Vec3;
+use metaverse_messages::http::item::ItemData;
+use metaverse_messages::utils::bake::BakeType;
 use uuid::Uuid;
 
 // Definitions for an avatar object
@@ -35,6 +37,20 @@ pub struct Avatar {
     pub outfit_size: usize,
 
     pub last_update: SystemTime,
+    /// The visual params from the avatar's last appearance, which set the shape of its skeleton
+    #[serde(default)]
+    pub visual_params: Vec<u8>,
+    /// How far the avatar is moved up or down from where it would normally stand
+    #[serde(default)]
+    pub hover_height: Vec3,
+    /// The textures the server baked the avatar's appearance into, which mesh bodies are drawn
+    /// with
+    #[serde(default)]
+    pub baked_textures: BTreeMap<BakeType, Uuid>,
+    /// The bakes most recently requested from the server. These are only moved into
+    /// baked_textures once they are downloaded, and aren't requested again if they fail.
+    #[serde(default)]
+    pub requested_bakes: BTreeMap<BakeType, Uuid>,
 }
 
 impl Avatar {
@@ -53,6 +69,10 @@ impl Avatar {
             outfit_size: 0,
             fully_loaded: false,
             used_joints: BTreeSet::new(),
+            visual_params: Vec::new(),
+            hover_height: Vec3::ZERO,
+            baked_textures: BTreeMap::new(),
+            requested_bakes: BTreeMap::new(),
         }
     }
 }
@@ -63,10 +83,10 @@ impl Avatar {
 pub enum OutfitObject {
     /// A MeshObject, containing a json containing mesh data that can be rendered
     MeshObject(PathBuf),
-    /// A generic bodypart, part of the original SL spec
-    Bodypart,
-    /// A generic clothing object, part of the original SL spec
-    Clothing,
+    /// A bodypart of the system avatar, such as its shape, skin, hair or eyes
+    Bodypart(ItemData),
+    /// A clothing layer of the system avatar, such as a shirt or pants
+    Clothing(ItemData),
     /// Other
     Other,
 }
@@ -3,9 +3,10 @@ use std::io::Error;
 use crate::avatar::Avatar;
 use benthic_asset_pipeline::generated::DEFAULT_SKELETON;
 use benthic_protocol::skeleton::{Joint, JointName, Skeleton, Transform};
-use glam::{Mat4, Vec4};
+use glam::{Mat4, Vec3, Vec4};
 use indexmap::IndexMap;
 use metaverse_messages::http::mesh::Skin;
+use metaverse_messages::utils::visual_params::AvatarShape;
 use uuid::Uuid;
 
 /// This function takes an object's skeleton, and applies it to the agent's combined skeleton. The
@@ -26,6 +27,98 @@ pub fn update_global_avatar_skeleton(avatar: &mut Avatar, skeleton: &Skeleton) {
         }
     }
 }
+/// Apply the shape from an avatar's visual params to its combined skeleton.
+///
+/// Each joint's transform relative to its parent is scaled and moved by the shape, and the
+/// results are chained back down from the root, so a longer spine also raises the head. The
+/// hover height moves the whole skeleton. The shaped transforms outrank every other transform of
+/// the joint, so they are the ones used to build the avatar.
+pub fn apply_avatar_shape(avatar: &mut Avatar, shape: &AvatarShape, hover_height: Vec3) {
+    let name = "avatar_shape".to_string();
+    let mut globals: IndexMap<JointName, Mat4> = IndexMap::new();
+    let mut shaped = IndexMap::new();
+
+    // parents are visited before their children, so their shaped transforms are always known
+    let mut stack: Vec<JointName> = avatar.skeleton.root.clone();
+    while let Some(joint_name) = stack.pop() {
+        let Some(joint) = avatar.skeleton.joints.get(&joint_name) else {
+            continue;
+        };
+        stack.extend(joint.children.iter().copied());
+        let Some(transform) = joint.transforms.last() else {
+            continue;
+        };
+
+        let parent = joint
+            .parent
+            .and_then(|parent| avatar.skeleton.joints.get(&parent))
+            .and_then(|parent| Some((parent.transforms.last()?, *globals.get(&parent.name)?)));
+        // IBM-based local matrix, with roots relative to the world
+        let local = match parent {
+            Some((parent_transform, _)) => {
+                parent_transform.transform * transform.transform.inverse()
+            }
+            None => transform.transform.inverse(),
+        };
+
+        let deformation = shape.joint(&joint_name);
+        let (scale, rotation, translation) = local.to_scale_rotation_translation();
+        let local = Mat4::from_scale_rotation_translation(
+            scale * deformation.scale,
+            rotation,
+            translation + deformation.offset,
+        );
+        let global = match parent {
+            Some((_, parent_global)) => parent_global * local,
+            None => Mat4::from_translation(hover_height) * local,
+        };
+        globals.insert(joint_name, global);
+
+        if !avatar.used_joints.contains(&joint_name) {
+            continue;
+        }
+        let rank = joint
+            .transforms
+            .iter()
+            .chain(&joint.local_transforms)
+            .map(|transform| transform.rank)
+            .max()
+            .unwrap_or_default()
+            + 1;
+        let transform = Transform {
+            name: name.clone(),
+            id: avatar.agent_id,
+            transform: global.inverse(),
+            rank,
+        };
+        // root joints store their own IBM as their local transform
+        let local_transform = Transform {
+            transform: if parent.is_some() {
+                local
+            } else {
+                global.inverse()
+            },
+            ..transform.clone()
+        };
+        shaped.insert(
+            joint_name,
+            Joint {
+                name: joint_name,
+                parent: joint.parent,
+                children: joint.children.clone(),
+                transforms: vec![transform],
+                local_transforms: vec![local_transform],
+            },
+        );
+    }
+
+    let skeleton = Skeleton {
+        root: avatar.skeleton.root.clone(),
+        joints: shaped,
+    };
+    update_global_avatar_skeleton(avatar, &skeleton);
+}
+
 /// Determine the local and global joint transforms for a skinned SceneObject.
 pub fn create_skeleton(object_name: String, id: Uuid, skin: &Skin) -> Result<Skeleton, Error> {
     // if the object has a mesh, handle the skeleton
@@ -0,0 +1,113 @@
+use benthic_protocol::skeleton::{Joint, JointName, Skeleton, Transform};
+use glam::{Mat4, Vec3};
+use indexmap::IndexMap;
+use metaverse_agent::{avatar::Avatar, skeleton::apply_avatar_shape};
+use metaverse_messages::utils::visual_params::{AvatarShape, JointDeformation};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// An avatar whose skeleton is a spine of mPelvis, mTorso, mNeck and mHead standing at the given
+/// heights, each the child of the one before
+fn avatar(heights: [f32; 4]) -> Avatar {
+    let spine = [
+        JointName::MPelvis,
+        JointName::MTorso,
+        JointName::MNeck,
+        JointName::MHead,
+    ];
+    let mut joints = IndexMap::new();
+    for (i, name) in spine.iter().enumerate() {
+        joints.insert(
+            *name,
+            Joint {
+                name: *name,
+                parent: i.checked_sub(1).map(|parent| spine[parent]),
+                children: spine.get(i + 1).copied().into_iter().collect(),
+                // inverse bind matrices
+                transforms: vec![Transform {
+                    name: "default".to_string(),
+                    id: Uuid::nil(),
+                    transform: Mat4::from_translation(Vec3::new(0.0, 0.0, -heights[i])),
+                    rank: 0,
+                }],
+                local_transforms: Vec::new(),
+            },
+        );
+    }
+    let mut avatar = Avatar::new(Uuid::from_u128(1), Vec3::ZERO);
+    avatar.skeleton = Skeleton {
+        root: vec![JointName::MPelvis],
+        joints,
+    };
+    avatar.used_joints = spine.into_iter().collect();
+    avatar
+}
+
+/// The world transform of a joint, from the highest ranked inverse bind matrix
+fn global(avatar: &Avatar, joint: JointName) -> (Vec3, Vec3) {
+    let transform = avatar.skeleton.joints[&joint].transforms.last().unwrap();
+    let (scale, _, translation) = transform
+        .transform
+        .inverse()
+        .to_scale_rotation_translation();
+    (scale, translation)
+}
+
+fn assert_near(actual: (Vec3, Vec3), expected: (Vec3, Vec3)) {
+    assert!(
+        actual.0.abs_diff_eq(expected.0, 1e-5) && actual.1.abs_diff_eq(expected.1, 1e-5),
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn test_unshaped_avatar_is_unchanged() {
+    let mut avatar = avatar([1.0, 1.5, 2.0, 2.2]);
+    apply_avatar_shape(&mut avatar, &AvatarShape::default(), Vec3::ZERO);
+    assert_near(
+        global(&avatar, JointName::MNeck),
+        (Vec3::ONE, Vec3::new(0.0, 0.0, 2.0)),
+    );
+    // the shaped transforms outrank the default ones
+    let neck = &avatar.skeleton.joints[&JointName::MNeck];
+    assert_eq!(neck.transforms.last().unwrap().name, "avatar_shape");
+    assert_eq!(neck.transforms.last().unwrap().rank, 1);
+}
+
+#[test]
+fn test_joint_offset_and_scale() {
+    let mut avatar = avatar([1.0, 1.5, 2.0, 2.2]);
+    let shape = AvatarShape {
+        joints: BTreeMap::from([(
+            JointName::MTorso,
+            JointDeformation {
+                scale: Vec3::splat(2.0),
+                offset: Vec3::new(0.0, 0.0, 0.1),
+            },
+        )]),
+        ..Default::default()
+    };
+    apply_avatar_shape(&mut avatar, &shape, Vec3::new(0.0, 0.0, 0.2));
+
+    // the hover height moves the root
+    assert_near(
+        global(&avatar, JointName::MPelvis),
+        (Vec3::ONE, Vec3::new(0.0, 0.0, 1.2)),
+    );
+    // the torso is moved from the pelvis by its offset, and scaled
+    assert_near(
+        global(&avatar, JointName::MTorso),
+        (Vec3::splat(2.0), Vec3::new(0.0, 0.0, 1.8)),
+    );
+    // its children inherit the scale, so they are pushed further up the spine
+    assert_near(
+        global(&avatar, JointName::MNeck),
+        (Vec3::splat(2.0), Vec3::new(0.0, 0.0, 2.8)),
+    );
+    assert_near(
+        global(&avatar, JointName::MHead),
+        (Vec3::splat(2.0), Vec3::new(0.0, 0.0, 3.2)),
+    );
+}
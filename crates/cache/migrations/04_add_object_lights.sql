@@ -0,0 +1,12 @@
+ALTER TABLE object_updates ADD COLUMN light_r          INTEGER;
+ALTER TABLE object_updates ADD COLUMN light_g          INTEGER;
+ALTER TABLE object_updates ADD COLUMN light_b          INTEGER;
+ALTER TABLE object_updates ADD COLUMN light_intensity  REAL;
+ALTER TABLE object_updates ADD COLUMN light_radius     REAL;
+ALTER TABLE object_updates ADD COLUMN light_cutoff     REAL;
+ALTER TABLE object_updates ADD COLUMN light_falloff    REAL;
+
+ALTER TABLE object_updates ADD COLUMN projector_texture  TEXT;
+ALTER TABLE object_updates ADD COLUMN projector_fov      REAL;
+ALTER TABLE object_updates ADD COLUMN projector_focus    REAL;
+ALTER TABLE object_updates ADD COLUMN projector_ambiance REAL;
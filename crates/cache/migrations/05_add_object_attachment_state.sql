@@ -0,0 +1 @@
+ALTER TABLE object_updates ADD COLUMN attachment_state INTEGER;
@@ -302,7 +302,9 @@ pub async fn sqlite_get_avatar(
     .bind(agent_id.to_string())
     .fetch_one(pool)
     .await?;
-    let data: String = agent_row.get("data");
+    // agents are inserted before their avatar has been built
+    let data: Option<String> = agent_row.get("data");
+    let data = data.ok_or_else(|| InventoryError::CacheMiss("Avatar not found in cache".to_string()))?;
     let avatar: Avatar = serde_json::from_str(&data)?;
     Ok(avatar)
 }
@@ -2,11 +2,13 @@ use std::{collections::HashSet, path::PathBuf};
 
 use crate::errors::InventoryError;
 use glam::{Quat, Vec3};
+use metaverse_messages::udp::object::object_update::{LightData, ProjectionData};
 use metaverse_messages::utils::object_types::ObjectType;
+use rgb::Rgb;
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GeneratorObject {
     pub full_id: Uuid,
     pub local_id: u32,
@@ -27,6 +29,10 @@ pub struct ObjectCache {
     pub position: Vec3,
     pub rotation: Quat,
     pub scale: Vec3,
+    pub light: Option<LightData>,
+    pub projection: Option<ProjectionData>,
+    /// The state byte of attachments, which holds the point they are attached to
+    pub attachment_state: Option<u8>,
 }
 
 fn vec3_from_row(
@@ -57,6 +63,22 @@ fn quat_from_row(
     ))
 }
 
+fn generator_object_from_row(
+    row: &sqlx::sqlite::SqliteRow,
+) -> Result<GeneratorObject, InventoryError> {
+    Ok(GeneratorObject {
+        full_id: {
+            let id_str: String = row.try_get("full_id")?;
+            Uuid::parse_str(&id_str)?
+        },
+        local_id: row.try_get("id")?,
+        parent_id: row.try_get("parent")?,
+        position: vec3_from_row(row, "pos_x", "pos_y", "pos_z")?,
+        scale: vec3_from_row(row, "scale_x", "scale_y", "scale_z")?,
+        rotation: quat_from_row(row, "rot_x", "rot_y", "rot_z", "rot_w")?,
+    })
+}
+
 pub async fn sqlite_check_cache(
     pool: &SqlitePool,
     id: u32,
@@ -93,17 +115,7 @@ pub async fn sqlite_check_cache(
     let glb: Option<String> = row.try_get("glb")?;
     let glb_path = glb.as_ref().map(PathBuf::from);
 
-    let generator = GeneratorObject {
-        full_id: {
-            let id_str: String = row.try_get("full_id")?;
-            Uuid::parse_str(&id_str)?
-        },
-        local_id: row.try_get("id")?,
-        parent_id: row.try_get("parent")?,
-        position: vec3_from_row(&row, "pos_x", "pos_y", "pos_z")?,
-        scale: vec3_from_row(&row, "scale_x", "scale_y", "scale_z")?,
-        rotation: quat_from_row(&row, "rot_x", "rot_y", "rot_z", "rot_w")?,
-    };
+    let generator = generator_object_from_row(&row)?;
 
     Ok((
         asset_id,
@@ -162,15 +174,24 @@ pub async fn sqlite_insert_object_update(
 ) -> Result<(), InventoryError> {
     let parent_id = object.parent_id.unwrap_or(0);
     let rotation = object.rotation.normalize();
+    let light = object.light.as_ref();
+    let projection = object.projection.as_ref();
     sqlx::query(
         r#"
         INSERT INTO object_updates (
             id, full_id, crc, region_id, parent, pcode,
             pos_x, pos_y, pos_z,
             rot_x, rot_y, rot_z, rot_w,
-            scale_x, scale_y, scale_z
+            scale_x, scale_y, scale_z,
+            light_r, light_g, light_b, light_intensity,
+            light_radius, light_cutoff, light_falloff,
+            projector_texture, projector_fov, projector_focus, projector_ambiance,
+            attachment_state
+        )
+        VALUES (
+            ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+            ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
         )
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ON CONFLICT(full_id) DO UPDATE SET
             id      = excluded.id,
             parent  = excluded.parent,
@@ -189,7 +210,22 @@ pub async fn sqlite_insert_object_update(
 
             scale_x = excluded.scale_x,
             scale_y = excluded.scale_y,
-            scale_z = excluded.scale_z
+            scale_z = excluded.scale_z,
+
+            light_r         = excluded.light_r,
+            light_g         = excluded.light_g,
+            light_b         = excluded.light_b,
+            light_intensity = excluded.light_intensity,
+            light_radius    = excluded.light_radius,
+            light_cutoff    = excluded.light_cutoff,
+            light_falloff   = excluded.light_falloff,
+
+            projector_texture  = excluded.projector_texture,
+            projector_fov      = excluded.projector_fov,
+            projector_focus    = excluded.projector_focus,
+            projector_ambiance = excluded.projector_ambiance,
+
+            attachment_state = excluded.attachment_state
         "#,
     )
     .bind(object.local_id as i64)
@@ -208,12 +244,102 @@ pub async fn sqlite_insert_object_update(
     .bind(object.scale.x)
     .bind(object.scale.y)
     .bind(object.scale.z)
+    .bind(light.map(|light| light.color.r))
+    .bind(light.map(|light| light.color.g))
+    .bind(light.map(|light| light.color.b))
+    .bind(light.map(|light| light.intensity))
+    .bind(light.map(|light| light.radius))
+    .bind(light.map(|light| light.cutoff))
+    .bind(light.map(|light| light.falloff))
+    .bind(projection.map(|projection| projection.texture_id.to_string()))
+    .bind(projection.map(|projection| projection.fov))
+    .bind(projection.map(|projection| projection.focus))
+    .bind(projection.map(|projection| projection.ambiance))
+    .bind(object.attachment_state)
     .execute(pool)
     .await?;
 
     Ok(())
 }
 
+/// Get the cached light of an object by its local ID in a region. Projection data is only
+/// returned for objects that are lights.
+pub async fn sqlite_get_object_light(
+    pool: &SqlitePool,
+    object_id: u32,
+    region_id: String,
+) -> Result<Option<(LightData, Option<ProjectionData>)>, InventoryError> {
+    let row = sqlx::query(
+        r#"
+        SELECT
+            light_r, light_g, light_b, light_intensity,
+            light_radius, light_cutoff, light_falloff,
+            projector_texture, projector_fov, projector_focus, projector_ambiance
+        FROM object_updates
+        WHERE id = ?
+          AND region_id = ?
+        "#,
+    )
+    .bind(object_id as i64)
+    .bind(region_id)
+    .fetch_optional(pool)
+    .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let Some(intensity) = row.try_get::<Option<f32>, &str>("light_intensity")? else {
+        return Ok(None);
+    };
+    let light = LightData {
+        color: Rgb::new(
+            row.try_get("light_r")?,
+            row.try_get("light_g")?,
+            row.try_get("light_b")?,
+        ),
+        intensity,
+        radius: row.try_get("light_radius")?,
+        cutoff: row.try_get("light_cutoff")?,
+        falloff: row.try_get("light_falloff")?,
+    };
+
+    let projection = match row.try_get::<Option<String>, &str>("projector_texture")? {
+        Some(texture_id) => Some(ProjectionData {
+            texture_id: Uuid::parse_str(&texture_id)?,
+            fov: row.try_get("projector_fov")?,
+            focus: row.try_get("projector_focus")?,
+            ambiance: row.try_get("projector_ambiance")?,
+        }),
+        None => None,
+    };
+    Ok(Some((light, projection)))
+}
+
+/// Get the cached state byte of an object by its local ID in a region, if the object is an
+/// attachment
+pub async fn sqlite_get_attachment_state(
+    pool: &SqlitePool,
+    object_id: u32,
+    region_id: String,
+) -> Result<Option<u8>, InventoryError> {
+    let row = sqlx::query(
+        r#"
+        SELECT attachment_state
+        FROM object_updates
+        WHERE id = ?
+          AND region_id = ?
+        "#,
+    )
+    .bind(object_id as i64)
+    .bind(region_id)
+    .fetch_optional(pool)
+    .await?;
+    match row {
+        Some(row) => Ok(row.try_get("attachment_state")?),
+        None => Ok(None),
+    }
+}
+
 /// Get object update by id
 pub async fn sqlite_get_parent(
     pool: &SqlitePool,
@@ -227,32 +353,38 @@ pub async fn sqlite_get_parent(
     Ok(row.try_get("parent")?)
 }
 
-pub async fn sqlite_get_object_scale_rotation_position(
+/// Get the cached transform and parent of an object by its local ID in a region
+pub async fn sqlite_get_generator_object(
     pool: &SqlitePool,
     object_id: u32,
-) -> Result<(Vec3, Quat, Vec3), InventoryError> {
+    region_id: String,
+) -> Result<GeneratorObject, InventoryError> {
     let row = sqlx::query(
         r#"
         SELECT
-            pos_x, pos_y, pos_z,
+            full_id,
+            id,
+            parent,
+            scale_x, scale_y, scale_z,
             rot_x, rot_y, rot_z, rot_w,
-            scale_x, scale_y, scale_z
+            pos_x, pos_y, pos_z
         FROM object_updates
         WHERE id = ?
+          AND region_id = ?
         "#,
     )
     .bind(object_id as i64)
+    .bind(region_id)
     .fetch_one(pool)
     .await?;
-    let position = vec3_from_row(&row, "pos_x", "pos_y", "pos_z")?;
-    let scale = vec3_from_row(&row, "scale_x", "scale_y", "scale_z")?;
-    let rotation = quat_from_row(&row, "rot_x", "rot_y", "rot_z", "rot_w")?;
-    Ok((scale, rotation, position))
+
+    generator_object_from_row(&row)
 }
 
 pub async fn set_object_transform_by_id(
     pool: &SqlitePool,
     object_id: u32,
+    region_id: String,
     position: Vec3,
     rotation: Quat,
     scale: Vec3,
@@ -277,6 +409,7 @@ pub async fn set_object_transform_by_id(
             scale_y = ?,
             scale_z = ?
         WHERE id = ?
+          AND region_id = ?
         "#,
     )
     // position
@@ -294,12 +427,60 @@ pub async fn set_object_transform_by_id(
     .bind(scale.z)
     // id
     .bind(object_id as i64)
+    .bind(region_id)
     .execute(pool)
     .await?;
 
     Ok(())
 }
 
+/// Delete objects and all of their children from the cache.
+/// Returns the local ID and full ID of every deleted object.
+pub async fn sqlite_delete_objects(
+    pool: &SqlitePool,
+    object_ids: &[u32],
+    region_id: String,
+) -> Result<Vec<(u32, Uuid)>, InventoryError> {
+    if object_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let placeholders = vec!["?"; object_ids.len()].join(",");
+    let query = format!(
+        r#"
+        WITH RECURSIVE linkset(id) AS (
+            SELECT id FROM object_updates
+            WHERE region_id = ? AND id IN ({})
+            UNION
+            SELECT object_updates.id FROM object_updates
+            JOIN linkset ON object_updates.parent = linkset.id
+            WHERE object_updates.region_id = ?
+        )
+        DELETE FROM object_updates
+        WHERE region_id = ? AND id IN linkset
+        RETURNING id, full_id
+        "#,
+        placeholders
+    );
+
+    let mut q = sqlx::query(&query).bind(region_id.clone());
+    for id in object_ids {
+        q = q.bind(*id as i64);
+    }
+    let rows = q
+        .bind(region_id.clone())
+        .bind(region_id)
+        .fetch_all(pool)
+        .await?;
+
+    let mut deleted = Vec::with_capacity(rows.len());
+    for row in rows {
+        let full_id: String = row.try_get("full_id")?;
+        deleted.push((row.try_get("id")?, Uuid::parse_str(&full_id)?));
+    }
+    Ok(deleted)
+}
+
 pub async fn get_missing_object_updates(
     pool: &SqlitePool,
     ids: &[Uuid],
@@ -0,0 +1,41 @@
+use glam::Vec3;
+use metaverse_agent::avatar::{Avatar, OutfitObject};
+use metaverse_cache::agent::{sqlite_get_avatar, sqlite_insert_avatar, sqlite_update_avatar};
+use metaverse_cache::errors::InventoryError;
+use metaverse_cache::initialize_sqlite::init_sqlite;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_avatar_cache() {
+    let temp_dir = TempDir::new().unwrap();
+    let pool = init_sqlite(temp_dir.path().join("cache.db")).await.unwrap();
+    let agent_id = Uuid::new_v4();
+
+    // avatars are inserted when they arrive, and only have data once they have been built
+    sqlite_insert_avatar(&pool, agent_id, 0).await.unwrap();
+    assert!(matches!(
+        sqlite_get_avatar(&pool, agent_id).await,
+        Err(InventoryError::CacheMiss(_))
+    ));
+
+    let mut avatar = Avatar::new(agent_id, Vec3::new(128.0, 128.0, 20.0));
+    avatar.path = Some(PathBuf::from("avatar_high.glb"));
+    avatar
+        .items
+        .push(OutfitObject::MeshObject(PathBuf::from("body.json")));
+    sqlite_update_avatar(&pool, avatar).await.unwrap();
+
+    let cached = sqlite_get_avatar(&pool, agent_id).await.unwrap();
+    assert_eq!(cached.agent_id, agent_id);
+    assert_eq!(cached.path, Some(PathBuf::from("avatar_high.glb")));
+    assert!(matches!(
+        cached.items.as_slice(),
+        [OutfitObject::MeshObject(path)] if path == &PathBuf::from("body.json")
+    ));
+
+    // inserting an agent that is already cached keeps its data
+    sqlite_insert_avatar(&pool, agent_id, 1).await.unwrap();
+    assert!(sqlite_get_avatar(&pool, agent_id).await.is_ok());
+}
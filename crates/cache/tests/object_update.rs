@@ -0,0 +1,251 @@
+use glam::{Quat, Vec3};
+use metaverse_cache::initialize_sqlite::init_sqlite;
+use metaverse_cache::object_update::{
+    ObjectCache, set_object_transform_by_id, sqlite_delete_objects, sqlite_get_attachment_state,
+    sqlite_get_generator_object, sqlite_get_object_light, sqlite_insert_object_update,
+};
+use metaverse_messages::udp::object::object_update::{LightData, ProjectionData};
+use metaverse_messages::utils::object_types::ObjectType;
+use rgb::Rgb;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+fn object(
+    local_id: u32,
+    light: Option<LightData>,
+    projection: Option<ProjectionData>,
+) -> ObjectCache {
+    ObjectCache {
+        full_id: Uuid::new_v4(),
+        local_id,
+        crc: 1,
+        region_id: "region".to_string(),
+        object_type: ObjectType::Prim,
+        parent_id: None,
+        position: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+        light,
+        projection,
+        attachment_state: None,
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_object_lights() {
+    let temp_dir = TempDir::new().unwrap();
+    let pool = init_sqlite(temp_dir.path().join("cache.db")).await.unwrap();
+
+    let light = LightData {
+        color: Rgb::new(255, 128, 0),
+        intensity: 0.5,
+        radius: 5.0,
+        cutoff: 0.0,
+        falloff: 1.5,
+    };
+    let projection = ProjectionData {
+        texture_id: Uuid::new_v4(),
+        fov: 1.0,
+        focus: -2.0,
+        ambiance: 0.25,
+    };
+    sqlite_insert_object_update(&pool, object(1, Some(light.clone()), None))
+        .await
+        .unwrap();
+    sqlite_insert_object_update(
+        &pool,
+        object(2, Some(light.clone()), Some(projection.clone())),
+    )
+    .await
+    .unwrap();
+    sqlite_insert_object_update(&pool, object(3, None, Some(projection.clone())))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        sqlite_get_object_light(&pool, 1, "region".to_string())
+            .await
+            .unwrap(),
+        Some((light.clone(), None))
+    );
+    assert_eq!(
+        sqlite_get_object_light(&pool, 2, "region".to_string())
+            .await
+            .unwrap(),
+        Some((light, Some(projection)))
+    );
+    // projectors only shine when the object is also a light
+    assert_eq!(
+        sqlite_get_object_light(&pool, 3, "region".to_string())
+            .await
+            .unwrap(),
+        None
+    );
+    // lights are looked up in the object's own region, and missing objects have none
+    assert_eq!(
+        sqlite_get_object_light(&pool, 1, "other region".to_string())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        sqlite_get_object_light(&pool, 4, "region".to_string())
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_attachment_state() {
+    let temp_dir = TempDir::new().unwrap();
+    let pool = init_sqlite(temp_dir.path().join("cache.db")).await.unwrap();
+
+    let mut attachment = object(1, None, None);
+    attachment.attachment_state = Some(0x52);
+    let full_id = attachment.full_id;
+    sqlite_insert_object_update(&pool, attachment)
+        .await
+        .unwrap();
+    sqlite_insert_object_update(&pool, object(2, None, None))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        sqlite_get_attachment_state(&pool, 1, "region".to_string())
+            .await
+            .unwrap(),
+        Some(0x52)
+    );
+    assert_eq!(
+        sqlite_get_attachment_state(&pool, 2, "region".to_string())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        sqlite_get_attachment_state(&pool, 1, "other region".to_string())
+            .await
+            .unwrap(),
+        None
+    );
+
+    // detaching the object clears its state
+    let mut detached = object(1, None, None);
+    detached.full_id = full_id;
+    sqlite_insert_object_update(&pool, detached).await.unwrap();
+    assert_eq!(
+        sqlite_get_attachment_state(&pool, 1, "region".to_string())
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_local_ids_are_per_region() {
+    let temp_dir = TempDir::new().unwrap();
+    let pool = init_sqlite(temp_dir.path().join("cache.db")).await.unwrap();
+
+    // local IDs are only unique within a region, so two regions can use the same one
+    let mut first = object(7, None, None);
+    first.position = Vec3::X;
+    let mut second = object(7, None, None);
+    second.region_id = "other region".to_string();
+    second.position = Vec3::Y;
+    second.parent_id = Some(3);
+    let (first_id, second_id) = (first.full_id, second.full_id);
+    sqlite_insert_object_update(&pool, first).await.unwrap();
+    sqlite_insert_object_update(&pool, second).await.unwrap();
+
+    let cached = sqlite_get_generator_object(&pool, 7, "region".to_string())
+        .await
+        .unwrap();
+    assert_eq!(cached.full_id, first_id);
+    assert_eq!(cached.position, Vec3::X);
+    let cached = sqlite_get_generator_object(&pool, 7, "other region".to_string())
+        .await
+        .unwrap();
+    assert_eq!(cached.full_id, second_id);
+    assert_eq!(cached.parent_id, Some(3));
+    assert!(
+        sqlite_get_generator_object(&pool, 7, "unknown region".to_string())
+            .await
+            .is_err()
+    );
+
+    // moving the object in one region leaves the other in place
+    set_object_transform_by_id(
+        &pool,
+        7,
+        "other region".to_string(),
+        Vec3::Z,
+        Quat::IDENTITY,
+        Vec3::ONE,
+    )
+    .await
+    .unwrap();
+    let cached = sqlite_get_generator_object(&pool, 7, "region".to_string())
+        .await
+        .unwrap();
+    assert_eq!(cached.position, Vec3::X);
+    let cached = sqlite_get_generator_object(&pool, 7, "other region".to_string())
+        .await
+        .unwrap();
+    assert_eq!(cached.position, Vec3::Z);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_delete_objects() {
+    let temp_dir = TempDir::new().unwrap();
+    let pool = init_sqlite(temp_dir.path().join("cache.db")).await.unwrap();
+
+    // a root with a child and grandchild, an unrelated object, and a copy of the linkset in
+    // another region that uses the same local IDs
+    let mut killed = Vec::new();
+    for region_id in ["region", "other region"] {
+        for (local_id, parent_id) in [(1, None), (2, Some(1)), (3, Some(2)), (4, None)] {
+            let mut object = object(local_id, None, None);
+            object.region_id = region_id.to_string();
+            object.parent_id = parent_id;
+            if region_id == "region" && local_id != 4 {
+                killed.push((local_id, object.full_id));
+            }
+            sqlite_insert_object_update(&pool, object).await.unwrap();
+        }
+    }
+
+    let mut deleted = sqlite_delete_objects(&pool, &[1], "region".to_string())
+        .await
+        .unwrap();
+    deleted.sort();
+    killed.sort();
+    assert_eq!(deleted, killed);
+
+    for local_id in [1, 2, 3] {
+        assert!(
+            sqlite_get_generator_object(&pool, local_id, "region".to_string())
+                .await
+                .is_err()
+        );
+    }
+    assert!(
+        sqlite_get_generator_object(&pool, 4, "region".to_string())
+            .await
+            .is_ok()
+    );
+    for local_id in [1, 2, 3, 4] {
+        assert!(
+            sqlite_get_generator_object(&pool, local_id, "other region".to_string())
+                .await
+                .is_ok()
+        );
+    }
+
+    assert!(
+        sqlite_delete_objects(&pool, &[], "region".to_string())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,94 @@
+use metaverse_messages::utils::keyframe_motion::KeyframeMotion;
+use serde::{Deserialize, Serialize};
+
+/// An animation in the JSON format the default animations are stored in, which
+/// generate_gltf_animation builds the glTF of an animation from.
+///
+/// Each joint has a channel for its rotation and one for its translation. A channel holds the
+/// time of each key and the value at that time as separate lists, the same way glTF animation
+/// samplers store their input and output.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnimationJson {
+    /// The name of the animation
+    pub name: String,
+    /// The length of the animation in seconds
+    pub duration: f32,
+    /// If the animation loops
+    pub looping: bool,
+    /// The time in seconds the animation loops back to
+    pub loop_start: f32,
+    /// The time in seconds the animation loops from
+    pub loop_end: f32,
+    /// The priority the animation plays at
+    pub priority: i32,
+    /// The animated joints
+    pub joints: Vec<JointAnimation>,
+}
+
+/// The keys of one animated joint
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JointAnimation {
+    /// The name of the joint, such as mPelvis
+    pub name: String,
+    /// The priority of the joint
+    pub priority: i32,
+    /// The rotation of the joint, as quaternions in x, y, z, w order
+    pub rotation: Channel<[f32; 4]>,
+    /// The offset of the joint from its rest position
+    pub translation: Channel<[f32; 3]>,
+}
+
+/// The keys of one property of a joint
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Channel<T> {
+    /// The time of each key in seconds
+    pub times: Vec<f32>,
+    /// The value of each key
+    pub values: Vec<T>,
+}
+
+impl AnimationJson {
+    /// Convert a custom animation downloaded from the server. Joints without keys are left out,
+    /// and joints that play at the animation's priority are given it.
+    pub fn from_keyframe_motion(name: &str, motion: &KeyframeMotion) -> Self {
+        let joints = motion
+            .joints
+            .iter()
+            .filter(|joint| !joint.rotation_keys.is_empty() || !joint.position_keys.is_empty())
+            .map(|joint| JointAnimation {
+                name: joint.name.clone(),
+                priority: if joint.priority < 0 {
+                    motion.priority
+                } else {
+                    joint.priority
+                },
+                rotation: Channel {
+                    times: joint.rotation_keys.iter().map(|key| key.time).collect(),
+                    values: joint
+                        .rotation_keys
+                        .iter()
+                        .map(|key| key.rotation.to_array())
+                        .collect(),
+                },
+                translation: Channel {
+                    times: joint.position_keys.iter().map(|key| key.time).collect(),
+                    values: joint
+                        .position_keys
+                        .iter()
+                        .map(|key| key.position.to_array())
+                        .collect(),
+                },
+            })
+            .collect();
+
+        AnimationJson {
+            name: name.to_string(),
+            duration: motion.duration,
+            looping: motion.looping,
+            loop_start: motion.loop_in_point,
+            loop_end: motion.loop_out_point,
+            priority: motion.priority,
+            joints,
+        }
+    }
+}
@@ -1,14 +1,20 @@
 use super::session::Mailbox;
+use crate::animation::AnimationJson;
 use crate::initialize::{create_agent_animation_dir, create_sub_agent_dir};
-use crate::session::SendUIMessage;
+use crate::materials::ObjectMaterials;
+use crate::session::{OutgoingPacket, SendUIMessage};
+use crate::system_avatar::{
+    bake_textures, bakes_on_mesh_textures, build_system_avatar, is_system_avatar_json,
+    server_bake_path, set_bakes_on_mesh, system_avatar_json,
+};
 use crate::transport::http_handler::{
-    download_asset, download_object, download_scene_group, download_texture,
+    download_asset, download_object, download_scene_group, download_texture, download_wearable,
 };
 use actix::{AsyncContext, Handler, Message, WrapFuture};
 use benthic_asset_pipeline::generated_asset_path;
 use benthic_protocol::default_animations::DefaultAnimation;
 use benthic_protocol::messages::ui::camera_position::CameraPosition;
-use benthic_protocol::messages::ui::mesh_update::{MeshType, MeshUpdate};
+use benthic_protocol::messages::ui::mesh_update::{LevelOfDetail, MeshType, MeshUpdate};
 use benthic_protocol::messages::ui::play_animation::PlayAnimation;
 use benthic_protocol::messages::ui::ui_messages::UIMessage;
 use benthic_protocol::render_data::{AvatarObject, RenderObject};
@@ -17,7 +23,7 @@ use glam::{Quat, Vec3};
 use log::{error, warn};
 use metaverse_agent::avatar::Avatar;
 use metaverse_agent::avatar::OutfitObject;
-use metaverse_agent::skeleton::update_global_avatar_skeleton;
+use metaverse_agent::skeleton::{apply_avatar_shape, update_global_avatar_skeleton};
 use metaverse_cache::agent::sqlite_get_current_outfit;
 use metaverse_cache::agent::{sqlite_get_avatar, sqlite_get_current_avatar_version};
 use metaverse_cache::agent::{sqlite_get_current_outfit_version, sqlite_insert_avatar};
@@ -25,27 +31,42 @@ use metaverse_cache::agent::{sqlite_update_avatar, sqlite_update_outfit_item_jso
 use metaverse_mesh::animation::generate::generate_gltf_animation;
 use metaverse_mesh::mesh::generate::generate_skinned_mesh;
 use metaverse_messages::http::capabilities::Capability;
+use metaverse_messages::http::item::ItemData;
+use metaverse_messages::packet::packet_protocol::Packet;
 use metaverse_messages::udp::agent::avatar_animation::AvatarAnimation;
 use metaverse_messages::udp::agent::avatar_appearance::AvatarAppearance;
+use metaverse_messages::udp::object::request_multiple_objects::{
+    CacheMissType, RequestMultipleObjects,
+};
+use metaverse_messages::utils::bake::BakeType;
+use metaverse_messages::utils::keyframe_motion::KeyframeMotion;
 use metaverse_messages::utils::object_types::ObjectType;
+use metaverse_messages::utils::visual_params::AvatarLad;
 use serde::Serialize;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::{self, File};
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::io::{self, Write};
 use std::path::PathBuf;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use uuid::Uuid;
 
+/// How long to wait after the last change to the meshes another agent wears before their avatar is
+/// rebuilt
+pub const WORN_MESH_SETTLE_TIME: Duration = Duration::from_secs(2);
+
 /// Requests Agent data from the ViewerAsset capability endpoint
 ///
-/// These are requested from inventory objects, and not ObjectUpdate packets.
+/// These are requested from inventory objects, and not ObjectUpdate packets. Bodyparts and
+/// clothing are downloaded as wearables, and everything else as objects.
 ///
 /// # Cause
 /// - [`HandleNewAvatar`]
 ///
 /// # Effects
 ///  - Dispatches an [`AddObjectToAvatar`] message on successful download
+///  - Dispatches an [`AddObjectToAvatar`] message for wearables that fail to download, so the
+///    outfit still finishes loading
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct DownloadAgentAsset {
@@ -67,7 +88,9 @@ pub struct DownloadAgentAsset {
 /// - [`AddObjectToAvatar`]
 ///
 /// # Effects
-/// - Dispatches a [`FinalizeAvatar`] message if the avatar's outfit has all items
+/// - If the avatar's outfit has all items
+///    - Dispatches a [`BakeSystemAvatar`] message if the avatar wears system bodyparts
+///    - else dispatches a [`FinalizeAvatar`] message
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct AddObjectToAvatar {
@@ -77,6 +100,48 @@ pub struct AddObjectToAvatar {
     pub object: OutfitObject,
 }
 
+/// Message to build the system avatar from the bodyparts and clothing an avatar wears
+///
+/// The textures of the wearables are baked into the head, upper body, lower body, eyes, skirt and
+/// hair bakes, and the meshes of the default avatar are morphed into the avatar's shape. Avatars
+/// without visual params are shaped by the params of their wearables. Parts the server has
+/// already baked are drawn with the server's bake instead.
+///
+/// # Cause
+/// - [`AddObjectToAvatar`] when the outfit of an avatar wearing system bodyparts is loaded
+/// - [`HandleNewAvatarAppearance`] if the avatar's shape changed after it was built
+/// - [`LoadFromCache`] if the avatar's shape changed since it was cached
+/// - [`SetServerBakes`]
+///
+/// # Effects
+/// - Dispatches an [`AddSystemMeshes`] message once the system avatar is built
+/// - Dispatches a [`FinalizeAvatar`] message if the system avatar can't be built
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct BakeSystemAvatar {
+    /// ID of the agent to bake
+    pub agent_id: Uuid,
+}
+
+/// Message to add the meshes of the system avatar to an avatar's outfit
+///
+/// The meshes replace the ones from an earlier bake, and their skeletons are added to the
+/// avatar's skeleton.
+///
+/// # Cause
+/// - [`BakeSystemAvatar`]
+///
+/// # Effects
+/// - Dispatches a [`FinalizeAvatar`] message
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct AddSystemMeshes {
+    /// ID of the agent the meshes belong to
+    pub agent_id: Uuid,
+    /// Path to the JSON of the meshes
+    pub path: PathBuf,
+}
+
 /// Message to set the outfit size of the avatar
 ///
 /// This must be set in order to trigger a render. Without this, the avatar doesn't know when the
@@ -104,13 +169,16 @@ pub struct SetOutfitSize {
 ///
 /// # Effect
 /// - Resends a [`HandleNewAvatar`] message if the inventory is not yet loaded
+/// - If the avatar is another agent's avatar seen for the first time
+///    - Dispatches a [`LoadFromCache`] message if the avatar was built on an earlier visit
 /// - If the avatar is the current player's avatar
 ///    - Dispatches a [`CameraPosition`] message
 ///    - If the avatar is in the cache:
 ///     - Dispatches a [`LoadFromCache`] message to skip asset downloading.
 ///    - else:
-///     - Dispatches a [`DownloadAgentAsset`] message for each object in the outfit
-///     - Dispatches a [`AddObjectToAvatar`] message for each bodypart in the outfit
+///     - Dispatches a [`DownloadAgentAsset`] message for each object, bodypart and clothing item
+///       in the outfit
+///     - Dispatches a [`AddObjectToAvatar`] message for each other item in the outfit
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct HandleNewAvatar {
@@ -121,11 +189,16 @@ pub struct HandleNewAvatar {
 /// Message to finalize the avatar
 ///
 /// This is triggered when all of the avatar's objects have loaded in. This finalizes the global
-/// skeleton, writes the JSON for the full baked avatar, generates the mesh from metaverse-mesh,
-/// and triggers a UI update.
+/// skeleton, shapes it with the avatar's visual params, writes the JSON for the full baked avatar,
+/// generates the mesh from metaverse-mesh, and triggers a UI update.
 ///
 /// # Cause
 /// - [`AddObjectToAvatar`]
+/// - [`AddSystemMeshes`]
+/// - [`BakeSystemAvatar`] if the system avatar can't be built
+/// - [`HandleNewAvatarAppearance`]
+/// - [`LoadFromCache`] if the avatar's shape or bakes changed since it was cached
+/// - [`SetServerBakes`]
 ///
 /// # Effects
 /// - Dispatches a [`RenderAvatar`] message to render the finalized avatar
@@ -144,6 +217,7 @@ pub struct FinalizeAvatar {
 ///
 /// # Effects
 /// - Dispatches a [`MeshUpdate`] message to inform the UI of an update
+/// - Resends the avatar's last [`HandleNewAvatarAnimation`] if the avatar was rebuilt
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct RenderAvatar {
@@ -152,6 +226,49 @@ pub struct RenderAvatar {
     skeleton: BTreeSet<JointName>,
 }
 
+/// Message to add a rigged mesh worn by another agent to their avatar
+///
+/// The inventories of other agents can't be read, so their avatars are built from the rigged
+/// attachments they wear. Rigged meshes bend with the avatar's skeleton, so they are part of the
+/// avatar's model instead of being drawn as objects of their own.
+///
+/// # Cause
+/// - [`AddRiggedMesh`] for a rigged attachment worn by another agent
+///
+/// # Effects
+/// - Dispatches a [`BuildWornAvatar`] message after [`WORN_MESH_SETTLE_TIME`]
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct AddWornMesh {
+    /// ID of the agent wearing the mesh
+    pub agent_id: Uuid,
+    /// scene local ID of the attachment
+    pub scene_id: u32,
+    /// ID of the mesh asset
+    pub asset_id: Uuid,
+    /// the downloaded mesh
+    pub render_object: RenderObject,
+}
+
+/// Message to rebuild another agent's avatar from the rigged meshes they wear
+///
+/// Attachments arrive one at a time, so the avatar is only built once none have been added or
+/// removed for [`WORN_MESH_SETTLE_TIME`]. Avatars whose meshes haven't changed since they were
+/// last built are left alone.
+///
+/// # Cause
+/// - [`AddWornMesh`]
+/// - [`HandleKillObject`] when a worn mesh is detached
+///
+/// # Effects
+/// - Dispatches an [`AddObjectToAvatar`] message for each worn mesh
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct BuildWornAvatar {
+    /// ID of the agent to build
+    pub agent_id: Uuid,
+}
+
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 struct LoadFromCache {
@@ -160,6 +277,9 @@ struct LoadFromCache {
 
 /// Message to handle an updated animation
 ///
+/// Default animations are read from the generated assets. Custom animations are downloaded and
+/// parsed from their keyframe motion, which is written to the agent's directory as json.
+///
 /// # Cause
 /// - Avatar Appearance packet received from UDP socket
 ///
@@ -174,10 +294,17 @@ pub struct HandleNewAvatarAnimation {
 
 /// Message to handle an updated avatar appearance
 ///
-/// TODO: currently a stub
+/// The appearance's visual params set the shape of the avatar's skeleton, like its height and
+/// proportions. Avatars that have already been built are rebuilt in their new shape.
 ///
 /// # Cause
 /// - Avatar Appearance packet received from UDP socket
+///
+/// # Effects
+/// - Dispatches a [`DownloadServerBakes`] message if the avatar's baked textures changed
+/// - If the avatar's shape changed after it was built
+///    - Dispatches a [`BakeSystemAvatar`] message if the avatar wears system bodyparts
+///    - else dispatches a [`FinalizeAvatar`] message
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct HandleNewAvatarAppearance {
@@ -185,6 +312,51 @@ pub struct HandleNewAvatarAppearance {
     pub avatar_appearance: AvatarAppearance,
 }
 
+/// Message to download the textures the server baked an avatar's appearance into
+///
+/// Mesh bodies are drawn with the bakes of the avatar wearing them, in place of their Bakes on
+/// Mesh textures. Each bake is downloaded to a file of its own texture ID, and bakes that were
+/// downloaded before aren't downloaded again.
+///
+/// # Cause
+/// - [`HandleNewAvatarAppearance`] if the avatar's baked textures changed
+///
+/// # Effects
+/// - Dispatches a [`SetServerBakes`] message once the bakes are downloaded
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct DownloadServerBakes {
+    /// ID of the agent the bakes belong to
+    pub agent_id: Uuid,
+    /// the texture of each bake
+    pub baked_textures: BTreeMap<BakeType, Uuid>,
+}
+
+/// Message to store the downloaded bakes of an avatar, and rebuild it with them
+///
+/// The downloaded bakes replace the avatar's previous bakes, unless a newer set of bakes was
+/// requested while they were downloading.
+///
+/// # Cause
+/// - [`DownloadServerBakes`]
+///
+/// # Effects
+/// - If the avatar has already been built
+///    - Dispatches a [`BakeSystemAvatar`] message if the avatar wears system bodyparts
+///    - else dispatches a [`FinalizeAvatar`] message
+/// - [`RequestMultipleObjects`] sent to the server for the prims the avatar wears, so they are
+///   rebuilt with the new bakes
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct SetServerBakes {
+    /// ID of the agent the bakes belong to
+    pub agent_id: Uuid,
+    /// the texture of each bake that was requested
+    pub requested_bakes: BTreeMap<BakeType, Uuid>,
+    /// the texture of each bake that was downloaded
+    pub baked_textures: BTreeMap<BakeType, Uuid>,
+}
+
 impl Handler<HandleNewAvatar> for Mailbox {
     type Result = ();
     fn handle(&mut self, msg: HandleNewAvatar, ctx: &mut Self::Context) -> Self::Result {
@@ -268,25 +440,15 @@ impl Handler<HandleNewAvatar> for Mailbox {
                                     };
                                     for item in outfit {
                                         match item.item_type {
-                                            ObjectType::Object => {
+                                            ObjectType::Object
+                                            | ObjectType::Bodypart
+                                            | ObjectType::Clothing => {
                                                 addr.do_send(DownloadAgentAsset {
                                                     asset_id: item.asset_id,
                                                     item_type: item.item_type,
                                                     agent_id,
                                                 });
                                             }
-                                            ObjectType::Bodypart => {
-                                                addr.do_send(AddObjectToAvatar {
-                                                    object: OutfitObject::Bodypart,
-                                                    agent_id,
-                                                });
-                                            }
-                                            ObjectType::Clothing => {
-                                                addr.do_send(AddObjectToAvatar {
-                                                    object: OutfitObject::Clothing,
-                                                    agent_id,
-                                                });
-                                            }
                                             _ => {
                                                 addr.do_send(AddObjectToAvatar {
                                                     object: OutfitObject::Other,
@@ -307,9 +469,28 @@ impl Handler<HandleNewAvatar> for Mailbox {
                     warn!("Inventory not yet ready. Requeueing avatar download...");
                     ctx.notify_later(msg, Duration::from_secs(1));
                 }
+            } else if let Some(avatar) = session.avatars.get_mut(&msg.avatar.agent_id) {
+                avatar.position = msg.avatar.position;
             } else {
-                // TODO: handle non-user avatar updates
-                warn!("Non-user avatar updates not yet supported...");
+                // other avatars are built by BuildWornAvatar as their attachments arrive. Until
+                // then, the model built on the agent's last visit is shown.
+                let agent_id = msg.avatar.agent_id;
+                session.avatars.insert(agent_id, msg.avatar);
+                let db_conn = self.inventory_db_connection.clone();
+                ctx.spawn(
+                    async move {
+                        if let Err(e) = sqlite_insert_avatar(&db_conn, agent_id, 0).await {
+                            error!("Failed to insert new avatar to cache: {:?}", e);
+                        }
+                        // agents that haven't been built before have no data to load
+                        if let Ok(avatar) = sqlite_get_avatar(&db_conn, agent_id).await
+                            && avatar.path.as_ref().is_some_and(|path| path.exists())
+                        {
+                            addr.do_send(LoadFromCache { avatar });
+                        }
+                    }
+                    .into_actor(self),
+                );
             }
         }
     }
@@ -319,26 +500,64 @@ impl Handler<LoadFromCache> for Mailbox {
     type Result = ();
     fn handle(&mut self, msg: LoadFromCache, ctx: &mut Self::Context) -> Self::Result {
         if let Some(session) = self.session.as_mut() {
+            let mut avatar = msg.avatar;
+            let mut reshape = false;
+            if let Some(current) = session.avatars.get(&avatar.agent_id) {
+                // a newer model was built while the cache was being read
+                if !current.items.is_empty() {
+                    return;
+                }
+                avatar.position = current.position;
+                // the avatar's appearance arrived while the cache was being read
+                if !current.visual_params.is_empty()
+                    && (current.visual_params != avatar.visual_params
+                        || current.hover_height != avatar.hover_height)
+                {
+                    avatar.visual_params = current.visual_params.clone();
+                    avatar.hover_height = current.hover_height;
+                    reshape = true;
+                }
+                // newer bakes were downloaded while the cache was being read
+                if !current.baked_textures.is_empty()
+                    && current.baked_textures != avatar.baked_textures
+                {
+                    avatar.baked_textures = current.baked_textures.clone();
+                    reshape = true;
+                }
+                if !current.requested_bakes.is_empty() {
+                    avatar.requested_bakes = current.requested_bakes.clone();
+                }
+            }
             // insert the avatar to the session
-            session
-                .avatars
-                .insert(msg.avatar.agent_id, msg.avatar.clone());
+            session.avatars.insert(avatar.agent_id, avatar.clone());
 
             // render the cached avatar
             ctx.address().do_send(RenderAvatar {
                 message: MeshUpdate {
-                    position: msg.avatar.position,
+                    position: avatar.position,
                     scale: Vec3::ONE,
                     rotation: Quat::IDENTITY,
                     parent: None,
                     scene_id: None,
-                    path: msg.avatar.path.unwrap(),
+                    path: avatar.path.unwrap(),
                     mesh_type: MeshType::Avatar,
-                    id: Some(msg.avatar.agent_id),
+                    id: Some(avatar.agent_id),
+                    lod: LevelOfDetail::High,
                 },
-                agent_id: msg.avatar.agent_id,
-                skeleton: msg.avatar.used_joints,
+                agent_id: avatar.agent_id,
+                skeleton: avatar.used_joints,
             });
+            if reshape {
+                if wears_system_avatar(&avatar) {
+                    ctx.address().do_send(BakeSystemAvatar {
+                        agent_id: avatar.agent_id,
+                    });
+                } else {
+                    ctx.address().do_send(FinalizeAvatar {
+                        agent_id: avatar.agent_id,
+                    });
+                }
+            }
         };
     }
 }
@@ -364,6 +583,34 @@ impl Handler<DownloadAgentAsset> for Mailbox {
                 .unwrap()
                 .to_string();
             let addr = ctx.address();
+            if matches!(msg.item_type, ObjectType::Bodypart | ObjectType::Clothing) {
+                ctx.spawn(
+                    async move {
+                        let object = match download_wearable(
+                            msg.item_type.to_string(),
+                            msg.asset_id,
+                            &server_endpoint,
+                        )
+                        .await
+                        {
+                            Ok(wearable) if msg.item_type == ObjectType::Bodypart => {
+                                OutfitObject::Bodypart(wearable)
+                            }
+                            Ok(wearable) => OutfitObject::Clothing(wearable),
+                            Err(e) => {
+                                error!("Failed to download wearable {:?}: {:?}", msg.asset_id, e);
+                                OutfitObject::Other
+                            }
+                        };
+                        addr.do_send(AddObjectToAvatar {
+                            object,
+                            agent_id: msg.agent_id,
+                        });
+                    }
+                    .into_actor(self),
+                );
+                return;
+            }
             let db_conn = self.inventory_db_connection.clone();
             ctx.spawn(
                 async move {
@@ -407,10 +654,17 @@ impl Handler<DownloadAgentAsset> for Mailbox {
                             };
 
                             // Download the mesh itself
+                            // mesh bodies are drawn with the avatar's bakes
+                            let mut textures = HashMap::from([(texture_id, texture_path)]);
+                            textures.extend(bakes_on_mesh_textures(msg.agent_id));
+                            let materials = ObjectMaterials {
+                                textures,
+                                ..Default::default()
+                            };
                             let render_objects = match download_scene_group(
                                 &scene_group,
                                 &server_endpoint,
-                                &texture_path,
+                                &materials,
                             )
                             .await
                             {
@@ -494,9 +748,15 @@ impl Handler<AddObjectToAvatar> for Mailbox {
                 avatar.items.push(msg.object);
 
                 if avatar.items.len() == avatar.outfit_size {
-                    ctx.address().do_send(FinalizeAvatar {
-                        agent_id: avatar.agent_id,
-                    });
+                    if wears_system_avatar(avatar) {
+                        ctx.address().do_send(BakeSystemAvatar {
+                            agent_id: avatar.agent_id,
+                        });
+                    } else {
+                        ctx.address().do_send(FinalizeAvatar {
+                            agent_id: avatar.agent_id,
+                        });
+                    }
                 }
             } else {
                 warn!("Agent not found for agent_id {:?}", &msg.agent_id);
@@ -505,6 +765,223 @@ impl Handler<AddObjectToAvatar> for Mailbox {
     }
 }
 
+impl Handler<BakeSystemAvatar> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: BakeSystemAvatar, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        let Some(avatar) = session.avatars.get_mut(&msg.agent_id) else {
+            warn!("Agent not found for agent_id {:?}", &msg.agent_id);
+            return;
+        };
+        let wearables: Vec<ItemData> = avatar
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                OutfitObject::Bodypart(wearable) | OutfitObject::Clothing(wearable) => {
+                    Some(wearable.clone())
+                }
+                _ => None,
+            })
+            .collect();
+
+        let avatar_lad = load_avatar_lad(&mut session.avatar_lad);
+        // the appearance of the avatar may not have arrived yet
+        if avatar.visual_params.is_empty()
+            && let Some(avatar_lad) = avatar_lad
+        {
+            let weights: HashMap<i32, f32> = wearables
+                .iter()
+                .flat_map(|wearable| wearable.parameters.clone())
+                .collect();
+            avatar.visual_params = avatar_lad.visual_params(&weights);
+        }
+        let morphs = avatar_lad
+            .map(|avatar_lad| avatar_lad.shape(&avatar.visual_params).morphs)
+            .unwrap_or_default();
+        let json_name = system_avatar_json(&wearables, &avatar.visual_params);
+        let baked_textures = avatar.baked_textures.clone();
+
+        let server_endpoint = session
+            .capability_urls
+            .get(&Capability::ViewerAsset)
+            .unwrap()
+            .to_string();
+        let agent_id = msg.agent_id;
+        let addr = ctx.address();
+        ctx.spawn(
+            async move {
+                let base_dir = match create_sub_agent_dir(&agent_id.to_string()) {
+                    Ok(base_dir) => base_dir,
+                    Err(e) => {
+                        error!("failed to create base dir: {:?}", e);
+                        addr.do_send(FinalizeAvatar { agent_id });
+                        return;
+                    }
+                };
+                // the server's bakes are used for the parts it has baked
+                let mut server_bakes = HashMap::new();
+                for (bake, texture_id) in baked_textures {
+                    match server_bake_path(agent_id, bake, texture_id) {
+                        Ok(path) if path.exists() => {
+                            server_bakes.insert(bake, path);
+                        }
+                        Ok(_) => {}
+                        Err(e) => error!("Failed to find {:?} bake: {:?}", bake, e),
+                    }
+                }
+                let bakes =
+                    bake_textures(&wearables, &server_bakes, &server_endpoint, &base_dir).await;
+                let path = match build_system_avatar(agent_id, &morphs, &bakes, &wearables)
+                    .and_then(|objects| write_json(&objects, agent_id, &json_name))
+                {
+                    Ok(path) => path,
+                    Err(e) => {
+                        error!("Failed to build system avatar for {:?}: {:?}", agent_id, e);
+                        addr.do_send(FinalizeAvatar { agent_id });
+                        return;
+                    }
+                };
+                addr.do_send(AddSystemMeshes { agent_id, path });
+            }
+            .into_actor(self),
+        );
+    }
+}
+
+impl Handler<AddSystemMeshes> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: AddSystemMeshes, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        let Some(avatar) = session.avatars.get_mut(&msg.agent_id) else {
+            warn!("Agent not found for agent_id {:?}", &msg.agent_id);
+            return;
+        };
+        let parts: Vec<RenderObject> = match fs::read_to_string(&msg.path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+        {
+            Ok(parts) => parts,
+            Err(e) => {
+                error!("Failed to read {:?}: {:?}", msg.path, e);
+                return;
+            }
+        };
+        for part in &parts {
+            if let Some(skin) = &part.skin {
+                update_global_avatar_skeleton(avatar, &skin.skeleton);
+            }
+        }
+
+        // the meshes of an earlier bake were made for a different shape or outfit
+        avatar.items.retain(
+            |item| !matches!(item, OutfitObject::MeshObject(path) if is_system_avatar_json(path)),
+        );
+        avatar.items.push(OutfitObject::MeshObject(msg.path));
+        // the outfit has finished loading by the time it is baked, so it is made up of exactly
+        // these items
+        avatar.outfit_size = avatar.items.len();
+
+        ctx.address().do_send(FinalizeAvatar {
+            agent_id: msg.agent_id,
+        });
+    }
+}
+
+impl Handler<AddWornMesh> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: AddWornMesh, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        let Some(avatar) = session.avatars.get_mut(&msg.agent_id) else {
+            warn!("Agent not found for agent_id {:?}", &msg.agent_id);
+            return;
+        };
+        // outfit objects are read as every part of their linkset, and worn meshes are only one
+        let json_path = match write_json(
+            &vec![msg.render_object],
+            msg.agent_id,
+            &msg.asset_id.to_string(),
+        ) {
+            Ok(json_path) => json_path,
+            Err(e) => {
+                error!("Failed to write json: {:?}", e);
+                return;
+            }
+        };
+        avatar.last_update = SystemTime::now();
+        session
+            .worn_meshes
+            .entry(msg.agent_id)
+            .or_default()
+            .insert(msg.scene_id, json_path);
+        ctx.notify_later(
+            BuildWornAvatar {
+                agent_id: msg.agent_id,
+            },
+            WORN_MESH_SETTLE_TIME,
+        );
+    }
+}
+
+impl Handler<BuildWornAvatar> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: BuildWornAvatar, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        let Some(avatar) = session.avatars.get_mut(&msg.agent_id) else {
+            return;
+        };
+        // every change schedules its own build, so only the last one goes through
+        if avatar.last_update.elapsed().unwrap_or_default() < WORN_MESH_SETTLE_TIME {
+            return;
+        }
+
+        // the json of worn meshes is named after their asset, so the same outfit is made of the
+        // same paths on every visit
+        let mut worn: Vec<PathBuf> = session
+            .worn_meshes
+            .get(&msg.agent_id)
+            .into_iter()
+            .flat_map(|meshes| meshes.values().cloned())
+            .collect();
+        worn.sort();
+        let mut built: Vec<&PathBuf> = avatar
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                OutfitObject::MeshObject(path) => Some(path),
+                _ => None,
+            })
+            .collect();
+        built.sort();
+        if worn.is_empty() || built.into_iter().eq(worn.iter()) {
+            return;
+        }
+
+        // the skeleton is built up from every mesh, so it starts over without the detached ones
+        let mut rebuilt = Avatar::new(msg.agent_id, avatar.position);
+        rebuilt.path = avatar.path.take();
+        rebuilt.outfit_size = worn.len();
+        rebuilt.visual_params = std::mem::take(&mut avatar.visual_params);
+        rebuilt.hover_height = avatar.hover_height;
+        rebuilt.baked_textures = std::mem::take(&mut avatar.baked_textures);
+        rebuilt.requested_bakes = std::mem::take(&mut avatar.requested_bakes);
+        *avatar = rebuilt;
+        for path in worn {
+            ctx.address().do_send(AddObjectToAvatar {
+                agent_id: msg.agent_id,
+                object: OutfitObject::MeshObject(path),
+            });
+        }
+    }
+}
+
 impl Handler<FinalizeAvatar> for Mailbox {
     type Result = ();
     fn handle(&mut self, msg: FinalizeAvatar, ctx: &mut Self::Context) -> Self::Result {
@@ -514,9 +991,21 @@ impl Handler<FinalizeAvatar> for Mailbox {
 
                 let agent_id = avatar.agent_id;
                 let position = avatar.position;
-                let skeleton = avatar.skeleton.clone();
                 let used_joints = avatar.used_joints.clone();
                 let items = avatar.items.clone();
+                let visual_params = avatar.visual_params.clone();
+                let hover_height = avatar.hover_height;
+                let baked_textures = avatar.baked_textures.clone();
+
+                // the shape is applied to a copy, so reshaping starts from the unshaped skeleton
+                let mut shaped = avatar.clone();
+                if !visual_params.is_empty()
+                    && let Some(avatar_lad) = load_avatar_lad(&mut session.avatar_lad)
+                {
+                    let shape = avatar_lad.shape(&visual_params);
+                    apply_avatar_shape(&mut shaped, &shape, hover_height);
+                }
+                let skeleton = shaped.skeleton;
 
                 let mut avatar_clone = avatar.clone();
                 let db_conn = self.inventory_db_connection.clone();
@@ -563,7 +1052,18 @@ impl Handler<FinalizeAvatar> for Mailbox {
                                 return;
                             }
                         };
-                        let glb_path = base_dir.join(format!("{:?}_high.glb", msg.agent_id));
+                        // models are stored by the items, shape and bakes they are built from, so
+                        // a model is rebuilt when the outfit, shape or bakes change
+                        let mut hasher = DefaultHasher::new();
+                        avatar_object.objects.hash(&mut hasher);
+                        visual_params.hash(&mut hasher);
+                        hover_height.to_array().map(f32::to_bits).hash(&mut hasher);
+                        baked_textures.hash(&mut hasher);
+                        let glb_path = base_dir.join(format!(
+                            "{:?}_{:016x}_high.glb",
+                            msg.agent_id,
+                            hasher.finish()
+                        ));
 
                         if !glb_path.exists()
                             && let Err(e) =
@@ -591,6 +1091,7 @@ impl Handler<FinalizeAvatar> for Mailbox {
                                 path: glb_path,
                                 mesh_type: MeshType::Avatar,
                                 id: Some(msg.agent_id),
+                                lod: LevelOfDetail::High,
                             },
                             agent_id,
                             skeleton: used_joints,
@@ -608,25 +1109,191 @@ impl Handler<RenderAvatar> for Mailbox {
         if let Some(session) = self.session.as_mut()
             && let Some(avatar) = session.avatars.get_mut(&msg.agent_id) {
                 avatar.used_joints = msg.skeleton;
+                let previous = avatar.path.replace(msg.message.path.clone());
                 let addr = ctx.address();
                 addr.do_send(SendUIMessage {
                     ui_message: UIMessage::new_mesh_update(msg.message),
                 });
                 avatar.fully_loaded = true;
+
+                // a rebuilt model replaces the one its animation was playing on, and may have
+                // different joints to animate
+                if previous.is_some_and(|previous| Some(&previous) != avatar.path.as_ref())
+                    && let Some(avatar_animation) = session.avatar_animations.get(&msg.agent_id)
+                {
+                    addr.do_send(HandleNewAvatarAnimation {
+                        avatar_animation: avatar_animation.clone(),
+                    });
+                }
             }
     }
 }
 
 impl Handler<HandleNewAvatarAppearance> for Mailbox {
     type Result = ();
-    fn handle(
-        &mut self,
-        _msg: HandleNewAvatarAppearance,
-        _ctx: &mut Self::Context,
-    ) -> Self::Result {
-        // TODO: implement this. AvatarAppearance packets change skeleton joint positions to change
-        // the appearance of the avatar.
-        warn!("AvatarAppearance packet received. Currently unimplemented");
+    fn handle(&mut self, msg: HandleNewAvatarAppearance, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_mut() else {
+            error!("Handle New Avatar Appearance failed. Session is not running.");
+            return;
+        };
+        let agent_id = msg.avatar_appearance.id;
+        let Some(avatar) = session.avatars.get_mut(&agent_id) else {
+            warn!(
+                "Appearance targeting player {:?}, not yet in scene. Queueing appearance...",
+                agent_id
+            );
+            ctx.notify_later(msg, Duration::from_secs(1));
+            return;
+        };
+
+        // bakes that failed to download aren't requested again until the server changes them
+        let baked_textures = msg.avatar_appearance.baked_textures();
+        if !baked_textures.is_empty() && avatar.requested_bakes != baked_textures {
+            avatar.requested_bakes = baked_textures.clone();
+            ctx.address().do_send(DownloadServerBakes {
+                agent_id,
+                baked_textures,
+            });
+        }
+
+        let hover_height = msg.avatar_appearance.hover_height.unwrap_or_default();
+        if avatar.visual_params == msg.avatar_appearance.visual_params
+            && avatar.hover_height == hover_height
+        {
+            return;
+        }
+        avatar.visual_params = msg.avatar_appearance.visual_params;
+        avatar.hover_height = hover_height;
+
+        // avatars that are still loading are shaped when they finish
+        if avatar.fully_loaded && !avatar.items.is_empty() {
+            if wears_system_avatar(avatar) {
+                ctx.address().do_send(BakeSystemAvatar { agent_id });
+            } else {
+                ctx.address().do_send(FinalizeAvatar { agent_id });
+            }
+        }
+    }
+}
+
+impl Handler<DownloadServerBakes> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: DownloadServerBakes, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        let server_endpoint = session
+            .capability_urls
+            .get(&Capability::ViewerAsset)
+            .unwrap()
+            .to_string();
+        let addr = ctx.address();
+        ctx.spawn(
+            async move {
+                let mut downloaded = BTreeMap::new();
+                for (bake, texture_id) in &msg.baked_textures {
+                    let path = match server_bake_path(msg.agent_id, *bake, *texture_id) {
+                        Ok(path) => path,
+                        Err(e) => {
+                            error!("Failed to create {:?} bake: {:?}", bake, e);
+                            continue;
+                        }
+                    };
+                    // bakes are stored by texture ID, so one that was downloaded before is
+                    // still current
+                    if path.exists() {
+                        downloaded.insert(*bake, *texture_id);
+                        continue;
+                    }
+                    match download_texture(
+                        ObjectType::Texture.to_string(),
+                        *texture_id,
+                        &server_endpoint,
+                        &path,
+                    )
+                    .await
+                    {
+                        Ok(_) => {
+                            downloaded.insert(*bake, *texture_id);
+                        }
+                        Err(e) => error!(
+                            "Failed to download {:?} bake {:?}: {:?}",
+                            bake, texture_id, e
+                        ),
+                    }
+                }
+                addr.do_send(SetServerBakes {
+                    agent_id: msg.agent_id,
+                    requested_bakes: msg.baked_textures,
+                    baked_textures: downloaded,
+                });
+            }
+            .into_actor(self),
+        );
+    }
+}
+
+impl Handler<SetServerBakes> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: SetServerBakes, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        let Some(avatar) = session.avatars.get_mut(&msg.agent_id) else {
+            return;
+        };
+        // a newer appearance was requested while these were downloading, and its bakes replace
+        // them when they arrive
+        if avatar.requested_bakes != msg.requested_bakes
+            || avatar.baked_textures == msg.baked_textures
+        {
+            return;
+        }
+        if let Err(e) = set_bakes_on_mesh(msg.agent_id, &msg.baked_textures) {
+            error!("Failed to set bakes of {:?}: {:?}", msg.agent_id, e);
+        }
+        avatar.baked_textures = msg.baked_textures;
+
+        // the model embeds its textures, so it is rebuilt to pick up the new bakes
+        if avatar.fully_loaded && !avatar.items.is_empty() {
+            if wears_system_avatar(avatar) {
+                ctx.address().do_send(BakeSystemAvatar {
+                    agent_id: msg.agent_id,
+                });
+            } else {
+                ctx.address().do_send(FinalizeAvatar {
+                    agent_id: msg.agent_id,
+                });
+            }
+        }
+
+        // prims worn by the avatar embed the bakes in their own models, and are rebuilt from a
+        // fresh copy of the object. Rigged meshes are part of the avatar's model instead.
+        let rigged = session.worn_meshes.get(&msg.agent_id);
+        let mut worn = Vec::new();
+        let mut linksets: Vec<u32> = session
+            .avatar_scene_ids
+            .iter()
+            .filter(|(_, agent_id)| **agent_id == msg.agent_id)
+            .map(|(scene_id, _)| *scene_id)
+            .collect();
+        while let Some(local_id) = linksets.pop() {
+            for child in session.scene_graph.children(local_id) {
+                linksets.push(child);
+                if !rigged.is_some_and(|rigged| rigged.contains_key(&child)) {
+                    worn.push((CacheMissType::Normal, child));
+                }
+            }
+        }
+        if !worn.is_empty() {
+            ctx.address().do_send(OutgoingPacket {
+                packet: Packet::new_request_multiple_objects(RequestMultipleObjects {
+                    session_id: session.session_id,
+                    agent_id: session.agent_id,
+                    requests: worn,
+                }),
+            });
+        }
     }
 }
 
@@ -640,6 +1307,9 @@ impl Handler<HandleNewAvatarAnimation> for Mailbox {
                 return;
             }
         };
+        session
+            .avatar_animations
+            .insert(msg.avatar_animation.sender_id, msg.avatar_animation.clone());
         let avatar = match session.avatars.get(&msg.avatar_animation.sender_id) {
             Some(avatar) => {
                 if !avatar.fully_loaded {
@@ -687,24 +1357,50 @@ impl Handler<HandleNewAvatarAnimation> for Mailbox {
                             .join("Animations")
                             .join(format!("{}.json", default_animation))
                     } else {
-                        match download_asset(
-                            ObjectType::Animation.to_string(),
-                            animation.anim_id,
-                            &viewer_asset_endpoint,
-                        )
-                        .await
-                        {
-                            Ok(_) => {
-                                //TODO: Implement this
-                                warn!("Retrieved animation, but non-default animations are currently unimplemented");
-                                return;
-                            }
-                            Err(e) => {
-                                error!(
-                                    "failed to retrieve animation {:?}: {:?}",
-                                    animation.anim_id, e
-                                );
-                                return;
+                        let name = format!("animation_{}", animation.anim_id);
+                        // animations are converted once, and reused from then on
+                        let converted = create_sub_agent_dir(&sender_id.to_string())
+                            .map(|dir| dir.join(format!("{}.json", name)));
+                        match converted {
+                            Ok(path) if path.exists() => path,
+                            _ => {
+                                let bytes = match download_asset(
+                                    ObjectType::Animation.to_string(),
+                                    animation.anim_id,
+                                    &viewer_asset_endpoint,
+                                )
+                                .await
+                                {
+                                    Ok(bytes) => bytes,
+                                    Err(e) => {
+                                        error!(
+                                            "failed to retrieve animation {:?}: {:?}",
+                                            animation.anim_id, e
+                                        );
+                                        continue;
+                                    }
+                                };
+                                let motion = match KeyframeMotion::from_bytes(&bytes) {
+                                    Ok(motion) => motion,
+                                    Err(e) => {
+                                        error!(
+                                            "Failed to parse animation {:?}: {:?}",
+                                            animation.anim_id, e
+                                        );
+                                        continue;
+                                    }
+                                };
+                                let json = AnimationJson::from_keyframe_motion(&name, &motion);
+                                match write_json(&json, sender_id, &name) {
+                                    Ok(path) => path,
+                                    Err(e) => {
+                                        error!(
+                                            "Failed to write animation {:?}: {:?}",
+                                            animation.anim_id, e
+                                        );
+                                        continue;
+                                    }
+                                }
                             }
                         }
                     };
@@ -728,7 +1424,7 @@ impl Handler<HandleNewAvatarAnimation> for Mailbox {
                             "Failed to generate animation {:?}: {:?}",
                             animation.anim_id, e
                         );
-                        return;
+                        continue;
                     }
 
                     addr.do_send(SendUIMessage {
@@ -770,3 +1466,35 @@ fn write_json<T: Serialize>(data: &T, agent_id: Uuid, filename: &str) -> io::Res
         }
     }
 }
+
+/// Load the visual param definitions from the default assets the first time an avatar is shaped.
+/// Avatars are left unshaped if they can't be read.
+fn load_avatar_lad(avatar_lad: &mut Option<Option<AvatarLad>>) -> Option<&AvatarLad> {
+    avatar_lad
+        .get_or_insert_with(|| {
+            let path = benthic_default_assets::character().join("avatar_lad.xml");
+            match fs::read(&path) {
+                Ok(bytes) => match AvatarLad::from_xml(&bytes) {
+                    Ok(lad) => Some(lad),
+                    Err(e) => {
+                        error!("Failed to parse {:?}: {:?}", path, e);
+                        None
+                    }
+                },
+                Err(e) => {
+                    error!("Failed to read {:?}: {:?}", path, e);
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// Avatars wearing system bodyparts are drawn with the meshes of the system avatar, which are
+/// baked again when their shape changes
+fn wears_system_avatar(avatar: &Avatar) -> bool {
+    avatar
+        .items
+        .iter()
+        .any(|item| matches!(item, OutfitObject::Bodypart(_)))
+}
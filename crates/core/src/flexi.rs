@@ -0,0 +1,170 @@
+use benthic_protocol::messages::ui::flexi_update::FlexiUpdate;
+use glam::{Quat, Vec3};
+
+/// The longest time step the simulation takes at once, in seconds. Longer frames are split into
+/// several steps, so slow frames don't make flexible prims explode.
+pub const MAX_TIME_STEP: f32 = 1.0 / 30.0;
+/// If the base of a prim moves further than this many times its length in a single frame, the
+/// prim is reset to its resting shape instead of being swung around. This happens when objects
+/// are teleported.
+pub const RESET_DISTANCE: f32 = 4.0;
+/// Converts the tension of a prim to the stiffness of the spring that pulls it back to its
+/// resting shape
+const TENSION_STIFFNESS: f32 = 10.0;
+/// Converts the gravity of a prim to meters per second squared, so a gravity of 1 pulls like the
+/// earth does
+const GRAVITY_SCALE: f32 = 9.81;
+
+/// A flexible prim, simulated as a chain of sections along its path.
+///
+/// The path of a prim runs along its Z axis, and the chain hangs from the bottom of the prim. The
+/// chain is moved by verlet integration, and each section keeps its length so the prim doesn't
+/// stretch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlexiChain {
+    /// the parameters of the prim
+    pub params: FlexiUpdate,
+    /// the joints between sections, in world space. The first joint is the base of the prim.
+    pub points: Vec<Vec3>,
+    /// where the joints were on the previous step
+    pub previous: Vec<Vec3>,
+    /// position of the prim
+    pub position: Vec3,
+    /// rotation of the prim
+    pub rotation: Quat,
+    /// scale of the prim
+    pub scale: Vec3,
+}
+
+impl FlexiChain {
+    /// Create a chain in the resting shape of the prim
+    pub fn new(params: FlexiUpdate, position: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        let mut chain = Self {
+            params,
+            points: Vec::new(),
+            previous: Vec::new(),
+            position,
+            rotation,
+            scale,
+        };
+        chain.reset();
+        chain
+    }
+
+    /// The number of sections the prim is simulated with
+    pub fn sections(&self) -> usize {
+        1 << self.params.softness.min(3)
+    }
+
+    /// Put every joint back in its resting position
+    pub fn reset(&mut self) {
+        self.points = (0..=self.sections())
+            .map(|joint| self.rest_point(joint))
+            .collect();
+        self.previous = self.points.clone();
+    }
+
+    /// Move the prim to a new transform and advance the simulation by `elapsed` seconds.
+    ///
+    /// Wind is in meters per second, in region coordinates.
+    pub fn step(&mut self, elapsed: f32, position: Vec3, rotation: Quat, scale: Vec3, wind: Vec3) {
+        let moved = self.position.distance(position);
+        self.position = position;
+        self.rotation = rotation;
+        self.scale = scale;
+        if self.points.len() != self.sections() + 1
+            || moved > RESET_DISTANCE * self.length().max(f32::EPSILON)
+        {
+            self.reset();
+            return;
+        }
+
+        let steps = (elapsed / MAX_TIME_STEP).ceil().max(1.0);
+        let dt = elapsed.max(0.0) / steps;
+        for _ in 0..steps as u32 {
+            self.integrate(dt, wind);
+        }
+    }
+
+    /// Find where a vertex of the prim's mesh is moved to by the bend of the chain.
+    ///
+    /// Vertices are in the prim's local space before scaling, where the path runs from -0.5 to
+    /// 0.5 along Z. Returns the moved vertex in the same space, and the rotation to apply to its
+    /// normal.
+    pub fn deform(&self, vertex: Vec3) -> (Vec3, Quat) {
+        let sections = self.sections();
+        let along = (vertex.z + 0.5).clamp(0.0, 1.0) * sections as f32;
+        let section = (along.floor() as usize).min(sections - 1);
+        let fraction = along - section as f32;
+
+        let bend = self.section_rotation(section).slerp(
+            self.section_rotation((section + 1).min(sections - 1)),
+            fraction,
+        );
+        let spine = self.points[section].lerp(self.points[section + 1], fraction);
+        let across = self.rotation * (Vec3::new(vertex.x, vertex.y, 0.0) * self.scale);
+        let world = spine + bend * across;
+
+        let local = self.rotation.inverse() * (world - self.position) / self.safe_scale();
+        let local_bend = self.rotation.inverse() * bend * self.rotation;
+        (local, local_bend)
+    }
+
+    fn integrate(&mut self, dt: f32, wind: Vec3) {
+        if dt <= 0.0 {
+            return;
+        }
+        let params = &self.params;
+        let sections = self.sections();
+        let segment_length = self.length() / sections as f32;
+        let damping = 1.0 - (params.drag * dt).min(1.0);
+        let gravity = Vec3::NEG_Z * params.gravity * GRAVITY_SCALE;
+        let stiffness = params.tension * TENSION_STIFFNESS;
+
+        let rest: Vec<Vec3> = (0..=sections).map(|joint| self.rest_point(joint)).collect();
+        self.points[0] = rest[0];
+        self.previous[0] = rest[0];
+        let joints = self
+            .points
+            .iter_mut()
+            .zip(self.previous.iter_mut())
+            .zip(&rest);
+        for ((point, previous), rest) in joints.skip(1) {
+            let velocity = (*point - *previous) / dt;
+            let acceleration = gravity
+                + params.force
+                + (wind - velocity) * params.wind
+                + (*rest - *point) * stiffness;
+            *previous = *point;
+            *point += velocity * dt * damping + acceleration * dt * dt;
+        }
+
+        // keep each section at its length, working out from the base
+        let direction = self.rotation * Vec3::Z;
+        for joint in 1..=sections {
+            let parent = self.points[joint - 1];
+            let offset = (self.points[joint] - parent).normalize_or(direction);
+            self.points[joint] = parent + offset * segment_length;
+        }
+    }
+
+    /// The rotation that turns the resting direction of the path into the direction of a section
+    fn section_rotation(&self, section: usize) -> Quat {
+        let rest = self.rotation * Vec3::Z;
+        let direction = (self.points[section + 1] - self.points[section]).normalize_or(rest);
+        Quat::from_rotation_arc(rest, direction)
+    }
+
+    fn rest_point(&self, joint: usize) -> Vec3 {
+        let along = joint as f32 / self.sections() as f32 - 0.5;
+        self.position + self.rotation * Vec3::new(0.0, 0.0, along * self.length())
+    }
+
+    fn length(&self) -> f32 {
+        self.scale.z
+    }
+
+    fn safe_scale(&self) -> Vec3 {
+        self.scale.max(Vec3::splat(f32::EPSILON))
+    }
+}
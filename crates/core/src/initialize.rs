@@ -10,7 +10,7 @@ use benthic_protocol::messages::ui::errors::SessionError;
 use log::error;
 use metaverse_cache::initialize_sqlite::init_sqlite;
 use portpicker::pick_unused_port;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::create_dir_all;
 use std::io;
 use std::path::Path;
@@ -44,6 +44,7 @@ pub async fn initialize(
         inventory_db_location: db_path,
 
         server_acks: HashSet::new(),
+        ack_flush_scheduled: false,
         viewer_acks: HashSet::new(),
 
         state: state.clone(),
@@ -55,6 +56,7 @@ pub async fn initialize(
             ping_latency: Duration::new(0, 0),
             last_ping: time::Instant::now(),
         },
+        circuit_stats: HashMap::new(),
     }
     .start();
     // wait until the mailbox starts
@@ -17,6 +17,8 @@
 //! This crate is under active development, and is not suitable for production use. APIs may change
 //! frequently, and many protocol features are currently unimplemented.
 #![warn(missing_docs)]
+/// Converts custom animations into the JSON format of the default animations
+pub mod animation;
 /// Handles mailbox events to do with handling avatars
 pub mod avatar;
 /// Handles mailbox events required for establishing viewer capabilities
@@ -25,13 +27,27 @@ pub mod capabilities;
 pub mod environment;
 /// This module stores custom error definitions
 pub mod errors;
+/// Soft body simulation of flexible prims, shared by UI frontends
+pub mod flexi;
 /// This module initializes the mailbox
 pub mod initialize;
 /// Handles mailbox events for handling and updating inventory
 pub mod inventory;
+/// Level of detail selection for meshes, shared by UI frontends
+pub mod lod;
+/// Maps the per-face texture entries of objects to the materials of their meshes
+pub mod materials;
+/// Dead reckoning and smoothing for moving objects, shared by UI frontends
+pub mod motion;
 /// Handles mailbox events for retrieving and rendering objects
 pub mod objects;
+/// Tracks the linksets of a region, and holds objects back until their parents arrive
+pub mod scene_graph;
 /// Handles mailbox events required for opening and maintaining the session
 pub mod session;
+/// Bakes the textures of system avatars, and builds their meshes from the default avatar
+pub mod system_avatar;
 /// handles packet sending between UI and core, and core and server
 pub mod transport;
+/// Generates the meshes of primitive geometry objects from their path and profile parameters
+pub mod volume;
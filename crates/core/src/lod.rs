@@ -0,0 +1,50 @@
+use benthic_protocol::messages::ui::mesh_update::LevelOfDetail;
+
+/// Scales how far away objects keep their detail. This matches the viewer's default
+/// RenderVolumeLODFactor.
+pub const DEFAULT_LOD_FACTOR: f32 = 1.25;
+
+/// Every level of detail, from the least detailed to the most
+pub const LEVELS_OF_DETAIL: [LevelOfDetail; 4] = [
+    LevelOfDetail::Lowest,
+    LevelOfDetail::Low,
+    LevelOfDetail::Medium,
+    LevelOfDetail::High,
+];
+
+/// The on screen sizes below which an object drops to the lowest, low and medium levels of
+/// detail
+const LOD_THRESHOLDS: [f32; 3] = [0.03, 0.06, 0.24];
+
+/// Choose the level of detail an object should be drawn at.
+///
+/// Like the viewer, this uses the size of the object on screen, which is the tangent of the
+/// angle its bounding sphere covers as seen from the camera.
+pub fn select_level_of_detail(radius: f32, distance: f32, lod_factor: f32) -> LevelOfDetail {
+    if distance <= 0.0 {
+        return LevelOfDetail::High;
+    }
+    let screen_size = lod_factor * radius / distance;
+    match LOD_THRESHOLDS
+        .iter()
+        .position(|threshold| screen_size <= *threshold)
+    {
+        Some(level) => LEVELS_OF_DETAIL[level],
+        None => LevelOfDetail::High,
+    }
+}
+
+/// Find the level of detail closest to the desired one that is available.
+///
+/// Levels are streamed in over time, so the desired level may not have arrived yet. When two
+/// levels are equally close, the more detailed one is used.
+pub fn closest_available(
+    desired: LevelOfDetail,
+    available: impl Fn(LevelOfDetail) -> bool,
+) -> Option<LevelOfDetail> {
+    let desired = LEVELS_OF_DETAIL.iter().position(|lod| *lod == desired)?;
+    (0..LEVELS_OF_DETAIL.len())
+        .flat_map(|step| [desired + step, desired.wrapping_sub(step)])
+        .filter_map(|index| LEVELS_OF_DETAIL.get(index).copied())
+        .find(|lod| available(*lod))
+}
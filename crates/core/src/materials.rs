@@ -0,0 +1,129 @@
+use benthic_protocol::render_data::RenderFace;
+use glam::Vec2;
+use metaverse_messages::http::{gltf_material::GltfMaterial, render_materials::LegacyMaterial};
+use metaverse_messages::utils::texture_entry::TextureEntry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Apply the repeats, offset and rotation of a face to a texture coordinate.
+///
+/// UVs are in image space like the rest of the render data, with V going down. The viewer
+/// transforms them with V going up, rotating and scaling around the center of the texture before
+/// offsetting them.
+pub fn transform_uv(uv: Vec2, entry: &TextureEntry) -> Vec2 {
+    let centered = Vec2::new(uv.x, 1.0 - uv.y) - 0.5;
+    let (sin, cos) = entry.rotation.sin_cos();
+    let rotated = Vec2::new(
+        centered.x * cos + centered.y * sin,
+        -centered.x * sin + centered.y * cos,
+    );
+    let transformed = rotated * Vec2::new(entry.repeat_u, entry.repeat_v)
+        + Vec2::new(entry.offset_u, entry.offset_v)
+        + 0.5;
+    Vec2::new(transformed.x, 1.0 - transformed.y)
+}
+
+/// Build the material of a face from its texture entry.
+///
+/// The face covers `index_count` indices of the object starting at `first_index`. Textures are
+/// looked up by ID in the textures downloaded for the object, and faces whose texture is missing
+/// are rendered with only their color.
+pub fn render_face(
+    entry: &TextureEntry,
+    first_index: usize,
+    index_count: usize,
+    textures: &HashMap<Uuid, PathBuf>,
+) -> RenderFace {
+    let rgba = entry.rgba;
+    RenderFace {
+        first_index,
+        index_count,
+        texture: textures.get(&entry.texture_id).cloned(),
+        color: [
+            srgb_to_linear(rgba.r),
+            srgb_to_linear(rgba.g),
+            srgb_to_linear(rgba.b),
+            rgba.a as f32 / 255.0,
+        ],
+        glow: entry.glow,
+        normal_texture: None,
+        metallic_roughness_texture: None,
+        emissive_texture: None,
+        metallic: 0.0,
+        roughness: 1.0,
+        emissive: [0.0; 3],
+    }
+}
+
+/// The materials of an object's faces, and the textures downloaded for them
+#[derive(Debug, Clone, Default)]
+pub struct ObjectMaterials {
+    /// downloaded textures by texture ID
+    pub textures: HashMap<Uuid, PathBuf>,
+    /// PBR materials by face index, with the object's overrides already applied
+    pub gltf: BTreeMap<u32, GltfMaterial>,
+    /// legacy materials by the material ID of the texture entry
+    pub legacy: HashMap<Uuid, LegacyMaterial>,
+}
+
+impl ObjectMaterials {
+    /// Build the material of a face.
+    ///
+    /// Faces with a PBR material use it in place of the texture and color of their texture
+    /// entry, and keep only the glow. Other faces are built from their texture entry, with the
+    /// normal map and shininess of their legacy material if they have one.
+    pub fn render_face(
+        &self,
+        face: u32,
+        entry: &TextureEntry,
+        first_index: usize,
+        index_count: usize,
+    ) -> RenderFace {
+        let mut render_face = render_face(entry, first_index, index_count, &self.textures);
+        if let Some(material) = self.gltf.get(&face) {
+            let texture = |id: Option<Uuid>| id.and_then(|id| self.textures.get(&id).cloned());
+            render_face.texture = texture(material.base_color_texture);
+            render_face.normal_texture = texture(material.normal_texture);
+            render_face.metallic_roughness_texture = texture(material.metallic_roughness_texture);
+            render_face.emissive_texture = texture(material.emissive_texture);
+            render_face.color = material.base_color;
+            render_face.emissive = material.emissive;
+            render_face.metallic = material.metallic;
+            render_face.roughness = material.roughness;
+        } else if let Some(material) = self.legacy.get(&entry.material_id) {
+            if !material.normal_map.is_nil() {
+                render_face.normal_texture = self.textures.get(&material.normal_map).cloned();
+            }
+            // legacy materials are shiny rather than rough, and reflect the environment rather
+            // than being metallic
+            render_face.roughness = 1.0 - material.specular_exponent as f32 / 255.0;
+            render_face.metallic = material.environment_intensity as f32 / 255.0;
+        }
+        render_face
+    }
+
+    /// The IDs of every texture the faces use
+    pub fn texture_ids(&self, texture_entry: &TextureEntry) -> HashSet<Uuid> {
+        std::iter::once(texture_entry.texture_id)
+            .chain(texture_entry.faces.values().map(|face| face.texture_id))
+            .chain(self.gltf.values().flat_map(|material| material.textures()))
+            .chain(
+                self.legacy
+                    .values()
+                    .map(|material| material.normal_map)
+                    .filter(|id| !id.is_nil()),
+            )
+            .collect()
+    }
+}
+
+/// Face colors are sent in sRGB, while material colors are linear
+fn srgb_to_linear(channel: u8) -> f32 {
+    let c = channel as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
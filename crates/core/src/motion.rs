@@ -0,0 +1,129 @@
+use glam::{Quat, Vec3};
+
+/// How long an object keeps moving after its last update, in seconds. Past this, the object is
+/// held in place until the simulator sends a new update.
+pub const MAX_EXTRAPOLATION_TIME: f32 = 1.0;
+/// How long it takes to correct most of the difference between where an object was predicted to
+/// be and where the simulator says it is, in seconds.
+pub const CORRECTION_TIME: f32 = 0.1;
+/// Corrections further than this are applied immediately instead of smoothed, in meters. This
+/// happens when objects are teleported, or moved by hand.
+pub const SNAP_DISTANCE: f32 = 4.0;
+
+/// The motion of an object, as last reported by the simulator.
+///
+/// Velocity and acceleration are in meters per second, and angular velocity is in radians per
+/// second around the world axes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionState {
+    /// position of the object
+    pub position: Vec3,
+    /// velocity of the object
+    pub velocity: Vec3,
+    /// acceleration of the object
+    pub acceleration: Vec3,
+    /// rotation of the object
+    pub rotation: Quat,
+    /// angular velocity of the object
+    pub angular_velocity: Vec3,
+}
+
+impl MotionState {
+    /// An object that is not moving
+    pub fn at_rest(position: Vec3, rotation: Quat) -> Self {
+        Self {
+            position,
+            velocity: Vec3::ZERO,
+            acceleration: Vec3::ZERO,
+            rotation,
+            angular_velocity: Vec3::ZERO,
+        }
+    }
+
+    /// Predict the position and rotation of the object after `elapsed` seconds, assuming nothing
+    /// changes its motion. Prediction stops after [`MAX_EXTRAPOLATION_TIME`].
+    pub fn extrapolate(&self, elapsed: f32) -> (Vec3, Quat) {
+        let elapsed = elapsed.clamp(0.0, MAX_EXTRAPOLATION_TIME);
+        let position =
+            self.position + self.velocity * elapsed + 0.5 * self.acceleration * elapsed * elapsed;
+        let rotation =
+            (Quat::from_scaled_axis(self.angular_velocity * elapsed) * self.rotation).normalize();
+        (position, rotation)
+    }
+}
+
+/// Smooths an object's motion between updates from the simulator.
+///
+/// Between updates the object is moved by dead reckoning. When a new update arrives, the object
+/// is not snapped to it. Instead, the difference between the displayed and the reported transform
+/// is kept as an error that decays over [`CORRECTION_TIME`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionSmoother {
+    /// the last motion reported by the simulator
+    pub state: MotionState,
+    /// seconds since the state was reported
+    pub elapsed: f32,
+    /// remaining position correction
+    pub position_error: Vec3,
+    /// remaining rotation correction
+    pub rotation_error: Quat,
+}
+
+impl MotionSmoother {
+    /// Start smoothing from a reported motion, without any correction
+    pub fn new(state: MotionState) -> Self {
+        Self {
+            state,
+            elapsed: 0.0,
+            position_error: Vec3::ZERO,
+            rotation_error: Quat::IDENTITY,
+        }
+    }
+
+    /// Replace the reported motion. The displayed transform stays where it is, and moves toward
+    /// the new motion over the next few frames.
+    pub fn update(&mut self, state: MotionState) {
+        let (position, rotation) = self.transform();
+        self.state = state;
+        self.elapsed = 0.0;
+
+        let position_error = position - state.position;
+        if position_error.length() > SNAP_DISTANCE {
+            self.position_error = Vec3::ZERO;
+            self.rotation_error = Quat::IDENTITY;
+        } else {
+            self.position_error = position_error;
+            self.rotation_error = (rotation * state.rotation.inverse()).normalize();
+        }
+    }
+
+    /// Advance time by `delta` seconds, and return the transform to display
+    pub fn advance(&mut self, delta: f32) -> (Vec3, Quat) {
+        self.elapsed += delta;
+        let remaining = (-delta.max(0.0) / CORRECTION_TIME).exp();
+        self.position_error *= remaining;
+        self.rotation_error = Quat::IDENTITY.slerp(self.rotation_error, remaining);
+        self.transform()
+    }
+
+    /// Whether the object has stopped moving and has no correction left to apply. Settled objects
+    /// don't need to be advanced until their next update.
+    pub fn is_settled(&self) -> bool {
+        let stopped = self.elapsed >= MAX_EXTRAPOLATION_TIME
+            || (self.state.velocity == Vec3::ZERO
+                && self.state.acceleration == Vec3::ZERO
+                && self.state.angular_velocity == Vec3::ZERO);
+        stopped
+            && self.position_error.length_squared() < 1e-8
+            && self.rotation_error.abs_diff_eq(Quat::IDENTITY, 1e-6)
+    }
+
+    /// The transform to display, including the remaining correction
+    pub fn transform(&self) -> (Vec3, Quat) {
+        let (position, rotation) = self.state.extrapolate(self.elapsed);
+        (
+            position + self.position_error,
+            (self.rotation_error * rotation).normalize(),
+        )
+    }
+}
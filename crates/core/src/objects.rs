@@ -1,26 +1,48 @@
 use super::session::Mailbox;
-use crate::avatar::HandleNewAvatar;
+use crate::avatar::{AddWornMesh, BuildWornAvatar, HandleNewAvatar, WORN_MESH_SETTLE_TIME};
 use crate::initialize::create_sub_agent_dir;
 use crate::initialize::create_sub_object_dir;
+use crate::lod::LEVELS_OF_DETAIL;
+use crate::materials::ObjectMaterials;
+use crate::scene_graph::{PARENT_TIMEOUT, SceneNode};
 use crate::session::OutgoingPacket;
 use crate::session::SendUIMessage;
+use crate::session::Session;
+use crate::system_avatar::bakes_on_mesh_path;
+use crate::transport::http_handler::download_gltf_material;
+use crate::transport::http_handler::download_mesh_source;
+use crate::transport::http_handler::download_render_materials;
 use crate::transport::http_handler::download_renderable_mesh;
 use crate::transport::http_handler::download_texture;
+use crate::volume::HIGH_DETAIL;
+use crate::volume::Volume;
+use actix::ActorFutureExt;
+use actix::Addr;
 use actix::AsyncContext;
 use actix::ResponseFuture;
 use actix::WrapFuture;
 use actix::{Handler, Message};
+use benthic_protocol::messages::ui::attachment_update::AttachmentUpdate;
+use benthic_protocol::messages::ui::flexi_update::FlexiUpdate;
+use benthic_protocol::messages::ui::kill_object::KillObject;
+use benthic_protocol::messages::ui::light_update::{LightUpdate, Projector};
+use benthic_protocol::messages::ui::mesh_update::LevelOfDetail;
 use benthic_protocol::messages::ui::mesh_update::MeshType;
 use benthic_protocol::messages::ui::mesh_update::MeshUpdate;
+use benthic_protocol::messages::ui::transform_update::TransformUpdate;
 use benthic_protocol::messages::ui::ui_messages::UIMessage;
+use benthic_protocol::render_data::RenderObject;
 use glam::Quat;
 use glam::Vec3;
 use log::info;
 use log::{error, warn};
 use metaverse_agent::avatar::Avatar;
+use metaverse_cache::object_update::set_object_transform_by_id;
 use metaverse_cache::object_update::sqlite_check_cache;
-use metaverse_cache::object_update::sqlite_get_object_scale_rotation_position;
-use metaverse_cache::object_update::sqlite_get_parent;
+use metaverse_cache::object_update::sqlite_delete_objects;
+use metaverse_cache::object_update::sqlite_get_attachment_state;
+use metaverse_cache::object_update::sqlite_get_generator_object;
+use metaverse_cache::object_update::sqlite_get_object_light;
 use metaverse_cache::object_update::sqlite_insert_object_update;
 use metaverse_cache::object_update::sqlite_update_object_glb_path;
 use metaverse_cache::object_update::sqlite_update_object_json_path;
@@ -28,20 +50,36 @@ use metaverse_cache::object_update::GeneratorObject;
 use metaverse_cache::object_update::ObjectCache;
 use metaverse_mesh::mesh::generate::generate_object_mesh;
 use metaverse_messages::http::capabilities::Capability;
+use metaverse_messages::http::scene::SculptType;
 use metaverse_messages::packet::packet_protocol::Packet;
+use metaverse_messages::udp::object::generic_streaming_message::GenericStreamingMessage;
+use metaverse_messages::udp::object::generic_streaming_message::METHOD_MATERIAL_OVERRIDE;
+use metaverse_messages::udp::object::generic_streaming_message::MaterialOverride;
 use metaverse_messages::udp::object::improved_terse_object_update::ImprovedTerseObjectUpdate;
 use metaverse_messages::udp::object::object_update::AttachItem;
 use metaverse_messages::udp::object::object_update::ExtraParams;
+use metaverse_messages::udp::object::object_update::LightData;
+use metaverse_messages::udp::object::object_update::MaterialsData;
+use metaverse_messages::udp::object::object_update::ProjectionData;
+use metaverse_messages::udp::object::object_update::SculptData;
 use metaverse_messages::udp::object::object_update_cached::ObjectUpdateCached;
 use metaverse_messages::udp::object::request_multiple_objects::CacheMissType;
 use metaverse_messages::udp::object::request_multiple_objects::RequestMultipleObjects;
+use metaverse_messages::utils::attachment_point::AttachmentPoint;
+use metaverse_messages::utils::bake::BakeType;
 use metaverse_messages::utils::object_types::ObjectType;
+use metaverse_messages::utils::path::Path;
 use metaverse_messages::utils::texture_entry::TextureEntry;
 use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fs::File;
 use std::io;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime};
 use uuid::Uuid;
 
 /// Handles received ObjectUpdate packets.
@@ -56,6 +94,7 @@ use uuid::Uuid;
 /// - Dispatches a [`Avatar`] message if the object is an avatar
 /// - Dispatches a [`HandleAttachment`] message if the object is an attachment object
 /// - Dispatches a [`HandlePrim`] message if the object is a prim
+/// - Dispatches a [`UpdateSceneNode`] message to add the object to the scene graph
 #[derive(Debug, Message, Clone)]
 #[rtype(result = "()")]
 pub struct HandleObjectUpdate {
@@ -90,12 +129,52 @@ pub struct HandleObjectUpdate {
     ///
     /// Can contain definitions for things like sculpts (which include meshes), flexi data, light, and more.
     pub extra_params: Option<Vec<ExtraParams>>,
+    /// The path and profile parameters that define the shape of a prim
+    pub path: Path,
 
     /// Object's texture data
     pub texture: TextureEntry,
 
     /// CRC to enable cache invalidation
     pub crc: u32,
+
+    /// The state of the object. For attachments, this holds the point they are attached to.
+    pub state: u8,
+}
+
+impl HandleObjectUpdate {
+    /// The light the object gives off, if it is a light
+    pub fn light(&self) -> Option<&LightData> {
+        self.extra_params
+            .iter()
+            .flatten()
+            .find_map(|param| match param {
+                ExtraParams::Light(light) => Some(light),
+                _ => None,
+            })
+    }
+
+    /// How the object's light is projected, if it is a spot light
+    pub fn projection(&self) -> Option<&ProjectionData> {
+        self.extra_params
+            .iter()
+            .flatten()
+            .find_map(|param| match param {
+                ExtraParams::Projection(projection) => Some(projection),
+                _ => None,
+            })
+    }
+
+    /// The PBR materials of the object's faces, if it has any
+    pub fn materials(&self) -> Option<&MaterialsData> {
+        self.extra_params
+            .iter()
+            .flatten()
+            .find_map(|param| match param {
+                ExtraParams::Materials(materials) => Some(materials),
+                _ => None,
+            })
+    }
 }
 
 /// Begins the pipeline for handling a prim object.
@@ -106,6 +185,9 @@ pub struct HandleObjectUpdate {
 ///
 /// # Effects
 /// - Dispatches a [`DownloadObject`] message to retrieve full object data from the server
+/// - Dispatches a [`GeneratePrimMesh`] message if the object is primitive geometry or a sculpt
+/// - Dispatches a [`FlexiUpdate`] UI message if the object is a flexible prim
+/// - Dispatches a [`LightUpdate`] UI message if the object is a light
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct HandlePrim {
@@ -115,29 +197,55 @@ pub struct HandlePrim {
 
 /// Begins the pipeline for handling an attachment object
 ///
+/// Attachments are the root objects of linksets worn by avatars. Their parent is the avatar, and
+/// their transform is relative to the joint or HUD point they are attached to.
+///
 /// # Cause
 /// - [`HandleObjectUpdate`]
 ///
-/// TODO: currently a stub with no effects
+/// # Effects
+/// - Dispatches a [`HandlePrim`] message to build the attachment's mesh
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct HandleAttachment {
     /// The attachment object to handle
     pub object: HandleObjectUpdate,
-    /// The attach item data
-    pub item: AttachItem,
+}
+
+/// Message for recording the point a cached attachment is attached to
+///
+/// ObjectUpdateCached packets don't contain the state of the object, so the state of attachments
+/// is kept in the cache along with the rest of the object.
+///
+/// # Cause
+/// - [`HandleObjectUpdateCached`] for a cached attachment
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct SetAttachmentPoint {
+    /// The scene local ID of the attachment
+    pub local_id: u32,
+    /// The point the attachment is attached to
+    pub point: AttachmentPoint,
 }
 
 /// Message for downloading object update from its capability endpoint
 ///
 /// This downloads the object data, writes the object to disk as json, triggers the metaverse-mesh
-/// library to generate its finalized file, and then triggers a MeshUpdate
+/// library to generate its finalized file, and then triggers a MeshUpdate. Every level of detail
+/// the mesh has is handled this way, starting with the lowest, so the UI can show the object
+/// before its most detailed level has finished downloading. The textures and materials of its
+/// faces are downloaded first, and shared by every level of detail.
+///
+/// Rigged meshes worn by an avatar bend with its skeleton, so they aren't drawn on their own.
+/// Those worn by other agents are added to the agent's avatar, and those worn by the user are
+/// already part of the avatar built from their outfit.
 ///  
 /// # Cause
 /// - [`HandlePrim`]
 ///
 /// # Effects
 /// - Dispatches a [`MeshUpdate`] to inform the UI of a new object
+/// - Dispatches an [`AddRiggedMesh`] message for rigged meshes
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct DownloadObject {
@@ -151,12 +259,68 @@ pub struct DownloadObject {
     pub position: Vec3,
 }
 
+/// Message for drawing a level of detail of a downloaded rigged mesh, once it is known who wears
+/// it
+///
+/// The avatar wearing a rigged attachment often arrives after the attachment's mesh has started
+/// downloading, so its wearer is only looked up once the mesh is ready. Meshes whose linkset
+/// hasn't reached its root yet are held in the session until it has.
+///
+/// # Cause
+/// - [`DownloadObject`] for a rigged mesh
+/// - [`UpdateSceneNode`] connecting a held rigged mesh to the root of its linkset
+///
+/// # Effects
+/// - Dispatches an [`AddWornMesh`] message for rigged meshes worn by other agents
+/// - Dispatches a [`GenerateMeshFromJson`] message for rigged meshes that aren't worn
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct AddRiggedMesh {
+    /// Minimal object for accessing sqlite fields
+    pub object: GeneratorObject,
+    /// ID of the mesh asset
+    pub asset_id: Uuid,
+    /// Path to the object's dir
+    pub base_dir: PathBuf,
+    /// The level of detail of the mesh
+    pub lod: LevelOfDetail,
+    /// the downloaded mesh
+    pub render_object: RenderObject,
+}
+
+/// Message for generating the mesh of a primitive geometry object or a sculpted prim
+///
+/// These prims have no mesh asset to download. Primitive geometry is generated from the path and
+/// profile parameters of the ObjectUpdate, and sculpted prims are generated from their sculpt map
+/// texture. The textures and materials of the faces are downloaded along with it. The result is
+/// written to disk as json, and then passed to the metaverse-mesh library like any other object.
+///
+/// # Cause
+/// - [`HandlePrim`]
+///
+/// # Effects
+/// - Dispatches a [`GenerateMeshFromJson`] message to create the prim's mesh file
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct GeneratePrimMesh {
+    /// the prim to generate the mesh of
+    pub object: HandleObjectUpdate,
+    /// the sculpt map to generate the mesh from, if the prim is sculpted
+    pub sculpt: Option<SculptData>,
+}
+
 /// Message for handing improved terse object update packets
 ///
-/// TODO: currently unimplemented
+/// Updates the cached transforms of objects that have moved, and moves them in the UI without
+/// reloading their meshes. Objects that have not been received in a full ObjectUpdate yet are
+/// ignored.
 ///
 /// # Cause
 /// - ImprovedTerseObjectUpdate packet received from UDP socket
+///
+/// # Effects
+/// - Dispatches a [`TransformUpdate`] UI message for each moved object. Children move with their
+///   parents in the UI, so their transforms are relative to their parent.
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct HandleImprovedTerseObjectUpdate {
@@ -173,7 +337,11 @@ pub struct HandleImprovedTerseObjectUpdate {
 /// - HandleObjectUpdateCached packet received from UDP socket
 ///
 /// # Effect
-/// - [`RequestMultipleObjects`] sent to server
+/// - [`RequestMultipleObjects`] sent to server for objects missing from the cache, or with a
+///   material override
+/// - Dispatches a [`LightUpdate`] UI message for cached objects that are lights
+/// - Dispatches a [`SetAttachmentPoint`] message for cached objects that are attachments
+/// - Dispatches a [`UpdateSceneNode`] message for each cached object
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct HandleObjectUpdateCached {
@@ -181,11 +349,67 @@ pub struct HandleObjectUpdateCached {
     pub object_update_cached: ObjectUpdateCached,
 }
 
+/// Message for handling KillObject packets
+///
+/// Removes the killed objects and their children from the object cache and the scene graph, and
+/// removes any killed avatars from the session.
+///
+/// # Cause
+/// - KillObject packet received from UDP socket
+///
+/// # Effect
+/// - Dispatches a [`KillObject`] UI message to despawn the objects and avatars
+/// - Dispatches a [`BuildWornAvatar`] message for avatars whose rigged meshes were detached
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct HandleKillObject {
+    /// The scene local IDs of the objects to remove
+    pub object_ids: Vec<u32>,
+}
+
+/// Message for handling GenericStreamingMessage packets
+///
+/// Stores the overrides objects make to their PBR materials, so they can be applied when the
+/// objects' materials are downloaded. Messages with other methods are ignored.
+///
+/// # Cause
+/// - GenericStreamingMessage packet received from UDP socket
+///
+/// # Effect
+/// - [`RequestMultipleObjects`] sent to server if the object is already in the scene and its
+///   override changed, so it is rebuilt with the override
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct HandleGenericStreamingMessage {
+    /// the generic streaming message packet to handle
+    pub message: GenericStreamingMessage,
+}
+
+/// Message for adding an object to the scene graph, or updating its place in it
+///
+/// # Cause
+/// - [`HandleObjectUpdate`]
+/// - [`HandleObjectUpdateCached`]
+///
+/// # Effects
+/// - Dispatches the [`MeshUpdate`] UI messages of the object and its descendants, if they were
+///   waiting for this object to connect them to their root
+/// - Dispatches the held [`AddRiggedMesh`] messages of rigged meshes it connected to their root
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct UpdateSceneNode {
+    /// The scene local ID of the object
+    pub local_id: u32,
+    /// The object's parent, and its transform relative to it
+    pub node: SceneNode,
+}
+
 /// Helper message to generate mesh from stored json
 ///
 /// # Cause
 /// [`HandleObjectUpdateCached`]
 /// [`DownloadObject`]
+/// [`GeneratePrimMesh`]
 ///
 /// # Effect
 /// [`RenderObjectFromFile`]
@@ -200,10 +424,15 @@ pub struct GenerateMeshFromJson {
     pub asset_id: Uuid,
     /// Minimal object for accessing sqlite fields
     pub object: GeneratorObject,
+    /// The level of detail the json holds
+    pub lod: LevelOfDetail,
 }
 
 /// Helper message to render objects from stored json files
 ///
+/// The meshes of child objects are held by the scene graph until every parent up to their root
+/// has arrived, and are sent with their transform relative to their parent.
+///
 /// # Cause
 /// [`HandleObjectUpdateCached`]
 /// [`GenerateMeshFromJson`]
@@ -221,27 +450,105 @@ pub struct RenderObjectFromFile {
     pub base_dir: PathBuf,
     /// minimal oject for accessing sqlite fields
     pub object: GeneratorObject,
-    /// How many times has this object been retried.
-    /// this if for timing out failed parent lookups
-    pub retry_count: u32,
-}
-
-fn backoff_ms(retry: u32) -> u64 {
-    let base = 50;
-    let cap = 2000;
-
-    (base * 2u64.pow(retry.min(6))).min(cap)
+    /// The level of detail of the mesh
+    pub lod: LevelOfDetail,
 }
 
 impl Handler<HandleImprovedTerseObjectUpdate> for Mailbox {
     type Result = ();
     fn handle(
         &mut self,
-        _msg: HandleImprovedTerseObjectUpdate,
-        _ctx: &mut Self::Context,
+        msg: HandleImprovedTerseObjectUpdate,
+        ctx: &mut Self::Context,
     ) -> Self::Result {
-        // TODO: unimplemented
-        warn!("ImprovedTerseObjectUpdate packet received. Currently unimplemented.")
+        let db_pool = self.inventory_db_connection.clone();
+        let addr = ctx.address();
+        let Some(region_id) = self
+            .session
+            .as_ref()
+            .map(|session| session.region_data.region_id.clone())
+        else {
+            return;
+        };
+
+        // the scene graph is kept up to date, so avatars sitting on moving objects can be placed
+        let mut objects = Vec::new();
+        for terse in msg.improved_terse_object_update.objects {
+            let world_transform = self.session.as_mut().and_then(|session| {
+                session
+                    .scene_graph
+                    .set_transform(terse.local_id, terse.position, terse.rotation);
+                session.scene_graph.world_transform(terse.local_id)
+            });
+            objects.push((terse, world_transform));
+        }
+
+        let fut = async move {
+            let mut avatar_positions = Vec::new();
+            for (terse, world_transform) in objects {
+                let cached =
+                    sqlite_get_generator_object(&db_pool, terse.local_id, region_id.clone()).await;
+                let object = match cached {
+                    Ok(object) => object,
+                    Err(e) => {
+                        info!(
+                            "Terse update for {} received before its ObjectUpdate: {:?}",
+                            terse.local_id, e
+                        );
+                        continue;
+                    }
+                };
+
+                if let Err(e) = set_object_transform_by_id(
+                    &db_pool,
+                    terse.local_id,
+                    region_id.clone(),
+                    terse.position,
+                    terse.rotation,
+                    object.scale,
+                )
+                .await
+                {
+                    error!("Failed to update transform of {}: {:?}", terse.local_id, e);
+                    continue;
+                }
+
+                // objects are parented to their parents in the UI, but avatars aren't parented to the
+                // objects they sit on, so they are moved by their position in the region
+                let agent_id = terse.avatar.then_some(object.full_id);
+                let (position, rotation) = match agent_id {
+                    Some(agent_id) => {
+                        let world_transform =
+                            world_transform.unwrap_or((terse.position, terse.rotation));
+                        avatar_positions.push((agent_id, world_transform.0));
+                        world_transform
+                    }
+                    None => (terse.position, terse.rotation),
+                };
+                addr.do_send(SendUIMessage {
+                    ui_message: UIMessage::new_transform_update(TransformUpdate {
+                        scene_id: terse.local_id,
+                        id: agent_id,
+                        position,
+                        rotation,
+                        velocity: terse.velocity,
+                        acceleration: terse.acceleration,
+                        angular_velocity: terse.angular_velocity,
+                    }),
+                });
+            }
+            avatar_positions
+        };
+
+        ctx.spawn(fut.into_actor(self).map(|avatar_positions, act, _ctx| {
+            if let Some(session) = act.session.as_mut() {
+                for (agent_id, position) in avatar_positions {
+                    if let Some(avatar) = session.avatars.get_mut(&agent_id) {
+                        avatar.position = position;
+                    }
+                }
+            }
+        }));
     }
 }
 
@@ -258,28 +565,80 @@ impl Handler<HandleObjectUpdateCached> for Mailbox {
         let region_id = session.region_data.region_id.clone();
         let session_id = session.session_id;
         let agent_id = session.agent_id;
+        let material_overrides = session.material_overrides.clone();
 
         Box::pin(async move {
             let mut requests = Vec::new();
             for object in &msg.object_update_cached.objects {
+                // cached meshes were built without the object's current material override, so
+                // the object is requested in full to build it with the override
+                if material_overrides.lock().unwrap().contains_key(&object.id) {
+                    requests.push((CacheMissType::Normal, object.id));
+                    continue;
+                }
                 match sqlite_check_cache(&db_pool, object.id, object.crc, region_id.clone()).await {
                     Ok((asset_id, json_path, glb, generator_object)) => {
+                        // the attachment point has to be known before the mesh is sent
+                        match sqlite_get_attachment_state(&db_pool, object.id, region_id.clone())
+                            .await
+                        {
+                            Ok(Some(state)) => addr.do_send(SetAttachmentPoint {
+                                local_id: object.id,
+                                point: AttachmentPoint::from_state(state),
+                            }),
+                            Ok(None) => {}
+                            Err(e) => warn!("Failed to get state of {}: {:?}", object.id, e),
+                        }
+                        addr.do_send(UpdateSceneNode {
+                            local_id: object.id,
+                            node: SceneNode::new(
+                                generator_object.parent_id,
+                                generator_object.position,
+                                generator_object.rotation,
+                                generator_object.scale,
+                            ),
+                        });
+                        match sqlite_get_object_light(&db_pool, object.id, region_id.clone()).await
+                        {
+                            Ok(Some((light, projection))) => addr.do_send(SendUIMessage {
+                                ui_message: UIMessage::new_light_update(light_update(
+                                    object.id,
+                                    &light,
+                                    projection.as_ref(),
+                                )),
+                            }),
+                            Ok(None) => {}
+                            Err(e) => warn!("Failed to get light of {}: {:?}", object.id, e),
+                        }
+
+                        // one object failing shouldn't keep the rest from loading
                         let base_dir = match create_sub_object_dir(&asset_id.to_string()) {
                             Ok(base_dir) => base_dir,
                             Err(e) => {
-                                error!("failed to create base dir: {:?}", e);
-                                return;
+                                error!("failed to create base dir for {}: {:?}", object.id, e);
+                                continue;
                             }
                         };
 
                         if let Some(mesh_path) = glb {
-                            addr.do_send(RenderObjectFromFile {
-                                mesh_path,
-                                base_dir,
-                                asset_id,
-                                object: generator_object,
-                                retry_count: 0,
-                            })
+                            // the cache holds the most detailed mesh, and the other levels of
+                            // detail are stored next to it
+                            for lod in LEVELS_OF_DETAIL {
+                                let mesh_path = match lod {
+                                    LevelOfDetail::High => mesh_path.clone(),
+                                    _ => base_dir
+                                        .join(format!("{}.glb", lod_file_name(asset_id, lod))),
+                                };
+                                if lod == LevelOfDetail::High || mesh_path.exists() {
+                                    addr.do_send(RenderObjectFromFile {
+                                        mesh_path,
+                                        base_dir: base_dir.clone(),
+                                        asset_id,
+                                        object: generator_object.clone(),
+                                        lod,
+                                    })
+                                }
+                            }
                         } else {
                             warn!(
                                 "Generated mesh file {:?} not found. Generating now.",
@@ -290,6 +649,7 @@ impl Handler<HandleObjectUpdateCached> for Mailbox {
                                 base_dir,
                                 asset_id,
                                 object: generator_object,
+                                lod: LevelOfDetail::High,
                             });
                         }
                     }
@@ -318,6 +678,156 @@ impl Handler<HandleObjectUpdateCached> for Mailbox {
     }
 }
 
+impl Handler<HandleKillObject> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: HandleKillObject, ctx: &mut Self::Context) -> Self::Result {
+        let session = match self.session.as_mut() {
+            Some(session) => session,
+            None => return,
+        };
+
+        let db_pool = self.inventory_db_connection.clone();
+        let region_id = session.region_data.region_id.clone();
+        let mut scene_ids = msg.object_ids.clone();
+        for object_id in &msg.object_ids {
+            for removed in session.scene_graph.remove(*object_id) {
+                if !scene_ids.contains(&removed) {
+                    scene_ids.push(removed);
+                }
+            }
+        }
+
+        let fut = async move { sqlite_delete_objects(&db_pool, &msg.object_ids, region_id).await };
+        ctx.spawn(fut.into_actor(self).map(|result, act, ctx| {
+            // children of the killed objects are removed along with them
+            let deleted = result.unwrap_or_else(|e| {
+                error!("Failed to remove killed objects from cache: {:?}", e);
+                Vec::new()
+            });
+
+            let mut agent_ids = Vec::new();
+            for (local_id, full_id) in deleted {
+                if !scene_ids.contains(&local_id) {
+                    scene_ids.push(local_id);
+                }
+                if let Some(session) = act.session.as_mut()
+                    && session.avatars.remove(&full_id).is_some()
+                {
+                    agent_ids.push(full_id);
+                }
+            }
+
+            if let Some(session) = act.session.as_mut() {
+                let mut material_overrides = session.material_overrides.lock().unwrap();
+                for scene_id in &scene_ids {
+                    material_overrides.remove(scene_id);
+                    session.avatar_scene_ids.remove(scene_id);
+                    session.attachments.remove(scene_id);
+                    session.pending_rigged_meshes.remove(scene_id);
+                }
+                drop(material_overrides);
+
+                for agent_id in &agent_ids {
+                    session.worn_meshes.remove(agent_id);
+                    session.avatar_animations.remove(agent_id);
+                }
+                // avatars are rebuilt without the meshes that were detached from them
+                for (agent_id, meshes) in session.worn_meshes.iter_mut() {
+                    let worn = meshes.len();
+                    meshes.retain(|scene_id, _| !scene_ids.contains(scene_id));
+                    if meshes.len() != worn
+                        && let Some(avatar) = session.avatars.get_mut(agent_id)
+                    {
+                        avatar.last_update = SystemTime::now();
+                        ctx.notify_later(
+                            BuildWornAvatar {
+                                agent_id: *agent_id,
+                            },
+                            WORN_MESH_SETTLE_TIME,
+                        );
+                    }
+                }
+            }
+
+            ctx.address().do_send(SendUIMessage {
+                ui_message: UIMessage::new_kill_object(KillObject {
+                    scene_ids,
+                    agent_ids,
+                }),
+            });
+        }));
+    }
+}
+
+impl Handler<HandleGenericStreamingMessage> for Mailbox {
+    type Result = ();
+    fn handle(
+        &mut self,
+        msg: HandleGenericStreamingMessage,
+        ctx: &mut Self::Context,
+    ) -> Self::Result {
+        if msg.message.method != METHOD_MATERIAL_OVERRIDE {
+            return;
+        }
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+        let material_override = match MaterialOverride::from_bytes(&msg.message.data) {
+            Ok(material_override) => material_override,
+            Err(e) => {
+                error!("Failed to parse material override: {:?}", e);
+                return;
+            }
+        };
+        let local_id = material_override.local_id;
+        let previous = session
+            .material_overrides
+            .lock()
+            .unwrap()
+            .insert(local_id, material_override.clone());
+
+        // objects that have already been built read their overrides before this one arrived
+        if session.scene_graph.get(local_id).is_some() && previous != Some(material_override) {
+            ctx.address().do_send(OutgoingPacket {
+                packet: Packet::new_request_multiple_objects(RequestMultipleObjects {
+                    session_id: session.session_id,
+                    agent_id: session.agent_id,
+                    requests: vec![(CacheMissType::Normal, local_id)],
+                }),
+            });
+        }
+    }
+}
+
+impl Handler<UpdateSceneNode> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: UpdateSceneNode, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        for mesh_update in session.scene_graph.insert(msg.local_id, msg.node) {
+            send_mesh_update(session, &ctx.address(), mesh_update);
+        }
+        // rigged meshes can be routed once it is known if an avatar is at the root of their
+        // linkset
+        let connected: Vec<u32> = session
+            .pending_rigged_meshes
+            .keys()
+            .copied()
+            .filter(|local_id| session.scene_graph.is_connected(*local_id))
+            .collect();
+        for local_id in connected {
+            for mesh in session
+                .pending_rigged_meshes
+                .remove(&local_id)
+                .unwrap_or_default()
+            {
+                ctx.address().do_send(mesh);
+            }
+        }
+    }
+}
+
 impl Handler<HandleObjectUpdate> for Mailbox {
     type Result = ResponseFuture<()>;
     fn handle(&mut self, msg: HandleObjectUpdate, ctx: &mut Self::Context) -> Self::Result {
@@ -325,12 +835,26 @@ impl Handler<HandleObjectUpdate> for Mailbox {
         let addr = ctx.address();
         let msg_cloned = msg.clone();
 
-        let session = match self.session.as_ref() {
+        let session = match self.session.as_mut() {
             Some(session) => session,
             None => return Box::pin(async {}),
         };
         let region_id = session.region_data.region_id.clone();
+        if msg.object_type == ObjectType::Avatar {
+            session.avatar_scene_ids.insert(msg.local_id, msg.full_id);
+        }
+        addr.do_send(UpdateSceneNode {
+            local_id: msg.local_id,
+            node: SceneNode::new(msg.parent_id, msg.position, msg.rotation, msg.scale),
+        });
         Box::pin(async move {
+            // prims whose name_value can be parsed as an attach item are attachments
+            let is_attachment = msg.object_type == ObjectType::Prim
+                && msg
+                    .name_value
+                    .clone()
+                    .is_some_and(|name_value| AttachItem::parse_attach_item(name_value).is_ok());
+
             // all object updates first should be added to the db.
             // if they cannot be added, the object should be retried.
             sqlite_insert_object_update(
@@ -345,6 +869,9 @@ impl Handler<HandleObjectUpdate> for Mailbox {
                     position: msg.position,
                     rotation: msg.rotation,
                     scale: msg.scale,
+                    light: msg.light().cloned(),
+                    projection: msg.projection().cloned(),
+                    attachment_state: is_attachment.then_some(msg.state),
                 },
             )
             .await
@@ -355,20 +882,9 @@ impl Handler<HandleObjectUpdate> for Mailbox {
 
             match msg.object_type {
                 ObjectType::Prim => {
-                    // if the msg.name_value can be parsed as an attachment, handle it as an
-                    // attachment.
-                    if let Some(name_value) = msg.name_value.clone() {
-                        match AttachItem::parse_attach_item(name_value) {
-                            Ok(item) => {
-                                addr.do_send(HandleAttachment { object: msg, item });
-                            }
-                            Err(_) => {
-                                // parsing failed, treat as generic
-                                addr.do_send(HandlePrim { object: msg });
-                            }
-                        }
+                    if is_attachment {
+                        addr.do_send(HandleAttachment { object: msg });
                     } else {
-                        // no name_value, treat as generic
                         addr.do_send(HandlePrim { object: msg });
                     }
                 }
@@ -401,56 +917,92 @@ impl Handler<HandleObjectUpdate> for Mailbox {
 impl Handler<HandlePrim> for Mailbox {
     type Result = ();
     fn handle(&mut self, msg: HandlePrim, ctx: &mut Self::Context) -> Self::Result {
-        if let Some(extra_params) = &msg.object.extra_params {
-            for param in extra_params {
-                match param {
-                    ExtraParams::Sculpt(sculpt) => {
-                        ctx.address().do_send(DownloadObject {
-                            asset_id: sculpt.texture_id,
-                            texture_id: Uuid::nil(),
-                            object: msg.object.clone(),
-                            position: msg.object.position,
-                        });
-                    }
-                    _ => {
-                        warn!("Recieved a non sculpt objectupdate. Currently unimplemented")
-                    }
-                }
+        if let Some(flexi) =
+            msg.object
+                .extra_params
+                .iter()
+                .flatten()
+                .find_map(|param| match param {
+                    ExtraParams::Flexi(flexi) => Some(flexi),
+                    _ => None,
+                })
+        {
+            ctx.address().do_send(SendUIMessage {
+                ui_message: UIMessage::new_flexi_update(FlexiUpdate {
+                    scene_id: msg.object.local_id,
+                    softness: flexi.softness,
+                    tension: flexi.tension,
+                    drag: flexi.drag,
+                    gravity: flexi.gravity,
+                    wind: flexi.wind,
+                    force: flexi.force,
+                }),
+            });
+        }
+
+        if let Some(light) = msg.object.light() {
+            ctx.address().do_send(SendUIMessage {
+                ui_message: UIMessage::new_light_update(light_update(
+                    msg.object.local_id,
+                    light,
+                    msg.object.projection(),
+                )),
+            });
+        }
+
+        let sculpt = msg
+            .object
+            .extra_params
+            .iter()
+            .flatten()
+            .find_map(|param| match param {
+                ExtraParams::Sculpt(sculpt) => Some(sculpt.clone()),
+                _ => None,
+            });
+
+        match sculpt {
+            Some(sculpt) if sculpt.sculpt_type == SculptType::Mesh => {
+                ctx.address().do_send(DownloadObject {
+                    asset_id: sculpt.texture_id,
+                    texture_id: Uuid::nil(),
+                    object: msg.object.clone(),
+                    position: msg.object.position,
+                })
             }
-        };
+            Some(sculpt) if sculpt.sculpt_type == SculptType::Unknown => {
+                warn!("Unknown sculpt type for {}", msg.object.full_id)
+            }
+            // legacy sculpts are built from their sculpt map, and everything else from the prim's
+            // shape parameters
+            sculpt => ctx.address().do_send(GeneratePrimMesh {
+                object: msg.object,
+                sculpt,
+            }),
+        }
     }
 }
 
 impl Handler<HandleAttachment> for Mailbox {
-    type Result = ResponseFuture<()>;
-    fn handle(&mut self, msg: HandleAttachment, _ctx: &mut Self::Context) -> Self::Result {
-        let db_pool = self.inventory_db_connection.clone();
-        Box::pin(async move {
-            let mut current_id = match msg.object.parent_id {
-                Some(id) => id,
-                None => return,
-            };
-
-            let mut visited = std::collections::HashSet::new();
-
-            loop {
-                if !visited.insert(current_id) {
-                    break;
-                }
-
-                let parent = match sqlite_get_parent(&db_pool, current_id).await {
-                    Ok(p) => p,
-                    Err(_) => return,
-                };
+    type Result = ();
+    fn handle(&mut self, msg: HandleAttachment, ctx: &mut Self::Context) -> Self::Result {
+        if let Some(session) = self.session.as_mut() {
+            session.attachments.insert(
+                msg.object.local_id,
+                AttachmentPoint::from_state(msg.object.state),
+            );
+        }
+        // attachments are built like any other prim, and their avatar is found when their mesh
+        // is sent to the UI
+        ctx.address().do_send(HandlePrim { object: msg.object });
+    }
+}
 
-                match parent {
-                    Some(p) => {
-                        current_id = p;
-                    }
-                    None => break,
-                }
-            }
-        })
+impl Handler<SetAttachmentPoint> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: SetAttachmentPoint, _: &mut Self::Context) -> Self::Result {
+        if let Some(session) = self.session.as_mut() {
+            session.attachments.insert(msg.local_id, msg.point);
+        }
     }
 }
 
@@ -463,6 +1015,12 @@ impl Handler<DownloadObject> for Mailbox {
                 .get(&Capability::ViewerAsset)
                 .unwrap()
                 .to_string();
+            let render_materials_endpoint = session
+                .capability_urls
+                .get(&Capability::RenderMaterials)
+                .cloned();
+            let material_overrides = session.material_overrides.clone();
+            let wearer = wearer(session, msg.object.local_id);
             let addr = ctx.address();
             let inventory_db = self.inventory_db_connection.clone();
             ctx.spawn(
@@ -475,76 +1033,241 @@ impl Handler<DownloadObject> for Mailbox {
                         }
                     };
 
-                    let texture_id = msg.object.texture.texture_id;
-                    let texture_path = base_dir.join(format!("{:?}.png", texture_id));
-                    let texture_path = match download_texture(
-                        ObjectType::Texture.to_string(),
-                        texture_id,
+                    let source = match download_mesh_source(msg.asset_id, &server_endpoint).await {
+                        Ok(source) => source,
+                        Err(e) => {
+                            error!("{:?}, {:?}", e, msg);
+                            return;
+                        }
+                    };
+                    let materials = download_face_materials(
+                        &msg.object,
                         &server_endpoint,
-                        &texture_path,
+                        render_materials_endpoint.as_deref(),
+                        &material_overrides,
+                        &base_dir,
+                        wearer,
                     )
-                    .await
-                    {
-                        Ok(_) => texture_path,
+                    .await;
+                    let object = GeneratorObject {
+                        full_id: msg.object.full_id,
+                        local_id: msg.object.local_id,
+                        parent_id: msg.object.parent_id,
+                        rotation: msg.object.rotation,
+                        scale: msg.object.scale,
+                        position: msg.object.position,
+                    };
+
+                    // objects don't send their names in object updates, so their meshes are
+                    // named after their asset
+                    let name = msg.asset_id.to_string();
+                    // start with the smallest level of detail, so the object can be shown while
+                    // the more detailed levels are still downloading
+                    for lod in LEVELS_OF_DETAIL {
+                        if source.level_of_detail(lod).is_none() {
+                            continue;
+                        }
+                        let render_object = match download_renderable_mesh(
+                            &source,
+                            lod,
+                            name.clone(),
+                            &server_endpoint,
+                            &msg.object.texture,
+                            &materials,
+                        )
+                        .await
+                        {
+                            Ok(render_object) => render_object,
+                            Err(e) => {
+                                error!("{:?}, {:?}", e, msg);
+                                continue;
+                            }
+                        };
+
+                        // who wears a rigged mesh is looked up once it is ready, as the avatar
+                        // may not have arrived yet
+                        if render_object.skin.is_some() {
+                            addr.do_send(AddRiggedMesh {
+                                object: object.clone(),
+                                asset_id: msg.asset_id,
+                                base_dir: base_dir.clone(),
+                                lod,
+                                render_object,
+                            });
+                            continue;
+                        }
+
+                        generate_from_render_object(
+                            &inventory_db,
+                            &addr,
+                            object.clone(),
+                            msg.asset_id,
+                            base_dir.clone(),
+                            &render_object,
+                            lod,
+                        )
+                        .await;
+                    }
+                }
+                .into_actor(self),
+            );
+        }
+    }
+}
+
+impl Handler<AddRiggedMesh> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: AddRiggedMesh, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        let local_id = msg.object.local_id;
+        if let Some(agent_id) = wearer(session, local_id) {
+            // avatars are built from the most detailed level of their meshes, and the user's own
+            // avatar is already built from their outfit
+            if agent_id != session.agent_id && msg.lod == LevelOfDetail::High {
+                ctx.address().do_send(AddWornMesh {
+                    agent_id,
+                    scene_id: local_id,
+                    asset_id: msg.asset_id,
+                    render_object: msg.render_object,
+                });
+            }
+            return;
+        }
+        if !session.scene_graph.is_connected(local_id) {
+            session
+                .pending_rigged_meshes
+                .entry(local_id)
+                .or_default()
+                .push(msg);
+            return;
+        }
+
+        // rigged meshes that no avatar wears are drawn like any other object
+        let inventory_db = self.inventory_db_connection.clone();
+        let addr = ctx.address();
+        ctx.spawn(
+            async move {
+                generate_from_render_object(
+                    &inventory_db,
+                    &addr,
+                    msg.object,
+                    msg.asset_id,
+                    msg.base_dir,
+                    &msg.render_object,
+                    msg.lod,
+                )
+                .await;
+            }
+            .into_actor(self),
+        );
+    }
+}
+
+impl Handler<GeneratePrimMesh> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: GeneratePrimMesh, ctx: &mut Self::Context) -> Self::Result {
+        if let Some(session) = self.session.as_mut() {
+            let server_endpoint = session
+                .capability_urls
+                .get(&Capability::ViewerAsset)
+                .unwrap()
+                .to_string();
+            let render_materials_endpoint = session
+                .capability_urls
+                .get(&Capability::RenderMaterials)
+                .cloned();
+            let material_overrides = session.material_overrides.clone();
+            let wearer = wearer(session, msg.object.local_id);
+            let addr = ctx.address();
+            let inventory_db = self.inventory_db_connection.clone();
+            ctx.spawn(
+                async move {
+                    // prims have no mesh asset of their own, and sculpts with the same map can
+                    // have different flags, so their files are stored by object ID
+                    let asset_id = msg.object.full_id;
+                    let base_dir = match create_sub_object_dir(&asset_id.to_string()) {
+                        Ok(base_dir) => base_dir,
                         Err(e) => {
-                            error!("Failed to download prim texture: {:?} {:?}", e, texture_id);
-                            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-                                .join("assets")
-                                .join("textures")
-                                .join("benthic_default_texture.png")
+                            error!("failed to create base dir: {:?}", e);
+                            return;
                         }
                     };
 
-                    match download_renderable_mesh(
-                        msg.asset_id,
-                        "name".to_string(),
+                    let materials = download_face_materials(
+                        &msg.object,
                         &server_endpoint,
-                        &texture_path,
+                        render_materials_endpoint.as_deref(),
+                        &material_overrides,
+                        &base_dir,
+                        wearer,
                     )
-                    .await
-                    {
-                        Ok(render_object) => {
-                            // write the json
-                            let json_path = match write_json(
-                                &render_object,
-                                msg.asset_id,
-                                msg.asset_id.to_string(),
-                            ) {
-                                Ok(json) => json,
-                                Err(e) => {
-                                    error!("Failed to write json: {:?}", e);
-                                    return;
-                                }
-                            };
+                    .await;
 
-                            sqlite_update_object_json_path(
-                                &inventory_db,
-                                msg.object.full_id,
-                                msg.asset_id,
-                                json_path.to_str().unwrap(),
+                    let volume = match &msg.sculpt {
+                        Some(sculpt) => {
+                            let map_path =
+                                base_dir.join(format!("{:?}_sculpt.png", sculpt.texture_id));
+                            match download_texture(
+                                ObjectType::Texture.to_string(),
+                                sculpt.texture_id,
+                                &server_endpoint,
+                                &map_path,
                             )
                             .await
-                            .unwrap_or_else(|e| {
-                                error!("Object Update Error: {:?}, {:?}", e, msg.object.full_id);
-                            });
-                            addr.do_send(GenerateMeshFromJson {
-                                object: GeneratorObject {
-                                    full_id: msg.object.full_id,
-                                    local_id: msg.object.local_id,
-                                    parent_id: msg.object.parent_id,
-                                    rotation: msg.object.rotation,
-                                    scale: msg.object.scale,
-                                    position: msg.object.position,
-                                },
-                                asset_id: msg.asset_id,
-                                base_dir,
-                                json_path,
-                            });
+                            {
+                                Ok(map) => Volume::from_sculpt(&map.to_rgb8(), sculpt, HIGH_DETAIL),
+                                Err(e) => {
+                                    error!(
+                                        "Failed to download sculpt map: {:?} {:?}",
+                                        e, sculpt.texture_id
+                                    );
+                                    return;
+                                }
+                            }
                         }
+                        None => Volume::from_path(&msg.object.path, HIGH_DETAIL),
+                    };
+                    let render_object = volume.to_render_object(
+                        asset_id.to_string(),
+                        asset_id,
+                        &msg.object.texture,
+                        &materials,
+                    );
+                    let json_path = match write_json(&render_object, asset_id, asset_id.to_string())
+                    {
+                        Ok(json) => json,
                         Err(e) => {
-                            error!("{:?}, {:?}", e, msg);
+                            error!("Failed to write json: {:?}", e);
+                            return;
                         }
-                    }
+                    };
+
+                    sqlite_update_object_json_path(
+                        &inventory_db,
+                        msg.object.full_id,
+                        asset_id,
+                        json_path.to_str().unwrap(),
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        error!("Object Update Error: {:?}, {:?}", e, msg.object.full_id);
+                    });
+                    addr.do_send(GenerateMeshFromJson {
+                        object: GeneratorObject {
+                            full_id: msg.object.full_id,
+                            local_id: msg.object.local_id,
+                            parent_id: msg.object.parent_id,
+                            rotation: msg.object.rotation,
+                            scale: msg.object.scale,
+                            position: msg.object.position,
+                        },
+                        asset_id,
+                        base_dir,
+                        json_path,
+                        lod: LevelOfDetail::High,
+                    });
                 }
                 .into_actor(self),
             );
@@ -559,8 +1282,16 @@ impl Handler<GenerateMeshFromJson> for Mailbox {
         let addr = ctx.address();
         ctx.spawn(
             async move {
-                let glb_path = msg.base_dir.join(format!("{:?}_high.glb", msg.asset_id));
+                let glb_path = msg
+                    .base_dir
+                    .join(format!("{}.glb", lod_file_name(msg.asset_id, msg.lod)));
                 match generate_object_mesh(msg.json_path, glb_path.clone()) {
+                    Ok(_) if msg.lod != LevelOfDetail::High => {
+                        info!(
+                            "Rendering {:?} detail of object: {:?}",
+                            msg.lod, msg.asset_id
+                        );
+                    }
                     Ok(_) => {
                         info!("Rendering object at: {:?}", msg.asset_id);
                         sqlite_update_object_glb_path(
@@ -580,7 +1311,7 @@ impl Handler<GenerateMeshFromJson> for Mailbox {
                     base_dir: msg.base_dir,
                     asset_id: msg.asset_id,
                     object: msg.object,
-                    retry_count: 0,
+                    lod: msg.lod,
                 })
             }
             .into_actor(self),
@@ -591,97 +1322,231 @@ impl Handler<GenerateMeshFromJson> for Mailbox {
 impl Handler<RenderObjectFromFile> for Mailbox {
     type Result = ();
 
-    fn handle(&mut self, mut msg: RenderObjectFromFile, ctx: &mut Self::Context) -> Self::Result {
-        let inventory_db = self.inventory_db_connection.clone();
-        let addr = ctx.address();
-
-        ctx.spawn(
-            async move {
-                let parent_id = match msg.object.parent_id {
-                    Some(0) | None => {
-                        // no parent, render directly
-                        addr.do_send(SendUIMessage {
-                            ui_message: UIMessage::new_mesh_update(MeshUpdate {
-                                position: msg.object.position,
-                                scale: msg.object.scale,
-                                rotation: msg.object.rotation,
-                                parent: msg.object.parent_id,
-                                scene_id: Some(msg.object.local_id),
-                                path: msg.mesh_path,
-                                mesh_type: MeshType::Object,
-                                id: None,
-                            }),
-                        });
-                        return;
-                    }
-                    Some(id) => id,
-                };
-
-                match sqlite_get_object_scale_rotation_position(&inventory_db, parent_id).await {
-                    Ok((_parent_scale, parent_rotation, parent_position)) => {
-                        let rotated_offset = parent_rotation.mul_vec3(msg.object.position);
-
-                        msg.object.position = parent_position + rotated_offset;
-                        msg.object.rotation = parent_rotation * msg.object.rotation;
-
-                        addr.do_send(SendUIMessage {
-                            ui_message: UIMessage::new_mesh_update(MeshUpdate {
-                                position: msg.object.position,
-                                scale: msg.object.scale,
-                                rotation: msg.object.rotation,
-                                parent: msg.object.parent_id,
-                                scene_id: Some(msg.object.local_id),
-                                path: msg.mesh_path,
-                                mesh_type: MeshType::Object,
-                                id: None,
-                            }),
-                        });
-                    }
+    fn handle(&mut self, msg: RenderObjectFromFile, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
 
-                    Err(inventory_error) => {
-                        warn!(
-                            "Parent {} not ready, requeuing object {}: {}",
-                            parent_id, msg.object.full_id, inventory_error
-                        );
+        // the object may have moved since its mesh started generating
+        let node = session
+            .scene_graph
+            .get(msg.object.local_id)
+            .copied()
+            .unwrap_or_else(|| {
+                SceneNode::new(
+                    msg.object.parent_id,
+                    msg.object.position,
+                    msg.object.rotation,
+                    msg.object.scale,
+                )
+            });
+        let mesh_update = MeshUpdate {
+            position: node.position,
+            scale: node.scale,
+            rotation: node.rotation,
+            parent: node.parent,
+            scene_id: Some(msg.object.local_id),
+            path: msg.mesh_path,
+            mesh_type: MeshType::Object,
+            id: None,
+            lod: msg.lod,
+        };
+        for mesh_update in session
+            .scene_graph
+            .expire_waiting(Instant::now(), PARENT_TIMEOUT)
+        {
+            warn!(
+                "Parents of {:?} never arrived, sending its mesh without them",
+                mesh_update.scene_id
+            );
+            send_mesh_update(session, &ctx.address(), mesh_update);
+        }
+        if let Some(mesh_update) = session
+            .scene_graph
+            .hold_until_connected(msg.object.local_id, mesh_update)
+        {
+            send_mesh_update(session, &ctx.address(), mesh_update);
+        }
+    }
+}
 
-                        schedule_retry(&addr, msg, parent_id, &inventory_error.to_string());
-                    }
-                }
-            }
-            .into_actor(self),
-        );
+/// Send the mesh of an object to the UI.
+///
+/// Avatars aren't objects in the UI, so attachments are sent without their avatar as their parent.
+/// Instead, an [`AttachmentUpdate`] tells the UI which joint or HUD point to attach them to.
+fn send_mesh_update(session: &Session, addr: &Addr<Mailbox>, mut mesh_update: MeshUpdate) {
+    if let Some(scene_id) = mesh_update.scene_id
+        && let Some(point) = session.attachments.get(&scene_id)
+        && let Some(agent_id) = mesh_update
+            .parent
+            .and_then(|parent| session.avatar_scene_ids.get(&parent))
+    {
+        addr.do_send(SendUIMessage {
+            ui_message: UIMessage::new_attachment_update(AttachmentUpdate {
+                scene_id,
+                agent_id: *agent_id,
+                joint: point.joint(),
+                hud_anchor: point.hud_anchor(),
+            }),
+        });
+        mesh_update.parent = None;
     }
+    addr.do_send(SendUIMessage {
+        ui_message: UIMessage::new_mesh_update(mesh_update),
+    });
 }
 
-fn schedule_retry(
-    addr: &actix::Addr<Mailbox>,
-    mut msg: RenderObjectFromFile,
-    parent_id: u32,
-    reason: &str,
+/// The agent wearing an object, if the root of its linkset is an avatar
+fn wearer(session: &Session, local_id: u32) -> Option<Uuid> {
+    let root = session.scene_graph.root(local_id)?;
+    session.avatar_scene_ids.get(&root).copied()
+}
+
+/// Write a level of detail of a downloaded mesh to disk as json, and generate the object's mesh
+/// from it. The cache regenerates objects from their most detailed json.
+async fn generate_from_render_object(
+    inventory_db: &Pool<Sqlite>,
+    addr: &Addr<Mailbox>,
+    object: GeneratorObject,
+    asset_id: Uuid,
+    base_dir: PathBuf,
+    render_object: &RenderObject,
+    lod: LevelOfDetail,
 ) {
-    if msg.retry_count >= 6 {
-        error!(
-            "Dropping object {} after max retries (parent {}): {}",
-            msg.object.full_id, parent_id, reason
-        );
-        return;
+    let json_path = match write_json(render_object, asset_id, lod_file_name(asset_id, lod)) {
+        Ok(json) => json,
+        Err(e) => {
+            error!("Failed to write json: {:?}", e);
+            return;
+        }
+    };
+
+    if lod == LevelOfDetail::High {
+        sqlite_update_object_json_path(
+            inventory_db,
+            object.full_id,
+            asset_id,
+            json_path.to_str().unwrap(),
+        )
+        .await
+        .unwrap_or_else(|e| {
+            error!("Object Update Error: {:?}, {:?}", e, object.full_id);
+        });
     }
+    addr.do_send(GenerateMeshFromJson {
+        object,
+        asset_id,
+        base_dir,
+        json_path,
+        lod,
+    });
+}
 
-    let delay = backoff_ms(msg.retry_count);
+/// Download the materials of an object's faces, and every texture they use into its object dir.
+///
+/// Faces with a PBR material get the overrides the object has made to it. Other faces get the
+/// legacy material of their texture entry, if the region has the RenderMaterials capability.
+/// Faces of worn objects with a Bakes on Mesh texture are drawn with the bake of their wearer.
+async fn download_face_materials(
+    object: &HandleObjectUpdate,
+    server_endpoint: &str,
+    render_materials_endpoint: Option<&str>,
+    material_overrides: &Mutex<HashMap<u32, MaterialOverride>>,
+    base_dir: &std::path::Path,
+    wearer: Option<Uuid>,
+) -> ObjectMaterials {
+    let mut materials = ObjectMaterials::default();
 
-    warn!(
-        "Parent {} not ready for {}, retry {} in {}ms",
-        parent_id, msg.object.full_id, msg.retry_count, delay
-    );
+    let mut downloaded = HashMap::new();
+    for (face, material_id) in object.materials().into_iter().flat_map(|m| &m.materials) {
+        if !downloaded.contains_key(material_id) {
+            match download_gltf_material(*material_id, server_endpoint).await {
+                Ok(material) => {
+                    downloaded.insert(*material_id, material);
+                }
+                Err(e) => {
+                    error!("Failed to download material: {:?} {:?}", e, material_id);
+                    continue;
+                }
+            }
+        }
+        materials
+            .gltf
+            .insert(*face as u32, downloaded[material_id].clone());
+    }
+    // overrides usually arrive right after the object, so they are read once the materials
+    // have downloaded
+    if let Some(material_override) = material_overrides.lock().unwrap().get(&object.local_id) {
+        for (face, data) in &material_override.faces {
+            if let Some(material) = materials.gltf.get_mut(&(*face as u32)) {
+                material.apply_override(data);
+            }
+        }
+    }
 
-    msg.retry_count += 1;
+    if let Some(endpoint) = render_materials_endpoint {
+        let texture_entry = &object.texture;
+        let material_ids: Vec<Uuid> = std::iter::once(texture_entry)
+            .chain(texture_entry.faces.values())
+            .map(|entry| entry.material_id)
+            .filter(|id| !id.is_nil())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        if !material_ids.is_empty() {
+            match download_render_materials(&material_ids, endpoint).await {
+                Ok(legacy) => materials.legacy = legacy,
+                Err(e) => error!("Failed to download render materials: {:?}", e),
+            }
+        }
+    }
 
-    let addr = addr.clone();
+    for texture_id in materials.texture_ids(&object.texture) {
+        if let Some(bake) = BakeType::from_bakes_on_mesh_texture(texture_id)
+            && let Some(agent_id) = wearer
+        {
+            match bakes_on_mesh_path(agent_id, bake) {
+                Ok(texture_path) => {
+                    materials.textures.insert(texture_id, texture_path);
+                    continue;
+                }
+                Err(e) => error!(
+                    "Failed to create {:?} bake of {:?}: {:?}",
+                    bake, agent_id, e
+                ),
+            }
+        }
+        let texture_path = download_prim_texture(texture_id, server_endpoint, base_dir).await;
+        materials.textures.insert(texture_id, texture_path);
+    }
+    materials
+}
 
-    actix::spawn(async move {
-        actix::clock::sleep(std::time::Duration::from_millis(delay)).await;
-        addr.do_send(msg);
-    });
+/// Download the texture of a prim into its object dir, falling back to the default texture if
+/// the download fails.
+async fn download_prim_texture(
+    texture_id: Uuid,
+    server_endpoint: &str,
+    base_dir: &std::path::Path,
+) -> PathBuf {
+    let texture_path = base_dir.join(format!("{:?}.png", texture_id));
+    match download_texture(
+        ObjectType::Texture.to_string(),
+        texture_id,
+        server_endpoint,
+        &texture_path,
+    )
+    .await
+    {
+        Ok(_) => texture_path,
+        Err(e) => {
+            error!("Failed to download prim texture: {:?} {:?}", e, texture_id);
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("assets")
+                .join("textures")
+                .join("benthic_default_texture.png")
+        }
+    }
 }
 
 /// When an object is retrieved in full, the data will be written in serializable json format, to
@@ -710,3 +1575,35 @@ pub fn write_json<T: Serialize>(data: &T, asset_id: Uuid, filename: String) -> i
         }
     }
 }
+
+/// Build the UI message for an object's light. Colors are sent in sRGB.
+fn light_update(
+    scene_id: u32,
+    light: &LightData,
+    projection: Option<&ProjectionData>,
+) -> LightUpdate {
+    LightUpdate {
+        scene_id,
+        color: [light.color.r, light.color.g, light.color.b].map(|channel| channel as f32 / 255.0),
+        intensity: light.intensity,
+        radius: light.radius,
+        falloff: light.falloff,
+        projector: projection.map(|projection| Projector {
+            texture_id: projection.texture_id,
+            fov: projection.fov,
+            focus: projection.focus,
+            ambiance: projection.ambiance,
+        }),
+    }
+}
+
+/// The name of the files a level of detail of a mesh is stored in, without an extension
+pub fn lod_file_name(asset_id: Uuid, lod: LevelOfDetail) -> String {
+    let lod = match lod {
+        LevelOfDetail::Lowest => "lowest",
+        LevelOfDetail::Low => "low",
+        LevelOfDetail::Medium => "medium",
+        LevelOfDetail::High => "high",
+    };
+    format!("{:?}_{}", asset_id, lod)
+}
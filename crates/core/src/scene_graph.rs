@@ -0,0 +1,215 @@
+use glam::{Quat, Vec3};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// How long the items of an object are held waiting for its parents. Parents that haven't arrived
+/// by then may never arrive, so the items are released without them.
+pub const PARENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An object's place in its linkset, and its transform relative to its parent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SceneNode {
+    /// Scene local ID of the object's parent, or None if the object is the root of its linkset
+    pub parent: Option<u32>,
+    /// Position relative to the parent, or to the region if the object has no parent
+    pub position: Vec3,
+    /// Rotation relative to the parent, or to the region if the object has no parent
+    pub rotation: Quat,
+    /// Scale of the object. Unlike the position and rotation, this is not affected by the parent.
+    pub scale: Vec3,
+}
+impl SceneNode {
+    /// Create a node from the fields of an object update, where a parent ID of 0 means the object
+    /// has no parent
+    pub fn new(parent: Option<u32>, position: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        SceneNode {
+            parent: parent.filter(|parent| *parent != 0),
+            position,
+            rotation,
+            scale,
+        }
+    }
+}
+
+/// The linksets of a region, keyed by the scene local IDs of their objects.
+///
+/// Linksets can nest to any depth, and attachments are children of the avatar wearing them. The
+/// server sends objects in no particular order, so children often arrive before their parents.
+/// Anything that has to wait for an object's parents, like the object's mesh, is held by the
+/// graph until every parent up to the root has arrived.
+#[derive(Debug)]
+pub struct SceneGraph<T> {
+    nodes: HashMap<u32, SceneNode>,
+    /// children by the ID of their parent, including children whose parent hasn't arrived
+    children: HashMap<u32, HashSet<u32>>,
+    /// items held until their object is connected to its root, and when the first was held
+    waiting: HashMap<u32, (Instant, Vec<T>)>,
+}
+impl<T> Default for SceneGraph<T> {
+    fn default() -> Self {
+        SceneGraph {
+            nodes: HashMap::new(),
+            children: HashMap::new(),
+            waiting: HashMap::new(),
+        }
+    }
+}
+
+impl<T> SceneGraph<T> {
+    /// Create an empty scene graph
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The node of an object, if it has arrived
+    pub fn get(&self, local_id: u32) -> Option<&SceneNode> {
+        self.nodes.get(&local_id)
+    }
+
+    /// The IDs of the objects that have arrived as children of an object
+    pub fn children(&self, local_id: u32) -> impl Iterator<Item = u32> + '_ {
+        self.children.get(&local_id).into_iter().flatten().copied()
+    }
+
+    /// Add or replace the node of an object, which also moves it to its new parent if it was
+    /// relinked.
+    ///
+    /// Returns the items that were waiting on this object, for the object itself and for every
+    /// descendant that it connected to the root.
+    pub fn insert(&mut self, local_id: u32, node: SceneNode) -> Vec<T> {
+        if let Some(previous) = self.nodes.insert(local_id, node)
+            && previous.parent != node.parent
+            && let Some(parent) = previous.parent
+        {
+            self.detach(parent, local_id);
+        }
+        if let Some(parent) = node.parent {
+            self.children.entry(parent).or_default().insert(local_id);
+        }
+        if !self.is_connected(local_id) {
+            return Vec::new();
+        }
+
+        // every descendant of a connected object is connected as well
+        let mut released = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![local_id];
+        while let Some(id) = stack.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            if let Some((_, items)) = self.waiting.remove(&id) {
+                released.extend(items);
+            }
+            stack.extend(self.children(id));
+        }
+        released
+    }
+
+    /// Update the transform of an object relative to its parent. Objects that haven't arrived are
+    /// ignored.
+    pub fn set_transform(&mut self, local_id: u32, position: Vec3, rotation: Quat) {
+        if let Some(node) = self.nodes.get_mut(&local_id) {
+            node.position = position;
+            node.rotation = rotation;
+        }
+    }
+
+    /// Return the item if the object is connected to its root, or hold it until it is
+    pub fn hold_until_connected(&mut self, local_id: u32, item: T) -> Option<T> {
+        if self.is_connected(local_id) {
+            Some(item)
+        } else {
+            self.waiting
+                .entry(local_id)
+                .or_insert_with(|| (Instant::now(), Vec::new()))
+                .1
+                .push(item);
+            None
+        }
+    }
+
+    /// Release the items of every object that has been waiting on its parents for longer than
+    /// the timeout, so objects whose parents never arrive aren't held forever.
+    pub fn expire_waiting(&mut self, now: Instant, timeout: Duration) -> Vec<T> {
+        let expired: Vec<u32> = self
+            .waiting
+            .iter()
+            .filter(|(_, (since, _))| now.saturating_duration_since(*since) > timeout)
+            .map(|(local_id, _)| *local_id)
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|local_id| self.waiting.remove(&local_id))
+            .flat_map(|(_, items)| items)
+            .collect()
+    }
+
+    /// If the object and every parent up to its root have arrived
+    pub fn is_connected(&self, local_id: u32) -> bool {
+        self.root(local_id).is_some()
+    }
+
+    /// The root of the linkset the object belongs to, if the object and every parent up to the
+    /// root have arrived
+    pub fn root(&self, local_id: u32) -> Option<u32> {
+        let mut current = local_id;
+        let mut visited = HashSet::new();
+        loop {
+            // a relinked object can briefly form a loop with its old parent
+            if !visited.insert(current) {
+                return None;
+            }
+            match self.nodes.get(&current)?.parent {
+                Some(parent) => current = parent,
+                None => return Some(current),
+            }
+        }
+    }
+
+    /// The position and rotation of an object in the region, if it is connected to its root
+    pub fn world_transform(&self, local_id: u32) -> Option<(Vec3, Quat)> {
+        let mut node = self.nodes.get(&local_id)?;
+        let (mut position, mut rotation) = (node.position, node.rotation);
+        let mut visited = HashSet::from([local_id]);
+        while let Some(parent) = node.parent {
+            if !visited.insert(parent) {
+                return None;
+            }
+            node = self.nodes.get(&parent)?;
+            position = node.position + node.rotation * position;
+            rotation = node.rotation * rotation;
+        }
+        Some((position, rotation))
+    }
+
+    /// Remove an object along with all of its descendants, and anything waiting on them.
+    ///
+    /// Returns the IDs of every removed object that had arrived.
+    pub fn remove(&mut self, local_id: u32) -> Vec<u32> {
+        let mut removed = Vec::new();
+        let mut stack = vec![local_id];
+        while let Some(id) = stack.pop() {
+            self.waiting.remove(&id);
+            if let Some(children) = self.children.remove(&id) {
+                stack.extend(children);
+            }
+            if let Some(node) = self.nodes.remove(&id) {
+                if let Some(parent) = node.parent {
+                    self.detach(parent, id);
+                }
+                removed.push(id);
+            }
+        }
+        removed
+    }
+
+    fn detach(&mut self, parent: u32, local_id: u32) {
+        if let Some(children) = self.children.get_mut(&parent) {
+            children.remove(&local_id);
+            if children.is_empty() {
+                self.children.remove(&parent);
+            }
+        }
+    }
+}
@@ -2,18 +2,24 @@ use super::{environment::EnvironmentCache, inventory::InventoryData};
 #[cfg(feature = "environment")]
 use crate::environment::FetchEnvironmentEvent;
 use crate::{
-    capabilities::SendCapabilityRequest, inventory::RefreshInventoryEvent,
-    transport::http_handler::login_to_simulator,
+    capabilities::SendCapabilityRequest,
+    inventory::RefreshInventoryEvent,
+    objects::AddRiggedMesh,
+    scene_graph::SceneGraph,
+    transport::{circuit::CircuitStats, http_handler::login_to_simulator},
 };
 use actix::prelude::*;
 use actix_rt::time;
 use benthic_protocol::messages::ui::{
+    circuit_stats_update::CircuitStatsUpdate,
     errors::{
-        CapabilityError, CircuitCodeError, CompleteAgentMovementError, FeatureError,
+        AckError, CapabilityError, CircuitCodeError, CompleteAgentMovementError, FeatureError,
         MailboxSessionError, SessionError,
     },
+    latency_update::LatencyUpdate,
     login_event::Login,
     login_response::LoginResponse,
+    mesh_update::MeshUpdate,
     ui_messages::{UIMessage, UIResponse},
     water_update::WaterUpdate,
 };
@@ -22,9 +28,9 @@ use log::{error, info};
 use metaverse_agent::avatar::Avatar;
 use metaverse_messages::{
     http::capabilities::{Capability, CapabilityRequest},
-    packet::packet_protocol::Packet,
+    packet::{header::MAX_APPENDED_ACKS, packet_protocol::Packet},
     udp::{
-        agent::agent_update::AgentUpdate,
+        agent::{agent_update::AgentUpdate, avatar_animation::AvatarAnimation},
         chat::chat_from_viewer::ChatFromViewer,
         core::{
             agent_throttle::{AgentThrottle, ThrottleData},
@@ -35,14 +41,17 @@ use metaverse_messages::{
             packet_ack::PacketAck,
             region_handshake::RegionHandshake,
             region_handshake_reply::RegionHandshakeReply,
+            start_ping_check::StartPingCheck,
         },
+        object::generic_streaming_message::MaterialOverride,
     },
+    utils::{attachment_point::AttachmentPoint, visual_params::AvatarLad},
 };
 use rgb::Rgba;
 use sqlx::{Pool, Sqlite};
 use std::{
-    collections::{HashMap, HashSet},
-    net::UdpSocket as SyncUdpSocket,
+    collections::{BTreeMap, HashMap, HashSet},
+    net::{SocketAddr, UdpSocket as SyncUdpSocket},
     path::PathBuf,
     sync::{Arc, Mutex},
     thread::sleep,
@@ -50,6 +59,36 @@ use std::{
 use tokio::{net::UdpSocket, sync::Notify, time::Duration};
 use uuid::Uuid;
 
+/// The largest UDP packet the core will send to the server, in bytes. Pending acks are only
+/// appended to an outgoing packet if they fit within this limit.
+pub const MTU: usize = 1200;
+
+/// How long acks for received reliable packets are held before they are flushed in a standalone
+/// [`PacketAck`], if no outgoing packet has carried them to the server in the meantime.
+pub const ACK_FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How many times a reliable packet is resent before it is dropped and reported to the UI.
+pub const MAX_RESENDS: u32 = 3;
+
+/// Time to wait for an ack before the first resend, used until a round trip time has been
+/// measured.
+pub const INITIAL_RETRANSMIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The shortest time to wait for an ack before resending a reliable packet.
+pub const MIN_RETRANSMIT_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// The longest time to wait for an ack before resending a reliable packet.
+pub const MAX_RETRANSMIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the simulator can go without sending anything before the circuit is considered dead.
+pub const CIRCUIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often the UDP read loop reports the stats of the incoming circuit to the mailbox.
+pub const CIRCUIT_STATS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the core sends a StartPingCheck to the simulator to measure the round trip time.
+pub const PING_INTERVAL: Duration = Duration::from_secs(5);
+
 /// Central Actix actor responsible for all client actix message handling within the session.
 #[derive(Debug)]
 pub struct Mailbox {
@@ -64,6 +103,8 @@ pub struct Mailbox {
     pub inventory_db_location: PathBuf,
     /// queue of acks sent from the server to be responded to by the client
     pub server_acks: HashSet<u32>,
+    /// true if a [`SendAckList`] flush is already scheduled for the pending server_acks
+    pub ack_flush_scheduled: bool,
     /// queue of packet IDs sent form the core to the server that the server hasn't yet acked
     pub viewer_acks: HashSet<u32>,
     /// state of the mailbox. If it is running or not.
@@ -76,19 +117,50 @@ pub struct Mailbox {
     pub sent_packet_count: u16,
     /// the global ping information
     pub ping_info: PingInfo,
+    /// the most recently reported stats of each incoming circuit, by the simulator's address
+    pub circuit_stats: HashMap<SocketAddr, CircuitStats>,
 }
 
 /// Information struct for storing latency and ping info
 #[derive(Debug)]
 pub struct PingInfo {
-    /// the number of the ping
+    /// the number of the last ping sent to the simulator. Rolls over to 0 after 255.
     pub ping_number: u8,
-    /// how long the latency is. Currently not doing anything.
+    /// smoothed round trip time between the core and the simulator. Zero until it has been
+    /// measured. Used to determine how long to wait before resending reliable packets.
     pub ping_latency: Duration,
-    /// time of last ping
+    /// time the last ping was sent to the simulator
     pub last_ping: time::Instant,
 }
 
+impl PingInfo {
+    /// Add a new round trip time measurement to the smoothed round trip time.
+    ///
+    /// The first measurement is used as is. After that, each new measurement contributes an
+    /// eighth of the result, so a single slow ping doesn't throw off the retransmit timeout.
+    pub fn record_round_trip(&mut self, sample: Duration) {
+        self.ping_latency = if self.ping_latency.is_zero() {
+            sample
+        } else {
+            (self.ping_latency * 7 + sample) / 8
+        };
+    }
+
+    /// Time to wait for an ack before resending a reliable packet.
+    ///
+    /// Starts at three times the round trip time, or [`INITIAL_RETRANSMIT_TIMEOUT`] if it hasn't
+    /// been measured yet, and doubles for every resend that has already been attempted.
+    pub fn retransmit_timeout(&self, retries: u32) -> Duration {
+        let base = if self.ping_latency.is_zero() {
+            INITIAL_RETRANSMIT_TIMEOUT
+        } else {
+            self.ping_latency * 3
+        };
+        base.saturating_mul(1 << retries.min(16))
+            .clamp(MIN_RETRANSMIT_TIMEOUT, MAX_RETRANSMIT_TIMEOUT)
+    }
+}
+
 /// Message and struct for the current user's session.
 ///
 /// This includes all data that will be used throughout the session, and much of it is populated by
@@ -128,6 +200,30 @@ pub struct Session {
     pub avatars: HashMap<Uuid, Avatar>,
     /// data about the region the user is currently in
     pub region_data: RegionData,
+    /// The overrides objects make to their PBR materials, by the object's scene local ID.
+    ///
+    /// These are read once by the tasks that download objects, when they download the object's
+    /// materials. Objects already in the scene when their override changes are requested again
+    /// from the simulator and rebuilt with it.
+    pub material_overrides: Arc<Mutex<HashMap<u32, MaterialOverride>>>,
+    /// The linksets of the region. Holds the meshes of objects until their parents have arrived.
+    pub scene_graph: SceneGraph<MeshUpdate>,
+    /// The agent IDs of the avatars in the region, by their scene local ID
+    pub avatar_scene_ids: HashMap<u32, Uuid>,
+    /// The points that objects worn by avatars are attached to, by the object's scene local ID
+    pub attachments: HashMap<u32, AttachmentPoint>,
+    /// The json of the rigged meshes other agents wear, by agent ID and the attachment's scene
+    /// local ID. Other agents' avatars are built from these.
+    pub worn_meshes: HashMap<Uuid, BTreeMap<u32, PathBuf>>,
+    /// Downloaded rigged meshes whose linkset hasn't reached its root yet, by the object's scene
+    /// local ID. They can't be routed until it is known if an avatar wears them.
+    pub pending_rigged_meshes: HashMap<u32, Vec<AddRiggedMesh>>,
+    /// The last animations played by each avatar, which are replayed when the avatar is rebuilt
+    pub avatar_animations: HashMap<Uuid, AvatarAnimation>,
+    /// The visual param definitions used to shape avatars. Loaded the first time an avatar is
+    /// shaped, and left as Some(None) if they couldn't be loaded so they aren't read again for
+    /// every avatar.
+    pub avatar_lad: Option<Option<AvatarLad>>,
 }
 
 #[derive(Debug, Message, Default)]
@@ -156,6 +252,39 @@ pub struct HandlePing {
     pub ping_id: u8,
 }
 
+/// Message for sending pings from the core to the server
+///
+/// Sends a StartPingCheck with the next ping number and the oldest unacked sequence number, and
+/// records when it was sent so the reply can be timed. Reschedules itself every [`PING_INTERVAL`]
+/// for as long as the session has a socket.
+///
+/// # Cause
+/// - The session's UDP socket is bound
+/// - [`SendPing`], after [`PING_INTERVAL`]
+///
+/// # Effect
+/// - Dispatches a StartPingCheck packet to the server
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct SendPing {}
+
+/// Handles replies to pings sent from the core
+///
+/// If the reply matches the last ping that was sent, the time since it was sent is added to the
+/// smoothed round trip time, which is then sent to the UI.
+///
+/// # Cause
+/// - Received CompletePingCheck packet from UDP socket
+///
+/// # Effect
+/// - [`SendUIMessage`] with the updated round trip time
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct HandleCompletePingCheck {
+    /// The ID of the ping being replied to
+    pub ping_id: u8,
+}
+
 /// The state of the Mailbox, if it is running, starting, stopping or stopped.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ServerState {
@@ -174,13 +303,14 @@ pub enum ServerState {
 /// When a UDP packet is received from the server with a reliable header, its sequence number is
 /// sent back to the server in a PacketAck, so the server knows that sequence number was received.
 /// Then the ack queue is cleared, to prevent sending acks for packets that have already been
-/// received.
+/// received. Acks that were already appended to an outgoing packet are no longer in the queue,
+/// so this only sends the ones that could not be piggybacked.
 ///
 /// # Cause
-/// - [`AddToAckList`]
+/// - [`AddToAckList`], after [`ACK_FLUSH_INTERVAL`]
 ///
 /// # Effects
-/// - Dispatches a [`PacketAck`] packet to the server
+/// - Dispatches one or more [`PacketAck`] packets to the server
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct SendAckList {}
@@ -190,8 +320,14 @@ pub struct SendAckList {}
 /// Messages marked as reliable sent from the core must be resent until the server replies with a
 /// PacketAck message. This adds thoes packets to the ack list.
 ///
+/// The acks are not sent right away. They are batched, and either appended to the next
+/// [`OutgoingPacket`] or flushed by [`SendAckList`] once [`ACK_FLUSH_INTERVAL`] has passed.
+///
 /// # Cause
 /// - Received a reliable packet from UDP socket
+///
+/// # Effects
+/// - Schedules a [`SendAckList`] if one is not already scheduled
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct AddToAckList {
@@ -203,24 +339,30 @@ pub struct AddToAckList {
 ///
 /// When an outgoing packet is labeled reliable, this message is used to determine if it should be
 /// resent to the server. An [`OutgoingPacket`] packet is sent initially, followed by a brief
-/// timeout. This allows the server enough time to respond with an ack. If an ack is not received,
-/// the packet is not resent. If it isn't, the packet will be resent.
+/// timeout. This allows the server enough time to respond with an ack. If an ack is received,
+/// the packet is not resent. If it isn't, the packet will be resent, and the timeout doubles.
+/// After [`MAX_RESENDS`] attempts the packet is dropped.
 ///
 /// # Cause
 /// - [`OutgoingPacket`] on a reliable packet
+/// - [`ResendPacket`] if the ack was still not received
 ///
 /// # Effect
-/// - [`OutgoingPacket`] if the ack was not received
+/// - [`OutgoingPacket`] and another [`ResendPacket`] if the ack was not received
+/// - [`SendUIMessage`] with an AckError if the packet was dropped
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct ResendPacket {
     /// the packet to resend
     pub packet: Packet,
+    /// the number of times the packet has already been resent
+    pub retries: u32,
 }
 
 /// Message for sending packets from the core to the server
 ///
-/// Simply a wrapper for the packet struct to send UDP packets to the server
+/// Simply a wrapper for the packet struct to send UDP packets to the server. Any pending server
+/// acks that fit under the [`MTU`] are appended to the end of the packet.
 ///
 /// # Effect
 /// - UDP packet sent to the server
@@ -255,6 +397,7 @@ pub struct HandleRegionHandshake {
 ///
 /// # Cause
 /// - Received a PacketAck packet from the UDP socket
+/// - Received any packet with acks appended to the end of it from the UDP socket
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct HandlePacketAck {
@@ -262,6 +405,39 @@ pub struct HandlePacketAck {
     pub packet_ack: PacketAck,
 }
 
+/// Message for handling a circuit that has stopped responding
+///
+/// If nothing has been received from the simulator for [`CIRCUIT_TIMEOUT`], the circuit is
+/// considered dead. Reliable packets waiting on an ack are abandoned, and the UI is informed.
+///
+/// # Cause
+/// - No packets received from the UDP socket for [`CIRCUIT_TIMEOUT`]
+///
+/// # Effect
+/// - [`SendUIMessage`] with a CircuitDead message
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct HandleCircuitDead {}
+
+/// Message for updating the stats of an incoming circuit
+///
+/// Counts of the dropped, duplicated and reordered packets received from a simulator, used to
+/// determine the quality of the connection.
+///
+/// # Cause
+/// - The UDP read loop, every [`CIRCUIT_STATS_INTERVAL`]
+///
+/// # Effect
+/// - [`SendUIMessage`] with a CircuitStatsUpdate message
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub struct HandleCircuitStats {
+    /// address of the simulator the circuit is with
+    pub circuit: SocketAddr,
+    /// the current stats of the circuit
+    pub stats: CircuitStats,
+}
+
 /// Message for receiving updates from the UI to the core
 ///
 /// # Cause
@@ -344,11 +520,13 @@ impl Handler<Session> for Mailbox {
             };
 
             // wait for the socket to be successfully bound and then assign it
-            ctx.spawn(fut.into_actor(self).map(|result, act, _| match result {
+            ctx.spawn(fut.into_actor(self).map(|result, act, ctx| match result {
                 Ok(sock) => {
                     if let Some(session) = &mut act.session {
                         session.socket = Some(sock);
                     }
+                    // start measuring the round trip time to the simulator
+                    ctx.notify(SendPing {});
                 }
                 Err(_) => {
                     panic!("Socket binding failed");
@@ -443,7 +621,10 @@ impl Handler<OutgoingPacket> for Mailbox {
                 session.sequence_number += 1;
             }
 
-            let data = msg.packet.to_bytes().clone();
+            // piggyback as many pending acks as will fit onto the packet
+            append_acks(&mut msg.packet, &mut self.server_acks);
+
+            let data = msg.packet.to_bytes();
             let socket_clone = session.socket.as_ref().unwrap().clone();
             let fut = async move {
                 if let Err(e) = socket_clone.send_to(&data, &addr).await {
@@ -452,14 +633,22 @@ impl Handler<OutgoingPacket> for Mailbox {
             };
             ctx.spawn(fut.into_actor(self));
 
-            // if the header is reliable, resend the packet until the viewer_acks contains the key
-            if msg.packet.header.reliable {
+            // if the header is reliable, resend the packet until the viewer_acks contains the key.
+            // resent packets are already being tracked by the ResendPacket that sent them.
+            if msg.packet.header.reliable && !msg.packet.header.resent {
                 self.viewer_acks.insert(msg.packet.header.sequence_number);
-                // give one second for the ack to come in.
+                // give the server time for the ack to come in.
                 // the ResendPacket message check if viewer_acks still contains the sequence
                 // number. if it doesn't, that means it's been removed by an ack. If it does,
                 // that means it should be resent with the resent flag
-                ctx.notify_later(ResendPacket { packet: msg.packet }, Duration::from_secs(1));
+                let timeout = self.ping_info.retransmit_timeout(0);
+                ctx.notify_later(
+                    ResendPacket {
+                        packet: msg.packet,
+                        retries: 0,
+                    },
+                    timeout,
+                );
             };
         }
     }
@@ -468,13 +657,56 @@ impl Handler<OutgoingPacket> for Mailbox {
 impl Handler<ResendPacket> for Mailbox {
     type Result = ();
     fn handle(&mut self, mut msg: ResendPacket, ctx: &mut Self::Context) -> Self::Result {
-        if self
-            .viewer_acks
-            .contains(&msg.packet.header.sequence_number)
-        {
-            msg.packet.header.resent = true;
-            ctx.address().do_send(OutgoingPacket { packet: msg.packet });
+        let sequence_number = msg.packet.header.sequence_number;
+        if !self.viewer_acks.contains(&sequence_number) {
+            return;
         }
+        if msg.retries >= MAX_RESENDS {
+            self.viewer_acks.remove(&sequence_number);
+            let message = format!(
+                "Dropped reliable packet {} ({:?}) after {} resends",
+                sequence_number, msg.packet.header.id, msg.retries
+            );
+            error!("{}", message);
+            ctx.address().do_send(SendUIMessage {
+                ui_message: UIMessage::new_session_error(SessionError::AckError(AckError {
+                    message,
+                })),
+            });
+            return;
+        }
+
+        msg.packet.header.resent = true;
+        // the acks sent with the first attempt have already been delivered or lost
+        msg.packet.header.ack_list = None;
+        ctx.address().do_send(OutgoingPacket {
+            packet: msg.packet.clone(),
+        });
+
+        let retries = msg.retries + 1;
+        let timeout = self.ping_info.retransmit_timeout(retries);
+        ctx.notify_later(
+            ResendPacket {
+                packet: msg.packet,
+                retries,
+            },
+            timeout,
+        );
+    }
+}
+
+impl Handler<HandleCircuitDead> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, _: HandleCircuitDead, ctx: &mut Self::Context) -> Self::Result {
+        error!(
+            "No packets received from the simulator in {:?}, circuit is dead",
+            CIRCUIT_TIMEOUT
+        );
+        // stop resending packets that will never be acked
+        self.viewer_acks.clear();
+        ctx.address().do_send(SendUIMessage {
+            ui_message: UIMessage::new_circuit_dead(),
+        });
     }
 }
 
@@ -510,7 +742,52 @@ impl Handler<HandlePing> for Mailbox {
                 ping_id: msg.ping_id,
             }),
         });
-        self.ping_info.ping_latency = time::Instant::now() - self.ping_info.last_ping;
+    }
+}
+
+impl Handler<SendPing> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, _: SendPing, ctx: &mut Self::Context) -> Self::Result {
+        let Some(session) = &self.session else {
+            return;
+        };
+        if session.socket.is_none() {
+            return;
+        }
+        // the oldest packet the server hasn't acked yet, or the next packet to be sent
+        let oldest_unacked = self
+            .viewer_acks
+            .iter()
+            .min()
+            .copied()
+            .unwrap_or(session.sequence_number as u32);
+
+        self.ping_info.ping_number = self.ping_info.ping_number.wrapping_add(1);
+        self.ping_info.last_ping = time::Instant::now();
+        ctx.address().do_send(OutgoingPacket {
+            packet: Packet::new_start_ping_check(StartPingCheck {
+                ping_id: self.ping_info.ping_number,
+                oldest_unacked,
+            }),
+        });
+        ctx.notify_later(SendPing {}, PING_INTERVAL);
+    }
+}
+
+impl Handler<HandleCompletePingCheck> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: HandleCompletePingCheck, ctx: &mut Self::Context) -> Self::Result {
+        // replies to older pings arrived too late to be timed against last_ping
+        if msg.ping_id != self.ping_info.ping_number {
+            return;
+        }
+        self.ping_info
+            .record_round_trip(time::Instant::now() - self.ping_info.last_ping);
+        ctx.address().do_send(SendUIMessage {
+            ui_message: UIMessage::new_latency_update(LatencyUpdate {
+                round_trip_time: self.ping_info.ping_latency,
+            }),
+        });
     }
 }
 
@@ -523,37 +800,96 @@ impl Handler<HandlePacketAck> for Mailbox {
     }
 }
 
+impl Handler<HandleCircuitStats> for Mailbox {
+    type Result = ();
+    fn handle(&mut self, msg: HandleCircuitStats, ctx: &mut Self::Context) -> Self::Result {
+        ctx.address().do_send(SendUIMessage {
+            ui_message: UIMessage::new_circuit_stats_update(CircuitStatsUpdate {
+                circuit: msg.circuit.to_string(),
+                received: msg.stats.received,
+                dropped: msg.stats.dropped,
+                duplicated: msg.stats.duplicated,
+                reordered: msg.stats.reordered,
+                resets: msg.stats.resets,
+            }),
+        });
+        self.circuit_stats.insert(msg.circuit, msg.stats);
+    }
+}
+
 impl Handler<AddToAckList> for Mailbox {
     type Result = ();
     fn handle(&mut self, msg: AddToAckList, ctx: &mut Self::Context) -> Self::Result {
         self.server_acks.insert(msg.id);
-        ctx.address().do_send(SendAckList {});
+        // wait for an outgoing packet to carry the ack, and flush whatever is left over
+        if !self.ack_flush_scheduled {
+            self.ack_flush_scheduled = true;
+            ctx.notify_later(SendAckList {}, ACK_FLUSH_INTERVAL);
+        }
     }
 }
 
 impl Handler<SendAckList> for Mailbox {
     type Result = ();
     fn handle(&mut self, _: SendAckList, ctx: &mut Self::Context) -> Self::Result {
+        self.ack_flush_scheduled = false;
         if let Some(ref session) = self.session {
             // send ack directly to the server
             if self.server_acks.is_empty() {
                 return;
             }
-            let packet_ids: Vec<u32> = self.server_acks.drain().collect();
-
             let addr = session.address.clone();
-            let packet = Packet::new_packet_ack(PacketAck { packet_ids }).to_bytes();
             let sock_clone = session.socket.clone().unwrap();
+            let packets: Vec<Vec<u8>> = ack_packets(&mut self.server_acks)
+                .iter()
+                .map(|packet| packet.to_bytes())
+                .collect();
             let ack_wait = async move {
-                if let Err(e) = sock_clone.send_to(&packet, addr).await {
-                    println!("Failed to send ack: {:?}", e)
-                };
+                for packet in packets {
+                    if let Err(e) = sock_clone.send_to(&packet, &addr).await {
+                        error!("Failed to send ack: {:?}", e)
+                    };
+                }
             };
             ctx.spawn(ack_wait.into_actor(self));
         }
     }
 }
 
+/// Move as many pending acks onto an outgoing packet as will fit within the [`MTU`].
+///
+/// Each appended ack takes four bytes, and the count of appended acks takes one more at the end
+/// of the packet. Acks that don't fit are left pending for the next packet or [`SendAckList`].
+pub fn append_acks(packet: &mut Packet, server_acks: &mut HashSet<u32>) {
+    if server_acks.is_empty() {
+        return;
+    }
+    let room = MTU.saturating_sub(packet.to_bytes().len() + 1) / 4;
+    let count = room.min(MAX_APPENDED_ACKS).min(server_acks.len());
+    if count > 0 {
+        let acks: Vec<u32> = server_acks.iter().take(count).copied().collect();
+        for ack in &acks {
+            server_acks.remove(ack);
+        }
+        packet.header.ack_list = Some(acks);
+    }
+}
+
+/// Drain every pending ack into standalone [`PacketAck`] packets.
+///
+/// The PacketAck count is a single byte, so large batches are split across several packets.
+pub fn ack_packets(server_acks: &mut HashSet<u32>) -> Vec<Packet> {
+    let packet_ids: Vec<u32> = server_acks.drain().collect();
+    packet_ids
+        .chunks(u8::MAX as usize)
+        .map(|ids| {
+            Packet::new_packet_ack(PacketAck {
+                packet_ids: ids.to_vec(),
+            })
+        })
+        .collect()
+}
+
 async fn handle_login(
     login_data: Login,
     mailbox_addr: &actix::Addr<Mailbox>,
@@ -629,6 +965,14 @@ async fn handle_login(
 
             #[cfg(feature = "agent")]
             avatars: HashMap::new(),
+            material_overrides: Arc::new(Mutex::new(HashMap::new())),
+            scene_graph: SceneGraph::new(),
+            avatar_scene_ids: HashMap::new(),
+            attachments: HashMap::new(),
+            worn_meshes: HashMap::new(),
+            pending_rigged_meshes: HashMap::new(),
+            avatar_animations: HashMap::new(),
+            avatar_lad: None,
         })
         .await
     {
@@ -641,6 +985,7 @@ async fn handle_login(
         Capability::ViewerAsset,
         Capability::FetchInventoryDescendents2,
         Capability::ExtEnvironment,
+        Capability::RenderMaterials,
     ]) {
         Ok(caps) => {
             if let Err(e) = mailbox_addr
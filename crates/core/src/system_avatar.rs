@@ -0,0 +1,468 @@
+use crate::initialize::create_sub_agent_dir;
+use crate::transport::http_handler::download_texture;
+use benthic_asset_pipeline::generated::DEFAULT_SKELETON;
+use benthic_protocol::render_data::{JointWeight, RenderFace, RenderObject, SkinData};
+use benthic_protocol::skeleton::{JointName, Skeleton};
+use glam::{Mat4, Vec3};
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+use log::{error, warn};
+use metaverse_agent::skeleton::create_skeleton;
+use metaverse_messages::http::item::ItemData;
+use metaverse_messages::http::mesh::Skin;
+use metaverse_messages::utils::avatar_mesh::AvatarMesh;
+use metaverse_messages::utils::bake::{BakeType, DEFAULT_AVATAR_TEXTURE};
+use metaverse_messages::utils::object_types::{ObjectType, WearableType};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, Error};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// The JSON of an agent's system avatar is named with this prefix, followed by a hash of the
+/// wearables and shape it was built from
+pub const SYSTEM_AVATAR_JSON: &str = "system_avatar";
+
+/// The meshes of the system avatar, and the bake each is drawn with
+const SYSTEM_AVATAR_PARTS: [(&str, BakeType); 6] = [
+    ("avatar_head.llm", BakeType::Head),
+    ("avatar_eyelashes.llm", BakeType::Head),
+    ("avatar_upper_body.llm", BakeType::UpperBody),
+    ("avatar_lower_body.llm", BakeType::LowerBody),
+    ("avatar_hair.llm", BakeType::Hair),
+    ("avatar_skirt.llm", BakeType::Skirt),
+];
+
+/// The eyeball mesh, which is drawn once on each eye joint
+const EYE_MESH: &str = "avatar_eye.llm";
+const EYE_JOINTS: [&str; 2] = ["mEyeLeft", "mEyeRight"];
+
+/// The color under the layers of a bake, which shows where no wearable has a texture. Skin is
+/// normally generated from the skin's visual params, which isn't implemented, so every skin has
+/// the same tone.
+pub fn base_color(bake: BakeType) -> Rgba<u8> {
+    match bake {
+        BakeType::Head
+        | BakeType::UpperBody
+        | BakeType::LowerBody
+        | BakeType::LeftArm
+        | BakeType::LeftLeg => Rgba([222, 182, 158, 255]),
+        BakeType::Eyes => Rgba([96, 112, 128, 255]),
+        BakeType::Hair => Rgba([84, 58, 38, 255]),
+        BakeType::Skirt | BakeType::Aux1 | BakeType::Aux2 | BakeType::Aux3 => {
+            Rgba([128, 128, 128, 255])
+        }
+    }
+}
+
+/// Bake the textures of a system avatar from the texture layers of its wearables.
+///
+/// Layers are drawn over each other in the order of their texture slots, and wearables in the
+/// same slot in the order they are worn. Alpha wearables then cut holes into the bakes. The
+/// colors and tints set by the params of clothing are not applied, so clothing is drawn in the
+/// colors of its textures.
+///
+/// Parts the server has already baked use the server's bake, and only the rest are baked here.
+/// Bakes are saved as pngs in `dir`, named by the textures they are made of. Bakes that fail are
+/// left out, and their meshes are drawn untextured.
+pub async fn bake_textures(
+    wearables: &[ItemData],
+    server_bakes: &HashMap<BakeType, PathBuf>,
+    server_endpoint: &str,
+    dir: &Path,
+) -> HashMap<BakeType, PathBuf> {
+    let mut bakes = HashMap::new();
+    for bake in BakeType::SYSTEM {
+        if let Some(path) = server_bakes.get(&bake) {
+            bakes.insert(bake, path.clone());
+            continue;
+        }
+        match bake_texture(bake, wearables, server_endpoint, dir).await {
+            Ok(path) => {
+                bakes.insert(bake, path);
+            }
+            Err(e) => error!("Failed to bake {:?}: {:?}", bake, e),
+        }
+    }
+    bakes
+}
+
+async fn bake_texture(
+    bake: BakeType,
+    wearables: &[ItemData],
+    server_endpoint: &str,
+    dir: &Path,
+) -> io::Result<PathBuf> {
+    let layers: Vec<Uuid> = bake
+        .layers()
+        .iter()
+        .flat_map(|slot| {
+            wearables
+                .iter()
+                .filter_map(move |wearable| wearable.textures.get(slot))
+        })
+        .copied()
+        .filter(|id| is_layer_texture(*id))
+        .collect();
+    let masks: Vec<Uuid> = bake
+        .alpha_slot()
+        .into_iter()
+        .flat_map(|slot| {
+            wearables
+                .iter()
+                .filter_map(move |wearable| wearable.textures.get(&slot))
+        })
+        .copied()
+        .filter(|id| is_layer_texture(*id))
+        .collect();
+
+    let mut hasher = DefaultHasher::new();
+    layers.hash(&mut hasher);
+    masks.hash(&mut hasher);
+    let path = dir.join(format!("bake_{:?}_{:016x}.png", bake, hasher.finish()));
+    if path.exists() {
+        return Ok(path);
+    }
+
+    let size = bake.size();
+    let mut layer_images = Vec::new();
+    for id in layers {
+        match layer_texture(id, server_endpoint, dir, size).await {
+            Ok(layer) => layer_images.push(layer),
+            Err(e) => warn!("Failed to load layer {:?} of {:?}: {:?}", id, bake, e),
+        }
+    }
+    let mut mask_images = Vec::new();
+    for id in masks {
+        match layer_texture(id, server_endpoint, dir, size).await {
+            Ok(mask) => mask_images.push(mask),
+            Err(e) => warn!("Failed to load alpha {:?} of {:?}: {:?}", id, bake, e),
+        }
+    }
+    composite_bake(bake, &layer_images, &mask_images)
+        .save(&path)
+        .map_err(Error::other)?;
+    Ok(path)
+}
+
+/// Draw the layers of a bake over its base color in order, and then cut the alpha of each mask
+/// out of it. Layers and masks are expected to be the size of the bake.
+pub fn composite_bake(bake: BakeType, layers: &[RgbaImage], masks: &[RgbaImage]) -> RgbaImage {
+    let size = bake.size();
+    let mut canvas = RgbaImage::from_pixel(size, size, base_color(bake));
+    for layer in layers {
+        imageops::overlay(&mut canvas, layer, 0, 0);
+    }
+    for mask in masks {
+        for (pixel, mask) in canvas.pixels_mut().zip(mask.pixels()) {
+            pixel[3] = ((pixel[3] as u16 * mask[3] as u16) / 255) as u8;
+        }
+    }
+    canvas
+}
+
+/// Wearables leave slots they don't draw on empty, or fill them with the default avatar texture
+fn is_layer_texture(id: Uuid) -> bool {
+    !id.is_nil() && id != DEFAULT_AVATAR_TEXTURE
+}
+
+/// Load a layer of a bake, scaled to the size of the bake. Textures are downloaded once and kept
+/// in `dir`.
+async fn layer_texture(
+    id: Uuid,
+    server_endpoint: &str,
+    dir: &Path,
+    size: u32,
+) -> io::Result<RgbaImage> {
+    let path = dir.join(format!("{:?}.png", id));
+    let image = if path.exists() {
+        image::open(&path).map_err(Error::other)?
+    } else {
+        download_texture(ObjectType::Texture.to_string(), id, server_endpoint, &path).await?
+    };
+    Ok(imageops::resize(
+        &image.to_rgba8(),
+        size,
+        size,
+        FilterType::Triangle,
+    ))
+}
+
+/// Where the bake the server made of an agent's appearance is downloaded to. Each bake is stored
+/// by its texture ID, so downloads of different bakes for the same part never overwrite each
+/// other.
+pub fn server_bake_path(agent_id: Uuid, bake: BakeType, texture_id: Uuid) -> io::Result<PathBuf> {
+    Ok(create_sub_agent_dir(&agent_id.to_string())?
+        .join(format!("server_bake_{:?}_{:?}.png", bake, texture_id)))
+}
+
+/// Where the texture drawn on an agent's Bakes on Mesh faces of a bake is stored.
+///
+/// Meshes refer to this path before the bake has been downloaded, so it holds the default
+/// texture until then. [`set_bakes_on_mesh`] copies the current bake over it.
+pub fn bakes_on_mesh_path(agent_id: Uuid, bake: BakeType) -> io::Result<PathBuf> {
+    let path =
+        create_sub_agent_dir(&agent_id.to_string())?.join(format!("bakes_on_mesh_{:?}.png", bake));
+    if !path.exists() {
+        fs::copy(default_texture(), &path)?;
+    }
+    Ok(path)
+}
+
+/// Copy the downloaded server bakes of an agent to the paths its Bakes on Mesh faces are drawn
+/// from. Parts without a bake go back to the default texture.
+pub fn set_bakes_on_mesh(
+    agent_id: Uuid,
+    baked_textures: &BTreeMap<BakeType, Uuid>,
+) -> io::Result<()> {
+    for bake in BakeType::ALL {
+        let source = match baked_textures.get(&bake) {
+            Some(texture_id) => server_bake_path(agent_id, bake, *texture_id)?,
+            None => default_texture(),
+        };
+        fs::copy(source, bakes_on_mesh_path(agent_id, bake)?)?;
+    }
+    Ok(())
+}
+
+fn default_texture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join("textures")
+        .join("benthic_default_texture.png")
+}
+
+/// The textures that replace the Bakes on Mesh textures of meshes worn by an agent, by the ID of
+/// the Bakes on Mesh texture
+pub fn bakes_on_mesh_textures(agent_id: Uuid) -> HashMap<Uuid, PathBuf> {
+    let mut textures = HashMap::new();
+    for bake in BakeType::ALL {
+        match bakes_on_mesh_path(agent_id, bake) {
+            Ok(path) => {
+                textures.insert(bake.bakes_on_mesh_texture(), path);
+            }
+            Err(e) => error!(
+                "Failed to create {:?} bake of {:?}: {:?}",
+                bake, agent_id, e
+            ),
+        }
+    }
+    textures
+}
+
+/// The name of the JSON of a system avatar built from the given wearables and visual params
+pub fn system_avatar_json(wearables: &[ItemData], visual_params: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    for wearable in wearables {
+        let mut textures: Vec<(u8, Uuid)> = wearable
+            .textures
+            .iter()
+            .map(|(slot, id)| (slot.index(), *id))
+            .collect();
+        textures.sort();
+        textures.hash(&mut hasher);
+    }
+    visual_params.hash(&mut hasher);
+    format!("{}_{:016x}", SYSTEM_AVATAR_JSON, hasher.finish())
+}
+
+/// If the path is the JSON of a system avatar
+pub fn is_system_avatar_json(path: &Path) -> bool {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.starts_with(SYSTEM_AVATAR_JSON))
+}
+
+/// Build the meshes of the system avatar from the .llm files of the default assets.
+///
+/// The meshes are morphed into the avatar's shape, and drawn with the bakes of the avatar. The
+/// skirt is only built if the avatar wears one.
+pub fn build_system_avatar(
+    agent_id: Uuid,
+    morphs: &BTreeMap<String, f32>,
+    bakes: &HashMap<BakeType, PathBuf>,
+    wearables: &[ItemData],
+) -> io::Result<Vec<RenderObject>> {
+    let wears_skirt = wearables
+        .iter()
+        .any(|wearable| wearable.wearable_type == WearableType::Skirt);
+    let character = benthic_default_assets::character();
+
+    let mut objects = Vec::new();
+    for (file, bake) in SYSTEM_AVATAR_PARTS {
+        if bake == BakeType::Skirt && !wears_skirt {
+            continue;
+        }
+        let mesh = read_avatar_mesh(&character.join(file))?;
+        objects.push(build_part(
+            file,
+            agent_id,
+            &mesh,
+            morphs,
+            None,
+            bakes.get(&bake).cloned(),
+        )?);
+    }
+
+    let eye = read_avatar_mesh(&character.join(EYE_MESH))?;
+    for joint in EYE_JOINTS {
+        let joint = JointName::from_str(joint)
+            .map_err(|_| Error::other(format!("Unknown eye joint {}", joint)))?;
+        objects.push(build_part(
+            &format!("{}_{:?}", EYE_MESH, joint),
+            agent_id,
+            &eye,
+            morphs,
+            Some(joint),
+            bakes.get(&BakeType::Eyes).cloned(),
+        )?);
+    }
+    Ok(objects)
+}
+
+fn read_avatar_mesh(path: &Path) -> io::Result<AvatarMesh> {
+    AvatarMesh::from_bytes(&fs::read(path)?)
+        .map_err(|e| Error::other(format!("Failed to parse {:?}: {}", path, e)))
+}
+
+/// Build one mesh of the system avatar. Meshes without weights follow `rigid_joint`, relative to
+/// which their vertices are placed.
+fn build_part(
+    name: &str,
+    agent_id: Uuid,
+    mesh: &AvatarMesh,
+    morphs: &BTreeMap<String, f32>,
+    rigid_joint: Option<JointName>,
+    texture: Option<PathBuf>,
+) -> io::Result<RenderObject> {
+    let mut vertices = mesh.morphed_vertices(morphs);
+
+    let (joint_names, weights) = match rigid_joint {
+        Some(joint) => {
+            let position = joint_position(&joint)
+                .ok_or_else(|| Error::other(format!("{:?} is not in the skeleton", joint)))?;
+            for vertex in &mut vertices {
+                *vertex += position;
+            }
+            let weight = JointWeight {
+                indices: [0; 4],
+                weights: [1.0, 0.0, 0.0, 0.0],
+                joint_name: [joint; 4],
+            };
+            (vec![joint], vec![weight; vertices.len()])
+        }
+        None => skin_weights(&DEFAULT_SKELETON, mesh)?,
+    };
+
+    let inverse_bind_matrices = joint_names
+        .iter()
+        .map(|joint| Mat4::from_translation(-joint_position(joint).unwrap_or_default()))
+        .collect();
+    let skin = Skin {
+        joint_names: joint_names.clone(),
+        inverse_bind_matrices,
+        bind_shape_matrix: Mat4::IDENTITY,
+    };
+    let skeleton = create_skeleton(name.to_string(), agent_id, &skin).unwrap_or_else(|e| {
+        warn!("Failed to create skeleton: {:?}", e);
+        Skeleton::default()
+    });
+
+    let face = RenderFace {
+        first_index: 0,
+        index_count: mesh.indices.len(),
+        texture: texture.clone(),
+        color: [1.0; 4],
+        glow: 0.0,
+        normal_texture: None,
+        metallic_roughness_texture: None,
+        emissive_texture: None,
+        metallic: 0.0,
+        roughness: 1.0,
+        emissive: [0.0; 3],
+    };
+    Ok(RenderObject {
+        name: name.to_string(),
+        id: agent_id,
+        indices: mesh.indices.clone(),
+        vertices,
+        skin: Some(SkinData {
+            skeleton,
+            weights,
+            joint_names: skin.joint_names,
+            inverse_bind_matrices: skin.inverse_bind_matrices,
+        }),
+        texture,
+        // avatar meshes have V going up
+        uv: Some(mesh.uvs.iter().map(|uv| [uv.x, 1.0 - uv.y]).collect()),
+        faces: vec![face],
+    })
+}
+
+/// Convert the weights of an avatar mesh to the joints and weights of a skin.
+///
+/// The weights of avatar meshes index the mesh's render joints, which are its skin joints with
+/// the parent of each inserted before it, unless the parent is the joint before. A vertex follows
+/// the joint of the integer part of its weight, and is blended into the next render joint by the
+/// fraction.
+pub fn skin_weights(
+    skeleton: &Skeleton,
+    mesh: &AvatarMesh,
+) -> io::Result<(Vec<JointName>, Vec<JointWeight>)> {
+    let render_joints = render_joints(skeleton, &mesh.skin_joints);
+    let mut joint_names: Vec<JointName> = Vec::new();
+    for joint in &render_joints {
+        if !joint_names.contains(joint) {
+            joint_names.push(*joint);
+        }
+    }
+    let index = |joint: &JointName| joint_names.iter().position(|name| name == joint);
+
+    let mut weights = Vec::with_capacity(mesh.weights.len());
+    for weight in &mesh.weights {
+        let first = weight.floor().max(0.0) as usize;
+        let blend = weight - first as f32;
+        let joint = render_joints
+            .get(first)
+            .ok_or_else(|| Error::other(format!("Weight {} is past the mesh's joints", weight)))?;
+        let next = render_joints.get(first + 1).unwrap_or(joint);
+        let (Some(joint_index), Some(next_index)) = (index(joint), index(next)) else {
+            return Err(Error::other("Render joint missing from the skin"));
+        };
+        weights.push(JointWeight {
+            indices: [joint_index as u8, next_index as u8, 0, 0],
+            weights: [1.0 - blend, blend, 0.0, 0.0],
+            joint_name: [*joint, *next, *joint, *joint],
+        });
+    }
+    Ok((joint_names, weights))
+}
+
+/// The render joints of a mesh, from walking the skeleton depth first. Root joints stand in for
+/// their own parent.
+pub fn render_joints(skeleton: &Skeleton, skin_joints: &[JointName]) -> Vec<JointName> {
+    let mut render_joints = Vec::new();
+    let mut stack: Vec<JointName> = skeleton.root.iter().rev().copied().collect();
+    while let Some(name) = stack.pop() {
+        let Some(joint) = skeleton.joints.get(&name) else {
+            continue;
+        };
+        if skin_joints.contains(&name) {
+            let parent = joint.parent.unwrap_or(name);
+            if render_joints.last() != Some(&parent) {
+                render_joints.push(parent);
+            }
+            render_joints.push(name);
+        }
+        stack.extend(joint.children.iter().rev().copied());
+    }
+    render_joints
+}
+
+/// The position of a joint in the default skeleton
+fn joint_position(joint: &JointName) -> Option<Vec3> {
+    let transform = DEFAULT_SKELETON.joints.get(joint)?.transforms.first()?;
+    Some(transform.transform.inverse().w_axis.truncate())
+}
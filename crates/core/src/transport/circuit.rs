@@ -0,0 +1,106 @@
+use std::collections::BTreeSet;
+
+/// How many sequence numbers behind the newest received packet are still tracked. Packets older
+/// than this mean the simulator has started its sequence numbers over, and skipped packets that
+/// fall out of it count as dropped.
+pub const SEQUENCE_WINDOW: u32 = 1024;
+
+/// Sequence numbers wrap back to 0 after this many packets
+pub const SEQUENCE_SPACE: u32 = 0x0100_0000;
+
+/// Counters describing the health of the incoming circuit from the simulator
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CircuitStats {
+    /// total number of packets received
+    pub received: u64,
+    /// sequence numbers that were skipped and never arrived before leaving the window
+    pub dropped: u64,
+    /// packets received more than once, usually resends of packets that were already handled
+    pub duplicated: u64,
+    /// packets that arrived after a packet with a higher sequence number
+    pub reordered: u64,
+    /// how many times the simulator started its sequence numbers over
+    pub resets: u64,
+}
+
+/// Where an incoming packet falls relative to the packets already received
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketOrder {
+    /// The packet is newer than everything received so far
+    InOrder,
+    /// The packet was skipped over earlier, and has now arrived late
+    Reordered,
+    /// The packet has already been received
+    Duplicate,
+}
+
+/// Sliding window of recently received sequence numbers for a single circuit.
+///
+/// Only the highest sequence number and the numbers skipped below it are stored. Anything at or
+/// below the highest that isn't in the skipped set has already been received. Sequence numbers
+/// are unwrapped into a count that keeps growing past [`SEQUENCE_SPACE`], so the window carries
+/// on across the wrap.
+#[derive(Debug, Default)]
+pub struct PacketWindow {
+    /// highest sequence number received so far, unwrapped
+    highest: Option<u64>,
+    /// unwrapped sequence numbers within the window that have been skipped over and not yet
+    /// received
+    missing: BTreeSet<u64>,
+    /// counters for the circuit
+    pub stats: CircuitStats,
+}
+
+impl PacketWindow {
+    /// Record an incoming sequence number, and determine if it is new, late or a duplicate.
+    /// Duplicates should be acked, but not handled again.
+    pub fn check(&mut self, sequence_number: u32) -> PacketOrder {
+        self.stats.received += 1;
+        let sequence_number = sequence_number % SEQUENCE_SPACE;
+        let Some(highest) = self.highest else {
+            self.highest = Some(sequence_number as u64);
+            return PacketOrder::InOrder;
+        };
+
+        // how far the packet is ahead of the highest, across the wrap
+        let ahead = sequence_number.wrapping_sub(highest as u32) % SEQUENCE_SPACE;
+        if ahead == 0 {
+            self.stats.duplicated += 1;
+            return PacketOrder::Duplicate;
+        }
+        if ahead < SEQUENCE_SPACE / 2 {
+            let sequence_number = highest + ahead as u64;
+            // everything skipped over is missing until it shows up. Gaps larger than the window
+            // are counted as dropped right away.
+            let floor = sequence_number.saturating_sub(SEQUENCE_WINDOW as u64);
+            let gap_start = (highest + 1).max(floor);
+            self.stats.dropped += gap_start - (highest + 1);
+            self.missing.extend(gap_start..sequence_number);
+            self.highest = Some(sequence_number);
+
+            // skipped packets that have left the window are never coming
+            let still_missing = self.missing.split_off(&floor);
+            self.stats.dropped += self.missing.len() as u64;
+            self.missing = still_missing;
+            return PacketOrder::InOrder;
+        }
+
+        let behind = (SEQUENCE_SPACE - ahead) as u64;
+        if behind > SEQUENCE_WINDOW as u64 {
+            // the simulator started over, such as after a restart, so the window does too
+            self.stats.resets += 1;
+            self.missing.clear();
+            self.highest = Some(sequence_number as u64);
+            PacketOrder::InOrder
+        } else if highest
+            .checked_sub(behind)
+            .is_some_and(|sequence_number| self.missing.remove(&sequence_number))
+        {
+            self.stats.reordered += 1;
+            PacketOrder::Reordered
+        } else {
+            self.stats.duplicated += 1;
+            PacketOrder::Duplicate
+        }
+    }
+}
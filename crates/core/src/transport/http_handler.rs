@@ -1,21 +1,34 @@
+use crate::materials::{ObjectMaterials, transform_uv};
+use awc::http::StatusCode;
 use benthic_protocol::messages::ui::login_error::{LoginError, Reason};
 use benthic_protocol::messages::ui::login_event::Login;
+use benthic_protocol::messages::ui::mesh_update::LevelOfDetail;
 use benthic_protocol::render_data::{RenderObject, SkinData};
 use benthic_protocol::skeleton::Skeleton;
-use glam::{Vec3, Vec4};
+use glam::{Vec2, Vec3, Vec4};
 use image::{DynamicImage, ImageBuffer, Luma, LumaA, Rgb, Rgba};
 use jpeg2k::{Image, ImagePixelData};
 use log::warn;
 use metaverse_agent::skeleton::create_skeleton;
+use metaverse_messages::http::gltf_material::GltfMaterial;
+use metaverse_messages::http::item::{Item, ItemData};
 use metaverse_messages::http::login::login_response::{LoginResponse, LoginStatus};
 use metaverse_messages::http::login::simulator_login_protocol::SimulatorLoginProtocol;
-use metaverse_messages::http::mesh::Mesh;
-use metaverse_messages::http::{item::Item, scene::SceneGroup};
+use metaverse_messages::http::mesh::{Mesh, MeshGeometry, MeshHeader, MeshSection, Skin};
+use metaverse_messages::http::render_materials::LegacyMaterial;
+use metaverse_messages::http::scene::SceneGroup;
 use metaverse_messages::utils::object_types::ObjectType;
+use metaverse_messages::utils::texture_entry::TextureEntry;
+use std::collections::HashMap;
 use std::io::Error;
-use std::path::{Path, PathBuf};
+use std::ops::Range;
+use std::path::PathBuf;
 use uuid::Uuid;
 
+/// Bytes requested for the header of a mesh. Headers only locate the sections of the mesh, so they
+/// are much smaller than this.
+const MESH_HEADER_RANGE: usize = 4096;
+
 /// send the login to simulator xml-rpc request
 pub async fn login_to_simulator(
     login: Login,
@@ -120,6 +133,45 @@ pub async fn download_asset(
     Ok(body_bytes)
 }
 
+/// Retrieve a range of bytes of an asset from the ViewerAsset endpoint.
+///
+/// Servers that don't support range requests send the whole asset, which is cut down to the
+/// requested range. The end of the range may be past the end of the asset.
+pub async fn download_asset_range(
+    item_type: String,
+    asset_id: Uuid,
+    server_endpoint: &str,
+    range: Range<usize>,
+) -> std::io::Result<bytes::Bytes> {
+    let client = awc::Client::default();
+    let url = format!("{}/?{}_id={}", server_endpoint, item_type, asset_id);
+    let mut response = client
+        .get(&url)
+        .insert_header((
+            "Range",
+            format!("bytes={}-{}", range.start, range.end.saturating_sub(1)),
+        ))
+        .send()
+        .await
+        .map_err(|e| io_error("Failed to send HTTP GET request", e))?;
+
+    let body_bytes = response
+        .body()
+        .await
+        .map_err(|e| io_error("Failed to read response body", e))?;
+    if body_bytes.is_empty() {
+        return Err(Error::other("Empty response body"));
+    }
+    if response.status() == StatusCode::PARTIAL_CONTENT {
+        return Ok(body_bytes);
+    }
+    let end = range.end.min(body_bytes.len());
+    if range.start >= end {
+        return Err(Error::other("Requested range is past the end of the asset"));
+    }
+    Ok(body_bytes.slice(range.start..end))
+}
+
 /// retrieve an Object from the ViewerAsset endpoint.
 /// this needs to be parsed as a SceneGroup.
 pub async fn download_object(
@@ -142,6 +194,17 @@ pub async fn download_item(
         .map_err(|e| Error::other(format!("Failed to parse item: {}", e)))
 }
 
+/// Retrieve a wearable, such as a bodypart or clothing, from the ViewerAsset endpoint.
+/// this needs to be parsed as an ItemData object
+pub async fn download_wearable(
+    item_type: String,
+    asset_id: Uuid,
+    server_endpoint: &str,
+) -> std::io::Result<ItemData> {
+    ItemData::from_bytes(&download_asset(item_type, asset_id, server_endpoint).await?)
+        .map_err(|e| Error::other(format!("Failed to parse wearable: {}", e)))
+}
+
 /// Retrieve a mesh from the ViewerAsset endpoint.
 /// This needs to be parsed as a Mesh object.
 pub async fn download_mesh(
@@ -191,6 +254,41 @@ pub async fn download_texture(
     output.save(path).unwrap();
     Ok(output)
 }
+
+/// Retrieve a PBR material from the ViewerAsset endpoint.
+pub async fn download_gltf_material(
+    asset_id: Uuid,
+    server_endpoint: &str,
+) -> std::io::Result<GltfMaterial> {
+    GltfMaterial::from_bytes(
+        &download_asset(ObjectType::Material.to_string(), asset_id, server_endpoint).await?,
+    )
+    .map_err(|e| Error::other(format!("Failed to parse material: {}", e)))
+}
+
+/// Retrieve the legacy materials with the given IDs from the RenderMaterials endpoint.
+pub async fn download_render_materials(
+    material_ids: &[Uuid],
+    server_endpoint: &str,
+) -> std::io::Result<HashMap<Uuid, LegacyMaterial>> {
+    let body = LegacyMaterial::request_body(material_ids)
+        .map_err(|e| io_error("Failed to create render materials request", e))?;
+    let client = awc::Client::default();
+    let mut response = client
+        .post(server_endpoint)
+        .insert_header(("Content-Type", "application/llsd+xml"))
+        .send_body(body)
+        .await
+        .map_err(|e| io_error("Failed to send HTTP POST request", e))?;
+
+    let body_bytes = response
+        .body()
+        .await
+        .map_err(|e| io_error("Failed to read response body", e))?;
+    LegacyMaterial::response_from_llsd(&body_bytes)
+        .map_err(|e| Error::other(format!("Failed to parse render materials: {}", e)))
+}
+
 fn io_error(msg: &str, err: impl std::fmt::Debug) -> std::io::Error {
     Error::other(format!("{}: {:?}", msg, err))
 }
@@ -201,16 +299,19 @@ fn io_error(msg: &str, err: impl std::fmt::Debug) -> std::io::Error {
 pub async fn download_scene_group(
     scene_group: &SceneGroup,
     url: &str,
-    texture_path: &Path,
+    materials: &ObjectMaterials,
 ) -> Result<Vec<RenderObject>, std::io::Error> {
     let mut meshes = Vec::new();
     for scene in &scene_group.parts {
+        let source = download_mesh_source(scene.sculpt.texture, url).await?;
         meshes.push(
             download_renderable_mesh(
-                scene.sculpt.texture,
+                &source,
+                LevelOfDetail::High,
                 scene.metadata.name.clone(),
                 url,
-                texture_path,
+                &scene.shape.texture,
+                materials,
             )
             .await?,
         );
@@ -218,21 +319,151 @@ pub async fn download_scene_group(
     Ok(meshes)
 }
 
-/// retrieves mesh data and does operations on the received data to ready it for the metaverse_mesh
-/// crate.
-pub async fn download_renderable_mesh(
+/// The parts of a mesh asset that every level of detail needs
+#[derive(Debug)]
+pub struct MeshSource {
+    /// Asset ID of the mesh
+    pub asset_id: Uuid,
+    /// The header locating each section of the mesh
+    pub header: MeshHeader,
+    /// The skin of the mesh, if it is rigged
+    pub skin: Option<Skin>,
+    /// The whole asset, if it had to be downloaded to read the header. Sections are read from it
+    /// instead of being requested again.
+    pub bytes: Option<bytes::Bytes>,
+}
+
+impl MeshSource {
+    /// The section of the asset that holds a level of detail, if the mesh has it
+    pub fn level_of_detail(&self, lod: LevelOfDetail) -> Option<MeshSection> {
+        match lod {
+            LevelOfDetail::Lowest => self.header.lowest_lod,
+            LevelOfDetail::Low => self.header.low_lod,
+            LevelOfDetail::Medium => self.header.medium_lod,
+            LevelOfDetail::High => self.header.high_lod,
+        }
+    }
+}
+
+/// Retrieve the header and skin of a mesh from the ViewerAsset endpoint, without any of its
+/// levels of detail.
+///
+/// Only the start of the asset is requested for the header. If the header doesn't fit, the whole
+/// asset is downloaded instead.
+pub async fn download_mesh_source(
     asset_id: Uuid,
+    server_endpoint: &str,
+) -> std::io::Result<MeshSource> {
+    let item_type = ObjectType::Mesh.to_string();
+    let prefix = download_asset_range(
+        item_type.clone(),
+        asset_id,
+        server_endpoint,
+        0..MESH_HEADER_RANGE,
+    )
+    .await?;
+    let (header, bytes) = match MeshHeader::from_bytes(&prefix) {
+        Ok(header) => (header, None),
+        Err(_) => {
+            let bytes = download_asset(item_type, asset_id, server_endpoint).await?;
+            let header = MeshHeader::from_bytes(&bytes)
+                .map_err(|e| Error::other(format!("Failed to parse mesh header: {}", e)))?;
+            (header, Some(bytes))
+        }
+    };
+
+    let mut source = MeshSource {
+        asset_id,
+        header,
+        skin: None,
+        bytes,
+    };
+    if let Some(section) = source.header.skin {
+        let bytes = download_mesh_section(&source, server_endpoint, &section).await?;
+        source.skin = Some(
+            Skin::from_compressed(&bytes)
+                .map_err(|e| Error::other(format!("Failed to parse mesh skin: {}", e)))?,
+        );
+    }
+    Ok(source)
+}
+
+/// Retrieve one compressed section of a mesh. The section is read from the whole asset if it has
+/// already been downloaded, and requested from the ViewerAsset endpoint otherwise.
+async fn download_mesh_section(
+    source: &MeshSource,
+    server_endpoint: &str,
+    section: &MeshSection,
+) -> std::io::Result<bytes::Bytes> {
+    let range = section.range(source.header.size);
+    match &source.bytes {
+        Some(bytes) => {
+            if range.end > bytes.len() {
+                return Err(Error::other(format!(
+                    "Mesh section {:?} is outside of the {} byte asset",
+                    range,
+                    bytes.len()
+                )));
+            }
+            Ok(bytes.slice(range))
+        }
+        None => {
+            download_asset_range(
+                ObjectType::Mesh.to_string(),
+                source.asset_id,
+                server_endpoint,
+                range,
+            )
+            .await
+        }
+    }
+}
+
+/// retrieves a level of detail of a mesh and does operations on the received data to ready it for
+/// the metaverse_mesh crate.
+///
+/// Each submesh of the mesh is a face of the object, and gets the material of its face in the
+/// object's texture entry.
+pub async fn download_renderable_mesh(
+    source: &MeshSource,
+    lod: LevelOfDetail,
     name: String,
     url: &str,
-    texture_path: &Path,
+    texture_entry: &TextureEntry,
+    materials: &ObjectMaterials,
 ) -> Result<RenderObject, std::io::Error> {
-    let mesh = download_mesh(ObjectType::Mesh.to_string(), asset_id, url).await?;
-    let domain = &mesh.high_level_of_detail.texture_coordinate_domain;
-    let uvs: Vec<[f32; 2]> = mesh
-        .high_level_of_detail
-        .texture_coordinate
-        .iter()
-        .map(|tc| {
+    let asset_id = source.asset_id;
+    let section = source
+        .level_of_detail(lod)
+        .ok_or_else(|| Error::other(format!("Mesh has no {:?} level of detail", lod)))?;
+    let bytes = download_mesh_section(source, url, &section).await?;
+    let submeshes = MeshGeometry::from_compressed(&bytes, &source.skin)
+        .map_err(|e| Error::other(format!("Failed to parse mesh: {}", e)))?;
+
+    let mut vertices: Vec<Vec3> = Vec::new();
+    let mut indices = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+    let mut weights = Vec::new();
+    let mut faces = Vec::new();
+    for (index, submesh) in submeshes.iter().enumerate() {
+        if submesh.no_geometry {
+            continue;
+        }
+        // the submeshes share one index buffer once they are merged
+        if vertices.len() + submesh.vertices.len() > u16::MAX as usize + 1 {
+            return Err(Error::other("Mesh has too many vertices to index"));
+        }
+        let offset = vertices.len() as u16;
+        let entry = texture_entry.face(index as u32);
+        faces.push(materials.render_face(
+            index as u32,
+            entry,
+            indices.len(),
+            submesh.indices.len(),
+        ));
+
+        let domain = &submesh.texture_coordinate_domain;
+        uvs.extend(submesh.texture_coordinate.iter().map(|tc| {
             // Normalize U and V from 0..65535 to 0..1
             let u_norm = tc.u as f32 / 65535.0;
             let v_norm = tc.v as f32 / 65535.0;
@@ -240,18 +471,21 @@ pub async fn download_renderable_mesh(
             // Flip V axis
             let v_flipped = 1.0 - v_norm;
 
-            [
+            let uv = Vec2::new(
                 domain.min[0] + u_norm * (domain.max[0] - domain.min[0]),
                 domain.min[1] + v_flipped * (domain.max[1] - domain.min[1]),
-            ]
-        })
-        .collect();
+            );
+            transform_uv(uv, entry).to_array()
+        }));
+        vertices.extend_from_slice(&submesh.vertices);
+        indices.extend(submesh.indices.iter().map(|i| i + offset));
+        weights.extend(submesh.weights.iter().flatten().cloned());
+    }
+    let texture = materials.textures.get(&texture_entry.texture_id).cloned();
 
-    let object = if let Some(skin) = &mesh.skin {
+    let object = if let Some(skin) = &source.skin {
         // Apply bind shape matrix
-        let vertices: Vec<Vec3> = mesh
-            .high_level_of_detail
-            .vertices
+        let vertices: Vec<Vec3> = vertices
             .iter()
             .map(|v| {
                 let v4 = skin.bind_shape_matrix * Vec4::new(v.x, v.y, v.z, 1.0);
@@ -266,11 +500,7 @@ pub async fn download_renderable_mesh(
 
         let skin_data = SkinData {
             skeleton,
-            weights: mesh
-                .high_level_of_detail
-                .weights
-                .clone()
-                .unwrap_or_default(),
+            weights,
             joint_names: skin.joint_names.clone(),
             inverse_bind_matrices: skin.inverse_bind_matrices.clone(),
         };
@@ -278,26 +508,23 @@ pub async fn download_renderable_mesh(
         RenderObject {
             name,
             id: asset_id,
-            indices: mesh.high_level_of_detail.indices,
+            indices,
             vertices,
             skin: Some(skin_data),
-            texture: Some(texture_path.to_path_buf()),
+            texture,
             uv: Some(uvs),
+            faces,
         }
     } else {
-        let vertices: Vec<Vec3> = mesh.high_level_of_detail.vertices;
-        //.iter()
-        //.map(|v| apply_scale_rotation(*v, scale, rotation))
-        //.collect();
-
         RenderObject {
             name,
             id: asset_id,
-            indices: mesh.high_level_of_detail.indices,
+            indices,
             vertices,
             skin: None,
-            texture: Some(texture_path.to_path_buf()),
+            texture,
             uv: Some(uvs),
+            faces,
         }
     };
 
@@ -1,3 +1,6 @@
+/// Tracks the order of packets received on the circuit between the core and the simulator.
+/// This is used to detect duplicate, reordered and dropped packets.
+pub mod circuit;
 /// handles sending requests to HTTP endpoints on the server.
 /// This includes logins, and capability endpoint requests.
 pub mod http_handler;
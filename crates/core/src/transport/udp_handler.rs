@@ -1,27 +1,53 @@
 use crate::avatar::{HandleNewAvatarAnimation, HandleNewAvatarAppearance};
 use crate::environment::{HandleLayerData, HandleSimulatorViewerTimeMessage};
 use crate::objects::{
-    HandleImprovedTerseObjectUpdate, HandleObjectUpdate, HandleObjectUpdateCached,
+    HandleGenericStreamingMessage, HandleImprovedTerseObjectUpdate, HandleKillObject,
+    HandleObjectUpdate, HandleObjectUpdateCached,
 };
 use crate::session::{
-    AddToAckList, HandlePacketAck, HandlePing, HandleRegionHandshake, Mailbox, SendUIMessage,
+    AddToAckList, CIRCUIT_STATS_INTERVAL, CIRCUIT_TIMEOUT, HandleCircuitDead, HandleCircuitStats,
+    HandleCompletePingCheck, HandlePacketAck, HandlePing, HandleRegionHandshake, Mailbox,
+    SendUIMessage,
 };
+use crate::transport::circuit::{PacketOrder, PacketWindow};
 use actix::Addr;
 use benthic_protocol::messages::ui::chat_from_simulator::ChatFromSimulator;
 use benthic_protocol::messages::ui::ui_messages::UIMessage;
 use log::{error, warn};
 use metaverse_messages::packet::{packet_protocol::Packet, packet_types::PacketType};
+use metaverse_messages::udp::core::packet_ack::PacketAck;
+use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::net::UdpSocket;
+use tokio::{net::UdpSocket, time};
 
 impl Mailbox {
     /// Start_udp_read is for reading packets coming from the external server
     pub async fn start_udp_read(sock: Arc<UdpSocket>, mailbox_address: Addr<Mailbox>) {
         let mut buf = [0; 1500];
+        // only report a dead circuit once, until the simulator starts sending again
+        let mut circuit_dead = false;
+        // track which sequence numbers have already been received from each simulator, to skip
+        // duplicates
+        let mut windows: HashMap<SocketAddr, PacketWindow> = HashMap::new();
+        let mut last_stats = time::Instant::now();
 
         loop {
-            match sock.recv_from(&mut buf).await {
-                Ok((size, _addr)) => {
+            let received = match time::timeout(CIRCUIT_TIMEOUT, sock.recv_from(&mut buf)).await {
+                Ok(received) => received,
+                Err(_) => {
+                    if !circuit_dead {
+                        circuit_dead = true;
+                        if let Err(e) = mailbox_address.send(HandleCircuitDead {}).await {
+                            error!("Failed to handle dead circuit {:?}", e)
+                        }
+                    }
+                    continue;
+                }
+            };
+            circuit_dead = false;
+            match received {
+                Ok((size, addr)) => {
                     let packet = match Packet::from_bytes(&buf[..size]) {
                         Ok(packet) => packet,
                         Err(e) => {
@@ -40,6 +66,44 @@ impl Mailbox {
                             warn!("Failed to send ping: {:?}", e)
                         }
 
+                    // acks can also be piggybacked onto the end of any packet. These are handled
+                    // exactly like the contents of a PacketAck.
+                    if let Some(packet_ids) = &packet.header.ack_list
+                        && !packet_ids.is_empty()
+                        && let Err(e) = mailbox_address
+                            .send(HandlePacketAck {
+                                packet_ack: PacketAck {
+                                    packet_ids: packet_ids.clone(),
+                                },
+                            })
+                            .await
+                    {
+                        error!("Failed to handle appended acks {:?}", e)
+                    }
+
+                    // duplicates have been acked again above, but were already handled
+                    let order = windows
+                        .entry(addr)
+                        .or_default()
+                        .check(packet.header.sequence_number);
+                    if last_stats.elapsed() >= CIRCUIT_STATS_INTERVAL {
+                        last_stats = time::Instant::now();
+                        for (circuit, window) in &windows {
+                            if let Err(e) = mailbox_address
+                                .send(HandleCircuitStats {
+                                    circuit: *circuit,
+                                    stats: window.stats.clone(),
+                                })
+                                .await
+                            {
+                                error!("Failed to handle circuit stats {:?}", e)
+                            }
+                        }
+                    }
+                    if order == PacketOrder::Duplicate {
+                        continue;
+                    }
+
                     match &packet.body {
                         PacketType::PacketAck(data) => {
                             if let Err(e) = mailbox_address
@@ -61,6 +125,16 @@ impl Mailbox {
                                 warn!("failed to handle pong {:?}", e)
                             };
                         }
+                        PacketType::CompletePingCheck(data) => {
+                            if let Err(e) = mailbox_address
+                                .send(HandleCompletePingCheck {
+                                    ping_id: data.ping_id,
+                                })
+                                .await
+                            {
+                                warn!("failed to handle ping reply {:?}", e)
+                            };
+                        }
                         PacketType::RegionHandshake(data) => {
                             if let Err(e) = mailbox_address
                                 .send(HandleRegionHandshake {
@@ -98,6 +172,8 @@ impl Mailbox {
                                     parent: Some(data.parent_id),
                                     texture: data.texture_entry.clone(),
                                     crc: data.crc,
+                                    path: data.primitive_geometry.clone(),
+                                    state: data.state,
                                 })
                                 .await
                             {
@@ -140,6 +216,8 @@ impl Mailbox {
                                         parent: object.parent_id,
                                         texture: object.texture_entry,
                                         crc: object.crc,
+                                        path: object.sculpt_path,
+                                        state: object.state,
                                     })
                                     .await
                                 {
@@ -147,6 +225,26 @@ impl Mailbox {
                                 };
                             }
                         }
+                        PacketType::GenericStreamingMessage(data) => {
+                            if let Err(e) = mailbox_address
+                                .send(HandleGenericStreamingMessage {
+                                    message: *data.clone(),
+                                })
+                                .await
+                            {
+                                error!("Failed to handle GenericStreamingMessage {:?}", e)
+                            };
+                        }
+                        PacketType::KillObject(data) => {
+                            if let Err(e) = mailbox_address
+                                .send(HandleKillObject {
+                                    object_ids: data.object_ids.clone(),
+                                })
+                                .await
+                            {
+                                error!("Failed to handle KillObject {:?}", e)
+                            };
+                        }
                         #[cfg(feature = "environment")]
                         PacketType::LayerData(data) => {
                             if let Err(e) = mailbox_address
@@ -0,0 +1,767 @@
+use crate::materials::{ObjectMaterials, transform_uv};
+use benthic_protocol::render_data::RenderObject;
+use glam::{Quat, Vec2, Vec3};
+use image::RgbImage;
+use metaverse_messages::http::scene::SculptType;
+use metaverse_messages::udp::object::object_update::SculptData;
+use metaverse_messages::utils::path::Path;
+use metaverse_messages::utils::texture_entry::TextureEntry;
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+use uuid::Uuid;
+
+/// Detail used by the viewer for its highest level of detail. Curved profiles and paths get six
+/// sides per unit of detail.
+pub const HIGH_DETAIL: f32 = 4.0;
+const MIN_DETAIL_FACES: f32 = 6.0;
+/// The smallest amount of a shape that a cut can leave behind
+const MIN_CUT_DELTA: f32 = 0.02;
+/// Hollows larger than this would make the walls of the shape disappear
+const MAX_HOLLOW: f32 = 0.95;
+
+/// Radii of regular polygons with few sides, scaled so that they fill the unit box
+const TABLE_SCALE: [f32; 8] = [1.0, 1.0, 1.0, 0.5, FRAC_1_SQRT_2, 0.53, 0.525, 0.5];
+
+const CUT_QUANTA: f32 = 0.00002;
+const SCALE_QUANTA: f32 = 0.01;
+const SHEAR_QUANTA: f32 = 0.01;
+const TAPER_QUANTA: f32 = 0.01;
+const REV_QUANTA: f32 = 0.015;
+
+/// The shape traced by the profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileCurve {
+    /// 0x00
+    Circle,
+    /// 0x01
+    Square,
+    /// 0x02
+    IsoscelesTriangle,
+    /// 0x03
+    EquilateralTriangle,
+    /// 0x04
+    RightTriangle,
+    /// 0x05
+    HalfCircle,
+}
+
+impl ProfileCurve {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0x0f {
+            0x01 => ProfileCurve::Square,
+            0x02 => ProfileCurve::IsoscelesTriangle,
+            0x03 => ProfileCurve::EquilateralTriangle,
+            0x04 => ProfileCurve::RightTriangle,
+            0x05 => ProfileCurve::HalfCircle,
+            _ => ProfileCurve::Circle,
+        }
+    }
+}
+
+/// The shape of the hollow cut into the profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoleShape {
+    /// 0x00, the same shape as the profile
+    Same,
+    /// 0x10
+    Circle,
+    /// 0x20
+    Square,
+    /// 0x30
+    Triangle,
+}
+
+impl HoleShape {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0xf0 {
+            0x10 => HoleShape::Circle,
+            0x20 => HoleShape::Square,
+            0x30 => HoleShape::Triangle,
+            _ => HoleShape::Same,
+        }
+    }
+}
+
+/// The path the profile is swept along
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathCurve {
+    /// 0x10, used by boxes, cylinders and prisms
+    Line,
+    /// 0x20, used by spheres, tori, tubes and rings
+    Circle,
+    /// 0x30, an alternate circular path
+    Circle2,
+    /// 0x80, a line that is bent by the viewer's flexible object simulation
+    Flexible,
+}
+
+impl PathCurve {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0xf0 {
+            0x20 => PathCurve::Circle,
+            0x30 => PathCurve::Circle2,
+            0x80 => PathCurve::Flexible,
+            _ => PathCurve::Line,
+        }
+    }
+}
+
+/// Prim shape parameters, dequantized from the [`Path`] sent in ObjectUpdate packets.
+///
+/// Cuts are fractions of the profile or path between 0 and 1. Scale, shear, twist, taper, radius
+/// offset and skew are fractions of the size of the prim, and twists are fractions of a half turn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeParams {
+    /// Shape of the profile
+    pub profile_curve: ProfileCurve,
+    /// Shape of the hollow
+    pub hole_shape: HoleShape,
+    /// Start of the profile cut
+    pub profile_begin: f32,
+    /// End of the profile cut
+    pub profile_end: f32,
+    /// Size of the hollow, as a fraction of the profile
+    pub hollow: f32,
+    /// Shape of the path
+    pub path_curve: PathCurve,
+    /// Start of the path cut
+    pub path_begin: f32,
+    /// End of the path cut
+    pub path_end: f32,
+    /// Size of the profile along the path. Values below 1 taper the end, values above 1 taper
+    /// the beginning
+    pub scale: Vec2,
+    /// Offset of the end of the path
+    pub shear: Vec2,
+    /// Twist at the beginning of the path
+    pub twist_begin: f32,
+    /// Twist at the end of the path
+    pub twist_end: f32,
+    /// Moves circular paths towards or away from their center
+    pub radius_offset: f32,
+    /// Taper along circular paths
+    pub taper: Vec2,
+    /// Number of times circular paths go around their center
+    pub revolutions: f32,
+    /// Offsets circular paths along their axis
+    pub skew: f32,
+}
+
+impl From<&Path> for VolumeParams {
+    fn from(path: &Path) -> Self {
+        let profile_begin = path.profile_begin as f32 * CUT_QUANTA;
+        let profile_end = (50000 - path.profile_end.min(50000)) as f32 * CUT_QUANTA;
+        let (profile_begin, profile_end) = sanitize_cut(profile_begin, profile_end);
+        let path_begin = path.begin as f32 * CUT_QUANTA;
+        let path_end = (50000 - path.end.min(50000)) as f32 * CUT_QUANTA;
+        let (path_begin, path_end) = sanitize_cut(path_begin, path_end);
+
+        // the message crate stores the hollow as a percentage
+        let hollow = path.profile_hollow / 100.0;
+
+        Self {
+            profile_curve: ProfileCurve::from_bits(path.profile_curve),
+            hole_shape: HoleShape::from_bits(path.profile_curve),
+            profile_begin,
+            profile_end,
+            hollow: hollow.clamp(0.0, MAX_HOLLOW),
+            path_curve: PathCurve::from_bits(path.curve),
+            path_begin,
+            path_end,
+            scale: Vec2::new(
+                (200.0 - path.scale_x as f32) * SCALE_QUANTA,
+                (200.0 - path.scale_y as f32) * SCALE_QUANTA,
+            )
+            .clamp(Vec2::ZERO, Vec2::splat(2.0)),
+            shear: Vec2::new(
+                path.shear_x as i8 as f32 * SHEAR_QUANTA,
+                path.shear_y as i8 as f32 * SHEAR_QUANTA,
+            ),
+            twist_begin: path.twist_begin as f32 * SCALE_QUANTA,
+            twist_end: path.twist_end as f32 * SCALE_QUANTA,
+            radius_offset: path.radius_offset as f32 * SCALE_QUANTA,
+            taper: Vec2::new(
+                path.taper_x as f32 * TAPER_QUANTA,
+                path.taper_y as f32 * TAPER_QUANTA,
+            ),
+            revolutions: path.revolutions as f32 * REV_QUANTA + 1.0,
+            skew: path.skew as f32 * SCALE_QUANTA,
+        }
+    }
+}
+
+/// Keep at least [`MIN_CUT_DELTA`] of a shape, so that it never disappears
+fn sanitize_cut(begin: f32, end: f32) -> (f32, f32) {
+    let begin = begin.clamp(0.0, 1.0 - MIN_CUT_DELTA);
+    let end = end.clamp(begin + MIN_CUT_DELTA, 1.0);
+    (begin, end)
+}
+
+/// Identifies a face of a generated prim
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaceKind {
+    /// The cap at the end of the path. This is the top of boxes and cylinders.
+    PathBegin,
+    /// The cap at the beginning of the path. This is the bottom of boxes and cylinders.
+    PathEnd,
+    /// The inside of the hollow
+    InnerSide,
+    /// The face left by cutting the beginning of the profile
+    ProfileBegin,
+    /// The face left by cutting the end of the profile
+    ProfileEnd,
+    /// The outside of the profile. Square and triangle profiles have one of these per side.
+    OuterSide(u8),
+}
+
+/// A single face of a generated prim.
+///
+/// Texture coordinates run from 0 to 1 across the face, with v pointing down the texture.
+#[derive(Debug, Clone)]
+pub struct VolumeFace {
+    /// Which part of the prim this face is
+    pub kind: FaceKind,
+    /// Vertex positions in the unit box
+    pub vertices: Vec<Vec3>,
+    /// Texture coordinates of each vertex
+    pub uvs: Vec<Vec2>,
+    /// Counter clockwise triangles
+    pub indices: Vec<u16>,
+}
+
+impl VolumeFace {
+    fn new(kind: FaceKind) -> Self {
+        Self {
+            kind,
+            vertices: vec![],
+            uvs: vec![],
+            indices: vec![],
+        }
+    }
+
+    fn add_vertex(&mut self, position: Vec3, uv: Vec2) -> u16 {
+        let index = self.vertices.len() as u16;
+        self.vertices.push(position);
+        self.uvs.push(uv);
+        index
+    }
+
+    /// Connect vertices that were added in rows of `width` into quads
+    fn add_grid_indices(&mut self, width: usize, rows: usize) {
+        let width = width as u16;
+        for row in 0..rows.saturating_sub(1) as u16 {
+            for column in 0..width.saturating_sub(1) {
+                let a = row * width + column;
+                let b = a + 1;
+                let c = a + width;
+                let d = c + 1;
+                self.indices.extend_from_slice(&[a, b, d, a, d, c]);
+            }
+        }
+    }
+}
+
+/// A prim mesh generated from its shape parameters.
+///
+/// Faces are in the same order the viewer numbers them, so `faces[i]` uses face `i` of the prim's
+/// texture entry.
+#[derive(Debug, Clone, Default)]
+pub struct Volume {
+    /// The faces of the prim
+    pub faces: Vec<VolumeFace>,
+}
+
+/// A point of the profile. `t` is how far around the profile the point is.
+#[derive(Debug, Clone, Copy)]
+struct ProfilePoint {
+    position: Vec2,
+    t: f32,
+}
+
+/// A point of the path, which the profile is placed at
+#[derive(Debug, Clone, Copy)]
+struct PathPoint {
+    position: Vec3,
+    rotation: Quat,
+    scale: Vec2,
+    tex_t: f32,
+}
+
+impl PathPoint {
+    fn place(&self, point: Vec2) -> Vec3 {
+        self.position + self.rotation * (point * self.scale).extend(0.0)
+    }
+}
+
+/// The outline of the shape, before it is swept along the path
+struct Profile {
+    outer: Vec<ProfilePoint>,
+    hole: Vec<ProfilePoint>,
+    /// How many flat sides the outside is split into, or 0 if it is curved
+    flat_sides: usize,
+    /// Whether the profile is cut, and needs faces to close it
+    open: bool,
+}
+
+impl Volume {
+    /// Generate a prim mesh from the shape parameters of an ObjectUpdate
+    pub fn from_path(path: &Path, detail: f32) -> Self {
+        Self::generate(&VolumeParams::from(path), detail)
+    }
+
+    /// Generate a prim mesh. The prim fills the box from -0.5 to 0.5 on each axis, and is scaled
+    /// to its final size by the object's scale.
+    pub fn generate(params: &VolumeParams, detail: f32) -> Self {
+        let profile = generate_profile(params, detail);
+        let (path, path_open) = generate_path(params, detail);
+
+        let mut faces = Vec::new();
+        let mut bottom = None;
+
+        if path_open {
+            faces.push(cap_face(&profile, &path, FaceKind::PathBegin));
+            bottom = Some(cap_face(&profile, &path, FaceKind::PathEnd));
+        }
+
+        let outer = &profile.outer;
+        if profile.flat_sides > 0 {
+            let sides = profile.flat_sides as f32;
+            for pair in outer.windows(2) {
+                let side = (pair[0].t * sides + 0.0001).floor() as usize;
+                let points = [
+                    (pair[0].position, pair[0].t * sides - side as f32),
+                    (pair[1].position, pair[1].t * sides - side as f32),
+                ];
+                faces.push(side_face(&points, &path, FaceKind::OuterSide(side as u8)));
+            }
+        } else {
+            let points: Vec<(Vec2, f32)> = outer.iter().map(|p| (p.position, p.t)).collect();
+            faces.push(side_face(&points, &path, FaceKind::OuterSide(0)));
+        }
+
+        if !profile.hole.is_empty() {
+            // the inside of the hollow is traced backwards, so that it faces into the hollow
+            let points: Vec<(Vec2, f32)> = profile
+                .hole
+                .iter()
+                .rev()
+                .map(|p| (p.position, 1.0 - p.t))
+                .collect();
+            faces.push(side_face(&points, &path, FaceKind::InnerSide));
+        }
+
+        faces.extend(bottom);
+
+        if profile.open {
+            let first = outer[0].position;
+            let last = outer[outer.len() - 1].position;
+            let (begin, end) = match (profile.hole.first(), profile.hole.last()) {
+                (Some(hole_first), Some(hole_last)) => (hole_first.position, hole_last.position),
+                _ => (Vec2::ZERO, Vec2::ZERO),
+            };
+            faces.push(side_face(
+                &[(begin, 0.0), (first, 1.0)],
+                &path,
+                FaceKind::ProfileBegin,
+            ));
+            faces.push(side_face(
+                &[(last, 0.0), (end, 1.0)],
+                &path,
+                FaceKind::ProfileEnd,
+            ));
+        }
+
+        Self { faces }
+    }
+
+    /// Generate the mesh of a sculpted prim from its sculpt map.
+    ///
+    /// Each pixel of the map is a position in the unit box, with red, green and blue as x, y and
+    /// z. The map is sampled as a grid, with columns going around the shape, and rows going up it
+    /// from the bottom of the image. The sculpt type decides which edges of the map are stitched
+    /// together. Sculpted prims have a single face.
+    pub fn from_sculpt(map: &RgbImage, sculpt: &SculptData, detail: f32) -> Self {
+        let (width, height) = map.dimensions();
+        let (columns, rows) = sculpt_resolution(width, height, detail);
+        let sculpt_type = sculpt.sculpt_type;
+        let wraps_sideways = matches!(
+            sculpt_type,
+            SculptType::Sphere | SculptType::Torus | SculptType::Cylinder
+        );
+
+        // mirroring flips the winding of the triangles, so the columns are read backwards to keep
+        // the outside facing out. Inverting reads them backwards to turn the mesh inside out.
+        let reverse = sculpt.invert != sculpt.mirror;
+
+        let mut face = VolumeFace::new(FaceKind::OuterSide(0));
+        for row in 0..=rows {
+            for column in 0..=columns {
+                let sampled = if reverse { columns - column } else { column };
+                let mut x = sampled * width / columns;
+                let mut y = row * height / rows;
+
+                // spheres are pinched to a point at the top and bottom
+                let pole = y == 0 || y == height;
+                if y == height {
+                    y = if sculpt_type == SculptType::Torus {
+                        0
+                    } else {
+                        height - 1
+                    };
+                }
+                if pole && sculpt_type == SculptType::Sphere {
+                    x = width / 2;
+                }
+                if x == width {
+                    x = if wraps_sideways { 0 } else { width - 1 };
+                }
+
+                let pixel = map.get_pixel(x, height - 1 - y);
+                let mut position = Vec3::from_array(pixel.0.map(|c| c as f32 / 255.0)) - 0.5;
+                if sculpt.mirror {
+                    position.x = -position.x;
+                }
+                let uv = Vec2::new(
+                    column as f32 / columns as f32,
+                    1.0 - row as f32 / rows as f32,
+                );
+                face.add_vertex(position, uv);
+            }
+        }
+        face.add_grid_indices(columns as usize + 1, rows as usize + 1);
+
+        Self { faces: vec![face] }
+    }
+
+    /// Merge the faces into a single object that can be turned into a mesh.
+    ///
+    /// Faces are numbered in the same order as the faces of the texture entry, so each face gets
+    /// the material and texture transform of its own entry.
+    pub fn to_render_object(
+        &self,
+        name: String,
+        id: Uuid,
+        texture_entry: &TextureEntry,
+        materials: &ObjectMaterials,
+    ) -> RenderObject {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut uv = Vec::new();
+        let mut faces = Vec::new();
+        for (index, face) in self.faces.iter().enumerate() {
+            let entry = texture_entry.face(index as u32);
+            let offset = vertices.len() as u16;
+            faces.push(materials.render_face(
+                index as u32,
+                entry,
+                indices.len(),
+                face.indices.len(),
+            ));
+            vertices.extend_from_slice(&face.vertices);
+            uv.extend(
+                face.uvs
+                    .iter()
+                    .map(|uv| transform_uv(*uv, entry).to_array()),
+            );
+            indices.extend(face.indices.iter().map(|i| i + offset));
+        }
+        RenderObject {
+            name,
+            id,
+            indices,
+            vertices,
+            skin: None,
+            texture: materials.textures.get(&texture_entry.texture_id).cloned(),
+            uv: Some(uv),
+            faces,
+        }
+    }
+}
+
+/// Number of quads across and down a sculpt map. The mesh keeps the aspect ratio of the map, and
+/// has no more than one quad for every four pixels, or than the detail allows.
+fn sculpt_resolution(width: u32, height: u32, detail: f32) -> (u32, u32) {
+    let max_for_detail = 2u32.pow(detail.clamp(0.0, 4.0) as u32 + 6);
+    let quads = max_for_detail.min(width * height / 4);
+    let ratio = width as f32 / height as f32;
+    let rows = ((quads as f32 / ratio).sqrt() as u32).max(4);
+    let columns = (quads / rows).max(4);
+    let rows = (quads / columns).max(1);
+    (columns, rows)
+}
+
+/// Generate the outline of the prim, and the outline of its hollow
+fn generate_profile(params: &VolumeParams, detail: f32) -> Profile {
+    let (begin, end) = (params.profile_begin, params.profile_end);
+    let hollow = params.hollow;
+    let circle_detail = MIN_DETAIL_FACES * detail;
+
+    let (outer, flat_sides, offset, ang_scale, hole_scale, default_hole) =
+        match params.profile_curve {
+            ProfileCurve::Square => (ngon(begin, end, 4, -0.375, 1.0), 4, -0.375, 1.0, 1.0, 4),
+            ProfileCurve::Circle => {
+                // square hollows need a multiple of four sides for their corners to line up
+                let sides = match params.hole_shape {
+                    HoleShape::Square if hollow > 0.0 => (circle_detail / 4.0).ceil() * 4.0,
+                    _ => circle_detail,
+                } as usize;
+                (ngon(begin, end, sides, 0.0, 1.0), 0, 0.0, 1.0, 1.0, sides)
+            }
+            ProfileCurve::HalfCircle => {
+                let sides = match params.hole_shape {
+                    HoleShape::Square if hollow > 0.0 => (circle_detail / 4.0).ceil() * 2.0,
+                    _ => circle_detail * 0.5,
+                } as usize;
+                (ngon(begin, end, sides, 0.5, 0.5), 0, 0.5, 0.5, 1.0, sides)
+            }
+            // triangles don't fill their bounding box, so their hollows are smaller
+            _ => (ngon(begin, end, 3, 0.0, 1.0), 3, 0.0, 1.0, 0.5, 3),
+        };
+
+    let hole = if hollow > 0.0 {
+        let sides = match params.hole_shape {
+            HoleShape::Same => default_hole,
+            HoleShape::Circle => (circle_detail * ang_scale) as usize,
+            HoleShape::Square => (4.0 * ang_scale) as usize,
+            HoleShape::Triangle => 3,
+        };
+        ngon(begin, end, sides, offset, ang_scale)
+            .into_iter()
+            .map(|point| ProfilePoint {
+                position: point.position * hollow * hole_scale,
+                t: point.t,
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    Profile {
+        outer,
+        hole,
+        flat_sides,
+        open: (end - begin) < 0.99,
+    }
+}
+
+/// Generate the points of a regular polygon between `begin` and `end`, starting at `offset`
+/// turns counter clockwise from the x axis. Cuts that fall between two corners get an extra
+/// point on the edge between them.
+fn ngon(begin: f32, end: f32, sides: usize, offset: f32, ang_scale: f32) -> Vec<ProfilePoint> {
+    let sides = sides.max(2);
+    let total_sides = (sides as f32 / ang_scale).round() as usize;
+    let scale = TABLE_SCALE.get(total_sides).copied().unwrap_or(0.5);
+    let point = |t: f32| {
+        let angle = 2.0 * PI * (t * ang_scale + offset);
+        ProfilePoint {
+            position: Vec2::new(angle.cos(), angle.sin()) * scale,
+            t,
+        }
+    };
+    let lerp = |a: ProfilePoint, b: ProfilePoint, fraction: f32| ProfilePoint {
+        position: a.position.lerp(b.position, fraction),
+        t: a.t + (b.t - a.t) * fraction,
+    };
+
+    let step = 1.0 / sides as f32;
+    let first = (begin * sides as f32).floor() as usize;
+    let mut points = vec![];
+
+    let fraction = (begin - first as f32 * step) * sides as f32;
+    if fraction < 0.9999 {
+        points.push(lerp(
+            point(first as f32 * step),
+            point((first + 1) as f32 * step),
+            fraction,
+        ));
+    }
+
+    let mut corner = first + 1;
+    while (corner as f32 * step) < end {
+        points.push(point(corner as f32 * step));
+        corner += 1;
+    }
+
+    let fraction = (end - (corner - 1) as f32 * step) * sides as f32;
+    if fraction > 0.0001 {
+        points.push(lerp(
+            point((corner - 1) as f32 * step),
+            point(corner as f32 * step),
+            fraction,
+        ));
+    }
+    points
+}
+
+/// Generate the points the profile is swept along, and whether the path is open at its ends
+fn generate_path(params: &VolumeParams, detail: f32) -> (Vec<PathPoint>, bool) {
+    let twist = (params.twist_begin - params.twist_end).abs();
+    match params.path_curve {
+        PathCurve::Circle | PathCurve::Circle2 => {
+            // increase the detail as the revolutions and twist increase
+            let sides = ((MIN_DETAIL_FACES * detail + twist * 3.5 * (detail - 0.5)).floor()
+                * params.revolutions)
+                .floor() as usize;
+            circle_path(params, sides.max(3))
+        }
+        PathCurve::Line | PathCurve::Flexible => (line_path(params, detail), true),
+    }
+}
+
+fn line_path(params: &VolumeParams, detail: f32) -> Vec<PathPoint> {
+    let twist = (params.twist_begin - params.twist_end).abs();
+    let count = ((twist * 3.5 * (detail - 0.5)).floor() as usize + 2).max(2);
+
+    // scales above 1 taper the beginning of the path instead of the end
+    let begin_scale = Vec2::select(
+        params.scale.cmpgt(Vec2::ONE),
+        Vec2::splat(2.0) - params.scale,
+        Vec2::ONE,
+    );
+    let end_scale = params.scale.min(Vec2::ONE);
+
+    (0..count)
+        .map(|i| {
+            let t = lerp(
+                params.path_begin,
+                params.path_end,
+                i as f32 / (count - 1) as f32,
+            );
+            let twist = lerp(PI * params.twist_begin, PI * params.twist_end, t);
+            PathPoint {
+                position: (params.shear * t).extend(t - 0.5),
+                rotation: Quat::from_rotation_z(twist),
+                scale: begin_scale.lerp(end_scale, t),
+                tex_t: t,
+            }
+        })
+        .collect()
+}
+
+fn circle_path(params: &VolumeParams, sides: usize) -> (Vec<PathPoint>, bool) {
+    let skew = params.skew;
+    let hole = Vec2::new(params.scale.x * (1.0 - skew.abs()), params.scale.y);
+
+    // negative tapers taper the beginning of the path
+    let mut taper_begin = Vec2::ONE;
+    let mut taper_end = Vec2::ONE - params.taper;
+    if taper_end.x > 1.0 {
+        taper_begin.x = 2.0 - taper_end.x;
+        taper_end.x = 1.0;
+    }
+    if taper_end.y > 1.0 {
+        taper_begin.y = 2.0 - taper_end.y;
+        taper_end.y = 1.0;
+    }
+
+    // spheres have no hole, so their radius is zero
+    let mut radius_begin = TABLE_SCALE.get(sides).copied().unwrap_or(0.5) * (1.0 - hole.y);
+    let mut radius_end = radius_begin;
+    if params.radius_offset < 0.0 {
+        radius_begin *= 1.0 + params.radius_offset;
+    } else {
+        radius_end *= 1.0 - params.radius_offset;
+    }
+
+    let open = params.path_end - params.path_begin < 1.0
+        || skew.abs() > 0.001
+        || (taper_end - taper_begin).abs().max_element() > 0.001
+        || (radius_end - radius_begin).abs() > 0.001;
+
+    let point = |t: f32| {
+        let angle = 2.0 * PI * params.revolutions * t;
+        let radius = lerp(radius_begin, radius_end, t);
+        let (s, c) = (angle.sin() * radius, angle.cos() * radius);
+        let twist = lerp(params.twist_begin, params.twist_end, t) * 2.0 * PI - PI;
+        PathPoint {
+            position: Vec3::new(
+                params.shear.x * s + lerp(-skew, skew, t) * 0.5,
+                c + params.shear.y * s,
+                s,
+            ),
+            rotation: Quat::from_rotation_x(angle) * Quat::from_rotation_z(twist),
+            scale: hole * taper_begin.lerp(taper_end, t),
+            tex_t: t,
+        }
+    };
+
+    // snap to whole steps between the cuts, so that cutting doesn't move the other points
+    let mut points = vec![point(params.path_begin)];
+    let mut step = (params.path_begin * sides as f32) as usize + 1;
+    while (step as f32 / sides as f32) < params.path_end {
+        points.push(point(step as f32 / sides as f32));
+        step += 1;
+    }
+    points.push(point(params.path_end));
+    (points, open)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Sweep a strip of profile points along the path. Each point carries its horizontal texture
+/// coordinate.
+fn side_face(points: &[(Vec2, f32)], path: &[PathPoint], kind: FaceKind) -> VolumeFace {
+    let mut face = VolumeFace::new(kind);
+    for path_point in path {
+        for (point, u) in points {
+            face.add_vertex(
+                path_point.place(*point),
+                Vec2::new(*u, 1.0 - path_point.tex_t),
+            );
+        }
+    }
+
+    face.add_grid_indices(points.len(), path.len());
+    face
+}
+
+/// Cover the end of the path. The top cap is at the end of the path, facing forward, and the
+/// bottom cap is at the start of the path, facing backward.
+fn cap_face(profile: &Profile, path: &[PathPoint], kind: FaceKind) -> VolumeFace {
+    let mut face = VolumeFace::new(kind);
+    let top = kind == FaceKind::PathBegin;
+    let path_point = if top { path[path.len() - 1] } else { path[0] };
+    let mut add = |point: Vec2| {
+        let uv = if top {
+            Vec2::new(point.x + 0.5, 0.5 - point.y)
+        } else {
+            Vec2::new(point.x + 0.5, 0.5 + point.y)
+        };
+        face.add_vertex(path_point.place(point), uv)
+    };
+
+    let outer: Vec<u16> = profile.outer.iter().map(|p| add(p.position)).collect();
+    let mut triangles = vec![];
+    if profile.hole.is_empty() {
+        // every profile can be seen from its center, so a fan covers it
+        let center = add(Vec2::ZERO);
+        for pair in outer.windows(2) {
+            triangles.push([center, pair[0], pair[1]]);
+        }
+    } else {
+        // zip the outline and the hollow together, always advancing along whichever is behind
+        let hole: Vec<u16> = profile.hole.iter().map(|p| add(p.position)).collect();
+        let (mut i, mut j) = (0, 0);
+        while i + 1 < outer.len() || j + 1 < hole.len() {
+            let advance_outer = j + 1 == hole.len()
+                || (i + 1 < outer.len() && profile.outer[i + 1].t <= profile.hole[j + 1].t);
+            if advance_outer {
+                triangles.push([outer[i], outer[i + 1], hole[j]]);
+                i += 1;
+            } else {
+                triangles.push([hole[j + 1], hole[j], outer[i]]);
+                j += 1;
+            }
+        }
+    }
+
+    for [a, b, c] in triangles {
+        if top {
+            face.indices.extend_from_slice(&[a, b, c]);
+        } else {
+            face.indices.extend_from_slice(&[a, c, b]);
+        }
+    }
+    face
+}
@@ -0,0 +1,92 @@
+use metaverse_core::session::{MTU, ack_packets, append_acks};
+use metaverse_messages::packet::{
+    header::MAX_APPENDED_ACKS, packet_protocol::Packet, packet_types::PacketType,
+};
+use metaverse_messages::udp::{
+    core::start_ping_check::StartPingCheck,
+    object::generic_streaming_message::{GenericStreamingMessage, METHOD_MATERIAL_OVERRIDE},
+};
+use std::collections::HashSet;
+
+/// A packet whose body is padded out to the given length
+fn packet_with_body(len: usize) -> Packet {
+    Packet::new_generic_streaming_message(GenericStreamingMessage {
+        method: METHOD_MATERIAL_OVERRIDE,
+        data: vec![1; len],
+    })
+}
+
+#[test]
+fn test_pending_acks_are_appended() {
+    let mut server_acks = HashSet::from([1, 2, 3]);
+    let mut packet = Packet::new_start_ping_check(StartPingCheck {
+        ping_id: 1,
+        oldest_unacked: 0,
+    });
+    append_acks(&mut packet, &mut server_acks);
+    assert!(server_acks.is_empty());
+
+    // the acks are sent at the end of the packet, and read back into its header
+    let parsed = Packet::from_bytes(&packet.to_bytes()).unwrap();
+    let acks: HashSet<u32> = parsed.header.ack_list.unwrap().into_iter().collect();
+    assert_eq!(acks, HashSet::from([1, 2, 3]));
+    assert!(matches!(parsed.body, PacketType::StartPingCheck(_)));
+}
+
+#[test]
+fn test_appended_acks_stay_within_mtu() {
+    for len in [0, 500, 1000, 1150, 1180, MTU] {
+        let mut server_acks: HashSet<u32> = (0..100).collect();
+        let mut packet = packet_with_body(len);
+        let unacked_len = packet.to_bytes().len();
+        append_acks(&mut packet, &mut server_acks);
+
+        let appended = packet.header.ack_list.as_ref().map_or(0, |acks| acks.len());
+        assert_eq!(appended + server_acks.len(), 100, "{}", len);
+        if unacked_len + 5 <= MTU {
+            assert!(appended > 0, "{}", len);
+            assert!(packet.to_bytes().len() <= MTU, "{}", len);
+        } else {
+            // packets without room for a single ack are sent unchanged
+            assert_eq!(appended, 0, "{}", len);
+            assert_eq!(packet.to_bytes().len(), unacked_len, "{}", len);
+        }
+    }
+
+    // no more acks are appended than the count byte can hold
+    let mut server_acks: HashSet<u32> = (0..1000).collect();
+    let mut packet = packet_with_body(0);
+    append_acks(&mut packet, &mut server_acks);
+    let appended = packet.header.ack_list.unwrap().len();
+    assert!(appended <= MAX_APPENDED_ACKS);
+    assert_eq!(appended + server_acks.len(), 1000);
+}
+
+#[test]
+fn test_leftover_acks_are_flushed() {
+    // acks that didn't fit on a packet are left for the flush
+    let mut server_acks: HashSet<u32> = (0..400).collect();
+    let mut packet = packet_with_body(MTU - 100);
+    append_acks(&mut packet, &mut server_acks);
+    let mut acked: Vec<u32> = packet.header.ack_list.unwrap();
+    assert!(!server_acks.is_empty());
+
+    let flushed = ack_packets(&mut server_acks);
+    assert!(server_acks.is_empty());
+    for packet in flushed {
+        let parsed = Packet::from_bytes(&packet.to_bytes()).unwrap();
+        match parsed.body {
+            PacketType::PacketAck(data) => {
+                assert!(data.packet_ids.len() <= u8::MAX as usize);
+                acked.extend(data.packet_ids);
+            }
+            other => panic!("flushed {:?}", other),
+        }
+    }
+    // every ack was sent exactly once, either on the packet or in a PacketAck
+    acked.sort();
+    assert_eq!(acked, (0..400).collect::<Vec<u32>>());
+
+    // nothing is flushed when there are no pending acks
+    assert!(ack_packets(&mut server_acks).is_empty());
+}
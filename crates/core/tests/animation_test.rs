@@ -0,0 +1,83 @@
+use glam::{Quat, Vec3};
+use metaverse_core::animation::AnimationJson;
+use metaverse_messages::utils::keyframe_motion::{
+    JointMotion, KeyframeMotion, PositionKey, RotationKey,
+};
+use serde_json::json;
+
+/// A looping animation that turns the pelvis and lifts it, with an unanimated joint
+fn motion() -> KeyframeMotion {
+    KeyframeMotion {
+        priority: 3,
+        duration: 2.0,
+        loop_in_point: 0.5,
+        loop_out_point: 2.0,
+        looping: true,
+        joints: vec![
+            JointMotion {
+                name: "mPelvis".to_string(),
+                priority: -1,
+                rotation_keys: vec![
+                    RotationKey {
+                        time: 0.0,
+                        rotation: Quat::IDENTITY,
+                    },
+                    RotationKey {
+                        time: 2.0,
+                        rotation: Quat::from_xyzw(0.0, 0.0, 1.0, 0.0),
+                    },
+                ],
+                position_keys: vec![PositionKey {
+                    time: 1.0,
+                    position: Vec3::new(0.0, 0.0, 0.5),
+                }],
+            },
+            JointMotion {
+                name: "mHead".to_string(),
+                priority: 5,
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_animation_json_shape() {
+    let animation = AnimationJson::from_keyframe_motion("wave", &motion());
+    assert_eq!(
+        serde_json::to_value(&animation).unwrap(),
+        json!({
+            "name": "wave",
+            "duration": 2.0,
+            "looping": true,
+            "loop_start": 0.5,
+            "loop_end": 2.0,
+            "priority": 3,
+            "joints": [
+                {
+                    "name": "mPelvis",
+                    "priority": 3,
+                    "rotation": {
+                        "times": [0.0, 2.0],
+                        "values": [[0.0, 0.0, 0.0, 1.0], [0.0, 0.0, 1.0, 0.0]]
+                    },
+                    "translation": {
+                        "times": [1.0],
+                        "values": [[0.0, 0.0, 0.5]]
+                    }
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_animation_json_round_trip() {
+    let animation = AnimationJson::from_keyframe_motion("wave", &motion());
+    let json = serde_json::to_string(&animation).unwrap();
+    assert_eq!(
+        serde_json::from_str::<AnimationJson>(&json).unwrap(),
+        animation
+    );
+}
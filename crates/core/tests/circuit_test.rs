@@ -0,0 +1,116 @@
+use metaverse_core::transport::circuit::{
+    CircuitStats, PacketOrder, PacketWindow, SEQUENCE_SPACE, SEQUENCE_WINDOW,
+};
+
+fn receive(window: &mut PacketWindow, sequence_numbers: &[u32]) -> Vec<PacketOrder> {
+    sequence_numbers
+        .iter()
+        .map(|sequence_number| window.check(*sequence_number))
+        .collect()
+}
+
+#[test]
+fn test_in_order() {
+    let mut window = PacketWindow::default();
+    assert_eq!(
+        receive(&mut window, &[1, 2, 3]),
+        vec![PacketOrder::InOrder; 3]
+    );
+    assert_eq!(
+        window.stats,
+        CircuitStats {
+            received: 3,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_duplicates() {
+    let mut window = PacketWindow::default();
+    assert_eq!(
+        receive(&mut window, &[1, 2, 2, 1, 3]),
+        vec![
+            PacketOrder::InOrder,
+            PacketOrder::InOrder,
+            PacketOrder::Duplicate,
+            PacketOrder::Duplicate,
+            PacketOrder::InOrder,
+        ]
+    );
+    assert_eq!(window.stats.duplicated, 2);
+    assert_eq!(window.stats.reordered, 0);
+}
+
+#[test]
+fn test_out_of_order() {
+    let mut window = PacketWindow::default();
+    assert_eq!(
+        receive(&mut window, &[1, 4, 3, 2, 3]),
+        vec![
+            PacketOrder::InOrder,
+            PacketOrder::InOrder,
+            PacketOrder::Reordered,
+            PacketOrder::Reordered,
+            PacketOrder::Duplicate,
+        ]
+    );
+    assert_eq!(window.stats.reordered, 2);
+    assert_eq!(window.stats.duplicated, 1);
+    assert_eq!(window.stats.dropped, 0);
+}
+
+#[test]
+fn test_gaps_are_dropped_once_they_leave_the_window() {
+    let mut window = PacketWindow::default();
+    receive(&mut window, &[1, 3]);
+    assert_eq!(window.stats.dropped, 0);
+
+    // 2 is still missing when it falls out of the window
+    window.check(3 + SEQUENCE_WINDOW);
+    assert_eq!(window.stats.dropped, 1);
+    assert_eq!(window.check(3), PacketOrder::Duplicate);
+    assert_eq!(window.check(4), PacketOrder::Reordered);
+
+    // gaps larger than the window are dropped straight away
+    let mut window = PacketWindow::default();
+    receive(&mut window, &[1, 2 + SEQUENCE_WINDOW * 2]);
+    assert_eq!(window.stats.dropped, SEQUENCE_WINDOW as u64);
+}
+
+#[test]
+fn test_wrap() {
+    let mut window = PacketWindow::default();
+    let last = SEQUENCE_SPACE - 1;
+    assert_eq!(
+        receive(&mut window, &[last - 1, 1, last, 0, 2]),
+        vec![
+            PacketOrder::InOrder,
+            PacketOrder::InOrder,
+            PacketOrder::Reordered,
+            PacketOrder::Reordered,
+            PacketOrder::InOrder,
+        ]
+    );
+    assert_eq!(window.check(last), PacketOrder::Duplicate);
+    assert_eq!(window.stats.resets, 0);
+    assert_eq!(window.stats.dropped, 0);
+}
+
+#[test]
+fn test_reset() {
+    let mut window = PacketWindow::default();
+    receive(&mut window, &[5000, 5001]);
+
+    // the simulator starts its sequence numbers over
+    assert_eq!(
+        receive(&mut window, &[1, 2, 1]),
+        vec![
+            PacketOrder::InOrder,
+            PacketOrder::InOrder,
+            PacketOrder::Duplicate,
+        ]
+    );
+    assert_eq!(window.stats.resets, 1);
+    assert_eq!(window.stats.duplicated, 1);
+}
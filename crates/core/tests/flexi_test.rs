@@ -0,0 +1,101 @@
+use benthic_protocol::messages::ui::flexi_update::FlexiUpdate;
+use glam::{Quat, Vec3};
+use metaverse_core::flexi::FlexiChain;
+use std::f32::consts::FRAC_PI_2;
+
+fn flexi(tension: f32, gravity: f32) -> FlexiUpdate {
+    FlexiUpdate {
+        scene_id: 1,
+        softness: 2,
+        tension,
+        drag: 2.0,
+        gravity,
+        wind: 0.0,
+        force: Vec3::ZERO,
+    }
+}
+
+fn simulate(chain: &mut FlexiChain, seconds: f32) {
+    for _ in 0..(seconds * 60.0) as u32 {
+        chain.step(
+            1.0 / 60.0,
+            chain.position,
+            chain.rotation,
+            chain.scale,
+            Vec3::ZERO,
+        );
+    }
+}
+
+#[test]
+fn test_rest_shape_keeps_vertices() {
+    let chain = FlexiChain::new(
+        flexi(1.0, 0.3),
+        Vec3::new(10.0, 20.0, 30.0),
+        Quat::from_rotation_x(0.5),
+        Vec3::new(0.2, 0.3, 2.0),
+    );
+    assert_eq!(chain.sections(), 4);
+    for vertex in [
+        Vec3::new(0.5, 0.5, -0.5),
+        Vec3::new(-0.5, 0.25, 0.0),
+        Vec3::new(0.1, -0.5, 0.5),
+    ] {
+        let (moved, bend) = chain.deform(vertex);
+        assert!(moved.abs_diff_eq(vertex, 1e-4), "{vertex} moved to {moved}");
+        assert!(bend.abs_diff_eq(Quat::IDENTITY, 1e-4));
+    }
+}
+
+#[test]
+fn test_gravity_bends_without_stretching() {
+    // lying on its side, with the base at +Y
+    let rotation = Quat::from_rotation_x(FRAC_PI_2);
+    let mut chain = FlexiChain::new(flexi(0.0, 1.0), Vec3::ZERO, rotation, Vec3::ONE);
+    simulate(&mut chain, 20.0);
+
+    // the base stays attached to the bottom of the prim
+    assert!(chain.points[0].abs_diff_eq(Vec3::new(0.0, 0.5, 0.0), 1e-5));
+    // with no tension the prim hangs straight down from its base
+    let tip = *chain.points.last().unwrap();
+    assert!(
+        tip.abs_diff_eq(Vec3::new(0.0, 0.5, -1.0), 1e-2),
+        "tip at {tip}"
+    );
+    for section in chain.points.windows(2) {
+        assert!((section[0].distance(section[1]) - 0.25).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn test_tension_holds_shape() {
+    let rotation = Quat::from_rotation_x(FRAC_PI_2);
+    let mut stiff = FlexiChain::new(flexi(10.0, 0.3), Vec3::ZERO, rotation, Vec3::ONE);
+    let mut soft = FlexiChain::new(flexi(1.0, 0.3), Vec3::ZERO, rotation, Vec3::ONE);
+    simulate(&mut stiff, 5.0);
+    simulate(&mut soft, 5.0);
+
+    let stiff_tip = *stiff.points.last().unwrap();
+    let soft_tip = *soft.points.last().unwrap();
+    assert!(soft_tip.z < stiff_tip.z);
+    assert!(stiff_tip.z > -0.1, "tip at {stiff_tip}");
+
+    // the mesh follows the bend of the chain
+    let (tip_vertex, _) = soft.deform(Vec3::new(0.0, 0.0, 0.5));
+    assert!(tip_vertex.abs_diff_eq(rotation.inverse() * soft_tip, 1e-4));
+}
+
+#[test]
+fn test_teleport_resets_shape() {
+    let mut chain = FlexiChain::new(flexi(0.0, 1.0), Vec3::ZERO, Quat::IDENTITY, Vec3::ONE);
+    simulate(&mut chain, 1.0);
+    chain.step(
+        1.0 / 60.0,
+        Vec3::new(100.0, 0.0, 0.0),
+        Quat::IDENTITY,
+        Vec3::ONE,
+        Vec3::ZERO,
+    );
+    let tip = *chain.points.last().unwrap();
+    assert!(tip.abs_diff_eq(Vec3::new(100.0, 0.0, 0.5), 1e-5));
+}
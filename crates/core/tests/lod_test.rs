@@ -0,0 +1,50 @@
+use benthic_protocol::messages::ui::mesh_update::LevelOfDetail;
+use metaverse_core::lod::{DEFAULT_LOD_FACTOR, closest_available, select_level_of_detail};
+
+#[test]
+fn test_detail_drops_with_distance() {
+    let levels: Vec<LevelOfDetail> = [1.0, 10.0, 30.0, 100.0]
+        .iter()
+        .map(|distance| select_level_of_detail(1.0, *distance, DEFAULT_LOD_FACTOR))
+        .collect();
+    assert_eq!(
+        levels,
+        vec![
+            LevelOfDetail::High,
+            LevelOfDetail::Medium,
+            LevelOfDetail::Low,
+            LevelOfDetail::Lowest,
+        ]
+    );
+
+    // a larger object keeps its detail further away
+    assert_eq!(
+        select_level_of_detail(10.0, 30.0, DEFAULT_LOD_FACTOR),
+        LevelOfDetail::High
+    );
+    // the camera inside the object
+    assert_eq!(
+        select_level_of_detail(1.0, 0.0, DEFAULT_LOD_FACTOR),
+        LevelOfDetail::High
+    );
+}
+
+#[test]
+fn test_closest_available() {
+    let streamed = [LevelOfDetail::Lowest, LevelOfDetail::High];
+    let available = |lod| streamed.contains(&lod);
+
+    assert_eq!(
+        closest_available(LevelOfDetail::Lowest, available),
+        Some(LevelOfDetail::Lowest)
+    );
+    assert_eq!(
+        closest_available(LevelOfDetail::Low, available),
+        Some(LevelOfDetail::Lowest)
+    );
+    assert_eq!(
+        closest_available(LevelOfDetail::Medium, available),
+        Some(LevelOfDetail::High)
+    );
+    assert_eq!(closest_available(LevelOfDetail::High, |_| false), None);
+}
@@ -0,0 +1,180 @@
+use glam::Vec2;
+use metaverse_core::materials::{ObjectMaterials, render_face, transform_uv};
+use metaverse_messages::http::{gltf_material::GltfMaterial, render_materials::LegacyMaterial};
+use metaverse_messages::utils::texture_entry::TextureEntry;
+use rgb::Rgba;
+use std::collections::{BTreeMap, HashMap};
+use std::f32::consts::FRAC_PI_2;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+fn assert_uv(actual: Vec2, expected: Vec2) {
+    assert!(
+        actual.abs_diff_eq(expected, 1e-5),
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn test_default_entry_keeps_uvs() {
+    let entry = TextureEntry::default();
+    for uv in [Vec2::ZERO, Vec2::ONE, Vec2::new(0.25, 0.75)] {
+        assert_uv(transform_uv(uv, &entry), uv);
+    }
+}
+
+#[test]
+fn test_repeats_scale_around_center() {
+    let entry = TextureEntry {
+        repeat_u: 2.0,
+        repeat_v: 3.0,
+        ..Default::default()
+    };
+    assert_uv(transform_uv(Vec2::splat(0.5), &entry), Vec2::splat(0.5));
+    assert_uv(transform_uv(Vec2::ZERO, &entry), Vec2::new(-0.5, -1.0));
+    assert_uv(transform_uv(Vec2::ONE, &entry), Vec2::new(1.5, 2.0));
+}
+
+#[test]
+fn test_offset_moves_up_the_texture() {
+    let entry = TextureEntry {
+        offset_u: 0.25,
+        offset_v: 0.25,
+        ..Default::default()
+    };
+    // offsets are applied with V going up, so they move image space UVs up
+    assert_uv(
+        transform_uv(Vec2::splat(0.5), &entry),
+        Vec2::new(0.75, 0.25),
+    );
+}
+
+#[test]
+fn test_rotation_turns_around_center() {
+    let entry = TextureEntry {
+        rotation: FRAC_PI_2,
+        ..Default::default()
+    };
+    assert_uv(transform_uv(Vec2::splat(0.5), &entry), Vec2::splat(0.5));
+    // the bottom left corner in image space is the origin of the viewer's UVs
+    assert_uv(
+        transform_uv(Vec2::new(0.0, 1.0), &entry),
+        Vec2::new(0.0, 0.0),
+    );
+    assert_uv(
+        transform_uv(Vec2::new(1.0, 1.0), &entry),
+        Vec2::new(0.0, 1.0),
+    );
+}
+
+#[test]
+fn test_render_face_material() {
+    let texture_id = Uuid::new_v4();
+    let textures = HashMap::from([(texture_id, PathBuf::from("texture.png"))]);
+    let entry = TextureEntry {
+        texture_id,
+        rgba: Rgba::new(255, 0, 0, 128),
+        glow: 0.5,
+        ..Default::default()
+    };
+
+    let face = render_face(&entry, 6, 12, &textures);
+    assert_eq!(face.first_index, 6);
+    assert_eq!(face.index_count, 12);
+    assert_eq!(face.texture, Some(PathBuf::from("texture.png")));
+    assert_eq!(face.color[0], 1.0);
+    assert_eq!(face.color[1], 0.0);
+    assert!((face.color[3] - 128.0 / 255.0).abs() < 1e-6);
+    assert_eq!(face.glow, 0.5);
+
+    // textures that were not downloaded leave the face untextured
+    let missing = TextureEntry::default();
+    assert_eq!(render_face(&missing, 0, 3, &textures).texture, None);
+}
+
+#[test]
+fn test_gltf_material_face() {
+    let diffuse = Uuid::new_v4();
+    let base_color = Uuid::new_v4();
+    let normal = Uuid::new_v4();
+    let materials = ObjectMaterials {
+        textures: HashMap::from([
+            (diffuse, PathBuf::from("diffuse.png")),
+            (base_color, PathBuf::from("base_color.png")),
+            (normal, PathBuf::from("normal.png")),
+        ]),
+        gltf: BTreeMap::from([(
+            1,
+            GltfMaterial {
+                base_color_texture: Some(base_color),
+                normal_texture: Some(normal),
+                base_color: [0.5, 0.5, 0.5, 1.0],
+                emissive: [1.0, 0.0, 0.0],
+                metallic: 0.25,
+                roughness: 0.75,
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    };
+    let entry = TextureEntry {
+        texture_id: diffuse,
+        rgba: Rgba::new(255, 0, 0, 255),
+        glow: 0.5,
+        ..Default::default()
+    };
+
+    // the PBR material replaces the texture and color of the face, and keeps its glow
+    let face = materials.render_face(1, &entry, 0, 3);
+    assert_eq!(face.texture, Some(PathBuf::from("base_color.png")));
+    assert_eq!(face.normal_texture, Some(PathBuf::from("normal.png")));
+    assert_eq!(face.metallic_roughness_texture, None);
+    assert_eq!(face.color, [0.5, 0.5, 0.5, 1.0]);
+    assert_eq!(face.emissive, [1.0, 0.0, 0.0]);
+    assert_eq!(face.metallic, 0.25);
+    assert_eq!(face.roughness, 0.75);
+    assert_eq!(face.glow, 0.5);
+
+    // faces without a material are built from their texture entry
+    let plain = materials.render_face(0, &entry, 0, 3);
+    assert_eq!(plain.texture, Some(PathBuf::from("diffuse.png")));
+    assert_eq!(plain.normal_texture, None);
+    assert_eq!(plain.metallic, 0.0);
+
+    let ids = materials.texture_ids(&entry);
+    assert_eq!(ids.len(), 3);
+    assert!(ids.contains(&normal));
+}
+
+#[test]
+fn test_legacy_material_face() {
+    let material_id = Uuid::new_v4();
+    let normal = Uuid::new_v4();
+    let materials = ObjectMaterials {
+        textures: HashMap::from([(normal, PathBuf::from("normal.png"))]),
+        legacy: HashMap::from([(
+            material_id,
+            LegacyMaterial {
+                normal_map: normal,
+                specular_exponent: 255,
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    };
+    let entry = TextureEntry {
+        material_id,
+        ..Default::default()
+    };
+
+    let face = materials.render_face(0, &entry, 0, 3);
+    assert_eq!(face.normal_texture, Some(PathBuf::from("normal.png")));
+    assert_eq!(face.roughness, 0.0);
+    assert_eq!(face.metallic, 0.0);
+    assert!(materials.texture_ids(&entry).contains(&normal));
+
+    // faces without a material ID are left rough
+    let plain = materials.render_face(0, &TextureEntry::default(), 0, 3);
+    assert_eq!(plain.normal_texture, None);
+    assert_eq!(plain.roughness, 1.0);
+}
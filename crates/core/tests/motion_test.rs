@@ -0,0 +1,90 @@
+use glam::{Quat, Vec3};
+use metaverse_core::motion::{
+    CORRECTION_TIME, MAX_EXTRAPOLATION_TIME, MotionSmoother, MotionState, SNAP_DISTANCE,
+};
+use std::f32::consts::FRAC_PI_2;
+
+fn moving(position: Vec3, velocity: Vec3) -> MotionState {
+    MotionState {
+        velocity,
+        ..MotionState::at_rest(position, Quat::IDENTITY)
+    }
+}
+
+#[test]
+fn test_extrapolate_velocity_and_acceleration() {
+    let state = MotionState {
+        acceleration: Vec3::new(0.0, 0.0, -2.0),
+        ..moving(Vec3::ZERO, Vec3::X)
+    };
+    let (position, rotation) = state.extrapolate(0.5);
+    assert!(position.abs_diff_eq(Vec3::new(0.5, 0.0, -0.25), 1e-6));
+    assert_eq!(rotation, Quat::IDENTITY);
+}
+
+#[test]
+fn test_extrapolate_angular_velocity() {
+    let state = MotionState {
+        angular_velocity: Vec3::new(0.0, 0.0, FRAC_PI_2),
+        ..MotionState::at_rest(Vec3::ZERO, Quat::IDENTITY)
+    };
+    let (_, rotation) = state.extrapolate(1.0);
+    assert!(rotation.abs_diff_eq(Quat::from_rotation_z(FRAC_PI_2), 1e-6));
+}
+
+#[test]
+fn test_extrapolation_stops() {
+    let state = moving(Vec3::ZERO, Vec3::X);
+    let (position, _) = state.extrapolate(MAX_EXTRAPOLATION_TIME * 10.0);
+    assert_eq!(position, Vec3::X * MAX_EXTRAPOLATION_TIME);
+}
+
+#[test]
+fn test_smoother_dead_reckoning() {
+    let mut smoother = MotionSmoother::new(moving(Vec3::ZERO, Vec3::X));
+    let (position, _) = smoother.advance(0.25);
+    assert!(position.abs_diff_eq(Vec3::new(0.25, 0.0, 0.0), 1e-6));
+}
+
+#[test]
+fn test_smoother_corrects_toward_update() {
+    let mut smoother = MotionSmoother::new(MotionState::at_rest(Vec3::ZERO, Quat::IDENTITY));
+    smoother.update(MotionState::at_rest(Vec3::X, Quat::from_rotation_z(0.5)));
+
+    // the update doesn't move the object on its own
+    let (position, rotation) = smoother.transform();
+    assert!(position.abs_diff_eq(Vec3::ZERO, 1e-6));
+    assert!(rotation.abs_diff_eq(Quat::IDENTITY, 1e-6));
+
+    // the object moves most of the way over the correction time
+    let (position, _) = smoother.advance(CORRECTION_TIME);
+    assert!(position.x > 0.5 && position.x < 1.0);
+
+    let (position, rotation) = smoother.advance(CORRECTION_TIME * 20.0);
+    assert!(position.abs_diff_eq(Vec3::X, 1e-4));
+    assert!(rotation.abs_diff_eq(Quat::from_rotation_z(0.5), 1e-4));
+}
+
+#[test]
+fn test_smoother_snaps_large_corrections() {
+    let mut smoother = MotionSmoother::new(MotionState::at_rest(Vec3::ZERO, Quat::IDENTITY));
+    let target = Vec3::X * (SNAP_DISTANCE + 1.0);
+    smoother.update(MotionState::at_rest(target, Quat::IDENTITY));
+    assert_eq!(smoother.transform().0, target);
+}
+
+#[test]
+fn test_smoother_settles() {
+    let mut smoother = MotionSmoother::new(MotionState::at_rest(Vec3::ZERO, Quat::IDENTITY));
+    assert!(smoother.is_settled());
+
+    smoother.update(moving(Vec3::ZERO, Vec3::X));
+    assert!(!smoother.is_settled());
+    smoother.advance(MAX_EXTRAPOLATION_TIME);
+    assert!(smoother.is_settled());
+
+    smoother.update(MotionState::at_rest(Vec3::Y, Quat::IDENTITY));
+    assert!(!smoother.is_settled());
+    smoother.advance(CORRECTION_TIME * 20.0);
+    assert!(smoother.is_settled());
+}
@@ -0,0 +1,68 @@
+use actix_rt::time::Instant;
+use metaverse_core::session::{
+    CIRCUIT_TIMEOUT, INITIAL_RETRANSMIT_TIMEOUT, MAX_RESENDS, MAX_RETRANSMIT_TIMEOUT,
+    MIN_RETRANSMIT_TIMEOUT, PingInfo,
+};
+use std::time::Duration;
+
+fn with_latency(ping_latency: Duration) -> PingInfo {
+    PingInfo {
+        ping_number: 0,
+        ping_latency,
+        last_ping: Instant::now(),
+    }
+}
+
+#[test]
+fn test_retransmit_timeout_backoff() {
+    // before a round trip time has been measured
+    let ping_info = with_latency(Duration::ZERO);
+    assert_eq!(ping_info.retransmit_timeout(0), INITIAL_RETRANSMIT_TIMEOUT);
+    assert_eq!(
+        ping_info.retransmit_timeout(1),
+        INITIAL_RETRANSMIT_TIMEOUT * 2
+    );
+    assert_eq!(
+        ping_info.retransmit_timeout(2),
+        INITIAL_RETRANSMIT_TIMEOUT * 4
+    );
+
+    // three times the round trip time, doubling on every resend
+    let ping_info = with_latency(Duration::from_millis(200));
+    assert_eq!(ping_info.retransmit_timeout(0), Duration::from_millis(600));
+    assert_eq!(ping_info.retransmit_timeout(1), Duration::from_millis(1200));
+    assert_eq!(ping_info.retransmit_timeout(2), Duration::from_millis(2400));
+}
+
+#[test]
+fn test_retransmit_timeout_bounds() {
+    // fast connections still wait the minimum
+    let ping_info = with_latency(Duration::from_millis(10));
+    assert_eq!(ping_info.retransmit_timeout(0), MIN_RETRANSMIT_TIMEOUT);
+
+    // slow connections and many resends never wait more than the maximum, or overflow
+    let ping_info = with_latency(Duration::from_secs(10));
+    assert_eq!(ping_info.retransmit_timeout(0), MAX_RETRANSMIT_TIMEOUT);
+    for retries in [10, 16, 17, 64, u32::MAX] {
+        assert_eq!(
+            ping_info.retransmit_timeout(retries),
+            MAX_RETRANSMIT_TIMEOUT
+        );
+        assert_eq!(
+            with_latency(Duration::ZERO).retransmit_timeout(retries),
+            MAX_RETRANSMIT_TIMEOUT
+        );
+    }
+}
+
+#[test]
+fn test_resends_give_up_before_the_circuit_dies() {
+    // even at the longest timeouts, a reliable packet is dropped before an unresponsive circuit
+    // is declared dead
+    let ping_info = with_latency(Duration::from_secs(10));
+    let total: Duration = (0..=MAX_RESENDS)
+        .map(|retries| ping_info.retransmit_timeout(retries))
+        .sum();
+    assert!(total < CIRCUIT_TIMEOUT);
+    assert!(MAX_RETRANSMIT_TIMEOUT < CIRCUIT_TIMEOUT);
+}
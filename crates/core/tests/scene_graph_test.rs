@@ -0,0 +1,115 @@
+use glam::{Quat, Vec3};
+use metaverse_core::scene_graph::{PARENT_TIMEOUT, SceneGraph, SceneNode};
+use std::f32::consts::FRAC_PI_2;
+use std::time::Instant;
+
+fn node(parent: Option<u32>, position: Vec3) -> SceneNode {
+    SceneNode::new(parent, position, Quat::IDENTITY, Vec3::ONE)
+}
+
+#[test]
+fn test_orphans_wait_for_their_parents() {
+    let mut graph = SceneGraph::new();
+
+    // the grandchild and child arrive before the root
+    assert!(graph.insert(3, node(Some(2), Vec3::Z)).is_empty());
+    assert_eq!(graph.hold_until_connected(3, "grandchild"), None);
+    assert!(graph.insert(2, node(Some(1), Vec3::Y)).is_empty());
+    assert_eq!(graph.hold_until_connected(2, "child"), None);
+    assert!(!graph.is_connected(3));
+
+    let mut released = graph.insert(1, node(Some(0), Vec3::X));
+    released.sort();
+    assert_eq!(released, vec!["child", "grandchild"]);
+    assert_eq!(graph.root(3), Some(1));
+
+    // once connected, items are returned straight away
+    assert_eq!(graph.hold_until_connected(3, "update"), Some("update"));
+    assert!(graph.insert(1, node(None, Vec3::X)).is_empty());
+}
+
+#[test]
+fn test_world_transform() {
+    let mut graph: SceneGraph<()> = SceneGraph::new();
+    let quarter_turn = Quat::from_rotation_z(FRAC_PI_2);
+    graph.insert(
+        1,
+        SceneNode::new(None, Vec3::new(10.0, 0.0, 0.0), quarter_turn, Vec3::ONE),
+    );
+    graph.insert(2, SceneNode::new(Some(1), Vec3::X, quarter_turn, Vec3::ONE));
+    graph.insert(3, node(Some(2), Vec3::X));
+
+    // each child is offset along the X axis of its parent, which the rotations turn
+    let (position, rotation) = graph.world_transform(3).unwrap();
+    assert!(position.abs_diff_eq(Vec3::new(9.0, 1.0, 0.0), 1e-5));
+    assert!(rotation.abs_diff_eq(quarter_turn * quarter_turn, 1e-5));
+
+    graph.set_transform(1, Vec3::ZERO, Quat::IDENTITY);
+    let (position, _) = graph.world_transform(3).unwrap();
+    assert!(position.abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-5));
+
+    assert_eq!(graph.world_transform(4), None);
+}
+
+#[test]
+fn test_relink() {
+    let mut graph: SceneGraph<()> = SceneGraph::new();
+    graph.insert(1, node(None, Vec3::ZERO));
+    graph.insert(2, node(None, Vec3::ZERO));
+    graph.insert(3, node(Some(1), Vec3::X));
+
+    graph.insert(3, node(Some(2), Vec3::X));
+    assert_eq!(graph.children(1).count(), 0);
+    assert_eq!(graph.children(2).collect::<Vec<_>>(), vec![3]);
+    assert_eq!(graph.root(3), Some(2));
+
+    // a loop never reaches a root
+    graph.insert(2, node(Some(3), Vec3::X));
+    assert!(!graph.is_connected(2));
+    assert_eq!(graph.world_transform(3), None);
+}
+
+#[test]
+fn test_remove_descendants() {
+    let mut graph = SceneGraph::new();
+    graph.insert(1, node(None, Vec3::ZERO));
+    graph.insert(2, node(Some(1), Vec3::X));
+    graph.insert(3, node(Some(2), Vec3::X));
+    graph.insert(5, node(Some(4), Vec3::X));
+    graph.hold_until_connected(5, "orphan");
+
+    let mut removed = graph.remove(1);
+    removed.sort();
+    assert_eq!(removed, vec![1, 2, 3]);
+    assert!(graph.get(3).is_none());
+
+    // orphans are dropped along with the parent they were waiting on
+    assert_eq!(graph.remove(4), vec![5]);
+    assert!(graph.insert(4, node(None, Vec3::ZERO)).is_empty());
+}
+
+#[test]
+fn test_orphans_are_released_when_their_parents_never_arrive() {
+    let mut graph = SceneGraph::new();
+    graph.insert(2, node(Some(1), Vec3::X));
+    assert_eq!(graph.hold_until_connected(2, "orphan"), None);
+    graph.insert(4, node(Some(3), Vec3::X));
+    assert_eq!(graph.hold_until_connected(4, "late orphan"), None);
+
+    // nothing is released before the timeout
+    let now = Instant::now();
+    assert!(graph.expire_waiting(now, PARENT_TIMEOUT).is_empty());
+
+    let mut released = graph.expire_waiting(now + PARENT_TIMEOUT * 2, PARENT_TIMEOUT);
+    released.sort();
+    assert_eq!(released, vec!["late orphan", "orphan"]);
+    assert!(
+        graph
+            .expire_waiting(now + PARENT_TIMEOUT * 2, PARENT_TIMEOUT)
+            .is_empty()
+    );
+
+    // released items are no longer held, even if the parent does arrive later
+    assert!(graph.insert(1, node(None, Vec3::ZERO)).is_empty());
+    assert!(graph.is_connected(2));
+}
@@ -0,0 +1,174 @@
+use benthic_protocol::skeleton::{Joint, JointName, Skeleton};
+use image::{Rgba, RgbaImage};
+use indexmap::IndexMap;
+use metaverse_core::system_avatar::{
+    SYSTEM_AVATAR_JSON, base_color, composite_bake, is_system_avatar_json, render_joints,
+    skin_weights, system_avatar_json,
+};
+use metaverse_messages::http::item::{ItemData, TextureSlot};
+use metaverse_messages::utils::avatar_mesh::AvatarMesh;
+use metaverse_messages::utils::bake::BakeType;
+use std::collections::HashMap;
+use std::path::Path;
+use uuid::Uuid;
+
+/// A spine of mPelvis, mTorso, mNeck and mHead, each the child of the one before
+fn skeleton() -> Skeleton {
+    let spine = [
+        JointName::MPelvis,
+        JointName::MTorso,
+        JointName::MNeck,
+        JointName::MHead,
+    ];
+    let mut joints = IndexMap::new();
+    for (i, name) in spine.iter().enumerate() {
+        joints.insert(
+            *name,
+            Joint {
+                name: *name,
+                parent: i.checked_sub(1).map(|parent| spine[parent]),
+                children: spine.get(i + 1).copied().into_iter().collect(),
+                transforms: Vec::new(),
+                local_transforms: Vec::new(),
+            },
+        );
+    }
+    Skeleton {
+        root: vec![JointName::MPelvis],
+        joints,
+    }
+}
+
+#[test]
+fn test_render_joints() {
+    let skeleton = skeleton();
+    // the parent of each skin joint is inserted before it
+    assert_eq!(
+        render_joints(&skeleton, &[JointName::MTorso, JointName::MHead]),
+        vec![
+            JointName::MPelvis,
+            JointName::MTorso,
+            JointName::MNeck,
+            JointName::MHead,
+        ]
+    );
+    // unless it is the joint before, and the root stands in for its own parent
+    assert_eq!(
+        render_joints(&skeleton, &[JointName::MPelvis, JointName::MTorso]),
+        vec![JointName::MPelvis, JointName::MPelvis, JointName::MTorso]
+    );
+}
+
+#[test]
+fn test_skin_weights() {
+    let mesh = AvatarMesh {
+        skin_joints: vec![JointName::MPelvis, JointName::MTorso],
+        weights: vec![1.5, 2.0],
+        ..Default::default()
+    };
+    let (joint_names, weights) = skin_weights(&skeleton(), &mesh).unwrap();
+    assert_eq!(joint_names, vec![JointName::MPelvis, JointName::MTorso]);
+
+    // half way between the second render joint and the next
+    assert_eq!(weights[0].indices, [0, 1, 0, 0]);
+    assert_eq!(weights[0].weights, [0.5, 0.5, 0.0, 0.0]);
+    assert_eq!(
+        weights[0].joint_name[..2],
+        [JointName::MPelvis, JointName::MTorso]
+    );
+
+    // the last render joint has no next joint to blend into
+    assert_eq!(weights[1].indices, [1, 1, 0, 0]);
+    assert_eq!(weights[1].weights, [1.0, 0.0, 0.0, 0.0]);
+
+    let past_the_end = AvatarMesh {
+        weights: vec![3.0],
+        ..mesh
+    };
+    assert!(skin_weights(&skeleton(), &past_the_end).is_err());
+}
+
+#[test]
+fn test_composite_bake() {
+    let size = BakeType::Eyes.size();
+    let red = Rgba([255, 0, 0, 255]);
+    let green = Rgba([0, 255, 0, 255]);
+    let clear = Rgba([0, 0, 0, 0]);
+
+    // a red left half, with a green top left quarter drawn over it
+    let left = RgbaImage::from_fn(size, size, |x, _| if x < size / 2 { red } else { clear });
+    let corner = RgbaImage::from_fn(size, size, |x, y| {
+        if x < size / 2 && y < size / 2 {
+            green
+        } else {
+            clear
+        }
+    });
+    let bake = composite_bake(BakeType::Eyes, &[left.clone(), corner.clone()], &[]);
+    assert_eq!(*bake.get_pixel(0, 0), green);
+    assert_eq!(*bake.get_pixel(0, size - 1), red);
+    // the base color shows where no layer covers it
+    assert_eq!(
+        *bake.get_pixel(size - 1, size - 1),
+        base_color(BakeType::Eyes)
+    );
+
+    // layers are drawn in order
+    let bake = composite_bake(BakeType::Eyes, &[corner, left], &[]);
+    assert_eq!(*bake.get_pixel(0, 0), red);
+}
+
+#[test]
+fn test_alpha_masks() {
+    let size = BakeType::Eyes.size();
+    // hides the top half, and half hides the bottom left
+    let mask = RgbaImage::from_fn(size, size, |x, y| match (x < size / 2, y < size / 2) {
+        (_, true) => Rgba([0, 0, 0, 0]),
+        (true, false) => Rgba([0, 0, 0, 128]),
+        (false, false) => Rgba([0, 0, 0, 255]),
+    });
+    let bake = composite_bake(BakeType::Eyes, &[], &[mask.clone()]);
+    assert_eq!(bake.get_pixel(0, 0)[3], 0);
+    assert_eq!(bake.get_pixel(0, size - 1)[3], 128);
+    assert_eq!(
+        *bake.get_pixel(size - 1, size - 1),
+        base_color(BakeType::Eyes)
+    );
+
+    // masks cut into each other
+    let bake = composite_bake(BakeType::Eyes, &[], &[mask.clone(), mask]);
+    assert_eq!(bake.get_pixel(0, size - 1)[3], 64);
+}
+
+#[test]
+fn test_system_avatar_json() {
+    let eyes = ItemData {
+        textures: HashMap::from([
+            (TextureSlot::EyesIris, Uuid::from_u128(1)),
+            (TextureSlot::EyesAlpha, Uuid::from_u128(2)),
+        ]),
+        ..Default::default()
+    };
+    let name = system_avatar_json(std::slice::from_ref(&eyes), &[1, 2, 3]);
+    assert!(name.starts_with(SYSTEM_AVATAR_JSON));
+    assert!(is_system_avatar_json(Path::new(&format!("{}.json", name))));
+    assert!(!is_system_avatar_json(Path::new("mesh.json")));
+
+    // the same wearables and shape always have the same name
+    let same = ItemData {
+        textures: eyes.textures.clone(),
+        ..Default::default()
+    };
+    assert_eq!(system_avatar_json(&[same], &[1, 2, 3]), name);
+
+    // and a different shape or texture is built separately
+    assert_ne!(
+        system_avatar_json(std::slice::from_ref(&eyes), &[1, 2, 4]),
+        name
+    );
+    let recolored = ItemData {
+        textures: HashMap::from([(TextureSlot::EyesIris, Uuid::from_u128(3))]),
+        ..Default::default()
+    };
+    assert_ne!(system_avatar_json(&[recolored], &[1, 2, 3]), name);
+}
@@ -0,0 +1,408 @@
+use glam::Vec3;
+use image::{Rgb, RgbImage};
+use metaverse_core::materials::ObjectMaterials;
+use metaverse_core::volume::{FaceKind, HIGH_DETAIL, Volume};
+use metaverse_messages::http::scene::SculptType;
+use metaverse_messages::udp::object::object_update::SculptData;
+use metaverse_messages::utils::path::Path;
+use metaverse_messages::utils::texture_entry::TextureEntry;
+use rgb::Rgba;
+use std::collections::HashMap;
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+const LINE: u8 = 0x10;
+const CIRCLE: u8 = 0x20;
+const PROFILE_CIRCLE: u8 = 0x00;
+const PROFILE_SQUARE: u8 = 0x01;
+const PROFILE_HALF_CIRCLE: u8 = 0x05;
+const HOLE_SQUARE: u8 = 0x20;
+
+/// An unmodified prim, as created by the viewer's build tools
+fn prim(curve: u8, profile_curve: u8) -> Path {
+    Path {
+        curve,
+        profile_curve,
+        scale_x: 100,
+        scale_y: 100,
+        ..Default::default()
+    }
+}
+
+/// Signed volume of the mesh. This is only correct if the mesh is closed and every triangle faces
+/// outward.
+fn signed_volume(volume: &Volume) -> f32 {
+    volume
+        .faces
+        .iter()
+        .map(|face| {
+            face.indices
+                .chunks(3)
+                .map(|t| {
+                    let [a, b, c] = [0, 1, 2].map(|i| face.vertices[t[i] as usize]);
+                    a.dot(b.cross(c)) / 6.0
+                })
+                .sum::<f32>()
+        })
+        .sum()
+}
+
+fn kinds(volume: &Volume) -> Vec<FaceKind> {
+    volume.faces.iter().map(|face| face.kind).collect()
+}
+
+fn vertices(volume: &Volume) -> impl Iterator<Item = Vec3> + '_ {
+    volume
+        .faces
+        .iter()
+        .flat_map(|face| face.vertices.iter().copied())
+}
+
+/// Area of a regular polygon with the given number of sides and radius
+fn ngon_area(sides: f32, radius: f32) -> f32 {
+    0.5 * sides * radius * radius * (2.0 * PI / sides).sin()
+}
+
+#[test]
+fn test_box() {
+    let volume = Volume::from_path(&prim(LINE, PROFILE_SQUARE), HIGH_DETAIL);
+    assert_eq!(
+        kinds(&volume),
+        vec![
+            FaceKind::PathBegin,
+            FaceKind::OuterSide(0),
+            FaceKind::OuterSide(1),
+            FaceKind::OuterSide(2),
+            FaceKind::OuterSide(3),
+            FaceKind::PathEnd,
+        ]
+    );
+    assert!((signed_volume(&volume) - 1.0).abs() < 1e-4);
+    for vertex in vertices(&volume) {
+        assert!(vertex.abs().max_element() <= 0.5 + 1e-5, "{vertex}");
+    }
+    for uv in volume.faces.iter().flat_map(|face| &face.uvs) {
+        assert!(
+            uv.min_element() >= -1e-5 && uv.max_element() <= 1.0 + 1e-5,
+            "{uv}"
+        );
+    }
+}
+
+#[test]
+fn test_hollow_box() {
+    let path = Path {
+        profile_hollow: 50.0,
+        ..prim(LINE, PROFILE_SQUARE)
+    };
+    let volume = Volume::from_path(&path, HIGH_DETAIL);
+    assert_eq!(volume.faces.len(), 7);
+    assert_eq!(volume.faces[5].kind, FaceKind::InnerSide);
+    assert_eq!(volume.faces[6].kind, FaceKind::PathEnd);
+    for vertex in &volume.faces[5].vertices {
+        assert!(
+            vertex.truncate().abs().max_element() <= 0.25 + 1e-5,
+            "{vertex}"
+        );
+    }
+    assert!((signed_volume(&volume) - 0.75).abs() < 1e-4);
+}
+
+#[test]
+fn test_cylinder() {
+    let volume = Volume::from_path(&prim(LINE, PROFILE_CIRCLE), HIGH_DETAIL);
+    assert_eq!(
+        kinds(&volume),
+        vec![
+            FaceKind::PathBegin,
+            FaceKind::OuterSide(0),
+            FaceKind::PathEnd
+        ]
+    );
+    for vertex in vertices(&volume) {
+        assert!(vertex.truncate().length() <= 0.5 + 1e-5, "{vertex}");
+    }
+    assert!((signed_volume(&volume) - ngon_area(24.0, 0.5)).abs() < 1e-4);
+}
+
+#[test]
+fn test_tube_with_square_hollow() {
+    let path = Path {
+        profile_curve: PROFILE_CIRCLE | HOLE_SQUARE,
+        profile_hollow: 50.0,
+        ..prim(LINE, PROFILE_CIRCLE)
+    };
+    let volume = Volume::from_path(&path, HIGH_DETAIL);
+    assert_eq!(
+        kinds(&volume),
+        vec![
+            FaceKind::PathBegin,
+            FaceKind::OuterSide(0),
+            FaceKind::InnerSide,
+            FaceKind::PathEnd,
+        ]
+    );
+
+    // the caps join 24 outer points to the 4 corners of the hollow
+    let hollow = ngon_area(4.0, FRAC_1_SQRT_2 * 0.5);
+    assert!((signed_volume(&volume) - (ngon_area(24.0, 0.5) - hollow)).abs() < 1e-4);
+}
+
+#[test]
+fn test_profile_cut_cylinder() {
+    // keep the first half of the profile
+    let path = Path {
+        profile_end: 25000,
+        ..prim(LINE, PROFILE_CIRCLE)
+    };
+    let volume = Volume::from_path(&path, HIGH_DETAIL);
+    assert_eq!(
+        kinds(&volume),
+        vec![
+            FaceKind::PathBegin,
+            FaceKind::OuterSide(0),
+            FaceKind::PathEnd,
+            FaceKind::ProfileBegin,
+            FaceKind::ProfileEnd,
+        ]
+    );
+    assert!((signed_volume(&volume) - ngon_area(24.0, 0.5) / 2.0).abs() < 1e-4);
+}
+
+#[test]
+fn test_path_cut_box() {
+    // cut away the bottom quarter of the box
+    let path = Path {
+        begin: 12500,
+        ..prim(LINE, PROFILE_SQUARE)
+    };
+    let volume = Volume::from_path(&path, HIGH_DETAIL);
+    assert!((signed_volume(&volume) - 0.75).abs() < 1e-4);
+    let lowest = vertices(&volume).map(|v| v.z).fold(f32::MAX, f32::min);
+    assert!((lowest + 0.25).abs() < 1e-5);
+}
+
+#[test]
+fn test_sphere() {
+    let volume = Volume::from_path(&prim(CIRCLE, PROFILE_HALF_CIRCLE), HIGH_DETAIL);
+    assert_eq!(kinds(&volume), vec![FaceKind::OuterSide(0)]);
+    for vertex in vertices(&volume) {
+        assert!((vertex.length() - 0.5).abs() < 1e-4, "{vertex}");
+    }
+    let sphere = 4.0 / 3.0 * PI * 0.125;
+    assert!((signed_volume(&volume) - sphere).abs() / sphere < 0.05);
+}
+
+#[test]
+fn test_torus() {
+    let path = Path {
+        scale_y: 175,
+        ..prim(CIRCLE, PROFILE_CIRCLE)
+    };
+    let volume = Volume::from_path(&path, HIGH_DETAIL);
+    assert_eq!(kinds(&volume), vec![FaceKind::OuterSide(0)]);
+
+    // the profile is revolved around the x axis, 0.375 from its center
+    let torus = 2.0 * PI * 0.375 * ngon_area(24.0, 0.5) * 0.25;
+    assert!((signed_volume(&volume) - torus).abs() / torus < 0.03);
+    for vertex in vertices(&volume) {
+        assert!(vertex.abs().max_element() <= 0.5 + 1e-5, "{vertex}");
+    }
+}
+
+#[test]
+fn test_twist() {
+    let straight = Volume::from_path(&prim(LINE, PROFILE_SQUARE), HIGH_DETAIL);
+    let twisted = Volume::from_path(
+        &Path {
+            twist_end: 25,
+            ..prim(LINE, PROFILE_SQUARE)
+        },
+        HIGH_DETAIL,
+    );
+    assert!(twisted.faces[1].vertices.len() > straight.faces[1].vertices.len());
+
+    // an eighth of a turn moves the corners of the top onto the axes
+    let top = &twisted.faces[0];
+    assert_eq!(top.kind, FaceKind::PathBegin);
+    for vertex in &top.vertices {
+        assert!((vertex.z - 0.5).abs() < 1e-5);
+        assert!(vertex.x.abs() < 1e-4 || vertex.y.abs() < 1e-4, "{vertex}");
+    }
+    let corner = top.vertices.iter().map(|v| v.x).fold(0.0, f32::max);
+    assert!((corner - 0.5 * 2f32.sqrt()).abs() < 1e-4);
+}
+
+#[test]
+fn test_render_object_offsets_indices() {
+    let volume = Volume::from_path(&prim(LINE, PROFILE_SQUARE), HIGH_DETAIL);
+    let object = volume.to_render_object(
+        "box".to_string(),
+        Uuid::nil(),
+        &TextureEntry::default(),
+        &ObjectMaterials::default(),
+    );
+
+    let vertex_count: usize = volume.faces.iter().map(|face| face.vertices.len()).sum();
+    let index_count: usize = volume.faces.iter().map(|face| face.indices.len()).sum();
+    assert_eq!(object.vertices.len(), vertex_count);
+    assert_eq!(object.indices.len(), index_count);
+    assert_eq!(object.uv.unwrap().len(), vertex_count);
+    assert!(object.indices.iter().all(|i| (*i as usize) < vertex_count));
+}
+
+#[test]
+fn test_render_object_face_materials() {
+    let volume = Volume::from_path(&prim(LINE, PROFILE_SQUARE), HIGH_DETAIL);
+    let default_texture = Uuid::new_v4();
+    let side_texture = Uuid::new_v4();
+    let materials = ObjectMaterials {
+        textures: HashMap::from([
+            (default_texture, PathBuf::from("default.png")),
+            (side_texture, PathBuf::from("side.png")),
+        ]),
+        ..Default::default()
+    };
+    let mut texture_entry = TextureEntry {
+        texture_id: default_texture,
+        ..Default::default()
+    };
+    texture_entry.faces.insert(
+        1,
+        TextureEntry {
+            texture_id: side_texture,
+            rgba: Rgba::new(255, 0, 0, 255),
+            repeat_u: 2.0,
+            ..Default::default()
+        },
+    );
+    let object =
+        volume.to_render_object("box".to_string(), Uuid::nil(), &texture_entry, &materials);
+
+    // one material per face, covering the indices of that face
+    assert_eq!(object.faces.len(), volume.faces.len());
+    let mut first_index = 0;
+    for (face, render_face) in volume.faces.iter().zip(&object.faces) {
+        assert_eq!(render_face.first_index, first_index);
+        assert_eq!(render_face.index_count, face.indices.len());
+        first_index += face.indices.len();
+    }
+    assert_eq!(object.texture, Some(PathBuf::from("default.png")));
+    assert_eq!(object.faces[0].texture, Some(PathBuf::from("default.png")));
+    assert_eq!(object.faces[1].texture, Some(PathBuf::from("side.png")));
+    assert_eq!(object.faces[1].color, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(object.faces[2].color, [1.0, 1.0, 1.0, 1.0]);
+
+    // the overridden face repeats its texture twice across
+    let uv = object.uv.unwrap();
+    let side_start = volume.faces[0].vertices.len();
+    let side_uvs = &uv[side_start..side_start + volume.faces[1].vertices.len()];
+    let width = side_uvs.iter().map(|uv| uv[0]).fold(f32::MIN, f32::max)
+        - side_uvs.iter().map(|uv| uv[0]).fold(f32::MAX, f32::min);
+    assert!((width - 2.0).abs() < 1e-4, "{width}");
+}
+
+/// A sculpt map of a sphere. Columns go around the sphere, and rows go from the bottom of the
+/// image to the top of the sphere.
+fn sphere_map(width: u32, height: u32) -> RgbImage {
+    RgbImage::from_fn(width, height, |x, y| {
+        let around = 2.0 * PI * x as f32 / width as f32;
+        let up = PI * (height - 1 - y) as f32 / (height - 1) as f32;
+        let position = Vec3::new(up.sin() * around.cos(), up.sin() * around.sin(), -up.cos()) * 0.5;
+        Rgb(((position + 0.5) * 255.0)
+            .round()
+            .to_array()
+            .map(|c| c as u8))
+    })
+}
+
+fn sculpt(sculpt_type: SculptType) -> SculptData {
+    SculptData {
+        sculpt_type,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_sculpted_sphere() {
+    let volume = Volume::from_sculpt(
+        &sphere_map(64, 64),
+        &sculpt(SculptType::Sphere),
+        HIGH_DETAIL,
+    );
+    assert_eq!(kinds(&volume), vec![FaceKind::OuterSide(0)]);
+    assert_eq!(volume.faces[0].vertices.len(), 33 * 33);
+    for vertex in vertices(&volume) {
+        assert!((vertex.length() - 0.5).abs() < 0.01, "{vertex}");
+    }
+    let sphere = 4.0 / 3.0 * PI * 0.125;
+    assert!((signed_volume(&volume) - sphere).abs() / sphere < 0.05);
+}
+
+#[test]
+fn test_sculpt_invert_and_mirror() {
+    let map = sphere_map(64, 64);
+    let sphere = signed_volume(&Volume::from_sculpt(
+        &map,
+        &sculpt(SculptType::Sphere),
+        HIGH_DETAIL,
+    ));
+
+    // inverted sculpts are inside out
+    let inverted = SculptData {
+        invert: true,
+        ..sculpt(SculptType::Sphere)
+    };
+    let inverted = signed_volume(&Volume::from_sculpt(&map, &inverted, HIGH_DETAIL));
+    assert!((inverted + sphere).abs() < 1e-4);
+
+    // mirrored sculpts still face outward
+    let mirrored = SculptData {
+        mirror: true,
+        ..sculpt(SculptType::Sphere)
+    };
+    let mirrored = Volume::from_sculpt(&map, &mirrored, HIGH_DETAIL);
+    assert!((signed_volume(&mirrored) - sphere).abs() < 1e-4);
+}
+
+#[test]
+fn test_sculpt_resolution_follows_map() {
+    // wide maps get more columns than rows, with at most one quad for every four pixels
+    let volume = Volume::from_sculpt(
+        &sphere_map(128, 32),
+        &sculpt(SculptType::Plane),
+        HIGH_DETAIL,
+    );
+    assert_eq!(volume.faces[0].vertices.len(), 65 * 17);
+
+    let volume = Volume::from_sculpt(&sphere_map(16, 16), &sculpt(SculptType::Plane), HIGH_DETAIL);
+    assert_eq!(volume.faces[0].vertices.len(), 9 * 9);
+}
+
+#[test]
+fn test_sculpt_stitching() {
+    let map = sphere_map(64, 64);
+    let row =
+        |volume: &Volume, row: usize| volume.faces[0].vertices[row * 33..(row + 1) * 33].to_vec();
+
+    // cylinders wrap around sideways, so the last column is the first column
+    let cylinder = Volume::from_sculpt(&map, &sculpt(SculptType::Cylinder), HIGH_DETAIL);
+    for vertices in (0..33).map(|i| row(&cylinder, i)) {
+        assert_eq!(vertices[0], vertices[32]);
+    }
+
+    // planes don't, so the last column is the edge of the map
+    let plane = Volume::from_sculpt(&map, &sculpt(SculptType::Plane), HIGH_DETAIL);
+    assert_ne!(row(&plane, 16)[0], row(&plane, 16)[32]);
+
+    // tori wrap around vertically too
+    let torus = Volume::from_sculpt(&map, &sculpt(SculptType::Torus), HIGH_DETAIL);
+    assert_eq!(row(&torus, 0), row(&torus, 32));
+
+    // spheres are pinched at the poles
+    let sphere = Volume::from_sculpt(&map, &sculpt(SculptType::Sphere), HIGH_DETAIL);
+    for pole in [row(&sphere, 0), row(&sphere, 32)] {
+        assert!(pole.iter().all(|vertex| *vertex == pole[0]));
+    }
+}
@@ -219,5 +219,6 @@ pub fn build_terrain(
         skin: None,
         texture: None,
         uv: None,
+        faces: Vec::new(),
     }
 }
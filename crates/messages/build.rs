@@ -0,0 +1,836 @@
+//! Generates packet definitions from message_template.msg.
+//!
+//! The template is parsed into messages, blocks and fields, and written out as rust structs with
+//! PacketData implementations, along with the ID, frequency, trust and encoding of each message.
+//! The generated file is included by the packet::message_template module.
+//!
+//! Messages that already have a hand written implementation are not generated. Their template
+//! definition only becomes an INFO constant on the hand written struct, and the packet registry
+//! in packet::packet_types is generated from the list of them, so its IDs always match the
+//! template.
+use std::{env, fmt::Write, fs, path::Path};
+
+const TEMPLATE: &str = "message_template.msg";
+
+/// The template messages with a hand written implementation, and the path of the struct that
+/// implements them. Packets are registered in this order.
+const HAND_WRITTEN: &[(&str, &str)] = &[
+    (
+        "StartPingCheck",
+        "crate::udp::core::start_ping_check::StartPingCheck",
+    ),
+    (
+        "CompletePingCheck",
+        "crate::udp::core::complete_ping_check::CompletePingCheck",
+    ),
+    (
+        "AgentUpdate",
+        "crate::udp::agent::agent_update::AgentUpdate",
+    ),
+    (
+        "LayerData",
+        "crate::udp::environment::layer_data::LayerData",
+    ),
+    (
+        "ObjectUpdate",
+        "crate::udp::object::object_update::ObjectUpdate",
+    ),
+    (
+        "ObjectUpdateCompressed",
+        "crate::udp::object::object_update_compressed::ObjectUpdateCompressed",
+    ),
+    (
+        "ObjectUpdateCached",
+        "crate::udp::object::object_update_cached::ObjectUpdateCached",
+    ),
+    (
+        "ImprovedTerseObjectUpdate",
+        "crate::udp::object::improved_terse_object_update::ImprovedTerseObjectUpdate",
+    ),
+    (
+        "AvatarAnimation",
+        "crate::udp::agent::avatar_animation::AvatarAnimation",
+    ),
+    ("KillObject", "crate::udp::object::kill_object::KillObject"),
+    (
+        "GenericStreamingMessage",
+        "crate::udp::object::generic_streaming_message::GenericStreamingMessage",
+    ),
+    (
+        "MultipleObjectUpdate",
+        "crate::udp::object::multiple_object_update::MultipleObjectUpdate",
+    ),
+    (
+        "RequestMultipleObjects",
+        "crate::udp::object::request_multiple_objects::RequestMultipleObjects",
+    ),
+    (
+        "CoarseLocationUpdate",
+        "crate::udp::agent::coarse_location_update::CoarseLocationUpdate",
+    ),
+    (
+        "ViewerEffect",
+        "crate::udp::core::viewer_effect::ViewerEffect",
+    ),
+    ("TestMessage", "crate::udp::core::test_packet::TestPacket"),
+    (
+        "UseCircuitCode",
+        "crate::udp::core::circuit_code::CircuitCode",
+    ),
+    (
+        "TeleportRequest",
+        "crate::udp::teleport::teleport_request::TeleportRequest",
+    ),
+    (
+        "TeleportStart",
+        "crate::udp::teleport::teleport_start::TeleportStart",
+    ),
+    (
+        "ChatFromViewer",
+        "crate::udp::chat::chat_from_viewer::ChatFromViewer",
+    ),
+    (
+        "AgentThrottle",
+        "crate::udp::core::agent_throttle::AgentThrottle",
+    ),
+    (
+        "ChatFromSimulator",
+        "crate::udp::chat::chat_from_simulator::ChatFromSimulator",
+    ),
+    ("SimStats", "crate::udp::core::sim_stats::SimStats"),
+    (
+        "RegionHandshake",
+        "crate::udp::core::region_handshake::RegionHandshake",
+    ),
+    (
+        "RegionHandshakeReply",
+        "crate::udp::core::region_handshake_reply::RegionHandshakeReply",
+    ),
+    (
+        "SimulatorViewerTimeMessage",
+        "crate::udp::core::simulator_viewer_time_message::SimulatorViewerTimeMessage",
+    ),
+    (
+        "DisableSimulator",
+        "crate::udp::core::disable_simulator::DisableSimulator",
+    ),
+    (
+        "EnableSimulator",
+        "crate::udp::core::enable_simulator::EnableSimulator",
+    ),
+    (
+        "AvatarAppearance",
+        "crate::udp::agent::avatar_appearance::AvatarAppearance",
+    ),
+    (
+        "ParcelOverlay",
+        "crate::udp::core::parcel_overlay::ParcelOverlay",
+    ),
+    (
+        "CompleteAgentMovement",
+        "crate::udp::core::complete_agent_movement::CompleteAgentMovementData",
+    ),
+    (
+        "AgentMovementComplete",
+        "crate::udp::core::agent_movement_complete::AgentMovementComplete",
+    ),
+    (
+        "LogoutRequest",
+        "crate::udp::core::logout_request::LogoutRequest",
+    ),
+    ("PacketAck", "crate::udp::core::packet_ack::PacketAck"),
+    (
+        "AgentWearablesUpdate",
+        "crate::legacy::udp::agent_wearables_update::AgentWearablesUpdate",
+    ),
+    (
+        "AgentWearablesRequest",
+        "crate::legacy::udp::agent_wearables_request::AgentWearablesRequest",
+    ),
+];
+
+/// The path of the hand written struct that implements a message, if it has one
+fn hand_written_path(name: &str) -> Option<&'static str> {
+    HAND_WRITTEN
+        .iter()
+        .find(|(template_name, _)| *template_name == name)
+        .map(|(_, path)| *path)
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", TEMPLATE);
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let source = fs::read_to_string(TEMPLATE).expect("failed to read message_template.msg");
+    let messages = match parse_template(&source) {
+        Ok(messages) => messages,
+        Err(e) => panic!("failed to parse message_template.msg: {}", e),
+    };
+
+    for (name, _) in HAND_WRITTEN {
+        if !messages.iter().any(|message| message.name == *name) {
+            panic!(
+                "hand written message {} is not in message_template.msg",
+                name
+            );
+        }
+    }
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is not set");
+    let out_path = Path::new(&out_dir).join("message_template.rs");
+    fs::write(out_path, generate(&messages)).expect("failed to write generated packets");
+    let registry_path = Path::new(&out_dir).join("packet_registry.rs");
+    fs::write(registry_path, generate_registry()).expect("failed to write packet registry");
+}
+
+struct Message {
+    name: String,
+    frequency: String,
+    id: u16,
+    trusted: bool,
+    zerocoded: bool,
+    deprecated: bool,
+    blocks: Vec<Block>,
+}
+
+enum BlockKind {
+    Single,
+    Multiple(usize),
+    Variable,
+}
+
+struct Block {
+    name: String,
+    kind: BlockKind,
+    fields: Vec<Field>,
+}
+
+enum FieldType {
+    U8,
+    U16,
+    U32,
+    U64,
+    S8,
+    S16,
+    S32,
+    S64,
+    F32,
+    F64,
+    Vector3,
+    Vector3d,
+    Vector4,
+    Quaternion,
+    Uuid,
+    Bool,
+    IpAddr,
+    IpPort,
+    Fixed(usize),
+    Variable1,
+    Variable2,
+}
+
+struct Field {
+    name: String,
+    template_type: String,
+    field_type: FieldType,
+}
+
+/// Split the template into braces and words, skipping comments.
+fn tokenize(source: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for line in source.lines() {
+        let line = match line.find("//") {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+        for word in line
+            .replace('{', " { ")
+            .replace('}', " } ")
+            .split_whitespace()
+        {
+            tokens.push(word.to_string());
+        }
+    }
+    tokens
+}
+
+struct Tokens {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Tokens {
+    fn next(&mut self) -> Result<String, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or("unexpected end of template")?;
+        self.position += 1;
+        Ok(token)
+    }
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        let token = self.next()?;
+        if token != expected {
+            return Err(format!("expected {} but found {}", expected, token));
+        }
+        Ok(())
+    }
+    fn number(&mut self) -> Result<usize, String> {
+        let token = self.next()?;
+        token
+            .parse()
+            .map_err(|_| format!("expected a number but found {}", token))
+    }
+}
+
+fn parse_template(source: &str) -> Result<Vec<Message>, String> {
+    let mut tokens = Tokens {
+        tokens: tokenize(source),
+        position: 0,
+    };
+    let mut messages = Vec::new();
+    while let Some(token) = tokens.peek() {
+        match token {
+            "version" => {
+                tokens.next()?;
+                tokens.next()?;
+            }
+            "{" => messages.push(parse_message(&mut tokens)?),
+            other => return Err(format!("unexpected token {}", other)),
+        }
+    }
+    Ok(messages)
+}
+
+fn parse_message(tokens: &mut Tokens) -> Result<Message, String> {
+    tokens.expect("{")?;
+    let name = tokens.next()?;
+    let frequency = tokens.next()?;
+    let number = tokens.next()?;
+    let id = match number.strip_prefix("0x").or(number.strip_prefix("0X")) {
+        // fixed messages are written as 0xFFFFFFxx, and are identified by the last byte
+        Some(hex) => u32::from_str_radix(hex, 16).map(|id| id & 0xFF),
+        None => number.parse::<u32>(),
+    }
+    .map_err(|_| format!("invalid message number {} for {}", number, name))? as u16;
+    if !matches!(frequency.as_str(), "High" | "Medium" | "Low" | "Fixed") {
+        return Err(format!("invalid frequency {} for {}", frequency, name));
+    }
+    let trusted = tokens.next()? == "Trusted";
+    let zerocoded = tokens.next()? == "Zerocoded";
+
+    let mut deprecated = false;
+    let mut blocks = Vec::new();
+    loop {
+        match tokens.peek() {
+            Some("{") => blocks.push(parse_block(tokens)?),
+            Some("}") => {
+                tokens.next()?;
+                break;
+            }
+            Some(_) => {
+                // Deprecated, UDPDeprecated or UDPBlackListed
+                deprecated = true;
+                tokens.next()?;
+            }
+            None => return Err(format!("unterminated message {}", name)),
+        }
+    }
+
+    Ok(Message {
+        name,
+        frequency,
+        id,
+        trusted,
+        zerocoded,
+        deprecated,
+        blocks,
+    })
+}
+
+fn parse_block(tokens: &mut Tokens) -> Result<Block, String> {
+    tokens.expect("{")?;
+    let name = tokens.next()?;
+    let kind = match tokens.next()?.as_str() {
+        "Single" => BlockKind::Single,
+        "Multiple" => BlockKind::Multiple(tokens.number()?),
+        "Variable" => BlockKind::Variable,
+        other => return Err(format!("invalid block type {} for {}", other, name)),
+    };
+
+    let mut fields = Vec::new();
+    while tokens.peek() == Some("{") {
+        tokens.expect("{")?;
+        let field_name = tokens.next()?;
+        let template_type = tokens.next()?;
+        let field_type = match template_type.as_str() {
+            "U8" => FieldType::U8,
+            "U16" => FieldType::U16,
+            "U32" => FieldType::U32,
+            "U64" => FieldType::U64,
+            "S8" => FieldType::S8,
+            "S16" => FieldType::S16,
+            "S32" => FieldType::S32,
+            "S64" => FieldType::S64,
+            "F32" => FieldType::F32,
+            "F64" => FieldType::F64,
+            "LLVector3" => FieldType::Vector3,
+            "LLVector3d" => FieldType::Vector3d,
+            "LLVector4" => FieldType::Vector4,
+            "LLQuaternion" => FieldType::Quaternion,
+            "LLUUID" => FieldType::Uuid,
+            "BOOL" => FieldType::Bool,
+            "IPADDR" => FieldType::IpAddr,
+            "IPPORT" => FieldType::IpPort,
+            "Fixed" => FieldType::Fixed(tokens.number()?),
+            "Variable" => match tokens.number()? {
+                1 => FieldType::Variable1,
+                2 => FieldType::Variable2,
+                size => return Err(format!("invalid variable size {} for {}", size, field_name)),
+            },
+            other => return Err(format!("invalid field type {} for {}", other, field_name)),
+        };
+        tokens.expect("}")?;
+        fields.push(Field {
+            name: field_name,
+            template_type,
+            field_type,
+        });
+    }
+    tokens.expect("}")?;
+
+    Ok(Block { name, kind, fields })
+}
+
+/// Convert a template name like RegionID or CPUClassID to snake case.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if previous.is_ascii_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_ascii_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+/// Field names that are keywords can't be used as plain identifiers.
+fn field_ident(name: &str) -> String {
+    let snake = snake_case(name);
+    match snake.as_str() {
+        "crate" | "self" | "super" => format!("{}_", snake),
+        "as" | "break" | "const" | "continue" | "else" | "enum" | "extern" | "false" | "fn"
+        | "for" | "if" | "impl" | "in" | "let" | "loop" | "match" | "mod" | "move" | "mut"
+        | "pub" | "ref" | "return" | "static" | "struct" | "trait" | "true" | "type" | "unsafe"
+        | "use" | "where" | "while" | "async" | "await" | "dyn" | "abstract" | "become" | "box"
+        | "do" | "final" | "macro" | "override" | "priv" | "typeof" | "unsized" | "virtual"
+        | "yield" | "try" | "gen" => format!("r#{}", snake),
+        _ => snake,
+    }
+}
+
+fn rust_type(field_type: &FieldType) -> &'static str {
+    match field_type {
+        FieldType::U8 => "u8",
+        FieldType::U16 | FieldType::IpPort => "u16",
+        FieldType::U32 => "u32",
+        FieldType::U64 => "u64",
+        FieldType::S8 => "i8",
+        FieldType::S16 => "i16",
+        FieldType::S32 => "i32",
+        FieldType::S64 => "i64",
+        FieldType::F32 => "f32",
+        FieldType::F64 => "f64",
+        FieldType::Vector3 => "glam::Vec3",
+        FieldType::Vector3d => "glam::DVec3",
+        FieldType::Vector4 => "glam::Vec4",
+        FieldType::Quaternion => "glam::Quat",
+        FieldType::Uuid => "uuid::Uuid",
+        FieldType::Bool => "bool",
+        FieldType::IpAddr => "std::net::Ipv4Addr",
+        FieldType::Fixed(_) | FieldType::Variable1 | FieldType::Variable2 => "Vec<u8>",
+    }
+}
+
+fn read_field(field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::IpPort => "read_port(cursor)?".to_string(),
+        FieldType::Fixed(size) => format!("read_fixed(cursor, {})?", size),
+        FieldType::Variable1 => "read_variable(cursor, 1)?".to_string(),
+        FieldType::Variable2 => "read_variable(cursor, 2)?".to_string(),
+        _ => "TemplateField::read(cursor)?".to_string(),
+    }
+}
+
+fn write_field(field_type: &FieldType, value: &str) -> String {
+    match field_type {
+        FieldType::IpPort => format!("write_port(bytes, {});", value),
+        FieldType::Fixed(size) => format!("write_fixed(bytes, &{}, {});", value, size),
+        FieldType::Variable1 => format!("write_variable(bytes, &{}, 1);", value),
+        FieldType::Variable2 => format!("write_variable(bytes, &{}, 2);", value),
+        _ => format!("{}.write(bytes);", value),
+    }
+}
+
+/// The default value of a field, if it isn't the type's Default.
+fn default_value(field_type: &FieldType) -> Option<&'static str> {
+    match field_type {
+        FieldType::IpAddr => Some("std::net::Ipv4Addr::UNSPECIFIED"),
+        FieldType::Quaternion => Some("glam::Quat::IDENTITY"),
+        _ => None,
+    }
+}
+
+fn frequency_name(message: &Message) -> &str {
+    &message.frequency
+}
+
+fn generate(messages: &[Message]) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// Generated by build.rs from message_template.msg. Do not edit."
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+
+    for message in messages {
+        match hand_written_path(&message.name) {
+            Some(path) => generate_info(&mut out, message, path),
+            None => generate_message(&mut out, message),
+        }
+    }
+
+    // metadata for every message in the template
+    writeln!(out, "/// Every message defined in message_template.msg").unwrap();
+    writeln!(out, "pub const MESSAGES: &[MessageInfo] = &[").unwrap();
+    for message in messages {
+        let path = hand_written_path(&message.name).unwrap_or(&message.name);
+        writeln!(out, "    {}::INFO,", path).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    writeln!(out).unwrap();
+
+    let generated: Vec<&Message> = messages
+        .iter()
+        .filter(|message| hand_written_path(&message.name).is_none())
+        .collect();
+
+    // the registry of generated messages
+    writeln!(
+        out,
+        "/// All of the messages generated from message_template.msg"
+    )
+    .unwrap();
+    writeln!(out, "#[derive(Debug, Clone, PartialEq)]").unwrap();
+    writeln!(out, "pub enum TemplateMessage {{").unwrap();
+    for message in &generated {
+        writeln!(out, "    /// The {} message", message.name).unwrap();
+        writeln!(out, "    {0}(Box<{0}>),", message.name).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+
+    writeln!(out, "impl TemplateMessage {{").unwrap();
+    writeln!(
+        out,
+        "    /// Parse the body of a message using its ID and frequency.\n    /// Returns None if the template has no message with that ID and frequency."
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "    pub fn from_id(id: u16, frequency: PacketFrequency, bytes: &[u8]) -> Option<Result<Self, ParseError>> {{"
+    )
+    .unwrap();
+    writeln!(out, "        let message = match (frequency, id) {{").unwrap();
+    for message in &generated {
+        writeln!(
+            out,
+            "            (PacketFrequency::{}, {}) => {}::from_bytes(bytes).map(|m| Self::{}(Box::new(m))),",
+            frequency_name(message),
+            message.id,
+            message.name,
+            message.name
+        )
+        .unwrap();
+    }
+    writeln!(out, "            _ => return None,").unwrap();
+    writeln!(out, "        }};").unwrap();
+    writeln!(out, "        Some(message)").unwrap();
+    writeln!(out, "    }}").unwrap();
+
+    writeln!(out, "    /// Serialize the body of the message").unwrap();
+    writeln!(out, "    pub fn to_bytes(&self) -> Vec<u8> {{").unwrap();
+    writeln!(out, "        match self {{").unwrap();
+    for message in &generated {
+        writeln!(
+            out,
+            "            Self::{}(data) => data.to_bytes(),",
+            message.name
+        )
+        .unwrap();
+    }
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+
+    writeln!(
+        out,
+        "    /// The ID, frequency, trust and encoding of the message"
+    )
+    .unwrap();
+    writeln!(out, "    pub fn info(&self) -> &'static MessageInfo {{").unwrap();
+    writeln!(out, "        match self {{").unwrap();
+    for message in &generated {
+        writeln!(
+            out,
+            "            Self::{}(_) => &{}::INFO,",
+            message.name, message.name
+        )
+        .unwrap();
+    }
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+
+    out
+}
+
+/// The INFO constant of a message, on the generated or hand written struct at the path
+fn generate_info(out: &mut String, message: &Message, path: &str) {
+    writeln!(out, "impl {} {{", path).unwrap();
+    writeln!(
+        out,
+        "    /// The ID, frequency, trust and encoding of the message"
+    )
+    .unwrap();
+    writeln!(out, "    pub const INFO: MessageInfo = MessageInfo {{").unwrap();
+    writeln!(out, "        name: \"{}\",", message.name).unwrap();
+    writeln!(out, "        id: {},", message.id).unwrap();
+    writeln!(
+        out,
+        "        frequency: PacketFrequency::{},",
+        frequency_name(message)
+    )
+    .unwrap();
+    writeln!(out, "        trusted: {},", message.trusted).unwrap();
+    writeln!(out, "        zerocoded: {},", message.zerocoded).unwrap();
+    writeln!(out, "        deprecated: {},", message.deprecated).unwrap();
+    writeln!(out, "    }};").unwrap();
+    writeln!(out, "}}").unwrap();
+}
+
+/// The define_packets! invocation that registers every hand written packet
+fn generate_registry() -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// Generated by build.rs from message_template.msg. Do not edit."
+    )
+    .unwrap();
+    writeln!(out, "define_packets! {{").unwrap();
+    for (_, path) in HAND_WRITTEN {
+        writeln!(out, "    {},", path.rsplit("::").next().unwrap()).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+fn generate_message(out: &mut String, message: &Message) {
+    // block structs
+    for block in &message.blocks {
+        let struct_name = format!("{}{}", message.name, block.name);
+        let needs_manual_default = block
+            .fields
+            .iter()
+            .any(|field| default_value(&field.field_type).is_some());
+
+        writeln!(
+            out,
+            "/// The {} block of the {} message",
+            block.name, message.name
+        )
+        .unwrap();
+        if needs_manual_default {
+            writeln!(out, "#[derive(Debug, Clone, PartialEq)]").unwrap();
+        } else {
+            writeln!(out, "#[derive(Debug, Clone, Default, PartialEq)]").unwrap();
+        }
+        writeln!(out, "pub struct {} {{", struct_name).unwrap();
+        for field in &block.fields {
+            writeln!(out, "    /// {} ({})", field.name, field.template_type).unwrap();
+            writeln!(
+                out,
+                "    pub {}: {},",
+                field_ident(&field.name),
+                rust_type(&field.field_type)
+            )
+            .unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+
+        if needs_manual_default {
+            writeln!(out, "impl Default for {} {{", struct_name).unwrap();
+            writeln!(out, "    fn default() -> Self {{").unwrap();
+            writeln!(out, "        Self {{").unwrap();
+            for field in &block.fields {
+                writeln!(
+                    out,
+                    "            {}: {},",
+                    field_ident(&field.name),
+                    default_value(&field.field_type).unwrap_or("Default::default()")
+                )
+                .unwrap();
+            }
+            writeln!(out, "        }}").unwrap();
+            writeln!(out, "    }}").unwrap();
+            writeln!(out, "}}").unwrap();
+        }
+
+        writeln!(out, "impl TemplateField for {} {{", struct_name).unwrap();
+        writeln!(
+            out,
+            "    fn read(cursor: &mut Cursor<&[u8]>) -> Result<Self, ParseError> {{"
+        )
+        .unwrap();
+        writeln!(out, "        Ok(Self {{").unwrap();
+        for field in &block.fields {
+            writeln!(
+                out,
+                "            {}: {},",
+                field_ident(&field.name),
+                read_field(&field.field_type)
+            )
+            .unwrap();
+        }
+        writeln!(out, "        }})").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "    fn write(&self, bytes: &mut Vec<u8>) {{").unwrap();
+        for field in &block.fields {
+            let value = format!("self.{}", field_ident(&field.name));
+            writeln!(out, "        {}", write_field(&field.field_type, &value)).unwrap();
+        }
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "}}").unwrap();
+        writeln!(out).unwrap();
+    }
+
+    // message struct
+    writeln!(
+        out,
+        "/// The {} message. {} frequency, ID {}.",
+        message.name,
+        frequency_name(message),
+        message.id
+    )
+    .unwrap();
+    writeln!(out, "#[derive(Debug, Clone, Default, PartialEq)]").unwrap();
+    writeln!(out, "pub struct {} {{", message.name).unwrap();
+    for block in &message.blocks {
+        let struct_name = format!("{}{}", message.name, block.name);
+        match block.kind {
+            BlockKind::Single => {
+                writeln!(out, "    /// {} block", block.name).unwrap();
+                writeln!(
+                    out,
+                    "    pub {}: {},",
+                    field_ident(&block.name),
+                    struct_name
+                )
+                .unwrap();
+            }
+            BlockKind::Multiple(count) => {
+                writeln!(
+                    out,
+                    "    /// {} blocks. Always sent as exactly {} blocks.",
+                    block.name, count
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "    pub {}: Vec<{}>,",
+                    field_ident(&block.name),
+                    struct_name
+                )
+                .unwrap();
+            }
+            BlockKind::Variable => {
+                writeln!(out, "    /// {} blocks", block.name).unwrap();
+                writeln!(
+                    out,
+                    "    pub {}: Vec<{}>,",
+                    field_ident(&block.name),
+                    struct_name
+                )
+                .unwrap();
+            }
+        }
+    }
+    writeln!(out, "}}").unwrap();
+
+    generate_info(out, message, &message.name);
+
+    writeln!(out, "impl PacketData for {} {{", message.name).unwrap();
+    if message.blocks.is_empty() {
+        writeln!(
+            out,
+            "    fn from_bytes(_bytes: &[u8]) -> Result<Self, ParseError> {{"
+        )
+        .unwrap();
+        writeln!(out, "        Ok(Self {{}})").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "    fn to_bytes(&self) -> Vec<u8> {{").unwrap();
+        writeln!(out, "        Vec::new()").unwrap();
+        writeln!(out, "    }}").unwrap();
+    } else {
+        writeln!(
+            out,
+            "    fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {{"
+        )
+        .unwrap();
+        writeln!(out, "        let cursor = &mut Cursor::new(bytes);").unwrap();
+        writeln!(out, "        Ok(Self {{").unwrap();
+        for block in &message.blocks {
+            let read = match block.kind {
+                BlockKind::Single => "TemplateField::read(cursor)?".to_string(),
+                BlockKind::Multiple(count) => format!("read_multiple_block(cursor, {})?", count),
+                BlockKind::Variable => "read_variable_block(cursor)?".to_string(),
+            };
+            writeln!(out, "            {}: {},", field_ident(&block.name), read).unwrap();
+        }
+        writeln!(out, "        }})").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "    fn to_bytes(&self) -> Vec<u8> {{").unwrap();
+        writeln!(out, "        let bytes = &mut Vec::new();").unwrap();
+        for block in &message.blocks {
+            let ident = field_ident(&block.name);
+            match block.kind {
+                BlockKind::Single => writeln!(out, "        self.{}.write(bytes);", ident),
+                BlockKind::Multiple(count) => writeln!(
+                    out,
+                    "        write_multiple_block(bytes, &self.{}, {});",
+                    ident, count
+                ),
+                BlockKind::Variable => {
+                    writeln!(out, "        write_variable_block(bytes, &self.{});", ident)
+                }
+            }
+            .unwrap();
+        }
+        writeln!(out, "        std::mem::take(bytes)").unwrap();
+        writeln!(out, "    }}").unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+}
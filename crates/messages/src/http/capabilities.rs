@@ -18,6 +18,8 @@ pub enum Capability {
     FetchInventoryDescendents2,
     /// Enable the viewer to retrieve extended environment data
     ExtEnvironment,
+    /// Enable the viewer to retrieve the legacy normal and specular materials of faces
+    RenderMaterials,
     /// Unknown
     Unknown,
 }
@@ -27,6 +29,7 @@ impl Capability {
             "ViewerAsset" => Self::ViewerAsset,
             "FetchInventoryDescendents2" => Self::FetchInventoryDescendents2,
             "ExtEnvironment" => Self::ExtEnvironment,
+            "RenderMaterials" => Self::RenderMaterials,
             _ => Self::Unknown,
         }
     }
@@ -37,6 +40,7 @@ impl Display for Capability {
             Self::ViewerAsset => write!(f, "ViewerAsset"),
             Self::FetchInventoryDescendents2 => write!(f, "FetchInventoryDescendents2"),
             Self::ExtEnvironment => write!(f, "ExtEnvironment"),
+            Self::RenderMaterials => write!(f, "RenderMaterials"),
             Self::Unknown => write!(f, "Unknown"),
         }
     }
@@ -0,0 +1,268 @@
+use crate::errors::ParseError;
+use serde::{Deserialize, Serialize};
+use serde_llsd_benthic::{LLSDValue, auto_from_str, de::binary};
+use uuid::Uuid;
+
+/// The header of binary LLSD. Material assets are usually saved as binary, but can also be XML.
+const LLSD_BINARY_HEADER: &[u8] = b"<? LLSD/Binary ?>";
+/// An override texture with this ID removes the texture from the material, where a nil ID leaves
+/// the material's own texture in place.
+pub const OVERRIDE_NULL_TEXTURE: Uuid = Uuid::max();
+
+/// How the alpha of the base color is used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlphaMode {
+    /// Alpha is ignored and the material is fully opaque
+    #[default]
+    Opaque,
+    /// Alpha blends the material with what is behind it
+    Blend,
+    /// Pixels with an alpha below the cutoff are not drawn, and the rest are opaque
+    Mask,
+}
+impl AlphaMode {
+    /// convert from the integer used by material overrides
+    pub fn from_override(value: i32) -> Self {
+        match value {
+            1 => AlphaMode::Blend,
+            2 => AlphaMode::Mask,
+            _ => AlphaMode::Opaque,
+        }
+    }
+}
+impl From<&str> for AlphaMode {
+    fn from(s: &str) -> Self {
+        match s {
+            "BLEND" => AlphaMode::Blend,
+            "MASK" => AlphaMode::Mask,
+            _ => AlphaMode::Opaque,
+        }
+    }
+}
+
+/// A PBR material, retrieved from the ViewerAsset endpoint as a material asset.
+///
+/// The asset is an LLSD map that holds a GLTF document in its data field. Only the first material
+/// of the document is used, and its textures are referenced by putting their texture IDs in the
+/// URIs of the document's images.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GltfMaterial {
+    /// Texture multiplied with the base color
+    pub base_color_texture: Option<Uuid>,
+    /// Tangent space normal map
+    pub normal_texture: Option<Uuid>,
+    /// Texture with roughness in its green channel and metalness in its blue channel
+    pub metallic_roughness_texture: Option<Uuid>,
+    /// Texture multiplied with the emissive color
+    pub emissive_texture: Option<Uuid>,
+    /// Linear RGBA color of the material
+    pub base_color: [f32; 4],
+    /// Linear RGB color of the light the material gives off
+    pub emissive: [f32; 3],
+    /// How metallic the material is, from 0 to 1
+    pub metallic: f32,
+    /// How rough the material is, from 0 to 1
+    pub roughness: f32,
+    /// How the alpha of the base color is used
+    pub alpha_mode: AlphaMode,
+    /// The alpha below which pixels are not drawn, if the alpha mode is [`AlphaMode::Mask`]
+    pub alpha_cutoff: f32,
+    /// If the back of the material is drawn
+    pub double_sided: bool,
+}
+impl Default for GltfMaterial {
+    /// The GLTF defaults, which are also used for any value a material leaves out
+    fn default() -> Self {
+        GltfMaterial {
+            base_color_texture: None,
+            normal_texture: None,
+            metallic_roughness_texture: None,
+            emissive_texture: None,
+            base_color: [1.0; 4],
+            emissive: [0.0; 3],
+            metallic: 1.0,
+            roughness: 1.0,
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+        }
+    }
+}
+
+impl GltfMaterial {
+    /// Parse a material asset
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        let llsd = if bytes.starts_with(LLSD_BINARY_HEADER) {
+            binary::from_bytes(bytes)?
+        } else {
+            auto_from_str(&String::from_utf8_lossy(bytes))?
+        };
+        let map = llsd
+            .as_map()
+            .ok_or_else(|| ParseError::InvalidField("Material asset is not a map".into()))?;
+        match map.get("data") {
+            Some(LLSDValue::String(data)) => Self::from_json(data),
+            _ => Err(ParseError::MissingField("data".into())),
+        }
+    }
+
+    /// Parse the first material of a GLTF document
+    pub fn from_json(json: &str) -> Result<Self, ParseError> {
+        let document: GltfDocument = serde_json::from_str(json)?;
+        let material = document
+            .materials
+            .first()
+            .ok_or_else(|| ParseError::MissingField("materials".into()))?;
+        let pbr = &material.pbr_metallic_roughness;
+        let defaults = GltfMaterial::default();
+        Ok(GltfMaterial {
+            base_color_texture: document.texture_id(&pbr.base_color_texture),
+            normal_texture: document.texture_id(&material.normal_texture),
+            metallic_roughness_texture: document.texture_id(&pbr.metallic_roughness_texture),
+            emissive_texture: document.texture_id(&material.emissive_texture),
+            base_color: pbr.base_color_factor.unwrap_or(defaults.base_color),
+            emissive: material.emissive_factor.unwrap_or(defaults.emissive),
+            metallic: pbr.metallic_factor.unwrap_or(defaults.metallic),
+            roughness: pbr.roughness_factor.unwrap_or(defaults.roughness),
+            alpha_mode: material
+                .alpha_mode
+                .as_deref()
+                .map(AlphaMode::from)
+                .unwrap_or_default(),
+            alpha_cutoff: material.alpha_cutoff.unwrap_or(defaults.alpha_cutoff),
+            double_sided: material.double_sided,
+        })
+    }
+
+    /// Apply the override a face of an object makes to its material.
+    ///
+    /// Overrides are sparse LLSD maps, which only contain the values that differ from the
+    /// material. Textures are overridden in the order base color, normal, metallic roughness and
+    /// emissive.
+    pub fn apply_override(&mut self, data: &LLSDValue) {
+        let Some(map) = data.as_map() else {
+            return;
+        };
+        if let Some(textures) = map.get("tex").and_then(|textures| textures.as_array()) {
+            let slots = [
+                &mut self.base_color_texture,
+                &mut self.normal_texture,
+                &mut self.metallic_roughness_texture,
+                &mut self.emissive_texture,
+            ];
+            for (slot, texture) in slots.into_iter().zip(textures) {
+                match texture.as_uuid() {
+                    Some(id) if *id == OVERRIDE_NULL_TEXTURE => *slot = None,
+                    Some(id) if !id.is_nil() => *slot = Some(*id),
+                    _ => {}
+                }
+            }
+        }
+        if let Some(color) = map.get("bc").and_then(llsd_floats::<4>) {
+            self.base_color = color;
+        }
+        if let Some(color) = map.get("ec").and_then(llsd_floats::<3>) {
+            self.emissive = color;
+        }
+        if let Some(metallic) = map.get("mf").and_then(llsd_float) {
+            self.metallic = metallic;
+        }
+        if let Some(roughness) = map.get("rf").and_then(llsd_float) {
+            self.roughness = roughness;
+        }
+        if let Some(mode) = map.get("am").and_then(|mode| mode.as_integer()) {
+            self.alpha_mode = AlphaMode::from_override(*mode);
+        }
+        if let Some(cutoff) = map.get("ac").and_then(llsd_float) {
+            self.alpha_cutoff = cutoff;
+        }
+        if let Some(double_sided) = map.get("ds").and_then(|value| value.as_boolean()) {
+            self.double_sided = *double_sided;
+        }
+    }
+
+    /// The IDs of every texture the material uses
+    pub fn textures(&self) -> impl Iterator<Item = Uuid> + '_ {
+        [
+            self.base_color_texture,
+            self.normal_texture,
+            self.metallic_roughness_texture,
+            self.emissive_texture,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+fn llsd_float(value: &LLSDValue) -> Option<f32> {
+    match value {
+        LLSDValue::Real(real) => Some(*real as f32),
+        LLSDValue::Integer(integer) => Some(*integer as f32),
+        _ => None,
+    }
+}
+
+fn llsd_floats<const N: usize>(value: &LLSDValue) -> Option<[f32; N]> {
+    let values = value
+        .as_array()?
+        .iter()
+        .map(llsd_float)
+        .collect::<Option<Vec<f32>>>()?;
+    values.try_into().ok()
+}
+
+/// The parts of a GLTF document used by materials
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GltfDocument {
+    materials: Vec<GltfDocumentMaterial>,
+    textures: Vec<GltfTexture>,
+    images: Vec<GltfImage>,
+}
+impl GltfDocument {
+    /// Follow a texture reference through the document's textures to the ID in its image's URI
+    fn texture_id(&self, reference: &Option<GltfTextureReference>) -> Option<Uuid> {
+        let texture = self.textures.get(reference.as_ref()?.index)?;
+        let image = self.images.get(texture.source?)?;
+        Uuid::parse_str(image.uri.as_deref()?).ok()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GltfDocumentMaterial {
+    pbr_metallic_roughness: GltfPbr,
+    normal_texture: Option<GltfTextureReference>,
+    emissive_texture: Option<GltfTextureReference>,
+    emissive_factor: Option<[f32; 3]>,
+    alpha_mode: Option<String>,
+    alpha_cutoff: Option<f32>,
+    double_sided: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GltfPbr {
+    base_color_factor: Option<[f32; 4]>,
+    base_color_texture: Option<GltfTextureReference>,
+    metallic_factor: Option<f32>,
+    roughness_factor: Option<f32>,
+    metallic_roughness_texture: Option<GltfTextureReference>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GltfTextureReference {
+    index: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GltfTexture {
+    source: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GltfImage {
+    uri: Option<String>,
+}
@@ -1,5 +1,9 @@
 use super::mesh::Mesh;
-use crate::{errors::ParseError, utils::item_metadata::ItemMetadata};
+use crate::{
+    errors::ParseError,
+    utils::{item_metadata::ItemMetadata, object_types::WearableType},
+};
+use serde::{Deserialize, Serialize};
 use serde_llsd_benthic::{auto_from_str, converter::get};
 use std::{
     collections::HashMap,
@@ -31,11 +35,14 @@ impl Item {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 /// The data received from the ViewerAsset endpoint for inventory objects.
 pub struct ItemData {
     /// The version of the item. For some wearables it comes in as something like "LLWearable version 22"
     pub version: u32,
+    /// The kind of wearable the item is, for bodyparts and clothing
+    #[serde(default)]
+    pub wearable_type: WearableType,
     /// A map of visual parameters for character customization. The key corresponds to a bodypart
     /// on the model, such as nose, or height. The value corresponds to the slider value of the
     /// modification of that bodypart. This will be rewritten to contain an enum mapping each parameter to
@@ -45,6 +52,7 @@ pub struct ItemData {
     pub textures: HashMap<TextureSlot, Uuid>,
     /// The optional mesh attached to an object.
     /// Will not be populated until the mesh is retrieved.
+    #[serde(skip)]
     pub mesh: Option<Mesh>,
 }
 
@@ -87,8 +95,15 @@ impl ItemData {
         let llsd = auto_from_str(data).map_err(|e| ParseError::Message(e.to_string()))?;
 
         let map = llsd.as_map().ok_or(ParseError::LLSDError())?;
+        let wearable_type = data
+            .lines()
+            .find_map(|line| line.strip_prefix("type "))
+            .and_then(|value| value.trim().parse().ok())
+            .map(WearableType::from_bytes)
+            .unwrap_or_default();
         Ok(ItemData {
             version: get("version", map),
+            wearable_type,
             parameters: get("parameters", map),
             textures: get("textures", map),
             mesh: None,
@@ -105,48 +120,139 @@ pub struct Texture {
     pub id: Uuid,
 }
 
-/// Could be totally wrong
-#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+/// The texture slots of an avatar. Wearables fill the slots of their layers, and the avatar's
+/// bakes are stored in the baked slots.
+///
+/// Slots are indexed the same way in wearables and in the texture entry of the AvatarAppearance
+/// packet.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum TextureSlot {
-    /// Texture is applied to the head
-    Head = 0,
-    /// Texture is applied to the upper body
-    UpperBody = 1,
-    /// Texture is applied to the lower body
-    LowerBody = 2,
-    /// Texture is applied to the eyes
-    Eyes = 3,
-    /// Texture is applied to the hair
+    /// The skin of the head
+    HeadBodypaint = 0,
+    /// Shirt layer of the upper body
+    UpperShirt = 1,
+    /// Pants layer of the lower body
+    LowerPants = 2,
+    /// Iris of the eyes
+    EyesIris = 3,
+    /// System hair
     Hair = 4,
-    /// Texture is applied to the shirt
-    Shirt = 5,
-    /// Texture is applied to the pants
-    Pants = 6,
-    /// Texture is applied to the shoes
-    Shoes = 7,
-    /// Texture is applied to the socks
-    Socks = 8,
-    /// Texture is applied to the jacket
-    Jacket = 9,
-    /// Unknown
+    /// The skin of the upper body
+    UpperBodypaint = 5,
+    /// The skin of the lower body
+    LowerBodypaint = 6,
+    /// Shoes layer of the lower body
+    LowerShoes = 7,
+    /// Bake of the head
+    HeadBaked = 8,
+    /// Bake of the upper body
+    UpperBaked = 9,
+    /// Bake of the lower body
+    LowerBaked = 10,
+    /// Bake of the eyes
+    EyesBaked = 11,
+    /// Socks layer of the lower body
+    LowerSocks = 12,
+    /// Jacket layer of the upper body
+    UpperJacket = 13,
+    /// Jacket layer of the lower body
+    LowerJacket = 14,
+    /// Gloves layer of the upper body
+    UpperGloves = 15,
+    /// Undershirt layer of the upper body
+    UpperUndershirt = 16,
+    /// Underpants layer of the lower body
+    LowerUnderpants = 17,
+    /// System skirt
+    Skirt = 18,
+    /// Bake of the skirt
+    SkirtBaked = 19,
+    /// Bake of the hair
+    HairBaked = 20,
+    /// Alpha mask of the lower body
+    LowerAlpha = 21,
+    /// Alpha mask of the upper body
+    UpperAlpha = 22,
+    /// Alpha mask of the head
+    HeadAlpha = 23,
+    /// Alpha mask of the eyes
+    EyesAlpha = 24,
+    /// Alpha mask of the hair
+    HairAlpha = 25,
+    /// Tattoo layer of the head
+    HeadTattoo = 26,
+    /// Tattoo layer of the upper body
+    UpperTattoo = 27,
+    /// Tattoo layer of the lower body
+    LowerTattoo = 28,
+    /// Bake of the left arm, used by mesh bodies
+    LeftArmBaked = 40,
+    /// Bake of the left leg, used by mesh bodies
+    LeftLegBaked = 41,
+    /// First auxiliary bake, used by mesh bodies
+    Aux1Baked = 42,
+    /// Second auxiliary bake, used by mesh bodies
+    Aux2Baked = 43,
+    /// Third auxiliary bake, used by mesh bodies
+    Aux3Baked = 44,
+    /// A slot this viewer doesn't use
     Unknown = 99,
 }
 
+impl TextureSlot {
+    /// Convert from the index of the slot
+    pub fn from_index(index: u8) -> Self {
+        match index {
+            0 => TextureSlot::HeadBodypaint,
+            1 => TextureSlot::UpperShirt,
+            2 => TextureSlot::LowerPants,
+            3 => TextureSlot::EyesIris,
+            4 => TextureSlot::Hair,
+            5 => TextureSlot::UpperBodypaint,
+            6 => TextureSlot::LowerBodypaint,
+            7 => TextureSlot::LowerShoes,
+            8 => TextureSlot::HeadBaked,
+            9 => TextureSlot::UpperBaked,
+            10 => TextureSlot::LowerBaked,
+            11 => TextureSlot::EyesBaked,
+            12 => TextureSlot::LowerSocks,
+            13 => TextureSlot::UpperJacket,
+            14 => TextureSlot::LowerJacket,
+            15 => TextureSlot::UpperGloves,
+            16 => TextureSlot::UpperUndershirt,
+            17 => TextureSlot::LowerUnderpants,
+            18 => TextureSlot::Skirt,
+            19 => TextureSlot::SkirtBaked,
+            20 => TextureSlot::HairBaked,
+            21 => TextureSlot::LowerAlpha,
+            22 => TextureSlot::UpperAlpha,
+            23 => TextureSlot::HeadAlpha,
+            24 => TextureSlot::EyesAlpha,
+            25 => TextureSlot::HairAlpha,
+            26 => TextureSlot::HeadTattoo,
+            27 => TextureSlot::UpperTattoo,
+            28 => TextureSlot::LowerTattoo,
+            40 => TextureSlot::LeftArmBaked,
+            41 => TextureSlot::LeftLegBaked,
+            42 => TextureSlot::Aux1Baked,
+            43 => TextureSlot::Aux2Baked,
+            44 => TextureSlot::Aux3Baked,
+            _ => TextureSlot::Unknown,
+        }
+    }
+
+    /// The index of the slot
+    pub fn index(&self) -> u8 {
+        *self as u8
+    }
+}
+
 impl FromStr for TextureSlot {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "0" => Ok(TextureSlot::Head),
-            "1" => Ok(TextureSlot::UpperBody),
-            "2" => Ok(TextureSlot::LowerBody),
-            "3" => Ok(TextureSlot::Eyes),
-            "4" => Ok(TextureSlot::Hair),
-            "5" => Ok(TextureSlot::Shirt),
-            "6" => Ok(TextureSlot::Pants),
-            "7" => Ok(TextureSlot::Shoes),
-            "8" => Ok(TextureSlot::Socks),
-            "9" => Ok(TextureSlot::Jacket),
-            _ => Ok(TextureSlot::Unknown),
-        }
+        Ok(s.trim()
+            .parse()
+            .map(TextureSlot::from_index)
+            .unwrap_or(TextureSlot::Unknown))
     }
 }
@@ -4,7 +4,7 @@ use flate2::bufread::ZlibDecoder;
 use glam::{Mat4, Vec3};
 use serde::{Deserialize, Serialize};
 use serde_llsd_benthic::{de::binary, LLSDValue};
-use std::{collections::HashMap, io::Read, str::FromStr};
+use std::{collections::HashMap, io::Read, ops::Range, str::FromStr};
 
 /// This is the Zlib magic number. In the binary, this is where the start of the zipped data
 /// begins. This is followed by
@@ -22,16 +22,18 @@ pub struct Mesh {
     pub position: Option<Vec3>,
     /// Data for rendering the highest level of detail. This contains the most polygons.
     /// This is the default level of detail, and must be present.
-    pub high_level_of_detail: MeshGeometry,
+    ///
+    /// Each level of detail holds one submesh per face of the object, in face order.
+    pub high_level_of_detail: Vec<MeshGeometry>,
     /// Data for rendering a medium level of detail. This is a lower resolution version of the
     /// model.
-    pub medium_level_of_detail: Option<MeshGeometry>,
+    pub medium_level_of_detail: Option<Vec<MeshGeometry>>,
     /// Data for rendering a low level of detail. This is an even lower resolution version of the
     /// model.
-    pub low_level_of_detail: Option<MeshGeometry>,
+    pub low_level_of_detail: Option<Vec<MeshGeometry>>,
     /// Data for rendering the lowest level of detail. This gives only a vague impression of the
     /// shape.
-    pub lowest_level_of_detail: Option<MeshGeometry>,
+    pub lowest_level_of_detail: Option<Vec<MeshGeometry>>,
     /// This is a physics representation taht uses convex hull approximation for collision and
     /// physics simulation.
     pub physics_convex: Option<Vec<u8>>,
@@ -63,92 +65,115 @@ impl Mesh {
     /// number for decompressing each section
     /// Once decompressed, the data is encoded in the same binary llsd format that the header is.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let mut mesh = Mesh {
-            ..Default::default()
+        let header = MeshHeader::from_bytes(bytes)?;
+        let section = |section: &Option<MeshSection>| -> Result<Option<&[u8]>, ParseError> {
+            section
+                .as_ref()
+                .map(|section| {
+                    bytes
+                        .get(section.range(header.size))
+                        .ok_or_else(|| ParseError::MeshError("Section is out of bounds".into()))
+                })
+                .transpose()
         };
-        let data = binary::from_bytes(bytes)?;
-        // Get the first ocurrence of the zlib magic number, which denotes the beginning of the
-        // first data block.
-        let sentinel_location = bytes
-            .windows(2)
-            .position(|w| w == [ZLIB_MAGIC_NUMBER, ZLIB_DECODING_TYPE])
-            .expect("Zlib header not found");
-        let compressed_data = &bytes[sentinel_location..];
 
-        let map_data = data.into_map()?;
-
-        let get_offset_size = |key: &str| -> Result<Option<(usize, usize)>, ParseError> {
-            map_data.get(key).map(extract_offset_size).transpose()
+        let skin = section(&header.skin)?
+            .map(Skin::from_compressed)
+            .transpose()?;
+        let geometry = |lod: &Option<MeshSection>| {
+            section(lod)?
+                .map(|bytes| MeshGeometry::from_compressed(bytes, &skin))
+                .transpose()
         };
 
-        // Helper to get optional offset/size
-        let (high_lod_offset, high_lod_size) = extract_offset_size(
-            map_data
-                .get("high_lod")
+        Ok(Mesh {
+            position: None,
+            high_level_of_detail: geometry(&header.high_lod)?
                 .ok_or(ParseError::MissingField("high_lod".into()))?,
-        )?;
-
-        let (medium_lod_offset, medium_lod_size) = get_offset_size("medium_lod")?.unwrap_or((0, 0));
-        let (low_lod_offset, low_lod_size) = get_offset_size("low_lod")?.unwrap_or((0, 0));
-        let (lowest_lod_offset, lowest_lod_size) = get_offset_size("lowest_lod")?.unwrap_or((0, 0));
-        let (physics_convex_offset, physics_convex_size) =
-            get_offset_size("physics_convex")?.unwrap_or((0, 0));
-        let (skin_offset, skin_size) = get_offset_size("skin")?.unwrap_or((0, 0));
-
-        let high_level_of_detail =
-            decompress_slice(&compressed_data[high_lod_offset..high_lod_offset + high_lod_size])?;
-
-        let medium_level_of_detail = if medium_lod_size > 0 {
-            Some(decompress_slice(
-                &compressed_data[medium_lod_offset..medium_lod_offset + medium_lod_size],
-            )?)
-        } else {
-            None
-        };
+            medium_level_of_detail: geometry(&header.medium_lod)?,
+            low_level_of_detail: geometry(&header.low_lod)?,
+            lowest_level_of_detail: geometry(&header.lowest_lod)?,
+            physics_convex: section(&header.physics_convex)?
+                .map(decompress_slice)
+                .transpose()?,
+            skin,
+        })
+    }
+}
 
-        let low_level_of_detail = if low_lod_size > 0 {
-            Some(decompress_slice(
-                &compressed_data[low_lod_offset..low_lod_offset + low_lod_size],
-            )?)
-        } else {
-            None
-        };
-        let lowest_level_of_detail = if lowest_lod_size > 0 {
-            Some(decompress_slice(
-                &compressed_data[lowest_lod_offset..lowest_lod_offset + lowest_lod_size],
-            )?)
-        } else {
-            None
-        };
+/// The location of a compressed section of a mesh asset
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeshSection {
+    /// Offset of the section from the end of the header
+    pub offset: usize,
+    /// Size of the compressed section in bytes
+    pub size: usize,
+}
 
-        if physics_convex_size > 0 {
-            let physics_convex = decompress_slice(
-                &compressed_data
-                    [physics_convex_offset..physics_convex_offset + physics_convex_size],
-            )?;
-            mesh.physics_convex = Some(physics_convex);
-        }
+impl MeshSection {
+    /// The byte range of the section within the whole asset
+    pub fn range(&self, header_size: usize) -> Range<usize> {
+        header_size + self.offset..header_size + self.offset + self.size
+    }
+}
 
-        if skin_size > 0 {
-            let skin = decompress_slice(&compressed_data[skin_offset..skin_offset + skin_size])?;
-            mesh.skin = Some(Skin::from_llsd(binary::from_bytes(&skin)?)?);
-        }
+/// The uncompressed header at the start of a mesh asset, which locates each of its sections.
+///
+/// The header is small, so it can be parsed from the first few kilobytes of the asset. This
+/// allows the sections to be fetched one at a time, starting with the cheapest level of detail.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MeshHeader {
+    /// Size of the header in bytes. Section offsets start counting from here.
+    pub size: usize,
+    /// The highest level of detail. This must be present.
+    pub high_lod: Option<MeshSection>,
+    /// The medium level of detail
+    pub medium_lod: Option<MeshSection>,
+    /// The low level of detail
+    pub low_lod: Option<MeshSection>,
+    /// The lowest level of detail
+    pub lowest_lod: Option<MeshSection>,
+    /// The physics convex hull
+    pub physics_convex: Option<MeshSection>,
+    /// The skinning information
+    pub skin: Option<MeshSection>,
+}
 
-        mesh.high_level_of_detail =
-            MeshGeometry::from_llsd(binary::from_bytes(&high_level_of_detail)?, &mesh.skin)?;
-        mesh.medium_level_of_detail = medium_level_of_detail
-            .as_ref()
-            .map(|bytes| MeshGeometry::from_llsd(binary::from_bytes(bytes).unwrap(), &mesh.skin))
-            .transpose()?;
-        mesh.low_level_of_detail = low_level_of_detail
-            .as_ref()
-            .map(|bytes| MeshGeometry::from_llsd(binary::from_bytes(bytes).unwrap(), &mesh.skin))
-            .transpose()?;
-        mesh.lowest_level_of_detail = lowest_level_of_detail
-            .as_ref()
-            .map(|bytes| MeshGeometry::from_llsd(binary::from_bytes(bytes).unwrap(), &mesh.skin))
-            .transpose()?;
-        Ok(mesh)
+impl MeshHeader {
+    /// Parse the header from the start of a mesh asset. The bytes only need to reach the start of
+    /// the first compressed section.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        // Get the first ocurrence of the zlib magic number, which denotes the beginning of the
+        // first data block.
+        let size = bytes
+            .windows(2)
+            .position(|w| w == [ZLIB_MAGIC_NUMBER, ZLIB_DECODING_TYPE])
+            .ok_or_else(|| ParseError::MeshError("Zlib header not found".into()))?;
+        let map_data = binary::from_bytes(bytes)?.into_map()?;
+
+        // sections without a size are treated as missing
+        let get_section = |key: &str| -> Result<Option<MeshSection>, ParseError> {
+            Ok(map_data
+                .get(key)
+                .map(extract_offset_size)
+                .transpose()?
+                .filter(|(_, size)| *size > 0)
+                .map(|(offset, size)| MeshSection { offset, size }))
+        };
+
+        let header = MeshHeader {
+            size,
+            high_lod: get_section("high_lod")?,
+            medium_lod: get_section("medium_lod")?,
+            low_lod: get_section("low_lod")?,
+            lowest_lod: get_section("lowest_lod")?,
+            physics_convex: get_section("physics_convex")?,
+            skin: get_section("skin")?,
+        };
+        if header.high_lod.is_none() {
+            return Err(ParseError::MissingField("high_lod".into()));
+        }
+        Ok(header)
     }
 }
 
@@ -158,7 +183,7 @@ impl Mesh {
 /// This includes all of the information required for creating and displaying the mesh.
 pub struct MeshGeometry {
     /// Boolean flag to show that there is no mesh geometry.
-    /// Unused and legacy code .
+    /// Set for faces that have no triangles in this level of detail.
     pub no_geometry: bool,
     /// Used to decode compressed triangle positions
     pub position_domain: Option<PositionDomain>,
@@ -182,16 +207,41 @@ pub struct MeshGeometry {
 }
 
 impl MeshGeometry {
-    fn from_llsd(data: LLSDValue, skin: &Option<Skin>) -> Result<Self, ParseError> {
+    /// Decompress and parse a level of detail section of a mesh asset
+    pub fn from_compressed(bytes: &[u8], skin: &Option<Skin>) -> Result<Vec<Self>, ParseError> {
+        Self::from_llsd(binary::from_bytes(&decompress_slice(bytes)?)?, skin)
+    }
+
+    /// Parse every submesh of a level of detail.
+    ///
+    /// Faces without any triangles are sent as a map with only a NoGeometry flag. These are kept
+    /// as empty submeshes so the submesh index always matches the face index.
+    fn from_llsd(data: LLSDValue, skin: &Option<Skin>) -> Result<Vec<Self>, ParseError> {
         let array = data
             .as_array()
             .ok_or_else(|| ParseError::MissingField("Expected top level array".into()))?;
 
-        let map = array
-            .first()
-            .and_then(LLSDValue::as_map)
-            .ok_or_else(|| ParseError::MissingField("Expected map inside array".into()))?;
+        array
+            .iter()
+            .map(|submesh| {
+                let map = submesh
+                    .as_map()
+                    .ok_or_else(|| ParseError::MissingField("Expected map inside array".into()))?;
+                if let Some(LLSDValue::Boolean(true)) = map.get("NoGeometry") {
+                    return Ok(MeshGeometry {
+                        no_geometry: true,
+                        ..Default::default()
+                    });
+                }
+                Self::from_llsd_submesh(map, skin)
+            })
+            .collect()
+    }
 
+    fn from_llsd_submesh(
+        map: &HashMap<String, LLSDValue>,
+        skin: &Option<Skin>,
+    ) -> Result<Self, ParseError> {
         let position_domain = map
             .get("PositionDomain")
             .and_then(LLSDValue::as_map)
@@ -462,6 +512,11 @@ pub struct Skin {
     pub bind_shape_matrix: Mat4,
 }
 impl Skin {
+    /// Decompress and parse the skin section of a mesh asset
+    pub fn from_compressed(bytes: &[u8]) -> Result<Self, ParseError> {
+        Self::from_llsd(binary::from_bytes(&decompress_slice(bytes)?)?)
+    }
+
     fn from_llsd(data: LLSDValue) -> Result<Self, ParseError> {
         let map = data
             .as_map()
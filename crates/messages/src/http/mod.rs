@@ -32,3 +32,9 @@ pub mod folder_request;
 
 /// Contains Environmental Enhancement Project structs and parsing
 pub mod environment_data;
+
+/// Handles PBR material assets, and the overrides objects make to them
+pub mod gltf_material;
+
+/// Handles the legacy normal and specular materials of the RenderMaterials capability
+pub mod render_materials;
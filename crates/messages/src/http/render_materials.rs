@@ -0,0 +1,138 @@
+use crate::errors::ParseError;
+use flate2::{Compression, bufread::ZlibDecoder, write::ZlibEncoder};
+use rgb::Rgba;
+use serde::{Deserialize, Serialize};
+use serde_llsd_benthic::{LLSDValue, de::binary, from_str, ser::xml};
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+use uuid::Uuid;
+
+/// A legacy material, which adds a normal map and specular highlights to a face.
+///
+/// Faces reference these by the material ID of their texture entry, and they are retrieved from
+/// the RenderMaterials capability endpoint. The normal and specular maps have their own repeats
+/// and offsets, which are not kept, so the maps are placed like the face's texture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LegacyMaterial {
+    /// Tangent space normal map
+    pub normal_map: Uuid,
+    /// Texture with the color of the specular highlights, and the glossiness in its alpha
+    pub specular_map: Uuid,
+    /// Color of the specular highlights, with the environment intensity in its alpha
+    pub specular_color: Rgba<u8>,
+    /// How sharp the specular highlights are, from 0 to 255
+    pub specular_exponent: u8,
+    /// How strongly the environment is reflected, from 0 to 255
+    pub environment_intensity: u8,
+    /// The alpha below which pixels are not drawn, when the diffuse alpha mode is masked
+    pub alpha_mask_cutoff: u8,
+    /// How the alpha of the face's texture is used. 0 is none, 1 is blend, 2 is mask and 3 is
+    /// emissive.
+    pub diffuse_alpha_mode: u8,
+}
+impl Default for LegacyMaterial {
+    /// The viewer's defaults for a new material
+    fn default() -> Self {
+        LegacyMaterial {
+            normal_map: Uuid::nil(),
+            specular_map: Uuid::nil(),
+            specular_color: Rgba::new(255, 255, 255, 255),
+            specular_exponent: 51,
+            environment_intensity: 0,
+            alpha_mask_cutoff: 0,
+            diffuse_alpha_mode: 1,
+        }
+    }
+}
+
+impl LegacyMaterial {
+    /// Create the body of a POST request to the RenderMaterials endpoint, asking for the
+    /// materials with the given IDs.
+    ///
+    /// The IDs are sent as binary LLSD, compressed and wrapped in an XML LLSD map.
+    pub fn request_body(material_ids: &[Uuid]) -> Result<String, ParseError> {
+        // binary LLSD array of 16 byte binary values
+        let mut ids = Vec::with_capacity(material_ids.len() * 21 + 5);
+        ids.push(b'[');
+        ids.extend((material_ids.len() as u32).to_be_bytes());
+        for material_id in material_ids {
+            ids.push(b'b');
+            ids.extend(16u32.to_be_bytes());
+            ids.extend(material_id.as_bytes());
+        }
+        ids.push(b']');
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&ids)?;
+        let zipped = encoder.finish()?;
+        let body = LLSDValue::Map(HashMap::from([(
+            "Zipped".to_string(),
+            LLSDValue::Binary(zipped),
+        )]));
+        Ok(xml::to_string(&body, false)?)
+    }
+
+    /// Parse the response of the RenderMaterials endpoint into materials by ID
+    pub fn response_from_llsd(xml_bytes: &[u8]) -> Result<HashMap<Uuid, Self>, ParseError> {
+        let response = from_str(&String::from_utf8_lossy(xml_bytes))?;
+        let zipped = match response.as_map().and_then(|map| map.get("Zipped")) {
+            Some(LLSDValue::Binary(zipped)) => zipped,
+            _ => return Err(ParseError::MissingField("Zipped".into())),
+        };
+        let mut unzipped = Vec::new();
+        ZlibDecoder::new(zipped.as_slice()).read_to_end(&mut unzipped)?;
+        let entries = binary::from_bytes(&unzipped)?;
+
+        let mut materials = HashMap::new();
+        for entry in entries.as_array().into_iter().flatten() {
+            let Some(entry) = entry.as_map() else {
+                continue;
+            };
+            let id = match entry.get("ID") {
+                Some(LLSDValue::Binary(id)) => Uuid::from_slice(id)?,
+                Some(LLSDValue::UUID(id)) => *id,
+                _ => return Err(ParseError::MissingField("ID".into())),
+            };
+            let material = entry
+                .get("Material")
+                .and_then(|material| material.as_map())
+                .ok_or_else(|| ParseError::MissingField("Material".into()))?;
+            materials.insert(id, Self::from_llsd(material));
+        }
+        Ok(materials)
+    }
+
+    /// Read a material from its LLSD map. Missing values keep their defaults.
+    fn from_llsd(map: &HashMap<String, LLSDValue>) -> Self {
+        let defaults = LegacyMaterial::default();
+        let uuid = |key: &str| map.get(key).and_then(|v| v.as_uuid()).copied();
+        let byte = |key: &str| {
+            map.get(key)
+                .and_then(|v| v.as_integer())
+                .map(|v| (*v).clamp(0, u8::MAX as i32) as u8)
+        };
+        let specular_color = map
+            .get("SpecColor")
+            .and_then(|v| v.as_array())
+            .map(|channels| {
+                let channel = |index: usize| {
+                    channels
+                        .get(index)
+                        .and_then(|v| v.as_integer())
+                        .map_or(u8::MAX, |v| (*v).clamp(0, u8::MAX as i32) as u8)
+                };
+                Rgba::new(channel(0), channel(1), channel(2), channel(3))
+            });
+        LegacyMaterial {
+            normal_map: uuid("NormMap").unwrap_or(defaults.normal_map),
+            specular_map: uuid("SpecMap").unwrap_or(defaults.specular_map),
+            specular_color: specular_color.unwrap_or(defaults.specular_color),
+            specular_exponent: byte("SpecExp").unwrap_or(defaults.specular_exponent),
+            environment_intensity: byte("EnvIntensity").unwrap_or(defaults.environment_intensity),
+            alpha_mask_cutoff: byte("AlphaMaskCutoff").unwrap_or(defaults.alpha_mask_cutoff),
+            diffuse_alpha_mode: byte("DiffuseAlphaMode").unwrap_or(defaults.diffuse_alpha_mode),
+        }
+    }
+}
@@ -765,7 +765,7 @@ impl PhysicsShapeType {
 
 /// Used for legacy compatability with SculptTextures.
 /// describes the different basic shapes the sculpttexture can deform.
-#[derive(Debug, Copy, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SculptType {
     /// The sculpt texture deforms a sphere
     Sphere,
@@ -806,4 +806,15 @@ impl SculptType {
             _ => Self::Unknown,
         }
     }
+    /// Convert a SculptType enum to its u8 byte
+    pub fn to_bytes(&self) -> u8 {
+        match self {
+            Self::Sphere => 1,
+            Self::Torus => 2,
+            Self::Plane => 3,
+            Self::Cylinder => 4,
+            Self::Mesh => 5,
+            Self::Unknown => 0,
+        }
+    }
 }
@@ -72,7 +72,7 @@ impl PacketData for AgentWearablesUpdate {
         let wearable_count = cursor.read_u8()?;
         let mut wearables = Vec::new();
 
-        for _ in (0..wearable_count).collect::<std::vec::Vec<u8>>() {
+        for _ in 0..wearable_count {
             let mut item_id_bytes = [0u8; 16];
             cursor.read_exact(&mut item_id_bytes)?;
             let item_id = Uuid::from_bytes(item_id_bytes);
@@ -102,8 +102,9 @@ impl PacketData for AgentWearablesUpdate {
         bytes.extend_from_slice(self.agent_id.as_bytes());
         bytes.extend_from_slice(self.session_id.as_bytes());
         bytes.extend_from_slice(&self.serial_number.to_le_bytes());
-        bytes.push(self.wearables.len() as u8);
-        for wearable in &self.wearables {
+        let wearables = &self.wearables[..self.wearables.len().min(u8::MAX as usize)];
+        bytes.push(wearables.len() as u8);
+        for wearable in wearables {
             bytes.extend_from_slice(wearable.item_id.as_bytes());
             bytes.extend_from_slice(wearable.asset_id.as_bytes());
             bytes.push(wearable.wearable_type.to_bytes());
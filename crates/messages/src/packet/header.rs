@@ -1,3 +1,4 @@
+use super::packet_protocol::zero_decode;
 use byteorder::BigEndian;
 use byteorder::ReadBytesExt;
 use core::fmt;
@@ -12,6 +13,15 @@ pub const MSG_RESENT: u8 = 0x20;
 pub const MSG_ZEROCODED: u8 = 0x80;
 /// flag if the packet has acks appended
 pub const MSG_APPENDED_ACKS: u8 = 0x10;
+/// the maximum number of acks that can be appended to a single packet.
+/// The ack count is stored in a single byte.
+pub const MAX_APPENDED_ACKS: usize = u8::MAX as usize;
+/// the size of the flags, sequence number and extra byte at the start of every packet.
+/// Zerocoding starts after these bytes, so the message number can be zerocoded.
+pub const HEADER_PREFIX_LEN: usize = 6;
+/// the largest number of bytes the message number can take up after zerocoding.
+/// The four byte low frequency number can contain at most two zeros, each expanding to two bytes.
+const MAX_ENCODED_MESSAGE_NUMBER_LEN: usize = 6;
 
 #[derive(Debug, Clone, Default)]
 /// The header for each packet coming from the server
@@ -34,13 +44,21 @@ pub struct Header {
     pub id: u16,
     /// packet frequency, used for grouping packets based on how often they are received
     pub frequency: PacketFrequency,
-    /// list of acks sent with the header
+    /// list of acks appended to the end of the packet.
+    ///
+    /// When parsing, this is filled from the trailing ack block if the appended acks flag is set.
+    /// When serializing, a non-empty list sets the appended acks flag, and the acks are written
+    /// to the end of the packet by [`Packet::to_bytes`](super::packet_protocol::Packet::to_bytes).
     pub ack_list: Option<Vec<u32>>,
-    /// the size of the packet
+    /// the size of the header, including the message number.
+    /// For zerocoded packets this is the offset of the body in the zero-decoded packet.
     pub size: Option<usize>,
 }
 impl Header {
     /// parse the header from incoming packet bytes. Can fail and return an io error.
+    ///
+    /// If the packet is zerocoded, the message number is zero-decoded before it is read, as
+    /// zerocoding covers everything after the first six bytes.
     pub fn try_from_bytes(bytes: &[u8]) -> Result<Header, std::io::Error> {
         let mut cursor = Cursor::new(bytes);
 
@@ -55,6 +73,25 @@ impl Header {
         // extra byte
         let _extra_info = cursor.read_u8()?;
 
+        // appended acks are stored at the very end of the packet, after the body. The last byte
+        // is the number of acks, preceded by that many big-endian u32 sequence numbers.
+        let ack_list = if appended_acks {
+            Some(Self::read_appended_acks(bytes)?)
+        } else {
+            None
+        };
+
+        // the appended acks are never zerocoded, so they are excluded before decoding.
+        let data_end = bytes.len() - ack_list.as_ref().map_or(0, |acks| 1 + acks.len() * 4);
+        let data = &bytes[HEADER_PREFIX_LEN.min(data_end)..data_end];
+        let message_number = if zerocoded {
+            // only the start of the data is needed to read the message number
+            zero_decode(&data[..data.len().min(MAX_ENCODED_MESSAGE_NUMBER_LEN)])
+        } else {
+            data.to_vec()
+        };
+        let mut cursor = Cursor::new(message_number.as_slice());
+
         // this handles the variable lengths of the frequency and ID.
         let (frequency, id) = match cursor.read_u8()? {
             // if the first byte is 255, it could be fixed, low or medium.
@@ -64,15 +101,9 @@ impl Header {
                         let fixed = cursor.read_u8()?;
                         (PacketFrequency::Fixed, fixed as u16)
                     }
-                    low => {
-                        let mut high = cursor.read_u8()?;
-                        // hardcoded fix for zerocoded headers
-                        if zerocoded && low == 0 && high == 1 {
-                            high = cursor.read_u8()?;
-                        }
-
-                        let combined: u16 = u16::from_le_bytes([high, low]);
-                        (PacketFrequency::Low, combined)
+                    high => {
+                        let low = cursor.read_u8()?;
+                        (PacketFrequency::Low, u16::from_be_bytes([high, low]))
                     }
                 },
                 medium => (PacketFrequency::Medium, medium as u16),
@@ -90,11 +121,56 @@ impl Header {
             sequence_number,
             frequency,
             id,
-            ack_list: None,
-            size: Some(cursor.position() as usize),
+            ack_list,
+            size: Some(HEADER_PREFIX_LEN + cursor.position() as usize),
         };
         Ok(header)
     }
+
+    /// Read the block of acks appended to the end of a packet.
+    ///
+    /// The final byte of the packet is the ack count, and the 4 * count bytes before it are the
+    /// sequence numbers being acked, stored big-endian.
+    fn read_appended_acks(bytes: &[u8]) -> Result<Vec<u32>, std::io::Error> {
+        let count = match bytes.last() {
+            Some(count) => *count as usize,
+            None => return Ok(Vec::new()),
+        };
+        // the flags, sequence number and extra byte must come before the acks
+        let ack_block_len = 1 + count * 4;
+        if ack_block_len + 6 > bytes.len() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "packet of {} bytes is too short for {} appended acks",
+                    bytes.len(),
+                    count
+                ),
+            ));
+        }
+
+        let start = bytes.len() - ack_block_len;
+        let mut cursor = Cursor::new(&bytes[start..bytes.len() - 1]);
+        let mut acks = Vec::with_capacity(count);
+        for _ in 0..count {
+            acks.push(cursor.read_u32::<BigEndian>()?);
+        }
+        Ok(acks)
+    }
+
+    /// The number of bytes the appended ack block takes up at the end of the packet.
+    /// This is zero if the packet has no appended acks.
+    pub fn appended_acks_len(&self) -> usize {
+        match &self.ack_list {
+            Some(acks) if self.appended_acks => 1 + acks.len() * 4,
+            _ => 0,
+        }
+    }
+
+    /// Returns true if the header has acks that should be appended to the packet
+    pub fn has_appended_acks(&self) -> bool {
+        self.ack_list.as_ref().is_some_and(|acks| !acks.is_empty())
+    }
     /// convert a header to bytes to send as a packet
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(10);
@@ -102,7 +178,7 @@ impl Header {
         // Add the flags byte
         // TODO fix this
         let mut flags = 0;
-        if self.appended_acks {
+        if self.has_appended_acks() {
             flags |= MSG_APPENDED_ACKS;
         }
         if self.reliable {
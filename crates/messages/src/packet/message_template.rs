@@ -0,0 +1,277 @@
+use super::header::{Header, PacketFrequency};
+use super::packet_protocol::{Packet, PacketData};
+use super::packet_types::PacketType;
+use crate::errors::ParseError;
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use glam::{DVec3, Quat, Vec3, Vec4};
+use std::io::{Cursor, Read};
+use std::net::Ipv4Addr;
+use uuid::Uuid;
+
+/// Information about a message, read from its definition in message_template.msg
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MessageInfo {
+    /// name of the message in the template
+    pub name: &'static str,
+    /// the message number. Fixed messages are identified by the last byte of their number.
+    pub id: u16,
+    /// frequency of the message, which determines the size of the message number
+    pub frequency: PacketFrequency,
+    /// if the message is only accepted from trusted sources, such as other simulators
+    pub trusted: bool,
+    /// if the message should be zerocoded when sent
+    pub zerocoded: bool,
+    /// if the message is deprecated, or no longer sent over UDP
+    pub deprecated: bool,
+}
+
+impl MessageInfo {
+    /// Find the template definition of a message by its ID and frequency
+    pub fn from_id(id: u16, frequency: PacketFrequency) -> Option<&'static MessageInfo> {
+        MESSAGES
+            .iter()
+            .find(|info| info.id == id && info.frequency == frequency)
+    }
+    /// Find the template definition of a message by its name
+    pub fn from_name(name: &str) -> Option<&'static MessageInfo> {
+        MESSAGES.iter().find(|info| info.name == name)
+    }
+    /// Create a header for sending the message.
+    /// Messages are sent reliably unless they are high frequency.
+    pub fn header(&self) -> Header {
+        Header {
+            id: self.id,
+            frequency: self.frequency,
+            zerocoded: self.zerocoded,
+            reliable: self.frequency != PacketFrequency::High,
+            ..Default::default()
+        }
+    }
+}
+
+impl Packet {
+    /// Create a new packet from any message generated from the template.
+    /// The header is filled in from the message's template definition.
+    pub fn new_template(message: TemplateMessage) -> Self {
+        Packet {
+            header: message.info().header(),
+            body: PacketType::Template(Box::new(message)),
+        }
+    }
+}
+
+/// Reading and writing the fields and blocks of template messages.
+/// Multi-byte values are little-endian unless the template says otherwise.
+pub trait TemplateField: Sized {
+    /// read the value from the cursor
+    fn read(cursor: &mut Cursor<&[u8]>) -> Result<Self, ParseError>;
+    /// write the value to the end of the bytes
+    fn write(&self, bytes: &mut Vec<u8>);
+}
+
+macro_rules! impl_template_number {
+    ( $( $ty:ty ),* $(,)? ) => {
+        $(
+            impl TemplateField for $ty {
+                fn read(cursor: &mut Cursor<&[u8]>) -> Result<Self, ParseError> {
+                    let mut buf = [0u8; size_of::<$ty>()];
+                    cursor.read_exact(&mut buf)?;
+                    Ok(<$ty>::from_le_bytes(buf))
+                }
+                fn write(&self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+impl_template_number!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl TemplateField for bool {
+    fn read(cursor: &mut Cursor<&[u8]>) -> Result<Self, ParseError> {
+        Ok(cursor.read_u8()? != 0)
+    }
+    fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.push(*self as u8);
+    }
+}
+
+impl TemplateField for Uuid {
+    fn read(cursor: &mut Cursor<&[u8]>) -> Result<Self, ParseError> {
+        let mut buf = [0u8; 16];
+        cursor.read_exact(&mut buf)?;
+        Ok(Uuid::from_bytes(buf))
+    }
+    fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl TemplateField for Vec3 {
+    fn read(cursor: &mut Cursor<&[u8]>) -> Result<Self, ParseError> {
+        Ok(Vec3::new(
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+        ))
+    }
+    fn write(&self, bytes: &mut Vec<u8>) {
+        for value in self.to_array() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+impl TemplateField for DVec3 {
+    fn read(cursor: &mut Cursor<&[u8]>) -> Result<Self, ParseError> {
+        Ok(DVec3::new(
+            cursor.read_f64::<LittleEndian>()?,
+            cursor.read_f64::<LittleEndian>()?,
+            cursor.read_f64::<LittleEndian>()?,
+        ))
+    }
+    fn write(&self, bytes: &mut Vec<u8>) {
+        for value in self.to_array() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+impl TemplateField for Vec4 {
+    fn read(cursor: &mut Cursor<&[u8]>) -> Result<Self, ParseError> {
+        Ok(Vec4::new(
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+        ))
+    }
+    fn write(&self, bytes: &mut Vec<u8>) {
+        for value in self.to_array() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+/// Quaternions are sent as x, y and z only. W is recomputed from the other three, which means the
+/// rotation must be normalized with a positive w before it is sent.
+impl TemplateField for Quat {
+    fn read(cursor: &mut Cursor<&[u8]>) -> Result<Self, ParseError> {
+        let x = cursor.read_f32::<LittleEndian>()?;
+        let y = cursor.read_f32::<LittleEndian>()?;
+        let z = cursor.read_f32::<LittleEndian>()?;
+        let w = (1.0 - (x * x + y * y + z * z)).max(0.0).sqrt();
+        Ok(Quat::from_xyzw(x, y, z, w))
+    }
+    fn write(&self, bytes: &mut Vec<u8>) {
+        let mut rotation = self.normalize();
+        if rotation.w < 0.0 {
+            rotation = -rotation;
+        }
+        Vec3::new(rotation.x, rotation.y, rotation.z).write(bytes);
+    }
+}
+
+impl TemplateField for Ipv4Addr {
+    fn read(cursor: &mut Cursor<&[u8]>) -> Result<Self, ParseError> {
+        let mut buf = [0u8; 4];
+        cursor.read_exact(&mut buf)?;
+        Ok(Ipv4Addr::from(buf))
+    }
+    fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.octets());
+    }
+}
+
+/// Ports are the only big-endian values in the template
+pub fn read_port(cursor: &mut Cursor<&[u8]>) -> Result<u16, ParseError> {
+    Ok(cursor.read_u16::<BigEndian>()?)
+}
+/// Write a big-endian port
+pub fn write_port(bytes: &mut Vec<u8>, port: u16) {
+    bytes.extend_from_slice(&port.to_be_bytes());
+}
+
+/// Read a fixed size field
+pub fn read_fixed(cursor: &mut Cursor<&[u8]>, size: usize) -> Result<Vec<u8>, ParseError> {
+    let mut buf = vec![0u8; size];
+    cursor.read_exact(&mut buf)?;
+    Ok(buf)
+}
+/// Write a fixed size field. Values that are too short are padded with zeros, and values that are
+/// too long are truncated.
+pub fn write_fixed(bytes: &mut Vec<u8>, value: &[u8], size: usize) {
+    let len = value.len().min(size);
+    bytes.extend_from_slice(&value[..len]);
+    bytes.resize(bytes.len() + size - len, 0);
+}
+
+/// Read a variable size field, prefixed by a one or two byte length
+pub fn read_variable(cursor: &mut Cursor<&[u8]>, prefix: usize) -> Result<Vec<u8>, ParseError> {
+    let len = match prefix {
+        1 => cursor.read_u8()? as usize,
+        _ => cursor.read_u16::<LittleEndian>()? as usize,
+    };
+    read_fixed(cursor, len)
+}
+/// Write a variable size field, prefixed by a one or two byte length.
+/// Values longer than the prefix can describe are truncated.
+pub fn write_variable(bytes: &mut Vec<u8>, value: &[u8], prefix: usize) {
+    match prefix {
+        1 => {
+            let len = value.len().min(u8::MAX as usize);
+            bytes.push(len as u8);
+            bytes.extend_from_slice(&value[..len]);
+        }
+        _ => {
+            let len = value.len().min(u16::MAX as usize);
+            bytes.extend_from_slice(&(len as u16).to_le_bytes());
+            bytes.extend_from_slice(&value[..len]);
+        }
+    }
+}
+
+/// Read a block that is repeated a fixed number of times
+pub fn read_multiple_block<T: TemplateField>(
+    cursor: &mut Cursor<&[u8]>,
+    count: usize,
+) -> Result<Vec<T>, ParseError> {
+    (0..count).map(|_| T::read(cursor)).collect()
+}
+/// Write a block that is repeated a fixed number of times.
+/// Missing blocks are filled with defaults, and extra blocks are dropped.
+pub fn write_multiple_block<T: TemplateField + Default>(
+    bytes: &mut Vec<u8>,
+    blocks: &[T],
+    count: usize,
+) {
+    for i in 0..count {
+        match blocks.get(i) {
+            Some(block) => block.write(bytes),
+            None => T::default().write(bytes),
+        }
+    }
+}
+
+/// Read a block prefixed by a one byte count.
+/// Simulators leave trailing variable blocks out entirely when they are empty, so running out of
+/// data is read as zero blocks.
+pub fn read_variable_block<T: TemplateField>(
+    cursor: &mut Cursor<&[u8]>,
+) -> Result<Vec<T>, ParseError> {
+    if cursor.position() as usize >= cursor.get_ref().len() {
+        return Ok(Vec::new());
+    }
+    let count = cursor.read_u8()? as usize;
+    (0..count).map(|_| T::read(cursor)).collect()
+}
+/// Write a block prefixed by a one byte count. Only the first 255 blocks are written.
+pub fn write_variable_block<T: TemplateField>(bytes: &mut Vec<u8>, blocks: &[T]) {
+    let count = blocks.len().min(u8::MAX as usize);
+    bytes.push(count as u8);
+    for block in &blocks[..count] {
+        block.write(bytes);
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/message_template.rs"));
@@ -20,6 +20,13 @@ pub mod header;
 /// Contains information about the packet layout used for serializing and deserializing packets.
 pub mod packet_protocol;
 
+/// # Message Template
+/// <https://wiki.secondlife.com/wiki/Message_Layout>
+///
+/// Packet definitions generated at build time from message_template.msg, along with the ID,
+/// frequency, trust and encoding of every message in the template.
+pub mod message_template;
+
 /// Contains structs and enums used for determining types of packets based on header values and
 /// assigning those valeus to rust data types.
 pub mod packet_types;
@@ -1,4 +1,4 @@
-use super::header::Header;
+use super::header::{HEADER_PREFIX_LEN, Header, MAX_APPENDED_ACKS};
 use super::packet_types::PacketType;
 use crate::errors::ParseError;
 use byteorder::ReadBytesExt;
@@ -30,46 +30,67 @@ pub trait PacketData: std::fmt::Debug + Send + Sync + 'static + Any {
 impl Packet {
     /// Read bytes and convert it to a packet.
     /// First parse the packet's header, and then parse the packet's body based on the ID parsed
-    /// from the header.
+    /// from the header. Appended acks are stripped from the end of the packet before the body is
+    /// parsed, and are stored in the header's ack_list.
+    /// Zerocoding covers the message number as well as the body, so a run of zeros can start in
+    /// the message number and end in the body. Everything after the first six bytes is decoded
+    /// together before the body is split off.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
         let header = Header::try_from_bytes(bytes)?;
-        // if the packet has a body, add the body to the packet
-        let body = if header.size.unwrap_or(0) < bytes.len() {
-            &bytes[header.size.unwrap_or(0)..]
+        let data_end = bytes.len() - header.appended_acks_len();
+        let data = &bytes[HEADER_PREFIX_LEN.min(data_end)..data_end];
+        let data = if header.zerocoded {
+            zero_decode(data)
         } else {
-            &[]
+            data.to_vec() // Convert slice to Vec<u8>
         };
-        let body_bytes = if header.zerocoded {
-            zero_decode(body)
+        // if the packet has a body, add the body to the packet
+        let body_start = header.size.unwrap_or(HEADER_PREFIX_LEN) - HEADER_PREFIX_LEN;
+        let body_bytes = if body_start < data.len() {
+            &data[body_start..]
         } else {
-            body.to_vec() // Convert slice to Vec<u8>
+            &[]
         };
 
-        let body = PacketType::from_id(header.id, header.frequency, body_bytes.as_slice())?;
+        let body = PacketType::from_id(header.id, header.frequency, body_bytes)?;
 
         Ok(Self { header, body })
     }
 
     /// convert a packet to bytes for sending.
     /// simply call the header and body's to_bytes() functions. If it is zerocoded, don't zerocode
-    /// the first six bytes of the header. for whatever reason.
+    /// the flags, sequence number and extra byte at the start of the header. The message number
+    /// and body are zerocoded together.
+    /// If the header contains an ack list, the acks are appended after the (possibly zerocoded)
+    /// body, followed by the ack count. Only the first [`MAX_APPENDED_ACKS`] acks are written.
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
         bytes.extend(self.header.to_bytes());
         bytes.extend(self.body.to_bytes());
         if self.header.zerocoded {
-            let mut zeroed = zero_encode(&bytes[6..]);
-            let mut final_bytes = Vec::with_capacity(6 + zeroed.len());
-            final_bytes.extend_from_slice(&bytes[0..6]);
+            let mut zeroed = zero_encode(&bytes[HEADER_PREFIX_LEN..]);
+            let mut final_bytes = Vec::with_capacity(HEADER_PREFIX_LEN + zeroed.len());
+            final_bytes.extend_from_slice(&bytes[0..HEADER_PREFIX_LEN]);
             final_bytes.append(&mut zeroed);
-            return final_bytes;
+            bytes = final_bytes;
+        }
+        if let Some(acks) = &self.header.ack_list
+            && !acks.is_empty()
+        {
+            let acks = &acks[..acks.len().min(MAX_APPENDED_ACKS)];
+            for ack in acks {
+                bytes.extend_from_slice(&ack.to_be_bytes());
+            }
+            bytes.push(acks.len() as u8);
         }
         bytes
     }
 }
 
-/// decompress zero encoded packets for parsing
-fn zero_decode(bytes: &[u8]) -> Vec<u8> {
+/// decompress zero encoded packets for parsing.
+/// Each 0x00 is followed by the number of zeros it expands to. A 0x00 at the very end of the
+/// data, with no count after it, is treated as a single zero.
+pub fn zero_decode(bytes: &[u8]) -> Vec<u8> {
     let mut cursor = Cursor::new(bytes);
     let mut dest = Vec::new();
 
@@ -87,21 +108,23 @@ fn zero_decode(bytes: &[u8]) -> Vec<u8> {
     dest
 }
 
-fn zero_encode(src: &[u8]) -> Vec<u8> {
+/// compress runs of zeros for sending zerocoded packets.
+/// Each run of zeros is written as 0x00 followed by the length of the run. The length is a single
+/// byte, so runs longer than 255 zeros are split into multiple 0x00 0xff pairs, followed by the
+/// remainder.
+pub fn zero_encode(src: &[u8]) -> Vec<u8> {
     let mut dest = Vec::with_capacity(src.len());
     let mut zerocount: u8 = 0;
-    let mut i = 0;
-
-    while i < src.len() {
-        if src[i] == 0x00 {
-            zerocount = zerocount.wrapping_add(1); // increment zero count with wraparound
 
-            // if overflow happens (count wraps to 0), write 0x00 0xff like OpenMetaverse
-            if zerocount == 0 {
+    for &byte in src {
+        if byte == 0x00 {
+            // a full run can't be counted any higher, so write it and start a new one
+            if zerocount == u8::MAX {
                 dest.push(0x00);
-                dest.push(0xff);
-                zerocount = 1; // reset to 1 after writing overflow
+                dest.push(zerocount);
+                zerocount = 0;
             }
+            zerocount += 1;
         } else {
             // flush any accumulated zeros
             if zerocount != 0 {
@@ -109,11 +132,8 @@ fn zero_encode(src: &[u8]) -> Vec<u8> {
                 dest.push(zerocount);
                 zerocount = 0;
             }
-
-            dest.push(src[i]);
+            dest.push(byte);
         }
-
-        i += 1;
     }
 
     // flush remaining zeros at the end
@@ -2,6 +2,7 @@ use super::header::PacketFrequency;
 use crate::errors::ParseError;
 use crate::legacy::udp::agent_wearables_request::AgentWearablesRequest;
 use crate::legacy::udp::agent_wearables_update::AgentWearablesUpdate;
+use crate::packet::message_template::TemplateMessage;
 use crate::packet::packet_protocol::PacketData;
 use crate::udp::agent::avatar_animation::AvatarAnimation;
 use crate::udp::agent::avatar_appearance::AvatarAppearance;
@@ -14,6 +15,7 @@ use crate::udp::core::sim_stats::SimStats;
 use crate::udp::core::simulator_viewer_time_message::SimulatorViewerTimeMessage;
 use crate::udp::core::test_packet::TestPacket;
 use crate::udp::core::viewer_effect::ViewerEffect;
+use crate::udp::object::generic_streaming_message::GenericStreamingMessage;
 use crate::udp::object::improved_terse_object_update::ImprovedTerseObjectUpdate;
 use crate::udp::object::kill_object::KillObject;
 use crate::udp::object::multiple_object_update::MultipleObjectUpdate;
@@ -37,32 +39,50 @@ use crate::{
 use std::fmt::Debug;
 
 macro_rules! define_packets {
-    ( $( $id:literal [$freq:ident] => $variant:ident ),* $(,)? ) => {
+    ( $( $variant:ident ),* $(,)? ) => {
         #[derive(Debug, Clone)]
         #[allow(missing_docs)]
         pub enum PacketType {
             $(
                 $variant(Box<$variant>),
             )*
+            /// A message without a hand written definition, generated from message_template.msg
+            Template(Box<TemplateMessage>),
         }
 
         impl PacketType {
+            /// The ID, frequency and name of every hand written packet, in the order they are
+            /// registered. The ID and frequency are read from the packet's message in
+            /// message_template.msg.
+            pub const REGISTERED: &[(u16, PacketFrequency, &str)] = &[
+                $(
+                    ($variant::INFO.id, $variant::INFO.frequency, stringify!($variant)),
+                )*
+            ];
+
             /// call the PacketType's ToBytes function
             pub fn to_bytes(&self) -> Vec<u8> {
                 match self {
                     $(
                         PacketType::$variant(data) => data.to_bytes(),
                     )*
+                    PacketType::Template(data) => data.to_bytes(),
                 }
             }
-            /// Determine the type of a packet using the ID and frequency
+            /// Determine the type of a packet using the ID and frequency.
+            /// Hand written packets are parsed by their own implementation, and anything else
+            /// defined in the message template is parsed as a generated message.
             pub fn from_id(id: u16, frequency: PacketFrequency, bytes: &[u8]) -> Result<Self, ParseError> {
                 $(
-                    if id == $id && frequency == PacketFrequency::$freq {
+                    if id == $variant::INFO.id && frequency == $variant::INFO.frequency {
                         return Ok(PacketType::$variant(Box::new(PacketData::from_bytes(bytes)?)));
                     }
                 )*
 
+                if let Some(message) = TemplateMessage::from_id(id, frequency, bytes) {
+                    return Ok(PacketType::Template(Box::new(message?)));
+                }
+
                 Err(ParseError::UnknownPacket{id, frequency})
             }
         }
@@ -70,46 +90,7 @@ macro_rules! define_packets {
 }
 
 // The packet type implementation for each packet.
-// packets are determined based on their ID and Frequency.
-// this macro allows for simple assinging IDs and Frequencies to packets
-define_packets! {
-    1 [High] => StartPingCheck,
-    2 [High] => CompletePingCheck,
-    4 [High] => AgentUpdate,
-    11 [High] => LayerData,
-    12 [High] => ObjectUpdate,
-    13 [High] => ObjectUpdateCompressed,
-    14 [High] => ObjectUpdateCached,
-    15 [High] => ImprovedTerseObjectUpdate,
-    20 [High] => AvatarAnimation,
-    16 [High] => KillObject,
-
-    2 [Medium] => MultipleObjectUpdate,
-    3 [Medium] => RequestMultipleObjects,
-    6 [Medium] => CoarseLocationUpdate,
-    17 [Medium] => ViewerEffect,
-
-    1 [Low] => TestPacket,
-    3 [Low] => CircuitCode,
-    62 [Low] => TeleportRequest,
-    73 [Low] => TeleportStart,
-    80 [Low] => ChatFromViewer,
-    81 [Low] => AgentThrottle,
-    139 [Low] => ChatFromSimulator,
-    140 [Low] => SimStats,
-    148 [Low] => RegionHandshake,
-    149 [Low] => RegionHandshakeReply,
-    150 [Low] => SimulatorViewerTimeMessage,
-    152 [Low] => DisableSimulator,
-    151 [Low] => EnableSimulator,
-    158 [Low] => AvatarAppearance,
-    196 [Low] => ParcelOverlay,
-    249 [Low] => CompleteAgentMovementData,
-    250 [Low]=> AgentMovementComplete,
-    252 [Low] => LogoutRequest,
-
-    251 [Fixed] => PacketAck,
-    // Legacy packets
-    382 [Low] => AgentWearablesUpdate,
-    381 [Low] => AgentWearablesRequest
-}
+// packets are determined based on their ID and Frequency, which are read from their message in
+// message_template.msg. The list of packets is generated by build.rs, from the template messages
+// that have a hand written implementation.
+include!(concat!(env!("OUT_DIR"), "/packet_registry.rs"));
@@ -4,11 +4,11 @@ use crate::packet::{
     packet_protocol::{Packet, PacketData},
     packet_types::PacketType,
 };
-use benthic_protocol::messages::utils::agent_update_types::{
-    ControlFlags, Flags, QuatBytes, State,
-};
+use benthic_protocol::messages::utils::agent_update_types::{ControlFlags, Flags, State};
+use byteorder::{LittleEndian, ReadBytesExt};
 use glam::{Quat, Vec3};
 use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Read};
 use uuid::Uuid;
 
 impl Packet {
@@ -78,38 +78,24 @@ impl Default for AgentUpdate {
 
 impl PacketData for AgentUpdate {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        // THIS DOES NOT WORK AT ALL
-        // THIS WILL CRASH AND BREAK YOUR SHIT
-        let agent_id = Uuid::from_slice(&bytes[0..16])?;
-        let session_id = Uuid::from_slice(&bytes[16..32])?;
-        let body_rotation = QuatBytes::from_bytes(&bytes[32..48])?;
-        let head_rotation = QuatBytes::from_bytes(&bytes[48..64])?;
-
-        let state = State::from_bytes(bytes[64]);
-        let camera_center = Vec3 {
-            x: f32::from_le_bytes(bytes[65..69].try_into()?),
-            y: f32::from_le_bytes(bytes[69..73].try_into()?),
-            z: f32::from_le_bytes(bytes[73..77].try_into()?),
-        };
-        let camera_at_axis = Vec3 {
-            x: f32::from_le_bytes(bytes[77..81].try_into()?),
-            y: f32::from_le_bytes(bytes[81..85].try_into()?),
-            z: f32::from_le_bytes(bytes[85..89].try_into()?),
-        };
-        let camera_left_axis = Vec3 {
-            x: f32::from_le_bytes(bytes[89..93].try_into()?),
-            y: f32::from_le_bytes(bytes[93..97].try_into()?),
-            z: f32::from_le_bytes(bytes[97..101].try_into()?),
-        };
-        let camera_up_axis = Vec3 {
-            x: f32::from_le_bytes(bytes[101..105].try_into()?),
-            y: f32::from_le_bytes(bytes[105..109].try_into()?),
-            z: f32::from_le_bytes(bytes[109..113].try_into()?),
-        };
-        let far = f32::from_le_bytes(bytes[113..117].try_into()?);
-        let control_flags =
-            ControlFlags::from_bytes(u32::from_le_bytes(bytes[117..121].try_into()?));
-        let flags = Flags::from_bytes(bytes[121]);
+        let mut cursor = Cursor::new(bytes);
+        let mut id_bytes = [0u8; 16];
+        cursor.read_exact(&mut id_bytes)?;
+        let agent_id = Uuid::from_bytes(id_bytes);
+        cursor.read_exact(&mut id_bytes)?;
+        let session_id = Uuid::from_bytes(id_bytes);
+
+        let body_rotation = read_quat(&mut cursor)?;
+        let head_rotation = read_quat(&mut cursor)?;
+
+        let state = State::from_bytes(cursor.read_u8()?);
+        let camera_center = read_vec3(&mut cursor)?;
+        let camera_at_axis = read_vec3(&mut cursor)?;
+        let camera_left_axis = read_vec3(&mut cursor)?;
+        let camera_up_axis = read_vec3(&mut cursor)?;
+        let far = cursor.read_f32::<LittleEndian>()?;
+        let control_flags = ControlFlags::from_bytes(cursor.read_u32::<LittleEndian>()?);
+        let flags = Flags::from_bytes(cursor.read_u8()?);
         Ok(Self {
             agent_id,
             session_id,
@@ -127,32 +113,24 @@ impl PacketData for AgentUpdate {
     }
 
     fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(121); // Total byte length
+        let mut bytes = Vec::with_capacity(114); // Total byte length
 
         // Serialize UUIDs
         bytes.extend_from_slice(self.agent_id.as_bytes());
         bytes.extend_from_slice(self.session_id.as_bytes());
 
         // Serialize Quaternions
-        bytes.extend_from_slice(&QuatBytes(self.body_rotation).to_bytes());
-        bytes.extend_from_slice(&QuatBytes(self.body_rotation).to_bytes());
+        write_quat(&mut bytes, self.body_rotation);
+        write_quat(&mut bytes, self.head_rotation);
 
         // Serialize State
         bytes.push(self.state.to_bytes());
 
         // Serialize Vector3s
-        bytes.extend_from_slice(&self.camera_center.x.to_le_bytes());
-        bytes.extend_from_slice(&self.camera_center.y.to_le_bytes());
-        bytes.extend_from_slice(&self.camera_center.z.to_le_bytes());
-        bytes.extend_from_slice(&self.camera_at_axis.x.to_le_bytes());
-        bytes.extend_from_slice(&self.camera_at_axis.y.to_le_bytes());
-        bytes.extend_from_slice(&self.camera_at_axis.z.to_le_bytes());
-        bytes.extend_from_slice(&self.camera_left_axis.x.to_le_bytes());
-        bytes.extend_from_slice(&self.camera_left_axis.y.to_le_bytes());
-        bytes.extend_from_slice(&self.camera_left_axis.z.to_le_bytes());
-        bytes.extend_from_slice(&self.camera_up_axis.x.to_le_bytes());
-        bytes.extend_from_slice(&self.camera_up_axis.y.to_le_bytes());
-        bytes.extend_from_slice(&self.camera_up_axis.z.to_le_bytes());
+        write_vec3(&mut bytes, self.camera_center);
+        write_vec3(&mut bytes, self.camera_at_axis);
+        write_vec3(&mut bytes, self.camera_left_axis);
+        write_vec3(&mut bytes, self.camera_up_axis);
 
         bytes.extend_from_slice(&self.far.to_le_bytes());
 
@@ -163,3 +141,33 @@ impl PacketData for AgentUpdate {
         bytes
     }
 }
+
+fn read_vec3(cursor: &mut Cursor<&[u8]>) -> Result<Vec3, ParseError> {
+    Ok(Vec3::new(
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+    ))
+}
+
+fn write_vec3(bytes: &mut Vec<u8>, value: Vec3) {
+    for value in value.to_array() {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Rotations are sent as x, y and z. W is recomputed from the other three values.
+fn read_quat(cursor: &mut Cursor<&[u8]>) -> Result<Quat, ParseError> {
+    let xyz = read_vec3(cursor)?;
+    let w = (1.0 - xyz.length_squared()).max(0.0).sqrt();
+    Ok(Quat::from_xyzw(xyz.x, xyz.y, xyz.z, w))
+}
+
+/// The rotation is normalized with a positive w, so it can be recomputed on the other side.
+fn write_quat(bytes: &mut Vec<u8>, value: Quat) {
+    let mut rotation = value.normalize();
+    if rotation.w < 0.0 {
+        rotation = -rotation;
+    }
+    write_vec3(bytes, rotation.xyz());
+}
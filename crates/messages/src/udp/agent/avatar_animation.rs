@@ -1,4 +1,4 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use uuid::Uuid;
 
 use crate::errors::ParseError;
@@ -84,6 +84,26 @@ impl PacketData for AvatarAnimation {
         Ok(anim)
     }
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.sender_id.as_bytes());
+
+        let animations = &self.animations[..self.animations.len().min(u8::MAX as usize)];
+        bytes.push(animations.len() as u8);
+        for animation in animations {
+            bytes.extend_from_slice(animation.anim_id.as_bytes());
+            bytes
+                .write_i32::<LittleEndian>(animation.sequence_id)
+                .unwrap();
+        }
+
+        let sources = &self.sources[..self.sources.len().min(u8::MAX as usize)];
+        bytes.push(sources.len() as u8);
+        for source in sources {
+            bytes.extend_from_slice(source.as_bytes());
+        }
+
+        // the physical avatar block is left empty
+        bytes.push(0);
+        bytes
     }
 }
@@ -1,5 +1,9 @@
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::{Cursor, Read};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use glam::Vec3;
+use std::{
+    collections::BTreeMap,
+    io::{Cursor, Read},
+};
 use uuid::Uuid;
 
 use crate::{
@@ -9,6 +13,11 @@ use crate::{
         packet_protocol::{Packet, PacketData},
         packet_types::PacketType,
     },
+    utils::{
+        attachment_point::AttachmentPoint,
+        bake::{BakeType, DEFAULT_AVATAR_TEXTURE},
+        texture_entry::TextureEntry,
+    },
 };
 
 impl Packet {
@@ -38,12 +47,57 @@ pub struct AvatarAppearance {
     pub id: Uuid,
     /// is the user a trial user
     pub is_trial: bool,
-    /// the bytes containing the texture data
-    pub texture_data: Vec<u8>,
-    /// the byes containing the visual param data
-    pub visual_param_data: Vec<u8>,
-    /// the bytes containing the remaining data
-    pub remaining_data: Vec<u8>,
+    /// The textures of the avatar. Each face of the entry is one of the avatar's texture slots,
+    /// which hold either the texture of a wearable or one of the avatar's bakes.
+    pub texture_entry: TextureEntry,
+    /// The values of the avatar's transmitted visual params, in order of their IDs. Each value is
+    /// quantized between the param's minimum and maximum, which are looked up with
+    /// [`AvatarLad`](crate::utils::visual_params::AvatarLad).
+    pub visual_params: Vec<u8>,
+    /// The versions the appearance was baked from. Not sent by older simulators.
+    pub appearance_data: Option<AppearanceData>,
+    /// How far the avatar is moved up or down from where it would normally stand. Not sent by
+    /// older simulators.
+    pub hover_height: Option<Vec3>,
+    /// The objects the avatar is wearing. Not sent by older simulators.
+    pub attachments: Vec<AppearanceAttachment>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The versions an avatar's appearance was baked from
+pub struct AppearanceData {
+    /// The version of the appearance system. 0 if the avatar's viewer baked the appearance
+    /// itself, and 1 if it was baked by the server.
+    pub appearance_version: u8,
+    /// The version of the agent's current outfit folder the appearance was baked from. Changes
+    /// every time the agent changes their outfit.
+    pub cof_version: i32,
+    /// Appearance flags. Currently unused.
+    pub flags: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An object worn by an avatar
+pub struct AppearanceAttachment {
+    /// ID of the worn item
+    pub id: Uuid,
+    /// where the item is attached
+    pub attachment_point: AttachmentPoint,
+}
+
+impl AvatarAppearance {
+    /// The textures the avatar's appearance was baked into, by bake. Bakes the avatar doesn't
+    /// have are left out, which the texture entry fills with the default avatar texture.
+    pub fn baked_textures(&self) -> BTreeMap<BakeType, Uuid> {
+        BakeType::ALL
+            .into_iter()
+            .map(|bake| {
+                let index = bake.baked_slot().index() as u32;
+                (bake, self.texture_entry.face(index).texture_id)
+            })
+            .filter(|(_, id)| !id.is_nil() && *id != DEFAULT_AVATAR_TEXTURE)
+            .collect()
+    }
 }
 
 impl PacketData for AvatarAppearance {
@@ -55,30 +109,96 @@ impl PacketData for AvatarAppearance {
         let id = Uuid::from_bytes(id_bytes);
 
         let is_trial = cursor.read_u8()? != 0;
-        let length = cursor.read_u16::<LittleEndian>()?;
+        let mut texture_data = vec![0u8; cursor.read_u16::<LittleEndian>()? as usize];
+        cursor.read_exact(&mut texture_data)?;
+        let texture_entry = TextureEntry::from_bytes(&texture_data)?;
 
-        let end = cursor.position() + length as u64;
-        let texture_data = cursor.get_ref()[cursor.position() as usize..end as usize].to_vec();
-        cursor.set_position(end);
+        let mut visual_params = vec![0u8; cursor.read_u8()? as usize];
+        cursor.read_exact(&mut visual_params)?;
 
-        let length = cursor.read_u8()?;
-        let end = cursor.position() + length as u64;
-        let visual_param_data = cursor.get_ref()[cursor.position() as usize..end as usize].to_vec();
-        cursor.set_position(end);
+        // older simulators end the packet after the visual params
+        let mut appearance_data = None;
+        if (cursor.position() as usize) < bytes.len() {
+            for _ in 0..cursor.read_u8()? {
+                let data = AppearanceData {
+                    appearance_version: cursor.read_u8()?,
+                    cof_version: cursor.read_i32::<LittleEndian>()?,
+                    flags: cursor.read_u32::<LittleEndian>()?,
+                };
+                appearance_data.get_or_insert(data);
+            }
+        }
 
-        let mut remaining_data = Vec::new();
-        cursor.read_to_end(&mut remaining_data)?;
+        let mut hover_height = None;
+        if (cursor.position() as usize) < bytes.len() {
+            for _ in 0..cursor.read_u8()? {
+                let height = Vec3::new(
+                    cursor.read_f32::<LittleEndian>()?,
+                    cursor.read_f32::<LittleEndian>()?,
+                    cursor.read_f32::<LittleEndian>()?,
+                );
+                hover_height.get_or_insert(height);
+            }
+        }
+
+        let mut attachments = Vec::new();
+        if (cursor.position() as usize) < bytes.len() {
+            for _ in 0..cursor.read_u8()? {
+                let mut id_bytes = [0u8; 16];
+                cursor.read_exact(&mut id_bytes)?;
+                attachments.push(AppearanceAttachment {
+                    id: Uuid::from_bytes(id_bytes),
+                    attachment_point: AttachmentPoint::from_u8(cursor.read_u8()?),
+                });
+            }
+        }
 
         Ok(AvatarAppearance {
             id,
             is_trial,
-            texture_data,
-            visual_param_data,
-            remaining_data,
+            texture_entry,
+            visual_params,
+            appearance_data,
+            hover_height,
+            attachments,
         })
     }
 
     fn to_bytes(&self) -> Vec<u8> {
-        vec![]
+        let texture_data = self.texture_entry.to_bytes();
+        let texture_data = &texture_data[..texture_data.len().min(u16::MAX as usize)];
+        let visual_params = &self.visual_params[..self.visual_params.len().min(u8::MAX as usize)];
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.id.as_bytes());
+        bytes.push(self.is_trial as u8);
+        bytes
+            .write_u16::<LittleEndian>(texture_data.len() as u16)
+            .unwrap();
+        bytes.extend_from_slice(texture_data);
+        bytes.push(visual_params.len() as u8);
+        bytes.extend_from_slice(visual_params);
+
+        bytes.push(self.appearance_data.is_some() as u8);
+        if let Some(data) = &self.appearance_data {
+            bytes.push(data.appearance_version);
+            bytes.write_i32::<LittleEndian>(data.cof_version).unwrap();
+            bytes.write_u32::<LittleEndian>(data.flags).unwrap();
+        }
+
+        bytes.push(self.hover_height.is_some() as u8);
+        if let Some(height) = self.hover_height {
+            for value in height.to_array() {
+                bytes.write_f32::<LittleEndian>(value).unwrap();
+            }
+        }
+
+        let attachments = &self.attachments[..self.attachments.len().min(u8::MAX as usize)];
+        bytes.push(attachments.len() as u8);
+        for attachment in attachments {
+            bytes.extend_from_slice(attachment.id.as_bytes());
+            bytes.push(attachment.attachment_point.to_u8());
+        }
+        bytes
     }
 }
@@ -1,6 +1,7 @@
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
-use std::io::{self, Cursor, Write};
+use std::io::{self, Cursor, Read, Write};
+use uuid::Uuid;
 
 use crate::{
     errors::ParseError,
@@ -30,9 +31,12 @@ impl Packet {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Defines locations of agent dots on the minimap.
 pub struct MinimapEntities {
-    x: u8,
-    y: u8,
-    z: u8,
+    /// x position of the agent in the region, in meters
+    pub x: u8,
+    /// y position of the agent in the region, in meters
+    pub y: u8,
+    /// z position of the agent in the region, in units of four meters
+    pub z: u8,
 }
 impl MinimapEntities {
     /// Converts xyz bytes to minimap entities
@@ -62,6 +66,8 @@ pub struct CoarseLocationUpdate {
     pub you: i16,
     /// the ID of the user you are following
     pub prey: i16,
+    /// the agent IDs of each location, in the same order as the locations
+    pub agent_ids: Vec<Uuid>,
 }
 
 impl PacketData for CoarseLocationUpdate {
@@ -81,10 +87,22 @@ impl PacketData for CoarseLocationUpdate {
         let you = cursor.read_i16::<LittleEndian>()?;
         let prey = cursor.read_i16::<LittleEndian>()?;
 
+        // older simulators do not send agent IDs
+        let mut agent_ids = Vec::new();
+        if (cursor.position() as usize) < bytes.len() {
+            let agent_count = cursor.read_u8()?;
+            for _ in 0..agent_count {
+                let mut id_bytes = [0u8; 16];
+                cursor.read_exact(&mut id_bytes)?;
+                agent_ids.push(Uuid::from_bytes(id_bytes));
+            }
+        }
+
         Ok(CoarseLocationUpdate {
             locations,
             you,
             prey,
+            agent_ids,
         })
     }
 
@@ -92,8 +110,9 @@ impl PacketData for CoarseLocationUpdate {
         let mut bytes = Vec::new();
 
         // Serialize LocationBlocks
-        bytes.push(self.locations.len() as u8);
-        for location in &self.locations {
+        let locations = &self.locations[..self.locations.len().min(u8::MAX as usize)];
+        bytes.push(locations.len() as u8);
+        for location in locations {
             bytes.push(location.x);
             bytes.push(location.y);
             bytes.push(location.z);
@@ -103,6 +122,13 @@ impl PacketData for CoarseLocationUpdate {
         bytes.write_i16::<LittleEndian>(self.you).unwrap();
         bytes.write_i16::<LittleEndian>(self.prey).unwrap();
 
+        // Serialize AgentData
+        let agent_ids = &self.agent_ids[..self.agent_ids.len().min(u8::MAX as usize)];
+        bytes.push(agent_ids.len() as u8);
+        for agent_id in agent_ids {
+            bytes.extend_from_slice(agent_id.as_bytes());
+        }
+
         bytes
     }
 }
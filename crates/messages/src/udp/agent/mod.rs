@@ -52,6 +52,7 @@ pub mod agent_update;
 /// | Locations\[Location\]    | 12 bytes         | [Vector3](glam::Vec3)[[u8]] | XYZ location of an agent|
 /// | You                    | 2 bytes          | [i16]         | Index of you in the list|
 /// | Prey                   | 2 bytes          | [i16]         | Index of who you are following in the list|
+/// | AgentIDs               | List of IDs      | [Uuid](uuid::Uuid) | ID of the agent at each location. Not sent by older simulators. |
 pub mod coarse_location_update;
 
 /// TODO: UNIMPLEMENTED
@@ -73,7 +74,16 @@ pub mod avatar_animation;
 /// | id           | 16 bytes | [Uuid](uuid::Uuid) | ID of the user |  
 /// | is_trial     | 1 byte   | [bool]             | Is the user a trial user|
 /// | texture_len  | 2 bytes  | [u16]              | length of the texture data block |
-/// | texture_data | variable bytes |              | Texture data for each face |
+/// | texture_data | variable bytes | [TextureEntry](crate::utils::texture_entry::TextureEntry) | Texture data for each face |
 /// | v_param_len  | 1 byte   | [u8]               | length of visual param block |
-/// | visual_param_data | variable byes |          | Bytes containing the visual param data |
+/// | visual_param_data | variable byes |          | One byte for each transmitted visual param |
+/// | appearance_data_count | 1 byte | [u8]        | Number of appearance data blocks. Not sent by older simulators. |
+/// | appearance_version | 1 byte | [u8]           | Version of the appearance system |
+/// | cof_version  | 4 bytes  | [i32]              | Version of the current outfit folder |
+/// | flags        | 4 bytes  | [u32]              | Appearance flags |
+/// | hover_count  | 1 byte   | [u8]               | Number of hover height blocks. Not sent by older simulators. |
+/// | hover_height | 12 bytes | [Vec3](glam::Vec3) | Offset of the avatar from where it would stand |
+/// | attachment_count | 1 byte | [u8]             | Number of attachment blocks. Not sent by older simulators. |
+/// | attachment_id | 16 bytes | [Uuid](uuid::Uuid) | ID of the worn item |
+/// | attachment_point | 1 byte | [u8]             | Where the item is attached |
 pub mod avatar_appearance;
@@ -69,18 +69,13 @@ impl PacketData for ChatFromSimulator {
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
         // SourceID
-        let source_id = Uuid::from_slice(
-            &cursor.get_ref()[cursor.position() as usize..cursor.position() as usize + 16],
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        cursor.set_position(cursor.position() + 16);
+        let mut id_bytes = [0u8; 16];
+        cursor.read_exact(&mut id_bytes)?;
+        let source_id = Uuid::from_bytes(id_bytes);
 
         // OwnerID
-        let owner_id = Uuid::from_slice(
-            &cursor.get_ref()[cursor.position() as usize..cursor.position() as usize + 16],
-        )
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        cursor.set_position(cursor.position() + 16);
+        cursor.read_exact(&mut id_bytes)?;
+        let owner_id = Uuid::from_bytes(id_bytes);
 
         // SourceType
         let source_type_byte = cursor.read_u8()?;
@@ -4,7 +4,10 @@ use crate::packet::{
     packet_protocol::{Packet, PacketData},
     packet_types::PacketType,
 };
-use std::io::Cursor;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use glam::Vec3;
+use std::io::{Cursor, Read};
+use uuid::Uuid;
 
 impl Packet {
     /// create a new agent movement complete packet
@@ -22,16 +25,80 @@ impl Packet {
     }
 }
 
-#[derive(Debug, Clone)]
-/// TODO: UNIMPLEMENTED
-pub struct AgentMovementComplete {}
+#[derive(Debug, Clone, Default)]
+/// Sent by the server in response to CompleteAgentMovement, once the agent has arrived in the
+/// region.
+pub struct AgentMovementComplete {
+    /// ID of the agent
+    pub agent_id: Uuid,
+    /// session ID of the agent
+    pub session_id: Uuid,
+    /// position of the agent in region local coordinates
+    pub position: Vec3,
+    /// direction the agent is facing
+    pub look_at: Vec3,
+    /// handle of the region the agent arrived in
+    pub region_handle: u64,
+    /// unix timestamp of the simulator
+    pub timestamp: u32,
+    /// version string of the simulator
+    pub channel_version: String,
+}
 
 impl PacketData for AgentMovementComplete {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let mut _cursor = Cursor::new(bytes);
-        Ok(AgentMovementComplete {})
+        let mut cursor = Cursor::new(bytes);
+
+        let mut id_bytes = [0u8; 16];
+        cursor.read_exact(&mut id_bytes)?;
+        let agent_id = Uuid::from_bytes(id_bytes);
+        cursor.read_exact(&mut id_bytes)?;
+        let session_id = Uuid::from_bytes(id_bytes);
+
+        let position = Vec3::new(
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+        );
+        let look_at = Vec3::new(
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+        );
+        let region_handle = cursor.read_u64::<LittleEndian>()?;
+        let timestamp = cursor.read_u32::<LittleEndian>()?;
+
+        let mut version_bytes = vec![0u8; cursor.read_u16::<LittleEndian>()? as usize];
+        cursor.read_exact(&mut version_bytes)?;
+        let channel_version = String::from_utf8_lossy(&version_bytes)
+            .trim_end_matches('\0')
+            .to_string();
+
+        Ok(AgentMovementComplete {
+            agent_id,
+            session_id,
+            position,
+            look_at,
+            region_handle,
+            timestamp,
+            channel_version,
+        })
     }
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+        for value in self.position.to_array().into_iter().chain(self.look_at.to_array()) {
+            bytes.write_f32::<LittleEndian>(value).unwrap();
+        }
+        bytes.write_u64::<LittleEndian>(self.region_handle).unwrap();
+        bytes.write_u32::<LittleEndian>(self.timestamp).unwrap();
+
+        // strings are sent null terminated
+        let mut version = self.channel_version.as_bytes().to_vec();
+        version.push(0);
+        bytes.write_u16::<LittleEndian>(version.len() as u16).unwrap();
+        bytes.extend(version);
+        bytes
     }
 }
@@ -158,6 +158,8 @@ impl PacketData for AgentThrottle {
         let session_id = Uuid::from_bytes(session_id_bytes);
         let circuit_code = cursor.read_u32::<LittleEndian>()?;
         let gen_counter = cursor.read_u32::<LittleEndian>()?;
+        // the throttles are sent as a variable block, prefixed with its length
+        let _throttle_length = cursor.read_u8()?;
         let throttles = ThrottleData::from_bytes(&mut cursor)?;
         Ok(Self {
             agent_id,
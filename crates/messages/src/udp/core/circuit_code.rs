@@ -6,6 +6,8 @@ use crate::{
         packet_types::PacketType,
     },
 };
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{Cursor, Read};
 use uuid::Uuid;
 
 impl Packet {
@@ -38,9 +40,13 @@ pub struct CircuitCode {
 
 impl PacketData for CircuitCode {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let code = u32::from_le_bytes(bytes[0..4].try_into()?);
-        let session_id = Uuid::from_slice(&bytes[4..20])?;
-        let id = Uuid::from_slice(&bytes[20..36])?;
+        let mut cursor = Cursor::new(bytes);
+        let code = cursor.read_u32::<LittleEndian>()?;
+        let mut id_bytes = [0u8; 16];
+        cursor.read_exact(&mut id_bytes)?;
+        let session_id = Uuid::from_bytes(id_bytes);
+        cursor.read_exact(&mut id_bytes)?;
+        let id = Uuid::from_bytes(id_bytes);
 
         Ok(Self {
             code,
@@ -6,6 +6,8 @@ use crate::{
         packet_types::PacketType,
     },
 };
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{Cursor, Read};
 use uuid::Uuid;
 
 impl Packet {
@@ -39,9 +41,13 @@ pub struct CompleteAgentMovementData {
 
 impl PacketData for CompleteAgentMovementData {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let circuit_code = u32::from_le_bytes(bytes[0..4].try_into()?);
-        let session_id = Uuid::from_slice(&bytes[4..20])?;
-        let agent_id = Uuid::from_slice(&bytes[20..36])?;
+        let mut cursor = Cursor::new(bytes);
+        let mut id_bytes = [0u8; 16];
+        cursor.read_exact(&mut id_bytes)?;
+        let agent_id = Uuid::from_bytes(id_bytes);
+        cursor.read_exact(&mut id_bytes)?;
+        let session_id = Uuid::from_bytes(id_bytes);
+        let circuit_code = cursor.read_u32::<LittleEndian>()?;
 
         Ok(CompleteAgentMovementData {
             agent_id,
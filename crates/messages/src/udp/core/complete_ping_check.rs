@@ -6,6 +6,8 @@ use crate::{
         packet_types::PacketType,
     },
 };
+use byteorder::ReadBytesExt;
+use std::io::Cursor;
 
 impl Packet {
     /// create a new complete ping check packet
@@ -32,12 +34,10 @@ pub struct CompletePingCheck {
 
 impl PacketData for CompletePingCheck {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let ping_id = bytes[0];
+        let ping_id = Cursor::new(bytes).read_u8()?;
         Ok(CompletePingCheck { ping_id })
     }
     fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(5);
-        bytes.push(self.ping_id);
-        bytes
+        vec![self.ping_id]
     }
 }
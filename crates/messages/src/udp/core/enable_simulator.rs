@@ -4,8 +4,9 @@ use crate::packet::{
     packet_protocol::{Packet, PacketData},
     packet_types::PacketType,
 };
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::io::{Cursor, Read};
+use std::net::Ipv4Addr;
 
 impl Packet {
     /// create a new enable simulator packet
@@ -30,8 +31,8 @@ pub struct EnableSimulator {
     /// the region handle of the region
     pub handle: u64,
     /// the IP of the ready viewer
-    pub ip: String,
-    /// the port the viewer is connected to
+    pub ip: Ipv4Addr,
+    /// the port the viewer is connected to. Sent big-endian.
     pub port: u16,
 }
 
@@ -42,17 +43,17 @@ impl PacketData for EnableSimulator {
 
         let mut ip_bytes = [0u8; 4];
         cursor.read_exact(&mut ip_bytes)?;
-        let ip = String::from_utf8_lossy(&ip_bytes).into_owned();
+        let ip = Ipv4Addr::from(ip_bytes);
 
-        let port = cursor.read_u16::<LittleEndian>()?;
+        let port = cursor.read_u16::<BigEndian>()?;
 
         Ok(EnableSimulator { handle, ip, port })
     }
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(14);
         bytes.write_u64::<LittleEndian>(self.handle).unwrap();
-        bytes.extend_from_slice(self.ip.as_bytes());
-        bytes.write_u16::<LittleEndian>(self.port).unwrap();
+        bytes.extend_from_slice(&self.ip.octets());
+        bytes.write_u16::<BigEndian>(self.port).unwrap();
 
         bytes
     }
@@ -210,6 +210,7 @@ pub mod complete_agent_movement;
 /// | session_id   | 16 bytes | [Uuid](uuid::Uuid) | The ID of the user session      |
 /// | circuit_code | 4 bytes  | [u32]| The CircuitCode                 |
 /// | gen_id       | 4 bytes  | [u32]| label to inform the viewer that one packet is more recent than the other. Can be set to 0. |
+/// | throttle_length | 1 byte | [u8] | length of the throttle data. Always 28. |
 /// | resend       | 4 bytes  | [f32]| maximum bytes per second for resent packets |
 /// | land         | 4 bytes  | [f32]| maximum bytes per second for land packets |   
 /// | wind         | 4 bytes  | [f32]| maximum bytes per second for wind packets |   
@@ -234,19 +235,126 @@ pub mod agent_throttle;
 /// | AgentThrottle |          |                    |                                 |
 /// |-----------------------|----------|--------------------|---------------------------------|
 /// | handle       | 8 bytes  | [u64]| ID of the region |
-/// | ip           | 4 bytes  | [String] | IP address of the ready viewer |
-/// | port        | 2 bytes  | [u16]| port the ready viewer is connected to |   
+/// | ip           | 4 bytes  | [Ipv4Addr](std::net::Ipv4Addr) | IP address of the ready viewer |
+/// | port        | 2 bytes  | [u16]| port the ready viewer is connected to. Big-endian. |   
 pub mod enable_simulator;
 
-/// TODO: UNIMPLEMENTED
+/// # Agent Movement Complete
+/// <https://wiki.secondlife.com/wiki/AgentMovementComplete>
+///
+/// Sent by the server once the agent has finished moving into the region.
+///
+/// ## Header
+/// | AgentMovementComplete |||||
+/// |--------------|-------------|----------------|-------------------|---------------------|
+/// | Packet Header| id: 250 | reliable: true | zerocoded: false | frequency: Low |
+///
+/// ## Packet Structure
+/// | AgentMovementComplete |          |                    |                                 |
+/// |-----------------------|----------|--------------------|---------------------------------|
+/// | AgentID | 16 bytes | [Uuid](uuid::Uuid) | ID of the agent |
+/// | SessionID | 16 bytes | [Uuid](uuid::Uuid) | ID of the session |
+/// | Position | 12 bytes | [Vec3](glam::Vec3) | position of the agent |
+/// | LookAt | 12 bytes | [Vec3](glam::Vec3) | direction the agent is facing |
+/// | RegionHandle | 8 bytes | [u64] | handle of the region |
+/// | Timestamp | 4 bytes | [u32] | unix timestamp of the simulator |
+/// | ChannelVersion | 2 + n bytes | [String] | length prefixed simulator version |
 pub mod agent_movement_complete;
-/// TODO: UNIMPLEMENTED
+/// # Parcel Overlay
+/// <https://wiki.secondlife.com/wiki/ParcelOverlay>
+///
+/// Ownership and border information for the parcels of a region, split across several packets.
+///
+/// ## Header
+/// | ParcelOverlay |||||
+/// |--------------|-------------|----------------|-------------------|---------------------|
+/// | Packet Header| id: 196 | reliable: true | zerocoded: false | frequency: Low |
+///
+/// ## Packet Structure
+/// | ParcelOverlay |          |                    |                                 |
+/// |-----------------------|----------|--------------------|---------------------------------|
+/// | SequenceID | 4 bytes | [i32] | which part of the region the packet covers |
+/// | Data | 2 + n bytes | [Vec<u8>] | one byte per 4x4 meter square of land |
 pub mod parcel_overlay;
-/// TODO: UNIMPLEMENTED
+/// # Sim Stats
+/// <https://wiki.secondlife.com/wiki/SimStats>
+///
+/// Statistics about the region, such as time dilation and frame rate.
+///
+/// ## Header
+/// | SimStats |||||
+/// |--------------|-------------|----------------|-------------------|---------------------|
+/// | Packet Header| id: 140 | reliable: true | zerocoded: false | frequency: Low |
+///
+/// ## Packet Structure
+/// | SimStats |          |                    |                                 |
+/// |-----------------------|----------|--------------------|---------------------------------|
+/// | RegionX | 4 bytes | [u32] | x position of the region on the grid |
+/// | RegionY | 4 bytes | [u32] | y position of the region on the grid |
+/// | RegionFlags | 4 bytes | [u32] | region flags |
+/// | ObjectCapacity | 4 bytes | [u32] | maximum number of objects |
+/// | StatCount | 1 byte | [u8] | number of stats |
+/// | Stat | 8 bytes each | [u32], [f32] | stat ID and value |
+/// | PID | 4 bytes | [i32] | process ID of the simulator |
+/// | RegionInfoCount | 1 byte | [u8] | number of extended flags. Can be left off. |
+/// | RegionFlagsExtended | 8 bytes each | [u64] | extended region flags |
 pub mod sim_stats;
-/// TODO: UNIMPLEMENTED
+/// # Simulator Viewer Time Message
+/// <https://wiki.secondlife.com/wiki/SimulatorViewerTimeMessage>
+///
+/// Sent by the server to tell the viewer the time of day and position of the sun.
+///
+/// ## Header
+/// | SimulatorViewerTimeMessage |||||
+/// |--------------|-------------|----------------|-------------------|---------------------|
+/// | Packet Header| id: 150 | reliable: true | zerocoded: false | frequency: Low |
+///
+/// ## Packet Structure
+/// | SimulatorViewerTimeMessage |          |                    |                                 |
+/// |-----------------------|----------|--------------------|---------------------------------|
+/// | UsecSinceStart | 8 bytes | [u64] | microseconds since the simulator started |
+/// | SecPerDay | 4 bytes | [u32] | seconds in one day |
+/// | SecPerYear | 4 bytes | [u32] | seconds in one year |
+/// | SunDirection | 12 bytes | [Vec3](glam::Vec3) | direction of the sun |
+/// | SunPhase | 4 bytes | [f32] | position of the sun in the sky |
+/// | SunAngVelocity | 12 bytes | [Vec3](glam::Vec3) | angular velocity of the sun |
 pub mod simulator_viewer_time_message;
-/// TODO: UNIMPLEMENTED
+/// # Test Message
+/// <https://wiki.secondlife.com/wiki/TestMessage>
+///
+/// Test message for checking that a circuit is working.
+///
+/// ## Header
+/// | TestMessage |||||
+/// |--------------|-------------|----------------|-------------------|---------------------|
+/// | Packet Header| id: 1 | reliable: true | zerocoded: false | frequency: Low |
+///
+/// ## Packet Structure
+/// | TestMessage |          |                    |                                 |
+/// |-----------------------|----------|--------------------|---------------------------------|
+/// | Test1 | 4 bytes | [u32] | test value |
+/// | NeighborBlock | 48 bytes | [u32] | four blocks of three test values |
 pub mod test_packet;
-/// TODO: UNIMPLEMENTED
+/// # Viewer Effect
+/// <https://wiki.secondlife.com/wiki/ViewerEffect>
+///
+/// Visual effects such as edit beams and look-at targets, relayed by the server to other viewers.
+///
+/// ## Header
+/// | ViewerEffect |||||
+/// |--------------|-------------|----------------|-------------------|---------------------|
+/// | Packet Header| id: 17 | reliable: false | zerocoded: false | frequency: Medium |
+///
+/// ## Packet Structure
+/// | ViewerEffect |          |                    |                                 |
+/// |-----------------------|----------|--------------------|---------------------------------|
+/// | AgentID | 16 bytes | [Uuid](uuid::Uuid) | ID of the agent |
+/// | SessionID | 16 bytes | [Uuid](uuid::Uuid) | ID of the session |
+/// | EffectCount | 1 byte | [u8] | number of effects |
+/// | ID | 16 bytes | [Uuid](uuid::Uuid) | ID of the effect |
+/// | AgentID | 16 bytes | [Uuid](uuid::Uuid) | agent that created the effect |
+/// | Type | 1 byte | [u8] | type of effect |
+/// | Duration | 4 bytes | [f32] | how long the effect lasts |
+/// | Color | 4 bytes | [u8; 4] | RGBA color |
+/// | TypeData | 1 + n bytes | [Vec<u8>] | length prefixed data for the effect type |
 pub mod viewer_effect;
@@ -4,7 +4,8 @@ use crate::packet::{
     packet_protocol::{Packet, PacketData},
     packet_types::PacketType,
 };
-use std::io::Cursor;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Cursor, Read};
 
 impl Packet {
     /// create a new parcel overlay packet
@@ -22,16 +23,30 @@ impl Packet {
     }
 }
 
-#[derive(Debug, Clone)]
-/// TODO: unimplemented
-pub struct ParcelOverlay {}
+#[derive(Debug, Clone, Default)]
+/// Describes the ownership of the parcels in the region, used to draw parcel boundaries.
+/// The overlay for the whole region is split across several packets.
+pub struct ParcelOverlay {
+    /// which part of the region this packet covers
+    pub sequence_id: i32,
+    /// one byte per 4x4 meter square of land, containing its ownership and border flags
+    pub data: Vec<u8>,
+}
 
 impl PacketData for ParcelOverlay {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let _cursor = Cursor::new(bytes);
-        Ok(ParcelOverlay {})
+        let mut cursor = Cursor::new(bytes);
+        let sequence_id = cursor.read_i32::<LittleEndian>()?;
+        let mut data = vec![0u8; cursor.read_u16::<LittleEndian>()? as usize];
+        cursor.read_exact(&mut data)?;
+        Ok(ParcelOverlay { sequence_id, data })
     }
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let data = &self.data[..self.data.len().min(u16::MAX as usize)];
+        let mut bytes = Vec::with_capacity(6 + data.len());
+        bytes.write_i32::<LittleEndian>(self.sequence_id).unwrap();
+        bytes.write_u16::<LittleEndian>(data.len() as u16).unwrap();
+        bytes.extend_from_slice(data);
+        bytes
     }
 }
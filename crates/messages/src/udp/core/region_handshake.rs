@@ -89,8 +89,12 @@ impl PacketData for RegionHandshake {
         let mut bytes = Vec::new();
         bytes.extend(&self.region_flags.to_le_bytes());
         bytes.push(self.sim_access.to_bytes());
-        bytes.extend(self.sim_name.len().to_le_bytes());
-        bytes.extend(self.sim_name.as_bytes());
+        // the sim name is null terminated, and prefixed by a one byte length
+        let mut sim_name = self.sim_name.as_bytes().to_vec();
+        sim_name.truncate(u8::MAX as usize - 1);
+        sim_name.push(0);
+        bytes.push(sim_name.len() as u8);
+        bytes.extend(sim_name);
         bytes.extend(self.sim_owner.as_bytes());
         bytes.push(self.is_estate_manager as u8);
         bytes.extend(&self.water_height.to_le_bytes());
@@ -125,6 +129,9 @@ impl PacketData for RegionHandshake {
         let sim_name_length = cursor.read_u8()?;
         let mut sim_name_bytes = vec![0u8; sim_name_length as usize];
         cursor.read_exact(&mut sim_name_bytes)?;
+        if let Some(&0) = sim_name_bytes.last() {
+            sim_name_bytes.pop();
+        }
         let sim_name = String::from_utf8(sim_name_bytes)?;
 
         let mut uuid_bytes = [0u8; 16];
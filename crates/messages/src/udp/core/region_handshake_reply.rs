@@ -6,6 +6,7 @@ use crate::{
         packet_types::PacketType,
     },
 };
+use byteorder::{LittleEndian, ReadBytesExt};
 use std::io::{self, Cursor, Read};
 use uuid::Uuid;
 
@@ -50,12 +51,7 @@ impl PacketData for RegionHandshakeReply {
         let session_id = Uuid::from_slice(&session_id_bytes)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
-        let mut region_info_bytes = [0u8; 4]; // 4 bytes for flags
-        cursor.read_exact(&mut region_info_bytes)?;
-
-        let flags = u32::from_le_bytes(bytes.try_into().map_err(|_| {
-            io::Error::new(io::ErrorKind::InvalidData, "Failed to convert bytes to u32")
-        })?);
+        let flags = cursor.read_u32::<LittleEndian>()?;
 
         Ok(RegionHandshakeReply {
             agent_id,
@@ -4,6 +4,7 @@ use crate::packet::{
     packet_protocol::{Packet, PacketData},
     packet_types::PacketType,
 };
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::io::Cursor;
 
 impl Packet {
@@ -22,16 +23,93 @@ impl Packet {
     }
 }
 
-#[derive(Debug, Clone)]
-/// TODO: UNIMPLEMENTED
-pub struct SimStats {}
+#[derive(Debug, Clone, Default)]
+/// Statistics about the region, sent periodically by the simulator
+pub struct SimStats {
+    /// x position of the region on the grid, in regions
+    pub region_x: u32,
+    /// y position of the region on the grid, in regions
+    pub region_y: u32,
+    /// region flags
+    pub region_flags: u32,
+    /// maximum number of objects the region can hold
+    pub object_capacity: u32,
+    /// the values of each statistic, identified by its stat ID
+    pub stats: Vec<Stat>,
+    /// process ID of the simulator
+    pub pid: i32,
+    /// extended region flags. Older simulators don't send these.
+    pub region_flags_extended: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+/// A single statistic of the region, such as time dilation or frame rate
+pub struct Stat {
+    /// ID of the stat being reported
+    pub id: u32,
+    /// value of the stat
+    pub value: f32,
+}
 
 impl PacketData for SimStats {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let mut _cursor = Cursor::new(bytes);
-        Ok(SimStats {})
+        let mut cursor = Cursor::new(bytes);
+        let region_x = cursor.read_u32::<LittleEndian>()?;
+        let region_y = cursor.read_u32::<LittleEndian>()?;
+        let region_flags = cursor.read_u32::<LittleEndian>()?;
+        let object_capacity = cursor.read_u32::<LittleEndian>()?;
+
+        let stat_count = cursor.read_u8()? as usize;
+        let mut stats = Vec::with_capacity(stat_count);
+        for _ in 0..stat_count {
+            stats.push(Stat {
+                id: cursor.read_u32::<LittleEndian>()?,
+                value: cursor.read_f32::<LittleEndian>()?,
+            });
+        }
+
+        let pid = cursor.read_i32::<LittleEndian>()?;
+
+        // the extended flags block is left off entirely by older simulators
+        let mut region_flags_extended = Vec::new();
+        if (cursor.position() as usize) < bytes.len() {
+            let count = cursor.read_u8()?;
+            for _ in 0..count {
+                region_flags_extended.push(cursor.read_u64::<LittleEndian>()?);
+            }
+        }
+
+        Ok(SimStats {
+            region_x,
+            region_y,
+            region_flags,
+            object_capacity,
+            stats,
+            pid,
+            region_flags_extended,
+        })
     }
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let mut bytes = Vec::new();
+        bytes.write_u32::<LittleEndian>(self.region_x).unwrap();
+        bytes.write_u32::<LittleEndian>(self.region_y).unwrap();
+        bytes.write_u32::<LittleEndian>(self.region_flags).unwrap();
+        bytes.write_u32::<LittleEndian>(self.object_capacity).unwrap();
+
+        let stats = &self.stats[..self.stats.len().min(u8::MAX as usize)];
+        bytes.push(stats.len() as u8);
+        for stat in stats {
+            bytes.write_u32::<LittleEndian>(stat.id).unwrap();
+            bytes.write_f32::<LittleEndian>(stat.value).unwrap();
+        }
+
+        bytes.write_i32::<LittleEndian>(self.pid).unwrap();
+
+        let flags = &self.region_flags_extended[..self.region_flags_extended.len().min(u8::MAX as usize)];
+        bytes.push(flags.len() as u8);
+        for flag in flags {
+            bytes.write_u64::<LittleEndian>(*flag).unwrap();
+        }
+        bytes
     }
 }
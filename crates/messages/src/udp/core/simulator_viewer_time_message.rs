@@ -1,4 +1,4 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use glam::Vec3;
 
 use crate::errors::ParseError;
@@ -27,7 +27,6 @@ impl Packet {
     }
 }
 
-/// TODO: unimplemented
 #[derive(Debug, Clone)]
 /// The viewer uses this packet to determine the time of day in the region.
 pub struct SimulatorViewerTimeMessage {
@@ -48,19 +47,19 @@ pub struct SimulatorViewerTimeMessage {
 impl PacketData for SimulatorViewerTimeMessage {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
         let mut cursor = Cursor::new(bytes);
-        let seconds_since_start = cursor.read_u64::<LittleEndian>().unwrap();
-        let seconds_per_day = cursor.read_u32::<LittleEndian>().unwrap();
-        let seconds_per_year = cursor.read_u32::<LittleEndian>().unwrap();
+        let seconds_since_start = cursor.read_u64::<LittleEndian>()?;
+        let seconds_per_day = cursor.read_u32::<LittleEndian>()?;
+        let seconds_per_year = cursor.read_u32::<LittleEndian>()?;
         let sun_direction = Vec3::new(
-            cursor.read_f32::<LittleEndian>().unwrap(),
-            cursor.read_f32::<LittleEndian>().unwrap(),
-            cursor.read_f32::<LittleEndian>().unwrap(),
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
         );
-        let sun_phase = cursor.read_f32::<LittleEndian>().unwrap();
+        let sun_phase = cursor.read_f32::<LittleEndian>()?;
         let sun_angle_velocity = Vec3::new(
-            cursor.read_f32::<LittleEndian>().unwrap(),
-            cursor.read_f32::<LittleEndian>().unwrap(),
-            cursor.read_f32::<LittleEndian>().unwrap(),
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
         );
         Ok(SimulatorViewerTimeMessage {
             seconds_since_start,
@@ -72,6 +71,17 @@ impl PacketData for SimulatorViewerTimeMessage {
         })
     }
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let mut bytes = Vec::with_capacity(44);
+        bytes.write_u64::<LittleEndian>(self.seconds_since_start).unwrap();
+        bytes.write_u32::<LittleEndian>(self.seconds_per_day).unwrap();
+        bytes.write_u32::<LittleEndian>(self.seconds_per_year).unwrap();
+        for value in self.sun_direction.to_array() {
+            bytes.write_f32::<LittleEndian>(value).unwrap();
+        }
+        bytes.write_f32::<LittleEndian>(self.sun_phase).unwrap();
+        for value in self.sun_angle_velocity.to_array() {
+            bytes.write_f32::<LittleEndian>(value).unwrap();
+        }
+        bytes
     }
 }
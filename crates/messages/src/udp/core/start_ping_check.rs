@@ -6,6 +6,8 @@ use crate::{
         packet_types::PacketType,
     },
 };
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::Cursor;
 
 impl Packet {
     /// create a new start ping check packet
@@ -36,8 +38,9 @@ pub struct StartPingCheck {
 
 impl PacketData for StartPingCheck {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let ping_id = bytes[0];
-        let oldest_unacked = u32::from_le_bytes(bytes[1..5].try_into()?);
+        let mut cursor = Cursor::new(bytes);
+        let ping_id = cursor.read_u8()?;
+        let oldest_unacked = cursor.read_u32::<LittleEndian>()?;
 
         Ok(StartPingCheck {
             ping_id,
@@ -4,6 +4,7 @@ use crate::packet::{
     packet_protocol::{Packet, PacketData},
     packet_types::PacketType,
 };
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::io::Cursor;
 
 impl Packet {
@@ -22,16 +23,36 @@ impl Packet {
     }
 }
 
-#[derive(Debug, Clone)]
-/// TODO: UNIMPLEMENTED
-pub struct TestPacket {}
+#[derive(Debug, Clone, Default)]
+/// Test message, used for checking that a circuit can send and receive packets
+pub struct TestPacket {
+    /// test value
+    pub test1: u32,
+    /// four blocks of three test values
+    pub neighbor_block: [[u32; 3]; 4],
+}
 
 impl PacketData for TestPacket {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let mut _cursor = Cursor::new(bytes);
-        Ok(TestPacket {})
+        let mut cursor = Cursor::new(bytes);
+        let test1 = cursor.read_u32::<LittleEndian>()?;
+        let mut neighbor_block = [[0u32; 3]; 4];
+        for block in neighbor_block.iter_mut() {
+            for value in block.iter_mut() {
+                *value = cursor.read_u32::<LittleEndian>()?;
+            }
+        }
+        Ok(TestPacket {
+            test1,
+            neighbor_block,
+        })
     }
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let mut bytes = Vec::with_capacity(4 + 4 * 3 * 4);
+        bytes.write_u32::<LittleEndian>(self.test1).unwrap();
+        for value in self.neighbor_block.iter().flatten() {
+            bytes.write_u32::<LittleEndian>(*value).unwrap();
+        }
+        bytes
     }
 }
@@ -4,7 +4,9 @@ use crate::packet::{
     packet_protocol::{Packet, PacketData},
     packet_types::PacketType,
 };
-use std::io::Cursor;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Cursor, Read};
+use uuid::Uuid;
 
 impl Packet {
     /// create a new viewer effect packet
@@ -22,16 +24,92 @@ impl Packet {
     }
 }
 
-#[derive(Debug, Clone)]
-/// TODO: UNIMPLEMENTED
-pub struct ViewerEffect {}
+#[derive(Debug, Clone, Default)]
+/// Visual effects drawn by the viewer, such as the beam when editing objects or the glow when
+/// pointing at something. Sent by the viewer, and relayed by the server to other viewers.
+pub struct ViewerEffect {
+    /// ID of the agent sending the effect
+    pub agent_id: Uuid,
+    /// session ID of the agent sending the effect
+    pub session_id: Uuid,
+    /// the effects to display
+    pub effects: Vec<Effect>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// A single visual effect
+pub struct Effect {
+    /// ID of the effect
+    pub id: Uuid,
+    /// the agent that created the effect
+    pub agent_id: Uuid,
+    /// type of effect, such as a beam, sphere or look-at target
+    pub effect_type: u8,
+    /// how long the effect lasts, in seconds
+    pub duration: f32,
+    /// RGBA color of the effect
+    pub color: [u8; 4],
+    /// extra data for the effect, which depends on the type
+    pub type_data: Vec<u8>,
+}
 
 impl PacketData for ViewerEffect {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let mut _cursor = Cursor::new(bytes);
-        Ok(ViewerEffect {})
+        let mut cursor = Cursor::new(bytes);
+
+        let mut id_bytes = [0u8; 16];
+        cursor.read_exact(&mut id_bytes)?;
+        let agent_id = Uuid::from_bytes(id_bytes);
+        cursor.read_exact(&mut id_bytes)?;
+        let session_id = Uuid::from_bytes(id_bytes);
+
+        let count = cursor.read_u8()? as usize;
+        let mut effects = Vec::with_capacity(count);
+        for _ in 0..count {
+            cursor.read_exact(&mut id_bytes)?;
+            let id = Uuid::from_bytes(id_bytes);
+            cursor.read_exact(&mut id_bytes)?;
+            let agent_id = Uuid::from_bytes(id_bytes);
+            let effect_type = cursor.read_u8()?;
+            let duration = cursor.read_f32::<LittleEndian>()?;
+            let mut color = [0u8; 4];
+            cursor.read_exact(&mut color)?;
+            let mut type_data = vec![0u8; cursor.read_u8()? as usize];
+            cursor.read_exact(&mut type_data)?;
+
+            effects.push(Effect {
+                id,
+                agent_id,
+                effect_type,
+                duration,
+                color,
+                type_data,
+            });
+        }
+
+        Ok(ViewerEffect {
+            agent_id,
+            session_id,
+            effects,
+        })
     }
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+
+        let effects = &self.effects[..self.effects.len().min(u8::MAX as usize)];
+        bytes.push(effects.len() as u8);
+        for effect in effects {
+            bytes.extend_from_slice(effect.id.as_bytes());
+            bytes.extend_from_slice(effect.agent_id.as_bytes());
+            bytes.push(effect.effect_type);
+            bytes.write_f32::<LittleEndian>(effect.duration).unwrap();
+            bytes.extend_from_slice(&effect.color);
+            let type_data = &effect.type_data[..effect.type_data.len().min(u8::MAX as usize)];
+            bytes.push(type_data.len() as u8);
+            bytes.extend_from_slice(type_data);
+        }
+        bytes
     }
 }
@@ -6,7 +6,7 @@ use crate::{
         packet_types::PacketType,
     },
 };
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::io::{Cursor, Read};
 
 impl Packet {
@@ -17,7 +17,7 @@ impl Packet {
                 id: 11,
                 reliable: true,
                 zerocoded: false,
-                frequency: PacketFrequency::Low,
+                frequency: PacketFrequency::High,
                 ..Default::default()
             },
             body: PacketType::LayerData(Box::new(layer_data)),
@@ -122,8 +122,17 @@ impl PacketData for LayerData {
         };
         Ok(data)
     }
-    // TOOD: fix this to_bytes function
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let mut bytes = Vec::with_capacity(self.layer_content.len() + 7);
+        bytes.push(self.layer_type.to_bytes());
+
+        // the data block contains the stride, patch size and layer type, followed by the patches
+        let data_size = (self.layer_content.len() + 4).min(u16::MAX as usize);
+        bytes.write_u16::<LittleEndian>(data_size as u16).unwrap();
+        bytes.write_u16::<LittleEndian>(self.stride).unwrap();
+        bytes.push(self.patch_size);
+        bytes.push(self.layer_type.to_bytes());
+        bytes.extend_from_slice(&self.layer_content[..data_size - 4]);
+        bytes
     }
 }
@@ -0,0 +1,98 @@
+use crate::errors::ParseError;
+use crate::packet::{
+    header::{Header, PacketFrequency},
+    packet_protocol::{Packet, PacketData},
+    packet_types::PacketType,
+};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde_llsd_benthic::{LLSDValue, de::notation};
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read};
+
+/// The method of messages that carry the overrides an object makes to its PBR materials
+pub const METHOD_MATERIAL_OVERRIDE: u16 = 0x4175;
+
+impl Packet {
+    /// create a new generic streaming message packet
+    pub fn new_generic_streaming_message(
+        generic_streaming_message: GenericStreamingMessage,
+    ) -> Self {
+        Packet {
+            header: Header {
+                id: 31,
+                reliable: false,
+                zerocoded: false,
+                frequency: PacketFrequency::High,
+                ..Default::default()
+            },
+            body: PacketType::GenericStreamingMessage(Box::new(generic_streaming_message)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Sent by the server to stream data that doesn't have a message of its own
+pub struct GenericStreamingMessage {
+    /// how the data is decoded
+    pub method: u16,
+    /// the streamed data
+    pub data: Vec<u8>,
+}
+
+impl PacketData for GenericStreamingMessage {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(bytes);
+        let method = cursor.read_u16::<LittleEndian>()?;
+        let length = cursor.read_u16::<LittleEndian>()?;
+        let mut data = vec![0u8; length as usize];
+        cursor.read_exact(&mut data)?;
+        Ok(GenericStreamingMessage { method, data })
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let length = self.data.len().min(u16::MAX as usize);
+        let mut bytes = Vec::with_capacity(4 + length);
+        bytes.write_u16::<LittleEndian>(self.method).unwrap();
+        bytes.write_u16::<LittleEndian>(length as u16).unwrap();
+        bytes.extend_from_slice(&self.data[..length]);
+        bytes
+    }
+}
+
+/// The overrides an object makes to the PBR materials of its faces.
+///
+/// Each override is a sparse LLSD map of the values that differ from the face's material, which
+/// can be applied with
+/// [`GltfMaterial::apply_override`](crate::http::gltf_material::GltfMaterial::apply_override).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MaterialOverride {
+    /// scene local ID of the object
+    pub local_id: u32,
+    /// the override of each overridden face, by face index
+    pub faces: BTreeMap<u8, LLSDValue>,
+}
+
+impl MaterialOverride {
+    /// Parse the data of a generic streaming message with the
+    /// [`METHOD_MATERIAL_OVERRIDE`] method
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        let data = notation::from_str(&String::from_utf8_lossy(bytes))?;
+        let map = data
+            .as_map()
+            .ok_or_else(|| ParseError::InvalidField("Material override is not a map".into()))?;
+        let local_id = map
+            .get("id")
+            .and_then(|id| id.as_integer())
+            .ok_or_else(|| ParseError::MissingField("id".into()))?;
+        let faces = map.get("te").and_then(|faces| faces.as_array());
+        let overrides = map.get("od").and_then(|overrides| overrides.as_array());
+        Ok(MaterialOverride {
+            local_id: *local_id as u32,
+            faces: faces
+                .into_iter()
+                .flatten()
+                .zip(overrides.into_iter().flatten())
+                .filter_map(|(face, data)| Some((*face.as_integer()? as u8, data.clone())))
+                .collect(),
+        })
+    }
+}
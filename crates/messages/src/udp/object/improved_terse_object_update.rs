@@ -4,7 +4,7 @@ use crate::packet::{
     packet_protocol::{Packet, PacketData},
     packet_types::PacketType,
 };
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use glam::{Quat, Vec3, Vec4};
 use std::io::{Cursor, Read};
 
@@ -59,52 +59,58 @@ pub struct TerseObjectData {
     pub rotation: Quat,
     /// angular velocity information
     pub angular_velocity: Vec3,
+    /// texture entry of the object, if its textures have changed. Usually empty.
+    pub texture_entry: Vec<u8>,
 }
 
 impl TerseObjectData {
     /// convert ImprovedTerseObjectData from bytes to a struct
-    pub fn from_bytes(data: &[u8]) -> Self {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ParseError> {
         let mut cursor = Cursor::new(data);
-        let local_id = cursor.read_u32::<LittleEndian>().unwrap();
-        let state = cursor.read_u8().unwrap();
-        let avatar = cursor.read_u8().unwrap() != 0;
+        let local_id = cursor.read_u32::<LittleEndian>()?;
+        let state = cursor.read_u8()?;
+        let avatar = cursor.read_u8()? != 0;
         let collision_plane = if avatar {
-            let x = cursor.read_f32::<LittleEndian>().unwrap();
-            let y = cursor.read_f32::<LittleEndian>().unwrap();
-            let z = cursor.read_f32::<LittleEndian>().unwrap();
-            let w = cursor.read_f32::<LittleEndian>().unwrap();
+            let x = cursor.read_f32::<LittleEndian>()?;
+            let y = cursor.read_f32::<LittleEndian>()?;
+            let z = cursor.read_f32::<LittleEndian>()?;
+            let w = cursor.read_f32::<LittleEndian>()?;
             Some(Vec4::new(x, y, z, w))
         } else {
             None
         };
         let position = Vec3::new(
-            cursor.read_f32::<LittleEndian>().unwrap(),
-            cursor.read_f32::<LittleEndian>().unwrap(),
-            cursor.read_f32::<LittleEndian>().unwrap(),
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
+            cursor.read_f32::<LittleEndian>()?,
         );
         let velocity = Vec3::new(
-            u16_to_float_cursor(&mut cursor, -128.0, 128.0),
-            u16_to_float_cursor(&mut cursor, -128.0, 128.0),
-            u16_to_float_cursor(&mut cursor, -128.0, 128.0),
+            u16_to_float_cursor(&mut cursor, -128.0, 128.0)?,
+            u16_to_float_cursor(&mut cursor, -128.0, 128.0)?,
+            u16_to_float_cursor(&mut cursor, -128.0, 128.0)?,
         );
         let acceleration = Vec3::new(
-            u16_to_float_cursor(&mut cursor, -64.0, 64.0),
-            u16_to_float_cursor(&mut cursor, -64.0, 64.0),
-            u16_to_float_cursor(&mut cursor, -64.0, 64.0),
+            u16_to_float_cursor(&mut cursor, -64.0, 64.0)?,
+            u16_to_float_cursor(&mut cursor, -64.0, 64.0)?,
+            u16_to_float_cursor(&mut cursor, -64.0, 64.0)?,
         );
-        let rotation = Quat::from_xyzw(
-            u16_to_float_cursor(&mut cursor, -1.0, 1.0),
-            u16_to_float_cursor(&mut cursor, -1.0, 1.0),
-            u16_to_float_cursor(&mut cursor, -1.0, 1.0),
-            u16_to_float_cursor(&mut cursor, -1.0, 1.0),
+        // quantization leaves the rotation slightly off unit length
+        let rotation = Quat::from_vec4(
+            Vec4::new(
+                u16_to_float_cursor(&mut cursor, -1.0, 1.0)?,
+                u16_to_float_cursor(&mut cursor, -1.0, 1.0)?,
+                u16_to_float_cursor(&mut cursor, -1.0, 1.0)?,
+                u16_to_float_cursor(&mut cursor, -1.0, 1.0)?,
+            )
+            .normalize_or(Vec4::W),
         );
         let angular_velocity = Vec3::new(
-            u16_to_float_cursor(&mut cursor, -64.0, 64.0),
-            u16_to_float_cursor(&mut cursor, -64.0, 64.0),
-            u16_to_float_cursor(&mut cursor, -64.0, 64.0),
+            u16_to_float_cursor(&mut cursor, -64.0, 64.0)?,
+            u16_to_float_cursor(&mut cursor, -64.0, 64.0)?,
+            u16_to_float_cursor(&mut cursor, -64.0, 64.0)?,
         );
 
-        Self {
+        Ok(Self {
             local_id,
             state,
             avatar,
@@ -114,13 +120,58 @@ impl TerseObjectData {
             acceleration,
             rotation,
             angular_velocity,
+            texture_entry: Vec::new(),
+        })
+    }
+
+    /// convert the terse object data to bytes. Velocity, acceleration, rotation and angular
+    /// velocity are quantized to u16s, so they lose precision.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(60);
+        bytes.write_u32::<LittleEndian>(self.local_id).unwrap();
+        bytes.push(self.state);
+        bytes.push(self.avatar as u8);
+        if self.avatar {
+            let plane = self.collision_plane.unwrap_or_default();
+            for value in plane.to_array() {
+                bytes.write_f32::<LittleEndian>(value).unwrap();
+            }
+        }
+        for value in self.position.to_array() {
+            bytes.write_f32::<LittleEndian>(value).unwrap();
+        }
+        for value in self.velocity.to_array() {
+            float_to_u16_bytes(&mut bytes, value, -128.0, 128.0);
+        }
+        for value in self.acceleration.to_array() {
+            float_to_u16_bytes(&mut bytes, value, -64.0, 64.0);
+        }
+        for value in self.rotation.to_array() {
+            float_to_u16_bytes(&mut bytes, value, -1.0, 1.0);
         }
+        for value in self.angular_velocity.to_array() {
+            float_to_u16_bytes(&mut bytes, value, -64.0, 64.0);
+        }
+        bytes
+    }
+}
+
+fn u16_to_float_cursor(cursor: &mut Cursor<&[u8]>, min: f32, max: f32) -> Result<f32, ParseError> {
+    let raw = cursor.read_u16::<LittleEndian>()?;
+    let step = (max - min) / 65535.0;
+    let value = min + (raw as f32) * step;
+    // zero can't be represented exactly, so values within one step of zero are snapped to it.
+    // Without this, stationary objects would drift.
+    if value.abs() < step {
+        Ok(0.0)
+    } else {
+        Ok(value)
     }
 }
 
-fn u16_to_float_cursor(cursor: &mut Cursor<&[u8]>, min: f32, max: f32) -> f32 {
-    let raw = cursor.read_u16::<LittleEndian>().unwrap();
-    min + (raw as f32) * ((max - min) / 65535.0)
+fn float_to_u16_bytes(bytes: &mut Vec<u8>, value: f32, min: f32, max: f32) {
+    let raw = ((value.clamp(min, max) - min) * (65535.0 / (max - min))).round() as u16;
+    bytes.write_u16::<LittleEndian>(raw).unwrap();
 }
 
 impl PacketData for ImprovedTerseObjectUpdate {
@@ -136,12 +187,13 @@ impl PacketData for ImprovedTerseObjectUpdate {
             let data_len = cursor.read_u8()?;
             let mut data_buf = vec![0; data_len as usize];
             cursor.read_exact(&mut data_buf)?;
-            let data = TerseObjectData::from_bytes(&data_buf);
-            objects.push(data);
+            let mut data = TerseObjectData::from_bytes(&data_buf)?;
 
             let tex_len = cursor.read_u16::<LittleEndian>()?;
             let mut tex_buf = vec![0; tex_len as usize];
             cursor.read_exact(&mut tex_buf)?;
+            data.texture_entry = tex_buf;
+            objects.push(data);
         }
 
         Ok(ImprovedTerseObjectUpdate {
@@ -151,6 +203,22 @@ impl PacketData for ImprovedTerseObjectUpdate {
         })
     }
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let objects = &self.objects[..self.objects.len().min(u8::MAX as usize)];
+        let mut bytes = Vec::new();
+        bytes.write_u64::<LittleEndian>(self.region_handle).unwrap();
+        bytes.write_u16::<LittleEndian>(self.time_dilation).unwrap();
+        bytes.push(objects.len() as u8);
+        for object in objects {
+            let data = object.to_bytes();
+            bytes.push(data.len() as u8);
+            bytes.extend(data);
+            let texture_entry =
+                &object.texture_entry[..object.texture_entry.len().min(u16::MAX as usize)];
+            bytes
+                .write_u16::<LittleEndian>(texture_entry.len() as u16)
+                .unwrap();
+            bytes.extend_from_slice(texture_entry);
+        }
+        bytes
     }
 }
@@ -4,6 +4,7 @@ use crate::packet::{
     packet_protocol::{Packet, PacketData},
     packet_types::PacketType,
 };
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::io::Cursor;
 
 impl Packet {
@@ -22,16 +23,33 @@ impl Packet {
     }
 }
 
-#[derive(Debug, Clone)]
-/// TODO: UNIMPLEMENTED
-pub struct KillObject {}
+#[derive(Debug, Clone, Default)]
+/// Sent by the server when objects are removed from the scene, such as when they are deleted,
+/// taken into inventory, or move out of view.
+pub struct KillObject {
+    /// local IDs of the objects to remove
+    pub object_ids: Vec<u32>,
+}
 
 impl PacketData for KillObject {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let mut _cursor = Cursor::new(bytes);
-        Ok(KillObject {})
+        let mut cursor = Cursor::new(bytes);
+
+        let count = cursor.read_u8()? as usize;
+        let mut object_ids = Vec::with_capacity(count);
+        for _ in 0..count {
+            object_ids.push(cursor.read_u32::<LittleEndian>()?);
+        }
+
+        Ok(KillObject { object_ids })
     }
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let count = self.object_ids.len().min(u8::MAX as usize);
+        let mut bytes = Vec::with_capacity(1 + count * 4);
+        bytes.push(count as u8);
+        for id in &self.object_ids[..count] {
+            bytes.write_u32::<LittleEndian>(*id).unwrap();
+        }
+        bytes
     }
 }
@@ -1,6 +1,38 @@
-/// TODO: UNIMPLEMENTED
+/// # Kill Object
+/// <https://wiki.secondlife.com/wiki/KillObject>
+///
+/// Sent by the server when objects are removed from the scene.
+///
+/// ## Header
+/// | KillObject |||||
+/// |--------------|-------------|----------------|-------------------|---------------------|
+/// | Packet Header| id: 16 | reliable: true | zerocoded: false | frequency: High |
+///
+/// ## Packet Structure
+/// | KillObject |          |                    |                                 |
+/// |-----------------------|----------|--------------------|---------------------------------|
+/// | ObjectCount | 1 byte | [u8] | number of objects |
+/// | ID | 4 bytes each | [u32] | local ID of each object to remove |
 pub mod kill_object;
-/// TODO: UNIMPLEMENTED
+/// # Multiple Object Update
+/// <https://wiki.secondlife.com/wiki/MultipleObjectUpdate>
+///
+/// Sent by the viewer to move, rotate or scale several objects at once.
+///
+/// ## Header
+/// | MultipleObjectUpdate |||||
+/// |--------------|-------------|----------------|-------------------|---------------------|
+/// | Packet Header| id: 2 | reliable: true | zerocoded: false | frequency: Medium |
+///
+/// ## Packet Structure
+/// | MultipleObjectUpdate |          |                    |                                 |
+/// |-----------------------|----------|--------------------|---------------------------------|
+/// | AgentID | 16 bytes | [Uuid](uuid::Uuid) | ID of the agent |
+/// | SessionID | 16 bytes | [Uuid](uuid::Uuid) | ID of the session |
+/// | ObjectCount | 1 byte | [u8] | number of updates |
+/// | ObjectLocalID | 4 bytes | [u32] | local ID of the object |
+/// | Type | 1 byte | [u8] | which values the data contains |
+/// | Data | 1 + n bytes | [Vec<u8>] | length prefixed position, rotation and scale |
 pub mod multiple_object_update;
 /// # Object Update Cached
 /// <https://wiki.secondlife.com/wiki/ObjectUpdateCached>
@@ -57,7 +89,7 @@ pub mod object_update_cached;
 /// | TerseObjectData| |       |                                          |
 /// |---------------|---------|-------|------------------------------------------|
 /// | local_id      | 4 bytes | [u32] | region local ID. used for most operations in lieu of the object's full UUID. |
-/// | state         | 1 byte  | [u8]  | Unused except by grass to determine grass species |
+/// | state         | 1 byte  | [u8]  | Grass and tree species, or the attachment point of attachments |
 /// | avatar        | 1 byte  | [bool]| flags the object as an avatar and not just an object|
 /// | collision_plane_x| 4 or 0 bytes | [f32] | If the object is an avatar, read 4 bytes for the collision plane x. If not, read 0. |
 /// | collision_plane_y| 4 or 0 bytes | [f32] | If the object is an avatar, read 4 bytes for the collision plane y. If not, read 0. |
@@ -79,6 +111,11 @@ pub mod object_update_cached;
 /// | angular_velocity_x| 2 bytes | [u16] | x angular velocity of the object |
 /// | angular_velocity_y| 2 bytes | [u16] | y angular velocity of the object |
 /// | angular_velocity_z| 2 bytes | [u16] | z angular velocity of the object |
+/// | texture_entry_length| 2 bytes | [u16] | length of the texture entry that follows the data block. Usually 0 |
+///
+/// The u16 values are quantized over a fixed range. Velocity is in -128 to 128, acceleration and
+/// angular velocity are in -64 to 64, and each rotation component is in -1 to 1. Zero falls
+/// between two steps, so values within one step of zero are read as zero.
 ///
 pub mod improved_terse_object_update;
 
@@ -110,7 +147,7 @@ pub mod improved_terse_object_update;
 /// | full_id       | 16 bytes| [Uuid](uuid::Uuid) | The full UUID of the object |
 /// | local_id      | 4 bytes | [u32] | region local ID. used for most operations in lieu of the object's full UUID. |
 /// | pcode         | 1 byte  | [u8]  | Type of object represented by the packet. Avatar, grass, tree, etc |
-/// | state         | 1 byte  | [u8]  | Unused except by grass to determine grass species |
+/// | state         | 1 byte  | [u8]  | Grass and tree species, or the attachment point of attachments |
 /// | crc           | 4 bytes | [u32] | CRC values. Not currently checked against anything. |
 /// | material      | 1 byte  | [u8]  | Type of material the object is made of. Wood, plastic, flesh, etc |
 /// | click_action  | 1 byte  | [u8]  | The default action taken when the object is clicked. Open, sit, etc |
@@ -123,35 +160,33 @@ pub mod improved_terse_object_update;
 /// | rotation_z    | 4 bytes | [f32] | the z value of the object's rotation |
 /// | rotation_z    | 4 bytes | [f32] | the z value of the object's rotation |
 /// | rotation_z    | 4 bytes | [f32] | the z value of the object's rotation |
-/// | owner_id      | 16 bytes or 0 | [Uuid](uuid::Uuid) | If the HasParticles, HasParticlesLegacy, or HasSound flags are present, read 16 bytes for the owner. Otherwise read 0.|
+/// | owner_id      | 16 bytes | [Uuid](uuid::Uuid) | The owner of the object. Only meaningful if the HasParticles, HasParticlesLegacy, or HasSound flags are present.|
 /// | angular_velocity_x| 4 bytes or 0| [f32] | If the HasAngularVelocity flag is present, read 4 bytes for the angular velocity x value. if not, read 0. |
 /// | angular_velocity_y| 4 bytes or 0| [f32] | If the HasAngularVelocity flag is present, read 4 bytes for the angular velocity y value. if not, read 0. |
 /// | angular_velocity_z| 4 bytes or 0| [f32] | If the HasAngularVelocity flag is present, read 4 bytes for the angular velocity z value. if not, read 0. |
-/// | parent        | 4 bytes or 0 |[f32]| If the HasParent flag is present, read 4 bytes for the parent's scene-local ID. If not, read 0.|
-/// | text_length   | 1 byte or 0 | [u8]  | If the HasText flag is present, read 1 byte for the text length. If not, read 0. |
-/// | text          | variable bytes || If the HasText flag is present, read text_length bytes, to use as the object's hover text.|
+/// | parent        | 4 bytes or 0 |[u32]| If the HasParent flag is present, read 4 bytes for the parent's scene-local ID. If not, read 0.|
+/// | tree_species  | 1 byte or 0 | [u8] | If the Tree flag is present, read 1 byte for the species of the tree or grass. If not, read 0.|
+/// | scratch_pad_length | 1 byte or 0 | [u8] | If the ScratchPad flag is present, read 1 byte for the scratch pad length. If not, read 0.|
+/// | scratch_pad   | variable bytes || If the ScratchPad flag is present, read scratch_pad_length bytes of scratch pad data. If not, read 0.|
+/// | text          | variable bytes || If the HasText flag is present, read a null terminated string to use as the object's hover text.|
 /// | text_color_r  | 1 or 0 bytes| [u8]| If the HasText flag is present, read 1 byte for the text's red value. If not read 0.|
 /// | text_color_g  | 1 or 0 bytes| [u8]| If the HasText flag is present, read 1 byte for the text's green value. If not read 0.|
 /// | text_color_b  | 1 or 0 bytes| [u8]| If the HasText flag is present, read 1 byte for the text's blue value. If not read 0.|
 /// | text_color_a  | 1 or 0 bytes| [u8]| If the HasText flag is present, read 1 byte for the text's alpha value. If not read 0.|
-/// | media_length  | 1 byte or 0 bytes | [u8]  | If the MediaURL flag is present, read 1 byte for the media url length. If not, read 0.|
-/// | media_url     | variable bytes || If the MediaURL flag is present, read media_length bytes, to use as the URL for any media attached to the object. Will always be a webpage. |
-/// | particle_system_legacy_len| 1 byte or 0 bytes| [u8]| If the HasParticlesLegacy or HasParticles flags are set, read 1 byte for the particle system's length. If not, read 0. |
-/// | particle_sytem_legacy| variable bytes || If the HasParticlesLegacy or HasParticles flags are set, read particle_system_len bytes for the particle system. If not, read 0. |
-/// | [extra_params](#extra-params)| variable bytes || The first byte in the extra_params field is the number of extra params present in the object. If it is zero, read nothing. If it is not zero, see [extra_params](#extra-params)|
+/// | media_url     | variable bytes || If the MediaURL flag is present, read a null terminated string to use as the URL for any media attached to the object. Will always be a webpage. |
+/// | particle_sytem_legacy| 86 or 0 bytes || If the HasParticlesLegacy flag is set, read 86 bytes for the legacy particle system. If not, read 0. |
+/// | [extra_params](#extra-params)| variable bytes || The first byte in the extra_params field is the number of extra params present in the object. If it is zero, read nothing else. If it is not zero, see [extra_params](#extra-params)|
 /// | sound_id      | 16 or 0 bytes| [Uuid](uuid::Uuid) | If the HasSound flag is present, read 16 bytes for the sound's UUID. If not, read 0. |
 /// | gain          | 4 or 0 bytes | [f32] | If the HasSound flag is present, read 4 bytes for the gain. If not, read 0. |
 /// | flags         | 1 or 0 byte  | [u8]  | If the HasSound flag is present, read 1 byte for the flags. If not, read 0.
 /// | radius        | 4 or 0 bytes | [f32] | If the HasSound flag is present, read 4 bytes for the audible radius. If not, read 0. |
-/// | name_value_length| 2 or 0 bytes| [u16] | if the HasNameValues flag is present, read 2 bytes for the name values length. If not, read 0. |
-/// | name_value    | variable bytes || If the HasNameValues flag is present, read name_value_length bytes a the name_value. If not, read 0. |
+/// | name_value    | variable bytes || If the HasNameValues flag is present, read a null terminated string as the name_value. If not, read 0. |
 /// | [primitive_geometry](#primitive-geometry) | 23 bytes | | Data for the viewer to draw primitive objects. Not optional, for some reason.|
-/// | texture_entry_length| 16 bytes| [Uuid](uuid::Uuid) | The length of the texture entry data |
+/// | texture_entry_length| 4 bytes| [u32] | The length of the texture entry data |
 /// | texture_entry | variable bytes || Texture data for the object |
-/// | texture_animation_length| 1 or 0 bytes |[u8]| If the TextureAnimation flag is present, read 1 byte for the texture animation length. If not, read 0. |
+/// | texture_animation_length| 4 or 0 bytes |[u32]| If the TextureAnimation flag is present, read 4 bytes for the texture animation length. If not, read 0. |
 /// | texture_aimation | variable bytes || If the TextureAnimation flag is present, read texture_animation_length bytes for the texture_animation data. If not, read 0. |
-/// | particle_system_length| 1 byte | [u8] | The length of the particle system data block |
-/// | particle_sytem | variable bytes || The particle system information |
+/// | particle_sytem | variable bytes || If the HasParticles flag is present, the rest of the data block is the particle system information. |
 pub mod object_update_compressed;
 
 /// # Request Multiple Objects
@@ -197,9 +232,9 @@ pub mod request_multiple_objects;
 /// |---------------|---------|-------|--------------------------------------|
 /// | region_handle | 8 bytes | [u64] | region handle ID of the simulator |
 /// | time_dilation | 2 bytes | [u16] | The current lag from the server. Used by physics simulations to keep up with real time. |
-/// | offset        | 1 byte  |       | Unused byte                          |
+/// | object_count  | 1 byte  | [u8]  | Number of objects in the packet. Only the first is read. |
 /// | id            | 4 bytes | [u32] | region local ID. used for most operations in lieu of the object's full UUID. |
-/// | state         | 1 byte  | [u8]  | Unused except by grass to determine grass species |
+/// | state         | 1 byte  | [u8]  | Grass and tree species, or the attachment point of attachments |
 /// | full_id       | 16 bytes| [Uuid](uuid::Uuid) | The full UUID of the object |
 /// | crc           | 4 bytes | [u32] | CRC values. Not currently checked against anything. |
 /// | pcode         | 1 byte  | [u8]  | Type of object represented by the packet. Avatar, grass, tree, etc |
@@ -221,13 +256,12 @@ pub mod request_multiple_objects;
 /// | name_value    | variable bytes || Name value pairs specific to the object. Used for avatar names. |
 /// | data_length   | 2 bytes | [u16] | Number of bytes to read for the generic appended data |
 /// | data          | variable bytes || Generic appended data |
-/// | text_length   | 2 bytes | [u16]  | Number of bytes to read for the text data |
+/// | text_length   | 1 byte  | [u8]  | Number of bytes to read for the text data |
 /// | text          | variable bytes || Text that hovers over the object |
-/// | text_color_r  | 1 or 0 bytes| [u8]| Hover text color's red val. If text_length is zero, don't read.|
-/// | text_color_g  | 1 or 0 bytes| [u8]| Hover text color's green value. If text_length is zero, don't read.|
-/// | text_color_b  | 1 or 0 bytes| [u8]| Hover text color's blue value. If text_length is zero, don't read.|
-/// | text_color_a  | 1 or 0 bytes| [u8]| Hover text color's alpha value. If text_length is zero, don't read. |
-/// | offset        | 0 or 3 bytes| [u8]| If text_color is not read, read 3 bytes of padding after the text_length.|
+/// | text_color_r  | 1 byte  | [u8]  | Hover text color's red value. Sent even if there is no text.|
+/// | text_color_g  | 1 byte  | [u8]  | Hover text color's green value. Sent even if there is no text.|
+/// | text_color_b  | 1 byte  | [u8]  | Hover text color's blue value. Sent even if there is no text.|
+/// | text_color_a  | 1 byte  | [u8]  | Hover text color's alpha value. Sent even if there is no text.|
 /// | media_length  | 1 byte  | [u8]  | Number of bytes to read for the media URL |
 /// | media_url     | variable bytes || URL for any media attached to the object. Will always be a webpage. |
 /// | particle_len  | 1 byte  | [u8]  | Number of bytes to read for the particle system data |
@@ -250,6 +284,10 @@ pub mod request_multiple_objects;
 /// bytes, using f32s for its value, and low precision updates use the smallest amount of bytes,
 /// using u8s for its value.
 ///
+/// Medium and low precision values are quantized across a range. Positions range from half a
+/// region below to one and a half regions above the region, velocity, acceleration and angular
+/// velocity range from -256 to 256, and rotations range from -1 to 1.
+///
 /// ### High Precision Update with Foot Collision Plane
 ///
 /// | Motion Data       | 76 bytes|||
@@ -376,15 +414,15 @@ pub mod request_multiple_objects;
 /// | Extra Params ||||
 /// |--------|---------|-------|--------------------------------------|
 /// | extra_params_count| 1 byte | [u8] | Number of objects in the extra params field |
-/// | tag    | 1 byte  |[u8]   | The type of parameter this is        |
-/// | offset | 3 bytes |       | Three unused bytes of padding        |
+/// | param_type | 2 bytes | [u16] | The type of parameter this is    |
+/// | param_size | 4 bytes | [u32] | Number of bytes in the parameter |
 /// | [sculpt_param](#sculpt_param), [flexi_param](#flexi_param), [light_param](#light_param), [projection_param](#projection_param),   [mesh_flags_param](#mesh_flags_param),   [reflection_probe_param](#reflection_probe_param), |variable bytes||Optional parameters describing various data. Each packet can contain multiple parameters. This is stored as a list.|
 ///
 /// ## Sculpt Param
 /// | Sculpt Param |        |                    |                                                                 |
 /// |--------------|--------|--------------------|-----------------------------------------------------------------|
 /// | texture_id | 16 bytes | [Uuid](uuid::Uuid) | The ID of the sculpt texture. This is also used as the mesh ID. |
-/// | sculpt_type| 1 byte   | [u8]               | The type of the sculpt. 5 denotes a mesh.                       |
+/// | sculpt_type| 1 byte   | [u8]               | The type of the sculpt in the low 3 bits. 1 is a sphere, 2 a torus, 3 a plane, 4 a cylinder and 5 a mesh. 0x40 inverts the sculpt, and 0x80 mirrors it. |
 ///
 /// ## Flexi Param
 /// TODO: UNIMPLEMENTED
@@ -396,13 +434,43 @@ pub mod request_multiple_objects;
 /// TODO: UNIMPLEMENTED
 ///
 /// ## Mesh Flags Param
-/// TODO: UNIMPLEMENTED
+/// | Mesh Flags Param |    |          |                                                        |
+/// |------------------|----|----------|--------------------------------------------------------|
+/// | flags | 4 bytes       | [u32]    | 0x01 makes a rigged mesh an animated object            |
 ///
 /// ## Materials Param
-/// TODO: UNIMPLEMENTED
+/// | Materials Param |      |                    |                                            |
+/// |-----------------|------|--------------------|--------------------------------------------|
+/// | count | 1 byte         | [u8]               | Number of faces with a PBR material        |
+/// | face  | 1 byte each    | [u8]               | Index of the face                          |
+/// | material_id | 16 bytes each | [Uuid](uuid::Uuid) | ID of the face's GLTF material asset |
 ///
 /// ## Reflection Probe Param
-/// TODO:UNIMPLEMENTED
+/// | Reflection Probe Param |  |       |                                                      |
+/// |------------------------|--|-------|------------------------------------------------------|
+/// | ambiance | 4 bytes        | [f32] | Ambient light added by the probe                     |
+/// | clip_distance | 4 bytes   | [f32] | Distance within which objects are not captured       |
+/// | flags | 1 byte            | [u8]  | 0x01 is a box volume, 0x02 dynamic, and 0x04 a mirror |
 pub mod object_update;
 
+/// # Generic Streaming Message
+/// <https://wiki.secondlife.com/wiki/GenericStreamingMessage>
+///
+/// Sent by the server to stream data that doesn't have a message of its own. The method decides
+/// how the data is decoded. The only method in use carries the overrides objects make to the
+/// PBR materials of their faces, as LLSD notation.
+///
+/// ## Header
+/// | GenericStreamingMessage |||||
+/// |--------------|-------------|----------------|-------------------|---------------------|
+/// | Packet Header| id: 31 | reliable: false | zerocoded: false | frequency: High |
+///
+/// ## Packet Structure
+/// | GenericStreamingMessage |          |                    |                                 |
+/// |-----------------------|----------|--------------------|---------------------------------|
+/// | Method | 2 bytes | [u16] | how the data is decoded. 0x4175 is a material override |
+/// | DataLength | 2 bytes | [u16] | length of the data |
+/// | Data | variable bytes | [Vec<u8>] | the streamed data |
+pub mod generic_streaming_message;
+
 mod util;
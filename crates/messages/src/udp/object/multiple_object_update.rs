@@ -4,7 +4,9 @@ use crate::packet::{
     packet_protocol::{Packet, PacketData},
     packet_types::PacketType,
 };
-use std::io::Cursor;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Cursor, Read};
+use uuid::Uuid;
 
 impl Packet {
     /// create a new multiple object update packet
@@ -22,16 +24,73 @@ impl Packet {
     }
 }
 
-#[derive(Debug, Clone)]
-/// TODO: UNIMPLLEMENTED
-pub struct MultipleObjectUpdate {}
+#[derive(Debug, Clone, Default)]
+/// Sent by the viewer to move, rotate or scale several objects at once
+pub struct MultipleObjectUpdate {
+    /// ID of the agent editing the objects
+    pub agent_id: Uuid,
+    /// session ID of the agent editing the objects
+    pub session_id: Uuid,
+    /// the changes to each object
+    pub updates: Vec<ObjectUpdateData>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// A change to a single object
+pub struct ObjectUpdateData {
+    /// local ID of the object being updated
+    pub local_id: u32,
+    /// flags describing which of position, rotation and scale are contained in the data, and
+    /// if the change applies to the whole linkset
+    pub update_type: u8,
+    /// the new position, rotation and scale values
+    pub data: Vec<u8>,
+}
 
 impl PacketData for MultipleObjectUpdate {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
-        let mut _cursor = Cursor::new(bytes);
-        Ok(MultipleObjectUpdate {})
+        let mut cursor = Cursor::new(bytes);
+
+        let mut id_bytes = [0u8; 16];
+        cursor.read_exact(&mut id_bytes)?;
+        let agent_id = Uuid::from_bytes(id_bytes);
+        cursor.read_exact(&mut id_bytes)?;
+        let session_id = Uuid::from_bytes(id_bytes);
+
+        let count = cursor.read_u8()? as usize;
+        let mut updates = Vec::with_capacity(count);
+        for _ in 0..count {
+            let local_id = cursor.read_u32::<LittleEndian>()?;
+            let update_type = cursor.read_u8()?;
+            let mut data = vec![0u8; cursor.read_u8()? as usize];
+            cursor.read_exact(&mut data)?;
+            updates.push(ObjectUpdateData {
+                local_id,
+                update_type,
+                data,
+            });
+        }
+
+        Ok(MultipleObjectUpdate {
+            agent_id,
+            session_id,
+            updates,
+        })
     }
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.agent_id.as_bytes());
+        bytes.extend_from_slice(self.session_id.as_bytes());
+
+        let updates = &self.updates[..self.updates.len().min(u8::MAX as usize)];
+        bytes.push(updates.len() as u8);
+        for update in updates {
+            bytes.write_u32::<LittleEndian>(update.local_id).unwrap();
+            bytes.push(update.update_type);
+            let data = &update.data[..update.data.len().min(u8::MAX as usize)];
+            bytes.push(data.len() as u8);
+            bytes.extend_from_slice(data);
+        }
+        bytes
     }
 }
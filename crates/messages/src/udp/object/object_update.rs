@@ -1,6 +1,6 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use glam::{Quat, Vec3, Vec4};
-use rgb::Rgba;
+use rgb::{Rgb, Rgba};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -18,6 +18,7 @@ use crate::{
         texture_entry::TextureEntry,
     },
 };
+use std::collections::BTreeMap;
 use std::io::{self, Cursor, Read};
 
 impl Packet {
@@ -46,7 +47,8 @@ pub struct ObjectUpdate {
     pub time_dilation: f32,
     /// The region local ID of the tasks. UsedSerializeSerialize for most operations in lieu of the task's full UUID
     pub id: u32,
-    /// unused except by grass. Used to determine species of grass.
+    /// Species of grass and trees, or the attachment point of attached objects. See
+    /// [`AttachmentPoint::from_state`](crate::utils::attachment_point::AttachmentPoint::from_state).
     pub state: u8,
     /// Full UUID of the object
     pub full_id: Uuid,
@@ -106,8 +108,8 @@ impl PacketData for ObjectUpdate {
         let region_handle = cursor.read_u64::<LittleEndian>()?;
         let time_dilation = cursor.read_u16::<LittleEndian>()? as f32 / 65535.0;
 
-        // unsure what this is, but this sets the correct packet alignment
-        let _offset = cursor.read_u8()?;
+        // the number of ObjectData blocks. Only the first object in the packet is read.
+        let _object_count = cursor.read_u8()?;
         let id = cursor.read_u32::<LittleEndian>()?;
         let state = cursor.read_u8()?;
 
@@ -162,28 +164,17 @@ impl PacketData for ObjectUpdate {
         let mut data = vec![0u8; data_length as usize];
         cursor.read_exact(&mut data)?;
 
-        let text_length = cursor.read_u16::<LittleEndian>()?;
-        // only read the text color if there is text
-        let (text, text_color) = if text_length != 0 {
-            let mut text = vec![0u8; text_length as usize];
-            cursor.read_exact(&mut text)?;
-
-            let text = String::from_utf8_lossy(&text).to_string();
-            let text_color_r = cursor.read_u8()?;
-            let text_color_g = cursor.read_u8()?;
-            let text_color_b = cursor.read_u8()?;
-            let text_color_a = cursor.read_u8()?;
-            let text_color = Rgba {
-                r: text_color_r,
-                g: text_color_g,
-                b: text_color_b,
-                a: text_color_a,
-            };
-            (text, text_color)
-        } else {
-            // the protocol pads this to 5 bytes if there is no data
-            cursor.read_exact(&mut [0u8; 3])?;
-            ("".to_string(), Rgba::new(0, 0, 0, 0))
+        let text_length = cursor.read_u8()?;
+        let mut text = vec![0u8; text_length as usize];
+        cursor.read_exact(&mut text)?;
+        let text = String::from_utf8_lossy(&text).to_string();
+
+        // the text color is always sent, even if there is no text
+        let text_color = Rgba {
+            r: cursor.read_u8()?,
+            g: cursor.read_u8()?,
+            b: cursor.read_u8()?,
+            a: cursor.read_u8()?,
         };
 
         let media_url_length = cursor.read_u8()?;
@@ -261,10 +252,94 @@ impl PacketData for ObjectUpdate {
     }
 
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let mut bytes = Vec::new();
+        bytes.write_u64::<LittleEndian>(self.region_handle).unwrap();
+        bytes
+            .write_u16::<LittleEndian>((self.time_dilation * 65535.0).round() as u16)
+            .unwrap();
+
+        // one ObjectData block
+        bytes.push(1);
+        bytes.write_u32::<LittleEndian>(self.id).unwrap();
+        bytes.push(self.state);
+        bytes.extend_from_slice(self.full_id.as_bytes());
+        bytes.write_u32::<LittleEndian>(self.crc).unwrap();
+        bytes.push(self.pcode.to_bytes());
+        bytes.push(self.material.to_bytes());
+        bytes.push(self.click_action);
+        write_vec3(&mut bytes, self.scale);
+
+        let motion_data = self.motion_data.to_bytes();
+        bytes.push(motion_data.len() as u8);
+        bytes.extend(motion_data);
+
+        bytes.write_u32::<LittleEndian>(self.parent_id).unwrap();
+        bytes
+            .write_u32::<LittleEndian>(ObjectFlag::to_bytes(&self.update_flags))
+            .unwrap();
+        bytes.extend(self.primitive_geometry.to_bytes());
+
+        write_variable_u16(&mut bytes, &self.texture_entry.to_bytes());
+        write_variable_u8(&mut bytes, &self.texture_anim);
+        write_variable_u16(&mut bytes, self.name_value.as_bytes());
+        write_variable_u16(&mut bytes, &self.data);
+        write_variable_u8(&mut bytes, self.text.as_bytes());
+        bytes.extend([
+            self.text_color.r,
+            self.text_color.g,
+            self.text_color.b,
+            self.text_color.a,
+        ]);
+        write_variable_u8(&mut bytes, self.media_url.as_bytes());
+        write_variable_u8(&mut bytes, &self.particle_system_block);
+        match &self.extra_params {
+            Some(extra_params) => {
+                write_variable_u8(&mut bytes, &ExtraParams::to_bytes(extra_params));
+            }
+            None => bytes.push(0),
+        }
+
+        bytes.extend(self.sound.to_bytes());
+        bytes.push(self.joint_type);
+        write_vec3(&mut bytes, self.joint_pivot);
+        write_vec3(&mut bytes, self.joint_axis_or_anchor);
+        bytes
+    }
+}
+
+fn write_vec3(bytes: &mut Vec<u8>, value: Vec3) {
+    for value in value.to_array() {
+        bytes.write_f32::<LittleEndian>(value).unwrap();
     }
 }
 
+/// write a field with a one byte length. Longer values are truncated.
+fn write_variable_u8(bytes: &mut Vec<u8>, value: &[u8]) {
+    let value = &value[..value.len().min(u8::MAX as usize)];
+    bytes.push(value.len() as u8);
+    bytes.extend_from_slice(value);
+}
+
+/// write a field with a two byte length. Longer values are truncated.
+fn write_variable_u16(bytes: &mut Vec<u8>, value: &[u8]) {
+    let value = &value[..value.len().min(u16::MAX as usize)];
+    bytes.write_u16::<LittleEndian>(value.len() as u16).unwrap();
+    bytes.extend_from_slice(value);
+}
+
+/// The precision MotionData was sent with. Simulators send lower precision updates for objects
+/// that are far away or moving slowly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MotionPrecision {
+    /// values are sent as f32s
+    #[default]
+    High,
+    /// values are quantized to u16s
+    Medium,
+    /// values are quantized to u8s
+    Low,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 /// Stores ObjectUpdate update fields
 /// This contains information about the position, velocity, acceleration and etc of the object.
@@ -282,6 +357,8 @@ pub struct MotionData {
     pub rotation: Quat,
     /// The angular velocity of the object
     pub angular_velocity: Vec3,
+    /// The precision the data was sent with, and will be written with
+    pub precision: MotionPrecision,
 }
 impl MotionData {
     /// Matches the length of the data to the correct parsing function
@@ -373,39 +450,39 @@ impl MotionData {
             acceleration,
             rotation,
             angular_velocity,
+            precision: MotionPrecision::High,
         })
     }
     fn from_bytes_medium(bytes: &[u8]) -> io::Result<Self> {
         let mut cursor = Cursor::new(bytes);
+        let mut read = |min: f32, max: f32| -> io::Result<f32> {
+            Ok(u16_to_float(cursor.read_u16::<LittleEndian>()?, min, max))
+        };
         let position = Vec3::new(
-            cursor.read_u16::<LittleEndian>()? as f32,
-            cursor.read_u16::<LittleEndian>()? as f32,
-            cursor.read_u16::<LittleEndian>()? as f32,
+            read(POSITION_MIN, POSITION_MAX)?,
+            read(POSITION_MIN, POSITION_MAX)?,
+            read(POSITION_MIN, POSITION_MAX)?,
         );
-
         let velocity = Vec3::new(
-            cursor.read_u16::<LittleEndian>()? as f32,
-            cursor.read_u16::<LittleEndian>()? as f32,
-            cursor.read_u16::<LittleEndian>()? as f32,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
         );
-
         let acceleration = Vec3::new(
-            cursor.read_u16::<LittleEndian>()? as f32,
-            cursor.read_u16::<LittleEndian>()? as f32,
-            cursor.read_u16::<LittleEndian>()? as f32,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
+        );
+        let rotation = Quat::from_xyzw(
+            read(-1.0, 1.0)?,
+            read(-1.0, 1.0)?,
+            read(-1.0, 1.0)?,
+            read(-1.0, 1.0)?,
         );
-
-        let x = cursor.read_u16::<LittleEndian>()? as f32;
-        let y = cursor.read_u16::<LittleEndian>()? as f32;
-        let z = cursor.read_u16::<LittleEndian>()? as f32;
-        let w_sq = 1.0 - x * x - y * y - z * z;
-        let w = if w_sq > 0.0 { w_sq.sqrt() } else { 0.0 };
-        let rotation = Quat::from_xyzw(x, y, z, w);
-
         let angular_velocity = Vec3::new(
-            cursor.read_u16::<LittleEndian>()? as f32,
-            cursor.read_u16::<LittleEndian>()? as f32,
-            cursor.read_u16::<LittleEndian>()? as f32,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
         );
         Ok(Self {
             foot_collision_plane: None,
@@ -414,39 +491,39 @@ impl MotionData {
             acceleration,
             rotation,
             angular_velocity,
+            precision: MotionPrecision::Medium,
         })
     }
     fn from_bytes_low(bytes: &[u8]) -> io::Result<Self> {
         let mut cursor = Cursor::new(bytes);
+        let mut read = |min: f32, max: f32| -> io::Result<f32> {
+            Ok(u8_to_float(cursor.read_u8()?, min, max))
+        };
         let position = Vec3::new(
-            cursor.read_u8()? as f32,
-            cursor.read_u8()? as f32,
-            cursor.read_u8()? as f32,
+            read(POSITION_MIN, POSITION_MAX)?,
+            read(POSITION_MIN, POSITION_MAX)?,
+            read(POSITION_MIN, POSITION_MAX)?,
         );
-
         let velocity = Vec3::new(
-            cursor.read_u8()? as f32,
-            cursor.read_u8()? as f32,
-            cursor.read_u8()? as f32,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
         );
-
         let acceleration = Vec3::new(
-            cursor.read_u8()? as f32,
-            cursor.read_u8()? as f32,
-            cursor.read_u8()? as f32,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
+        );
+        let rotation = Quat::from_xyzw(
+            read(-1.0, 1.0)?,
+            read(-1.0, 1.0)?,
+            read(-1.0, 1.0)?,
+            read(-1.0, 1.0)?,
         );
-
-        let x = cursor.read_u8()? as f32;
-        let y = cursor.read_u8()? as f32;
-        let z = cursor.read_u8()? as f32;
-        let w_sq = 1.0 - x * x - y * y - z * z;
-        let w = if w_sq > 0.0 { w_sq.sqrt() } else { 0.0 };
-        let rotation = Quat::from_xyzw(x, y, z, w);
-
         let angular_velocity = Vec3::new(
-            cursor.read_u8()? as f32,
-            cursor.read_u8()? as f32,
-            cursor.read_u8()? as f32,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
+            read(-REGION_SIZE, REGION_SIZE)?,
         );
         Ok(Self {
             foot_collision_plane: None,
@@ -455,8 +532,78 @@ impl MotionData {
             acceleration,
             rotation,
             angular_velocity,
+            precision: MotionPrecision::Low,
         })
     }
+
+    /// Convert MotionData to bytes, using the precision it was read with. Medium and low precision
+    /// values are quantized, so they lose precision. Low precision data is never sent with a
+    /// collision plane.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(76);
+        if self.precision != MotionPrecision::Low
+            && let Some(plane) = self.foot_collision_plane
+        {
+            for value in plane.to_array() {
+                bytes.write_f32::<LittleEndian>(value).unwrap();
+            }
+        }
+
+        // high precision rotations are sent without w, which is recomputed as positive
+        let rotation = match self.precision {
+            MotionPrecision::High => {
+                let mut rotation = self.rotation.normalize();
+                if rotation.w < 0.0 {
+                    rotation = -rotation;
+                }
+                rotation.xyz().to_array().to_vec()
+            }
+            _ => self.rotation.to_array().to_vec(),
+        };
+
+        let mut write = |value: f32, min: f32, max: f32| match self.precision {
+            MotionPrecision::High => bytes.write_f32::<LittleEndian>(value).unwrap(),
+            MotionPrecision::Medium => bytes
+                .write_u16::<LittleEndian>(float_to_u16(value, min, max))
+                .unwrap(),
+            MotionPrecision::Low => bytes.push(float_to_u8(value, min, max)),
+        };
+        for value in self.position.to_array() {
+            write(value, POSITION_MIN, POSITION_MAX);
+        }
+        for value in self.velocity.to_array() {
+            write(value, -REGION_SIZE, REGION_SIZE);
+        }
+        for value in self.acceleration.to_array() {
+            write(value, -REGION_SIZE, REGION_SIZE);
+        }
+        for value in rotation {
+            write(value, -1.0, 1.0);
+        }
+        for value in self.angular_velocity.to_array() {
+            write(value, -REGION_SIZE, REGION_SIZE);
+        }
+        bytes
+    }
+}
+
+/// Quantized motion values are scaled to the width of the region
+const REGION_SIZE: f32 = 256.0;
+/// Quantized positions can be up to half a region outside of the region
+const POSITION_MIN: f32 = -0.5 * REGION_SIZE;
+const POSITION_MAX: f32 = 1.5 * REGION_SIZE;
+
+fn u16_to_float(value: u16, min: f32, max: f32) -> f32 {
+    min + value as f32 * ((max - min) / u16::MAX as f32)
+}
+fn float_to_u16(value: f32, min: f32, max: f32) -> u16 {
+    ((value.clamp(min, max) - min) * (u16::MAX as f32 / (max - min))).round() as u16
+}
+fn u8_to_float(value: u8, min: f32, max: f32) -> f32 {
+    min + value as f32 * ((max - min) / u8::MAX as f32)
+}
+fn float_to_u8(value: f32, min: f32, max: f32) -> u8 {
+    ((value.clamp(min, max) - min) * (u8::MAX as f32 / (max - min))).round() as u8
 }
 
 /// Type enum for extra parmeters included in object updates, used for decoding from a byte to a
@@ -482,8 +629,8 @@ pub enum ParamTypeTag {
 }
 
 impl ParamTypeTag {
-    /// convert from u8 byte to a parameter enum
-    pub fn from_bytes(byte: &u8) -> Self {
+    /// convert from the u16 type to a parameter enum
+    pub fn from_bytes(byte: &u16) -> Self {
         match byte {
             16 => ParamTypeTag::Flexi,
             32 => ParamTypeTag::Light,
@@ -497,6 +644,22 @@ impl ParamTypeTag {
     }
 }
 
+impl ExtraParams {
+    /// the u16 type of the parameter
+    pub fn param_type(&self) -> u16 {
+        match self {
+            ExtraParams::Flexi(_) => 16,
+            ExtraParams::Light(_) => 32,
+            ExtraParams::Sculpt(_) => 48,
+            ExtraParams::Projection(_) => 64,
+            ExtraParams::MeshFlags(_) => 112,
+            ExtraParams::Materials(_) => 128,
+            ExtraParams::ReflectionProbe(_) => 144,
+            ExtraParams::Unknown(data) => data.param_type,
+        }
+    }
+}
+
 /// Extra parameter enum to allow the object update to contain multiple params
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExtraParams {
@@ -526,7 +689,17 @@ pub struct SculptData {
     pub texture_id: Uuid,
     /// the type of the sculpt. If the sculpt type is 5, the packet contains a mesh.
     pub sculpt_type: SculptType,
+    /// Turns a sculpted prim inside out
+    pub invert: bool,
+    /// Mirrors a sculpted prim along its x axis
+    pub mirror: bool,
 }
+
+/// The low three bits of the sculpt type byte are the type, and the high bits are flags
+const SCULPT_TYPE_MASK: u8 = 0x07;
+const SCULPT_FLAG_INVERT: u8 = 0x40;
+const SCULPT_FLAG_MIRROR: u8 = 0x80;
+
 impl SculptData {
     /// converts bytes to a SculptData object
     pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
@@ -535,116 +708,346 @@ impl SculptData {
         cursor.read_exact(&mut texture_id_bytes)?;
         let texture_id = Uuid::from_bytes(texture_id_bytes);
 
-        let sculpt_type = SculptType::from_bytes(&cursor.read_u8()?);
+        let sculpt_type = cursor.read_u8()?;
         Ok(SculptData {
             texture_id,
-            sculpt_type,
+            sculpt_type: SculptType::from_bytes(&(sculpt_type & SCULPT_TYPE_MASK)),
+            invert: sculpt_type & SCULPT_FLAG_INVERT != 0,
+            mirror: sculpt_type & SCULPT_FLAG_MIRROR != 0,
         })
     }
+    /// converts a SculptData object to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.texture_id.as_bytes().to_vec();
+        let mut sculpt_type = self.sculpt_type.to_bytes();
+        if self.invert {
+            sculpt_type |= SCULPT_FLAG_INVERT;
+        }
+        if self.mirror {
+            sculpt_type |= SCULPT_FLAG_MIRROR;
+        }
+        bytes.push(sculpt_type);
+        bytes
+    }
 }
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-///TODO: UNIMPLEMENTED
+/// Flexible prim data. Flexible prims bend along their path like hair, flags and skirts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlexiData {
-    ///TODO: UNIMPLEMENTED
-    pub bytes: Vec<u8>,
+    /// How many sections the path is simulated with, from 0 to 3. Each level doubles the number
+    /// of sections.
+    pub softness: u8,
+    /// How strongly the prim returns to its resting shape, from 0 to 10
+    pub tension: f32,
+    /// How much the air slows the prim down, from 0 to 10
+    pub drag: f32,
+    /// How strongly gravity pulls the prim down, from -10 to 10
+    pub gravity: f32,
+    /// How strongly the wind pushes the prim, from 0 to 10
+    pub wind: f32,
+    /// A constant force applied to the prim, in region coordinates
+    pub force: Vec3,
 }
+impl Default for FlexiData {
+    /// The viewer's defaults for a newly created flexible prim
+    fn default() -> Self {
+        FlexiData {
+            softness: 2,
+            tension: 1.0,
+            drag: 2.0,
+            gravity: 0.3,
+            wind: 0.0,
+            force: Vec3::ZERO,
+        }
+    }
+}
+
+/// Tension and drag are stored in the low seven bits of their bytes, and the high bits of the two
+/// bytes hold the softness
+const FLEXI_VALUE_MASK: u8 = 0x7f;
+const FLEXI_SOFTNESS_BIT: u8 = 0x80;
+
 impl FlexiData {
-    ///TODO: UNIMPLEMENTED
+    /// converts bytes to a FlexiData object.
+    ///
+    /// Values are packed into single bytes in tenths. The force is optional, and older objects
+    /// leave it out.
     pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut cursor = Cursor::new(bytes);
+        let tension = cursor.read_u8()?;
+        let drag = cursor.read_u8()?;
+        let gravity = cursor.read_u8()?;
+        let wind = cursor.read_u8()?;
+        let force = if cursor.position() < bytes.len() as u64 {
+            Vec3::new(
+                cursor.read_f32::<LittleEndian>()?,
+                cursor.read_f32::<LittleEndian>()?,
+                cursor.read_f32::<LittleEndian>()?,
+            )
+        } else {
+            Vec3::ZERO
+        };
         Ok(FlexiData {
-            bytes: bytes.to_vec(),
+            softness: ((tension & FLEXI_SOFTNESS_BIT) >> 6) | ((drag & FLEXI_SOFTNESS_BIT) >> 7),
+            tension: (tension & FLEXI_VALUE_MASK) as f32 / 10.0,
+            drag: (drag & FLEXI_VALUE_MASK) as f32 / 10.0,
+            gravity: gravity as f32 / 10.0 - 10.0,
+            wind: wind as f32 / 10.0,
+            force,
         })
     }
+    /// converts a FlexiData object to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // the viewer rounds up slightly, so values like 0.3 don't truncate to 0.2
+        let pack = |value: f32| (value * 10.01).clamp(0.0, 255.0) as u8;
+        let mut bytes = vec![
+            (pack(self.tension) & FLEXI_VALUE_MASK) | ((self.softness & 2) << 6),
+            (pack(self.drag) & FLEXI_VALUE_MASK) | ((self.softness & 1) << 7),
+            pack(self.gravity + 10.0),
+            pack(self.wind),
+        ];
+        for value in self.force.to_array() {
+            bytes.write_f32::<LittleEndian>(value).unwrap();
+        }
+        bytes
+    }
 }
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-
-///TODO: UNIMPLEMENTED
+/// Light data. Turns the object into a point light, or a spot light if it also has
+/// [`ProjectionData`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LightData {
-    ///TODO: UNIMPLEMENTED
-    pub bytes: Vec<u8>,
+    /// The color of the light, in sRGB
+    pub color: Rgb<u8>,
+    /// How bright the light is, from 0 to 1
+    pub intensity: f32,
+    /// How far the light reaches, in meters
+    pub radius: f32,
+    /// Stored with the light, but not used by the viewer
+    pub cutoff: f32,
+    /// How quickly the light fades towards its radius, from 0 to 2
+    pub falloff: f32,
+}
+impl Default for LightData {
+    /// The viewer's defaults for a newly created light
+    fn default() -> Self {
+        LightData {
+            color: Rgb::new(255, 255, 255),
+            intensity: 1.0,
+            radius: 10.0,
+            cutoff: 0.0,
+            falloff: 0.75,
+        }
+    }
 }
 impl LightData {
-    ///TODO: UNIMPLEMENTED
+    /// converts bytes to a LightData object. The intensity is stored in the alpha of the color.
     pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut cursor = Cursor::new(bytes);
+        let mut color = [0u8; 4];
+        cursor.read_exact(&mut color)?;
         Ok(LightData {
-            bytes: bytes.to_vec(),
+            color: Rgb::new(color[0], color[1], color[2]),
+            intensity: color[3] as f32 / 255.0,
+            radius: cursor.read_f32::<LittleEndian>()?,
+            cutoff: cursor.read_f32::<LittleEndian>()?,
+            falloff: cursor.read_f32::<LittleEndian>()?,
         })
     }
+    /// converts a LightData object to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![
+            self.color.r,
+            self.color.g,
+            self.color.b,
+            (self.intensity.clamp(0.0, 1.0) * 255.0).round() as u8,
+        ];
+        for value in [self.radius, self.cutoff, self.falloff] {
+            bytes.write_f32::<LittleEndian>(value).unwrap();
+        }
+        bytes
+    }
 }
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-///TODO: UNIMPLEMENTED
+/// Projection data. Turns a light into a spot light that projects a texture along the object's
+/// negative Z axis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProjectionData {
-    ///TODO: UNIMPLEMENTED
-    pub bytes: Vec<u8>,
+    /// The texture the light projects. A nil ID projects plain light.
+    pub texture_id: Uuid,
+    /// The angle the light spreads over, in radians
+    pub fov: f32,
+    /// How sharply the texture is projected. Negative values blur it.
+    pub focus: f32,
+    /// How much light the projection adds to surfaces facing away from it
+    pub ambiance: f32,
+}
+impl Default for ProjectionData {
+    /// The viewer's defaults for a newly created projector
+    fn default() -> Self {
+        ProjectionData {
+            texture_id: Uuid::nil(),
+            fov: std::f32::consts::FRAC_PI_2,
+            focus: 0.0,
+            ambiance: 0.0,
+        }
+    }
 }
 impl ProjectionData {
-    ///TODO: UNIMPLEMENTED
+    /// converts bytes to a ProjectionData object
     pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut cursor = Cursor::new(bytes);
+        let mut texture_id = [0u8; 16];
+        cursor.read_exact(&mut texture_id)?;
         Ok(ProjectionData {
-            bytes: bytes.to_vec(),
+            texture_id: Uuid::from_bytes(texture_id),
+            fov: cursor.read_f32::<LittleEndian>()?,
+            focus: cursor.read_f32::<LittleEndian>()?,
+            ambiance: cursor.read_f32::<LittleEndian>()?,
         })
     }
+    /// converts a ProjectionData object to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.texture_id.as_bytes().to_vec();
+        for value in [self.fov, self.focus, self.ambiance] {
+            bytes.write_f32::<LittleEndian>(value).unwrap();
+        }
+        bytes
+    }
 }
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-///TODO: UNIMPLEMENTED
+/// Extended mesh data. Marks a rigged mesh as an animated object, which animates a skeleton of
+/// its own instead of being worn by an avatar.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct MeshFlagsData {
-    ///TODO: UNIMPLEMENTED
-    pub bytes: Vec<u8>,
+    /// Animates the mesh with its own skeleton
+    pub animated_mesh: bool,
 }
+
+const MESH_FLAG_ANIMATED: u32 = 0x01;
 impl MeshFlagsData {
-    ///TODO: UNIMPLEMENTED
+    /// converts bytes to a MeshFlagsData object. The flags are a single u32.
     pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let flags = Cursor::new(bytes).read_u32::<LittleEndian>()?;
         Ok(MeshFlagsData {
-            bytes: bytes.to_vec(),
+            animated_mesh: flags & MESH_FLAG_ANIMATED != 0,
         })
     }
+    /// converts a MeshFlagsData object to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut flags = 0;
+        if self.animated_mesh {
+            flags |= MESH_FLAG_ANIMATED;
+        }
+        flags.to_le_bytes().to_vec()
+    }
 }
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-///TODO: UNIMPLEMENTED
+/// Render material data. Gives faces of the object a PBR material, which replaces the texture
+/// and color of the face's texture entry.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct MaterialsData {
-    ///TODO: UNIMPLEMENTED
-    pub bytes: Vec<u8>,
+    /// The ID of the GLTF material asset of each face that has one, by face index
+    pub materials: BTreeMap<u8, Uuid>,
 }
 impl MaterialsData {
-    ///TODO: UNIMPLEMENTED
+    /// converts bytes to a MaterialsData object. The materials are a count, followed by the face
+    /// index and material ID of each material.
     pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
-        Ok(MaterialsData {
-            bytes: bytes.to_vec(),
-        })
+        let mut cursor = Cursor::new(bytes);
+        let count = cursor.read_u8()?;
+        let mut materials = BTreeMap::new();
+        for _ in 0..count {
+            let face = cursor.read_u8()?;
+            let mut material_id = [0u8; 16];
+            cursor.read_exact(&mut material_id)?;
+            materials.insert(face, Uuid::from_bytes(material_id));
+        }
+        Ok(MaterialsData { materials })
+    }
+    /// converts a MaterialsData object to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.materials.len() as u8];
+        for (face, material_id) in &self.materials {
+            bytes.push(*face);
+            bytes.extend_from_slice(material_id.as_bytes());
+        }
+        bytes
     }
 }
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-///TODO: UNIMPLEMENTED
+/// Reflection probe data. Turns the object into a probe that captures the scene around it for
+/// reflections on nearby surfaces.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ReflectionProbeData {
-    ///TODO: UNIMPLEMENTED
-    pub bytes: Vec<u8>,
+    /// How much the probe adds to the ambient light of the scene
+    pub ambiance: f32,
+    /// The distance from the probe within which objects are not captured
+    pub clip_distance: f32,
+    /// Captures a box shaped volume instead of a sphere
+    pub box_volume: bool,
+    /// Captures avatars and other moving objects as well as the static scene
+    pub dynamic: bool,
+    /// Renders the probe as a mirror
+    pub mirror: bool,
 }
+
+const PROBE_FLAG_BOX_VOLUME: u8 = 0x01;
+const PROBE_FLAG_DYNAMIC: u8 = 0x02;
+const PROBE_FLAG_MIRROR: u8 = 0x04;
 impl ReflectionProbeData {
-    ///TODO: UNIMPLEMENTED
+    /// converts bytes to a ReflectionProbeData object
     pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut cursor = Cursor::new(bytes);
+        let ambiance = cursor.read_f32::<LittleEndian>()?;
+        let clip_distance = cursor.read_f32::<LittleEndian>()?;
+        let flags = cursor.read_u8()?;
         Ok(ReflectionProbeData {
-            bytes: bytes.to_vec(),
+            ambiance,
+            clip_distance,
+            box_volume: flags & PROBE_FLAG_BOX_VOLUME != 0,
+            dynamic: flags & PROBE_FLAG_DYNAMIC != 0,
+            mirror: flags & PROBE_FLAG_MIRROR != 0,
         })
     }
+    /// converts a ReflectionProbeData object to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(9);
+        bytes.write_f32::<LittleEndian>(self.ambiance).unwrap();
+        bytes.write_f32::<LittleEndian>(self.clip_distance).unwrap();
+        let mut flags = 0;
+        for (set, flag) in [
+            (self.box_volume, PROBE_FLAG_BOX_VOLUME),
+            (self.dynamic, PROBE_FLAG_DYNAMIC),
+            (self.mirror, PROBE_FLAG_MIRROR),
+        ] {
+            if set {
+                flags |= flag;
+            }
+        }
+        bytes.push(flags);
+        bytes
+    }
 }
 
 /// If the type is unknown, store the extra params directly as bytes
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UnknownData {
+    /// the type of the parameter, kept so the parameter can be sent on unchanged
+    pub param_type: u16,
     /// the unknown bytes
     pub bytes: Vec<u8>,
 }
 impl UnknownData {
     /// directly store the bytes
-    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+    pub fn from_bytes(param_type: u16, bytes: &[u8]) -> io::Result<Self> {
         Ok(UnknownData {
+            param_type,
             bytes: bytes.to_vec(),
         })
     }
+    /// directly return the bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bytes.clone()
+    }
 }
 impl Default for ExtraParams {
     fn default() -> Self {
-        ExtraParams::Unknown(UnknownData { bytes: Vec::new() })
+        ExtraParams::Unknown(UnknownData::default())
     }
 }
 
@@ -660,17 +1063,9 @@ impl ExtraParams {
         };
         let mut extra_params = Vec::new();
         for _ in 0..extra_params_count {
-            let param_type_tag = ParamTypeTag::from_bytes(&cursor.read_u8()?);
-
-            // padding byte
-            cursor.read_u8()?;
-
-            let param_length = cursor.read_u8()?;
-
-            // three padding bytes
-            cursor.read_u8()?;
-            cursor.read_u8()?;
-            cursor.read_u8()?;
+            let param_type = cursor.read_u16::<LittleEndian>()?;
+            let param_type_tag = ParamTypeTag::from_bytes(&param_type);
+            let param_length = cursor.read_u32::<LittleEndian>()?;
             let mut param_data = vec![0u8; param_length as usize];
             cursor.read_exact(&mut param_data)?;
 
@@ -691,13 +1086,36 @@ impl ExtraParams {
                     ExtraParams::ReflectionProbe(ReflectionProbeData::from_bytes(&param_data)?)
                 }
                 ParamTypeTag::Unknown => {
-                    ExtraParams::Unknown(UnknownData::from_bytes(&param_data)?)
+                    ExtraParams::Unknown(UnknownData::from_bytes(param_type, &param_data)?)
                 }
             };
             extra_params.push(param);
         }
         Ok((extra_params, (cursor.position() - start)))
     }
+
+    /// convert a list of extra params to bytes, prefixed by the number of params.
+    /// Only the first 255 params are written.
+    pub fn to_bytes(extra_params: &[Self]) -> Vec<u8> {
+        let extra_params = &extra_params[..extra_params.len().min(u8::MAX as usize)];
+        let mut bytes = vec![extra_params.len() as u8];
+        for param in extra_params {
+            let data = match param {
+                ExtraParams::Flexi(data) => data.to_bytes(),
+                ExtraParams::Light(data) => data.to_bytes(),
+                ExtraParams::Sculpt(data) => data.to_bytes(),
+                ExtraParams::Projection(data) => data.to_bytes(),
+                ExtraParams::MeshFlags(data) => data.to_bytes(),
+                ExtraParams::Materials(data) => data.to_bytes(),
+                ExtraParams::ReflectionProbe(data) => data.to_bytes(),
+                ExtraParams::Unknown(data) => data.to_bytes(),
+            };
+            bytes.write_u16::<LittleEndian>(param.param_type()).unwrap();
+            bytes.write_u32::<LittleEndian>(data.len() as u32).unwrap();
+            bytes.extend(data);
+        }
+        bytes
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
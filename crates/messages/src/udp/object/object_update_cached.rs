@@ -1,4 +1,4 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
 use crate::errors::ParseError;
 use crate::packet::{
@@ -70,6 +70,18 @@ impl PacketData for ObjectUpdateCached {
         })
     }
     fn to_bytes(&self) -> Vec<u8> {
-        Vec::new()
+        let objects = &self.objects[..self.objects.len().min(u8::MAX as usize)];
+        let mut bytes = Vec::with_capacity(11 + objects.len() * 12);
+        bytes.write_u64::<LittleEndian>(self.region_handle).unwrap();
+        bytes.write_u16::<LittleEndian>(self.time_dilation).unwrap();
+        bytes.push(objects.len() as u8);
+        for object in objects {
+            bytes.write_u32::<LittleEndian>(object.id).unwrap();
+            bytes.write_u32::<LittleEndian>(object.crc).unwrap();
+            bytes
+                .write_u32::<LittleEndian>(ObjectFlag::to_bytes(&object.flags))
+                .unwrap();
+        }
+        bytes
     }
 }
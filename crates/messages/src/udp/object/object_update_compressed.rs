@@ -1,4 +1,4 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use glam::{Quat, Vec3};
 use rgb::Rgba;
 use uuid::Uuid;
@@ -17,7 +17,7 @@ use crate::utils::path::Path;
 use crate::utils::sound::AttachedSound;
 use crate::utils::texture_entry::TextureEntry;
 
-use std::io::{Cursor, Read};
+use std::io::{BufRead, Cursor, Read};
 
 /// bitflags for compressed data flags. CompressedObjectUpdates are decoded conditionally, based on
 /// the flags defined here. If these are not present, portions are not decoded.
@@ -73,6 +73,10 @@ impl CompressedFlag {
         }
         flags
     }
+    /// convert a list of compressed flags to their bits
+    pub fn to_bytes(flags: &[CompressedFlag]) -> u32 {
+        flags.iter().fold(0, |bits, flag| bits | *flag as u32)
+    }
 }
 impl Packet {
     /// create a new object update compressed packet
@@ -114,7 +118,7 @@ pub struct ObjectDataCompressed {
     pub local_id: u32,
     /// type of the object
     pub pcode: ObjectType,
-    /// object state
+    /// object state. Holds the attachment point of attached objects.
     pub state: u8,
     /// crc or pseudo crc
     pub crc: u32,
@@ -136,6 +140,10 @@ pub struct ObjectDataCompressed {
     pub angular_velocity: Option<Vec3>,
     /// local ID of the parent within the scene
     pub parent_id: Option<u32>,
+    /// species of the tree or grass, for tree and grass objects
+    pub tree_species: Option<u8>,
+    /// scratch pad data. Unused by viewers.
+    pub scratch_pad: Option<Vec<u8>>,
     /// Hovering text above the object
     pub text: Option<String>,
     /// text color above the object
@@ -156,7 +164,7 @@ pub struct ObjectDataCompressed {
     pub texture_entry: TextureEntry,
     /// texture animation data for the object
     pub texture_animation: Option<Vec<u8>>,
-    /// particle system information
+    /// particle system information. Empty if the object has no particle system.
     pub particle_system: Vec<u8>,
 }
 
@@ -169,222 +177,368 @@ impl PacketData for ObjectUpdateCompressed {
         let mut object_data = Vec::new();
         for _ in 0..object_data_length {
             let update_flags = ObjectFlag::from_bytes(cursor.read_u32::<LittleEndian>()?);
-            let _data_size = cursor.read_u16::<LittleEndian>()? as usize;
-
-            let mut full_id_bytes = [0u8; 16];
-            cursor.read_exact(&mut full_id_bytes)?;
-            let full_id = Uuid::from_bytes(full_id_bytes);
-
-            let local_id = cursor.read_u32::<LittleEndian>()?;
-
-            let pcode = ObjectType::from_bytes(&cursor.read_u8()?);
-            let state = cursor.read_u8()?;
-            let crc = cursor.read_u32::<LittleEndian>()?;
-            let material = MaterialType::from_bytes(&cursor.read_u8()?);
-            let click_action = cursor.read_u8()?;
-
-            let scale_x = cursor.read_f32::<LittleEndian>()?;
-            let scale_y = cursor.read_f32::<LittleEndian>()?;
-            let scale_z = cursor.read_f32::<LittleEndian>()?;
-            let scale = Vec3 {
-                x: scale_x,
-                y: scale_y,
-                z: scale_z,
-            };
-            // flip the Z and Y positions for easier rendering in other coordinate spaces
-            let position_x = cursor.read_f32::<LittleEndian>()?;
-            let position_y = cursor.read_f32::<LittleEndian>()?;
-            let position_z = cursor.read_f32::<LittleEndian>()?;
-            let position = Vec3 {
-                x: position_x,
-                y: position_y,
-                z: position_z,
-            };
+            let data_size = cursor.read_u16::<LittleEndian>()? as usize;
+            let mut data = vec![0u8; data_size];
+            cursor.read_exact(&mut data)?;
+            object_data.push(ObjectDataCompressed::from_bytes(update_flags, &data)?);
+        }
+        Ok(ObjectUpdateCompressed {
+            region_handle,
+            time_dilation,
+            object_data,
+        })
+    }
+    fn to_bytes(&self) -> Vec<u8> {
+        let object_data = &self.object_data[..self.object_data.len().min(u8::MAX as usize)];
+        let mut bytes = Vec::new();
+        bytes.write_u64::<LittleEndian>(self.region_handle).unwrap();
+        bytes.write_u16::<LittleEndian>(self.time_dilation).unwrap();
+        bytes.push(object_data.len() as u8);
+        for object in object_data {
+            bytes
+                .write_u32::<LittleEndian>(ObjectFlag::to_bytes(&object.update_flags))
+                .unwrap();
+            let data = object.to_bytes();
+            bytes.write_u16::<LittleEndian>(data.len() as u16).unwrap();
+            bytes.extend(data);
+        }
+        bytes
+    }
+}
 
-            let x = cursor.read_f32::<LittleEndian>()?;
-            let y = cursor.read_f32::<LittleEndian>()?;
-            let z = cursor.read_f32::<LittleEndian>()?;
-            let w_sq = 1.0 - x * x - y * y - z * z;
-            let w = if w_sq > 0.0 { w_sq.sqrt() } else { 0.0 };
-            let rotation = Quat::from_xyzw(x, y, z, w);
-
-            let compressed_flags = CompressedFlag::from_bytes(cursor.read_u32::<LittleEndian>()?);
-
-            let mut owner_id_bytes = [0u8; 16];
-            cursor.read_exact(&mut owner_id_bytes)?;
-            let owner_id = if compressed_flags.contains(&CompressedFlag::HasParticles)
-                || compressed_flags.contains(&CompressedFlag::HasParticlesLegacy)
-                || compressed_flags.contains(&CompressedFlag::HasSound)
-            {
-                Some(Uuid::from_bytes(owner_id_bytes))
-            } else {
-                None
-            };
+impl ObjectDataCompressed {
+    /// Decode the compressed data of a single object. Which fields are present is determined by
+    /// the compressed flags inside of the data.
+    pub fn from_bytes(update_flags: Vec<ObjectFlag>, bytes: &[u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(bytes);
 
-            let angular_velocity = if compressed_flags.contains(&CompressedFlag::HasAngularVelocity)
-            {
-                let angular_velocity_x = cursor.read_f32::<LittleEndian>()?;
-                let angular_velocity_y = cursor.read_f32::<LittleEndian>()?;
-                let angular_velocity_z = cursor.read_f32::<LittleEndian>()?;
-                Some(Vec3 {
-                    x: angular_velocity_x,
-                    y: angular_velocity_y,
-                    z: angular_velocity_z,
-                })
-            } else {
-                None
-            };
+        let mut full_id_bytes = [0u8; 16];
+        cursor.read_exact(&mut full_id_bytes)?;
+        let full_id = Uuid::from_bytes(full_id_bytes);
 
-            let parent_id = if compressed_flags.contains(&CompressedFlag::HasParent) {
-                Some(cursor.read_u32::<LittleEndian>()?)
-            } else {
-                None
-            };
+        let local_id = cursor.read_u32::<LittleEndian>()?;
 
-            let (text, text_color) = if compressed_flags.contains(&CompressedFlag::HasText) {
-                let text_length = cursor.read_u8()?;
-                let mut text = vec![0u8; text_length as usize];
-                cursor.read_exact(&mut text)?;
-                let text = String::from_utf8_lossy(&text).to_string();
-
-                let text_color_r = cursor.read_u8()?;
-                let text_color_g = cursor.read_u8()?;
-                let text_color_b = cursor.read_u8()?;
-                let text_color_a = cursor.read_u8()?;
-                let text_color = Rgba {
-                    r: text_color_r,
-                    g: text_color_g,
-                    b: text_color_b,
-                    a: text_color_a,
-                };
-                (Some(text), Some(text_color))
-            } else {
-                (None, None)
-            };
+        let pcode = ObjectType::from_bytes(&cursor.read_u8()?);
+        let state = cursor.read_u8()?;
+        let crc = cursor.read_u32::<LittleEndian>()?;
+        let material = MaterialType::from_bytes(&cursor.read_u8()?);
+        let click_action = cursor.read_u8()?;
 
-            let media_url = if compressed_flags.contains(&CompressedFlag::MediaURL) {
-                let media_url_length = cursor.read_u8()?;
-                let mut media_url = vec![0u8; media_url_length as usize];
-                cursor.read_exact(&mut media_url)?;
-                Some(String::from_utf8_lossy(&media_url).to_string())
-            } else {
-                None
+        let scale = read_vec3(&mut cursor)?;
+        let position = read_vec3(&mut cursor)?;
+
+        let x = cursor.read_f32::<LittleEndian>()?;
+        let y = cursor.read_f32::<LittleEndian>()?;
+        let z = cursor.read_f32::<LittleEndian>()?;
+        let w_sq = 1.0 - x * x - y * y - z * z;
+        let w = if w_sq > 0.0 { w_sq.sqrt() } else { 0.0 };
+        let rotation = Quat::from_xyzw(x, y, z, w);
+
+        let compressed_flags = CompressedFlag::from_bytes(cursor.read_u32::<LittleEndian>()?);
+
+        // the owner ID is always sent, but is only meaningful for objects with sounds or particles
+        let mut owner_id_bytes = [0u8; 16];
+        cursor.read_exact(&mut owner_id_bytes)?;
+        let owner_id = if compressed_flags.contains(&CompressedFlag::HasParticles)
+            || compressed_flags.contains(&CompressedFlag::HasParticlesLegacy)
+            || compressed_flags.contains(&CompressedFlag::HasSound)
+        {
+            Some(Uuid::from_bytes(owner_id_bytes))
+        } else {
+            None
+        };
+
+        let angular_velocity = if compressed_flags.contains(&CompressedFlag::HasAngularVelocity) {
+            Some(read_vec3(&mut cursor)?)
+        } else {
+            None
+        };
+
+        let parent_id = if compressed_flags.contains(&CompressedFlag::HasParent) {
+            Some(cursor.read_u32::<LittleEndian>()?)
+        } else {
+            None
+        };
+
+        let tree_species = if compressed_flags.contains(&CompressedFlag::Tree) {
+            Some(cursor.read_u8()?)
+        } else {
+            None
+        };
+
+        let scratch_pad = if compressed_flags.contains(&CompressedFlag::ScratchPad) {
+            let scratch_pad_length = cursor.read_u8()?;
+            let mut scratch_pad = vec![0u8; scratch_pad_length as usize];
+            cursor.read_exact(&mut scratch_pad)?;
+            Some(scratch_pad)
+        } else {
+            None
+        };
+
+        let (text, text_color) = if compressed_flags.contains(&CompressedFlag::HasText) {
+            let text = read_null_terminated(&mut cursor)?;
+            let text_color = Rgba {
+                r: cursor.read_u8()?,
+                g: cursor.read_u8()?,
+                b: cursor.read_u8()?,
+                a: cursor.read_u8()?,
             };
+            (Some(text), Some(text_color))
+        } else {
+            (None, None)
+        };
+
+        let media_url = if compressed_flags.contains(&CompressedFlag::MediaURL) {
+            Some(read_null_terminated(&mut cursor)?)
+        } else {
+            None
+        };
 
-            let particle_system_legacy = if compressed_flags
-                .contains(&CompressedFlag::HasParticlesLegacy)
-                || compressed_flags.contains(&CompressedFlag::HasParticles)
-            {
-                let particle_system_block_length = cursor.read_u8()?;
-                let mut particle_system_block = vec![0u8; particle_system_block_length as usize];
+        // legacy particle systems are always a fixed size
+        let particle_system_legacy =
+            if compressed_flags.contains(&CompressedFlag::HasParticlesLegacy) {
+                let mut particle_system_block = vec![0u8; LEGACY_PARTICLE_SYSTEM_SIZE];
                 cursor.read_exact(&mut particle_system_block)?;
                 Some(particle_system_block)
             } else {
                 None
             };
 
-            // peek at the length of the extra params without reading the byte
-            let pos = cursor.position() as usize;
-            let extra_params_count = cursor.get_ref()[pos];
-            let extra_params = if extra_params_count == 0 {
-                None
-            } else {
-                let (extra_params, position) = ExtraParams::from_bytes(&cursor.get_ref()[pos..])?;
-                cursor.set_position(cursor.position() + position);
-                Some(extra_params)
-            };
+        let pos = cursor.position() as usize;
+        let (extra_params, read_count) = ExtraParams::from_bytes(&bytes[pos..])?;
+        cursor.set_position(cursor.position() + read_count);
+        let extra_params = if extra_params.is_empty() {
+            None
+        } else {
+            Some(extra_params)
+        };
 
-            // TODO: make the sound struct
-            let sound = if compressed_flags.contains(&CompressedFlag::HasSound) {
-                let mut sound_id_bytes = [0u8; 16];
-                cursor.read_exact(&mut sound_id_bytes)?;
-                let sound_id = Uuid::from_bytes(sound_id_bytes);
-                let gain = cursor.read_f32::<LittleEndian>()?;
-                let flags = cursor.read_u8()?;
-                let radius = cursor.read_f32::<LittleEndian>()?;
-                Some(AttachedSound {
-                    owner_id: None,
-                    sound_id,
-                    gain,
-                    flags,
-                    radius,
-                })
-            } else {
-                None
-            };
+        let sound = if compressed_flags.contains(&CompressedFlag::HasSound) {
+            let mut sound_id_bytes = [0u8; 16];
+            cursor.read_exact(&mut sound_id_bytes)?;
+            let sound_id = Uuid::from_bytes(sound_id_bytes);
+            let gain = cursor.read_f32::<LittleEndian>()?;
+            let flags = cursor.read_u8()?;
+            let radius = cursor.read_f32::<LittleEndian>()?;
+            Some(AttachedSound {
+                owner_id: None,
+                sound_id,
+                gain,
+                flags,
+                radius,
+            })
+        } else {
+            None
+        };
 
-            let name_values = if compressed_flags.contains(&CompressedFlag::HasNameValues) {
-                let name_value_length = cursor.read_u16::<LittleEndian>()?;
-                let mut name_value = vec![0u8; name_value_length as usize];
-                cursor.read_exact(&mut name_value)?;
-                Some(String::from_utf8_lossy(&name_value).to_string())
-            } else {
-                None
-            };
+        let name_values = if compressed_flags.contains(&CompressedFlag::HasNameValues) {
+            Some(read_null_terminated(&mut cursor)?)
+        } else {
+            None
+        };
 
-            let mut geometry_bytes = [0u8; 23];
-            cursor.read_exact(&mut geometry_bytes)?;
-            let sculpt_path = Path::from_bytes(&geometry_bytes)?;
-
-            let texture_entry_length = cursor.read_u16::<LittleEndian>()?;
-            let mut texture_entry_bytes = vec![0u8; texture_entry_length as usize];
-            cursor.read_exact(&mut texture_entry_bytes)?;
-            let texture_entry = TextureEntry::from_bytes(&texture_entry_bytes)?;
-            let texture_animation = if compressed_flags.contains(&CompressedFlag::TextureAnimation)
-            {
-                let texture_anim_length = cursor.read_u8()?;
-                let mut texture_anim = vec![0u8; texture_anim_length as usize];
-                cursor.read_exact(&mut texture_anim)?;
-                Some(texture_anim)
-            } else {
-                None
-            };
+        let mut geometry_bytes = [0u8; 23];
+        cursor.read_exact(&mut geometry_bytes)?;
+        let sculpt_path = Path::from_bytes(&geometry_bytes)?;
 
-            let particle_system_length = cursor.read_u8()?;
-            let mut particle_system = vec![0u8; particle_system_length as usize];
-            cursor.read_exact(&mut particle_system)?;
-
-            // one unhandled zero at the end of the packet.
-            // hopefully this isn't an issue
-            cursor.read_u8()?;
-
-            let data = ObjectDataCompressed {
-                update_flags,
-                full_id,
-                local_id,
-                pcode,
-                state,
-                crc,
-                material,
-                click_action,
-                scale,
-                position,
-                rotation,
-                owner_id,
-                angular_velocity,
-                parent_id,
-                text,
-                text_color,
-                media_url,
-                particle_system_legacy,
-                extra_params,
-                sound,
-                name_values,
-                sculpt_path,
-                texture_entry,
-                texture_animation,
-                particle_system,
-            };
-            object_data.push(data);
+        let texture_entry_length = cursor.read_u32::<LittleEndian>()?;
+        let mut texture_entry_bytes = vec![0u8; texture_entry_length as usize];
+        cursor.read_exact(&mut texture_entry_bytes)?;
+        let texture_entry = TextureEntry::from_bytes(&texture_entry_bytes)?;
+
+        let texture_animation = if compressed_flags.contains(&CompressedFlag::TextureAnimation) {
+            let texture_anim_length = cursor.read_u32::<LittleEndian>()?;
+            let mut texture_anim = vec![0u8; texture_anim_length as usize];
+            cursor.read_exact(&mut texture_anim)?;
+            Some(texture_anim)
+        } else {
+            None
+        };
+
+        // the new particle system is the last field, and takes up the rest of the data
+        let mut particle_system = Vec::new();
+        if compressed_flags.contains(&CompressedFlag::HasParticles) {
+            cursor.read_to_end(&mut particle_system)?;
         }
-        Ok(ObjectUpdateCompressed {
-            region_handle,
-            time_dilation,
-            object_data,
+
+        Ok(ObjectDataCompressed {
+            update_flags,
+            full_id,
+            local_id,
+            pcode,
+            state,
+            crc,
+            material,
+            click_action,
+            scale,
+            position,
+            rotation,
+            owner_id,
+            angular_velocity,
+            parent_id,
+            tree_species,
+            scratch_pad,
+            text,
+            text_color,
+            media_url,
+            particle_system_legacy,
+            extra_params,
+            sound,
+            name_values,
+            sculpt_path,
+            texture_entry,
+            texture_animation,
+            particle_system,
         })
     }
-    fn to_bytes(&self) -> Vec<u8> {
-        // push your data into the new vector
-        Vec::new()
+
+    /// Encode the data of a single object. The compressed flags are set from whichever optional
+    /// fields are present.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut compressed_flags = Vec::new();
+        if self.scratch_pad.is_some() {
+            compressed_flags.push(CompressedFlag::ScratchPad);
+        }
+        if self.tree_species.is_some() {
+            compressed_flags.push(CompressedFlag::Tree);
+        }
+        if self.text.is_some() {
+            compressed_flags.push(CompressedFlag::HasText);
+        }
+        if self.particle_system_legacy.is_some() {
+            compressed_flags.push(CompressedFlag::HasParticlesLegacy);
+        }
+        if self.sound.is_some() {
+            compressed_flags.push(CompressedFlag::HasSound);
+        }
+        if self.parent_id.is_some() {
+            compressed_flags.push(CompressedFlag::HasParent);
+        }
+        if self.texture_animation.is_some() {
+            compressed_flags.push(CompressedFlag::TextureAnimation);
+        }
+        if self.angular_velocity.is_some() {
+            compressed_flags.push(CompressedFlag::HasAngularVelocity);
+        }
+        if self.name_values.is_some() {
+            compressed_flags.push(CompressedFlag::HasNameValues);
+        }
+        if self.media_url.is_some() {
+            compressed_flags.push(CompressedFlag::MediaURL);
+        }
+        if !self.particle_system.is_empty() {
+            compressed_flags.push(CompressedFlag::HasParticles);
+        }
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(self.full_id.as_bytes());
+        bytes.write_u32::<LittleEndian>(self.local_id).unwrap();
+        bytes.push(self.pcode.to_bytes());
+        bytes.push(self.state);
+        bytes.write_u32::<LittleEndian>(self.crc).unwrap();
+        bytes.push(self.material.to_bytes());
+        bytes.push(self.click_action);
+        write_vec3(&mut bytes, self.scale);
+        write_vec3(&mut bytes, self.position);
+
+        let mut rotation = self.rotation.normalize();
+        if rotation.w < 0.0 {
+            rotation = -rotation;
+        }
+        write_vec3(&mut bytes, rotation.xyz());
+
+        bytes
+            .write_u32::<LittleEndian>(CompressedFlag::to_bytes(&compressed_flags))
+            .unwrap();
+        bytes.extend_from_slice(self.owner_id.unwrap_or_default().as_bytes());
+
+        if let Some(angular_velocity) = self.angular_velocity {
+            write_vec3(&mut bytes, angular_velocity);
+        }
+        if let Some(parent_id) = self.parent_id {
+            bytes.write_u32::<LittleEndian>(parent_id).unwrap();
+        }
+        if let Some(tree_species) = self.tree_species {
+            bytes.push(tree_species);
+        }
+        if let Some(scratch_pad) = &self.scratch_pad {
+            let scratch_pad = &scratch_pad[..scratch_pad.len().min(u8::MAX as usize)];
+            bytes.push(scratch_pad.len() as u8);
+            bytes.extend_from_slice(scratch_pad);
+        }
+        if let Some(text) = &self.text {
+            write_null_terminated(&mut bytes, text);
+            let color = self.text_color.unwrap_or(Rgba::new(0, 0, 0, 0));
+            bytes.extend([color.r, color.g, color.b, color.a]);
+        }
+        if let Some(media_url) = &self.media_url {
+            write_null_terminated(&mut bytes, media_url);
+        }
+        if let Some(particle_system_legacy) = &self.particle_system_legacy {
+            let mut block = particle_system_legacy.clone();
+            block.resize(LEGACY_PARTICLE_SYSTEM_SIZE, 0);
+            bytes.extend(block);
+        }
+        bytes.extend(ExtraParams::to_bytes(
+            self.extra_params.as_deref().unwrap_or_default(),
+        ));
+        if let Some(sound) = &self.sound {
+            bytes.extend_from_slice(sound.sound_id.as_bytes());
+            bytes.write_f32::<LittleEndian>(sound.gain).unwrap();
+            bytes.push(sound.flags);
+            bytes.write_f32::<LittleEndian>(sound.radius).unwrap();
+        }
+        if let Some(name_values) = &self.name_values {
+            write_null_terminated(&mut bytes, name_values);
+        }
+
+        bytes.extend(self.sculpt_path.to_bytes());
+
+        let texture_entry = self.texture_entry.to_bytes();
+        bytes
+            .write_u32::<LittleEndian>(texture_entry.len() as u32)
+            .unwrap();
+        bytes.extend(texture_entry);
+
+        if let Some(texture_animation) = &self.texture_animation {
+            bytes
+                .write_u32::<LittleEndian>(texture_animation.len() as u32)
+                .unwrap();
+            bytes.extend_from_slice(texture_animation);
+        }
+        bytes.extend_from_slice(&self.particle_system);
+        bytes
+    }
+}
+
+/// the size of the particle system block sent by older simulators
+const LEGACY_PARTICLE_SYSTEM_SIZE: usize = 86;
+
+fn read_vec3(cursor: &mut Cursor<&[u8]>) -> Result<Vec3, ParseError> {
+    Ok(Vec3::new(
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+    ))
+}
+
+fn write_vec3(bytes: &mut Vec<u8>, value: Vec3) {
+    for value in value.to_array() {
+        bytes.write_f32::<LittleEndian>(value).unwrap();
+    }
+}
+
+/// strings in compressed updates are null terminated instead of length prefixed
+fn read_null_terminated(cursor: &mut Cursor<&[u8]>) -> Result<String, ParseError> {
+    let mut string = Vec::new();
+    cursor.read_until(0, &mut string)?;
+    if string.last() == Some(&0) {
+        string.pop();
     }
+    Ok(String::from_utf8_lossy(&string).to_string())
+}
+
+fn write_null_terminated(bytes: &mut Vec<u8>, string: &str) {
+    bytes.extend_from_slice(string.as_bytes());
+    bytes.push(0);
 }
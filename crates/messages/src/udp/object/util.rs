@@ -72,4 +72,7 @@ impl ObjectFlag {
         }
         flags
     }
+    pub fn to_bytes(flags: &[ObjectFlag]) -> u32 {
+        flags.iter().fold(0, |bits, flag| bits | *flag as u32)
+    }
 }
@@ -0,0 +1,146 @@
+use benthic_protocol::skeleton::JointName;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Generates the attachment point enum from the table of the points' IDs, the joints they are
+/// attached to, and their names.
+macro_rules! attachment_points {
+    ($($id:literal => $variant:ident, $joint:expr, $name:literal;)*) => {
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+        /// The points on an avatar, or on the user's screen, that objects can be attached to
+        pub enum AttachmentPoint {
+            /// The point the object was last attached to, or the right hand if it has never been
+            /// attached
+            #[default]
+            Default,
+            $(
+                #[doc = $name]
+                $variant,
+            )*
+            /// An attachment point this viewer doesn't know about
+            Unknown(u8),
+        }
+
+        impl AttachmentPoint {
+            /// Convert from the attachment point's ID
+            pub fn from_u8(id: u8) -> Self {
+                match id {
+                    0 => AttachmentPoint::Default,
+                    $($id => AttachmentPoint::$variant,)*
+                    id => AttachmentPoint::Unknown(id),
+                }
+            }
+
+            /// Convert to the attachment point's ID
+            pub fn to_u8(&self) -> u8 {
+                match self {
+                    AttachmentPoint::Default => 0,
+                    $(AttachmentPoint::$variant => $id,)*
+                    AttachmentPoint::Unknown(id) => *id,
+                }
+            }
+
+            /// The name of the skeleton joint the point is attached to. HUD points are attached to
+            /// the screen, so they have none.
+            pub fn joint_name(&self) -> Option<&'static str> {
+                match self {
+                    $(AttachmentPoint::$variant => $joint,)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+attachment_points! {
+    1 => Chest, Some("mChest"), "Chest";
+    2 => Skull, Some("mHead"), "Skull";
+    3 => LeftShoulder, Some("mCollarLeft"), "Left shoulder";
+    4 => RightShoulder, Some("mCollarRight"), "Right shoulder";
+    5 => LeftHand, Some("mWristLeft"), "Left hand";
+    6 => RightHand, Some("mWristRight"), "Right hand";
+    7 => LeftFoot, Some("mFootLeft"), "Left foot";
+    8 => RightFoot, Some("mFootRight"), "Right foot";
+    9 => Spine, Some("mChest"), "Spine";
+    10 => Pelvis, Some("mPelvis"), "Pelvis";
+    11 => Mouth, Some("mHead"), "Mouth";
+    12 => Chin, Some("mHead"), "Chin";
+    13 => LeftEar, Some("mHead"), "Left ear";
+    14 => RightEar, Some("mHead"), "Right ear";
+    15 => LeftEyeball, Some("mEyeLeft"), "Left eyeball";
+    16 => RightEyeball, Some("mEyeRight"), "Right eyeball";
+    17 => Nose, Some("mHead"), "Nose";
+    18 => RightUpperArm, Some("mShoulderRight"), "Right upper arm";
+    19 => RightForearm, Some("mElbowRight"), "Right forearm";
+    20 => LeftUpperArm, Some("mShoulderLeft"), "Left upper arm";
+    21 => LeftForearm, Some("mElbowLeft"), "Left forearm";
+    22 => RightHip, Some("mHipRight"), "Right hip";
+    23 => RightUpperLeg, Some("mHipRight"), "Right upper leg";
+    24 => RightLowerLeg, Some("mKneeRight"), "Right lower leg";
+    25 => LeftHip, Some("mHipLeft"), "Left hip";
+    26 => LeftUpperLeg, Some("mHipLeft"), "Left upper leg";
+    27 => LeftLowerLeg, Some("mKneeLeft"), "Left lower leg";
+    28 => Stomach, Some("mPelvis"), "Stomach";
+    29 => LeftPec, Some("mTorso"), "Left pectoral";
+    30 => RightPec, Some("mTorso"), "Right pectoral";
+    31 => HudCenter2, None, "Second center of the screen";
+    32 => HudTopRight, None, "Top right corner of the screen";
+    33 => HudTop, None, "Top edge of the screen";
+    34 => HudTopLeft, None, "Top left corner of the screen";
+    35 => HudCenter, None, "Center of the screen";
+    36 => HudBottomLeft, None, "Bottom left corner of the screen";
+    37 => HudBottom, None, "Bottom edge of the screen";
+    38 => HudBottomRight, None, "Bottom right corner of the screen";
+    39 => Neck, Some("mNeck"), "Neck";
+    40 => AvatarCenter, Some("mPelvis"), "Avatar center";
+    41 => LeftRingFinger, Some("mHandRing1Left"), "Left ring finger";
+    42 => RightRingFinger, Some("mHandRing1Right"), "Right ring finger";
+    43 => TailBase, Some("mTail1"), "Base of the tail";
+    44 => TailTip, Some("mTail6"), "Tip of the tail";
+    45 => LeftWing, Some("mWing4Left"), "Left wing";
+    46 => RightWing, Some("mWing4Right"), "Right wing";
+    47 => Jaw, Some("mFaceJaw"), "Jaw";
+    48 => AltLeftEar, Some("mFaceEar1Left"), "Alternate left ear";
+    49 => AltRightEar, Some("mFaceEar1Right"), "Alternate right ear";
+    50 => AltLeftEye, Some("mFaceEyeAltLeft"), "Alternate left eye";
+    51 => AltRightEye, Some("mFaceEyeAltRight"), "Alternate right eye";
+    52 => Tongue, Some("mFaceTongueBase"), "Tongue";
+    53 => Groin, Some("mGroin"), "Groin";
+    54 => LeftHindFoot, Some("mHindLimb4Left"), "Left hind foot";
+    55 => RightHindFoot, Some("mHindLimb4Right"), "Right hind foot";
+}
+
+impl AttachmentPoint {
+    /// Read the attachment point of an attached object from the state field of its object
+    /// update, which stores the point's ID with its two nibbles swapped.
+    pub fn from_state(state: u8) -> Self {
+        Self::from_u8(state.rotate_left(4))
+    }
+
+    /// The skeleton joint the point is attached to
+    pub fn joint(&self) -> Option<JointName> {
+        JointName::from_str(self.joint_name()?).ok()
+    }
+
+    /// If the point is on the user's screen instead of their avatar
+    pub fn is_hud(&self) -> bool {
+        (31..=38).contains(&self.to_u8())
+    }
+
+    /// Where on the screen a HUD point is, from -1 to 1 with positive X to the right and positive
+    /// Y up
+    pub fn hud_anchor(&self) -> Option<Vec2> {
+        let anchor = match self {
+            AttachmentPoint::HudCenter2 | AttachmentPoint::HudCenter => Vec2::ZERO,
+            AttachmentPoint::HudTopRight => Vec2::new(1.0, 1.0),
+            AttachmentPoint::HudTop => Vec2::new(0.0, 1.0),
+            AttachmentPoint::HudTopLeft => Vec2::new(-1.0, 1.0),
+            AttachmentPoint::HudBottomLeft => Vec2::new(-1.0, -1.0),
+            AttachmentPoint::HudBottom => Vec2::new(0.0, -1.0),
+            AttachmentPoint::HudBottomRight => Vec2::new(1.0, -1.0),
+            _ => return None,
+        };
+        Some(anchor)
+    }
+}
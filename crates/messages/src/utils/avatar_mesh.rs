@@ -0,0 +1,210 @@
+use crate::errors::ParseError;
+use benthic_protocol::skeleton::JointName;
+use byteorder::{LittleEndian, ReadBytesExt};
+use glam::{Vec2, Vec3};
+use std::{
+    collections::BTreeMap,
+    io::{Cursor, Read},
+    str::{FromStr, from_utf8},
+};
+
+/// The header every binary avatar mesh starts with, padded to 24 bytes
+pub const AVATAR_MESH_HEADER: &str = "Linden Binary Mesh 1.0";
+
+/// The section name that ends the list of morphs
+const END_MORPHS: &str = "End Morphs";
+
+#[derive(Debug, Clone, Default)]
+/// A part of the default system avatar, read from the .llm files shipped with every viewer.
+///
+/// The vertices are in the avatar's bind pose. Morphs move them to change the shape of the
+/// avatar, and are blended in by the weights of the visual params they are named after.
+pub struct AvatarMesh {
+    /// Position of each vertex
+    pub vertices: Vec<Vec3>,
+    /// Normal of each vertex
+    pub normals: Vec<Vec3>,
+    /// Texture coordinate of each vertex, with V pointing up
+    pub uvs: Vec<Vec2>,
+    /// The skin weight of each vertex. The integer part is the index of the vertex's joint in the
+    /// render joints of the mesh, and the fraction is how much it is blended into the next one.
+    /// Empty if the mesh follows a single joint.
+    pub weights: Vec<f32>,
+    /// The triangles of the mesh
+    pub indices: Vec<u16>,
+    /// The joints the mesh is skinned to
+    pub skin_joints: Vec<JointName>,
+    /// The morphs of the mesh, by name
+    pub morphs: BTreeMap<String, Vec<MorphVertex>>,
+    /// Vertices that duplicate another vertex along a seam, and the vertex they copy, which they
+    /// follow when the mesh is morphed
+    pub shared_vertices: Vec<(u32, u32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// How a morph moves one vertex at a weight of 1
+pub struct MorphVertex {
+    /// Index of the vertex
+    pub index: u32,
+    /// Added to the vertex's position
+    pub position: Vec3,
+    /// Added to the vertex's normal
+    pub normal: Vec3,
+    /// Added to the vertex's texture coordinate
+    pub uv: Vec2,
+}
+
+impl AvatarMesh {
+    /// Parse an avatar mesh from the bytes of an .llm file. Level of detail files, which only
+    /// contain triangles, are not supported.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(bytes);
+        let header = read_name(&mut cursor, 24)?;
+        if header != AVATAR_MESH_HEADER {
+            return Err(ParseError::InvalidField(format!(
+                "Not an avatar mesh: {:?}",
+                header
+            )));
+        }
+        let has_weights = cursor.read_u8()? != 0;
+        let has_detail_uvs = cursor.read_u8()? != 0;
+        // the position, rotation, rotation order and scale of the mesh are unused
+        cursor.set_position(cursor.position() + 12 + 12 + 1 + 12);
+
+        let vertex_count = cursor.read_u16::<LittleEndian>()? as usize;
+        let vertices = read_vec3s(&mut cursor, vertex_count)?;
+        let normals = read_vec3s(&mut cursor, vertex_count)?;
+        // binormals are only needed for bump mapping
+        read_vec3s(&mut cursor, vertex_count)?;
+        let uvs = read_vec2s(&mut cursor, vertex_count)?;
+        if has_detail_uvs {
+            read_vec2s(&mut cursor, vertex_count)?;
+        }
+        let mut weights = Vec::new();
+        if has_weights {
+            for _ in 0..vertex_count {
+                weights.push(cursor.read_f32::<LittleEndian>()?);
+            }
+        }
+
+        let face_count = cursor.read_u16::<LittleEndian>()? as usize;
+        let mut indices = Vec::with_capacity(face_count * 3);
+        for _ in 0..face_count * 3 {
+            indices.push(cursor.read_i16::<LittleEndian>()? as u16);
+        }
+
+        let mut skin_joints = Vec::new();
+        if has_weights {
+            for _ in 0..cursor.read_u16::<LittleEndian>()? {
+                let name = read_name(&mut cursor, 64)?;
+                let joint = JointName::from_str(&name).map_err(|_| {
+                    ParseError::InvalidField(format!("Unknown joint name: {}", name))
+                })?;
+                skin_joints.push(joint);
+            }
+        }
+
+        let mut morphs = BTreeMap::new();
+        while (cursor.position() as usize) < bytes.len() {
+            let name = read_name(&mut cursor, 64)?;
+            if name == END_MORPHS {
+                break;
+            }
+            let count = cursor.read_i32::<LittleEndian>()?.max(0) as usize;
+            let mut morph = Vec::with_capacity(count);
+            for _ in 0..count {
+                let index = cursor.read_u32::<LittleEndian>()?;
+                let position = read_vec3(&mut cursor)?;
+                let normal = read_vec3(&mut cursor)?;
+                // binormal
+                read_vec3(&mut cursor)?;
+                let uv = Vec2::new(
+                    cursor.read_f32::<LittleEndian>()?,
+                    cursor.read_f32::<LittleEndian>()?,
+                );
+                morph.push(MorphVertex {
+                    index,
+                    position,
+                    normal,
+                    uv,
+                });
+            }
+            morphs.insert(name, morph);
+        }
+
+        let mut shared_vertices = Vec::new();
+        if (cursor.position() as usize) < bytes.len() {
+            for _ in 0..cursor.read_i32::<LittleEndian>()?.max(0) {
+                let vertex = cursor.read_i32::<LittleEndian>()? as u32;
+                let source = cursor.read_i32::<LittleEndian>()? as u32;
+                shared_vertices.push((vertex, source));
+            }
+        }
+
+        Ok(AvatarMesh {
+            vertices,
+            normals,
+            uvs,
+            weights,
+            indices,
+            skin_joints,
+            morphs,
+            shared_vertices,
+        })
+    }
+
+    /// The positions of the vertices with the morphs blended in by their weights. Morphs the mesh
+    /// doesn't have are ignored.
+    pub fn morphed_vertices(&self, weights: &BTreeMap<String, f32>) -> Vec<Vec3> {
+        let mut vertices = self.vertices.clone();
+        for (name, weight) in weights {
+            let Some(morph) = self.morphs.get(name) else {
+                continue;
+            };
+            for delta in morph {
+                if let Some(vertex) = vertices.get_mut(delta.index as usize) {
+                    *vertex += delta.position * *weight;
+                }
+            }
+        }
+        for (vertex, source) in &self.shared_vertices {
+            if let Some(position) = vertices.get(*source as usize).copied()
+                && let Some(vertex) = vertices.get_mut(*vertex as usize)
+            {
+                *vertex = position;
+            }
+        }
+        vertices
+    }
+}
+
+/// Read a null padded name of a fixed length
+fn read_name(cursor: &mut Cursor<&[u8]>, length: usize) -> Result<String, ParseError> {
+    let mut bytes = vec![0u8; length];
+    cursor.read_exact(&mut bytes)?;
+    let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(length);
+    Ok(from_utf8(&bytes[..end])?.trim().to_string())
+}
+
+fn read_vec3(cursor: &mut Cursor<&[u8]>) -> Result<Vec3, ParseError> {
+    Ok(Vec3::new(
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+    ))
+}
+
+fn read_vec3s(cursor: &mut Cursor<&[u8]>, count: usize) -> Result<Vec<Vec3>, ParseError> {
+    (0..count).map(|_| read_vec3(cursor)).collect()
+}
+
+fn read_vec2s(cursor: &mut Cursor<&[u8]>, count: usize) -> Result<Vec<Vec2>, ParseError> {
+    (0..count)
+        .map(|_| {
+            Ok(Vec2::new(
+                cursor.read_f32::<LittleEndian>()?,
+                cursor.read_f32::<LittleEndian>()?,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,170 @@
+use crate::http::item::TextureSlot;
+use serde::{Deserialize, Serialize};
+use uuid::{Uuid, uuid};
+
+/// The texture of the default system avatar, used by wearables for slots that have no texture of
+/// their own
+pub const DEFAULT_AVATAR_TEXTURE: Uuid = uuid!("c228d1cf-4b5d-4ba8-84f4-899a0796aa97");
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+/// The textures a system avatar is drawn with. Each is baked from the layers of the avatar's
+/// wearables.
+///
+/// Mesh bodies draw the bakes on faces textured with the bake's Bakes on Mesh texture, which
+/// isn't a real texture and is replaced by the bake of the avatar wearing the mesh.
+pub enum BakeType {
+    /// The head and eyelashes
+    Head,
+    /// The upper body and arms
+    UpperBody,
+    /// The lower body and legs
+    LowerBody,
+    /// The eyes
+    Eyes,
+    /// The system skirt
+    Skirt,
+    /// The system hair
+    Hair,
+    /// The left arm of mesh bodies
+    LeftArm,
+    /// The left leg of mesh bodies
+    LeftLeg,
+    /// The first extra channel of mesh bodies
+    Aux1,
+    /// The second extra channel of mesh bodies
+    Aux2,
+    /// The third extra channel of mesh bodies
+    Aux3,
+}
+
+impl BakeType {
+    /// Every bake, in the order of their baked texture slots
+    pub const ALL: [BakeType; 11] = [
+        BakeType::Head,
+        BakeType::UpperBody,
+        BakeType::LowerBody,
+        BakeType::Eyes,
+        BakeType::Skirt,
+        BakeType::Hair,
+        BakeType::LeftArm,
+        BakeType::LeftLeg,
+        BakeType::Aux1,
+        BakeType::Aux2,
+        BakeType::Aux3,
+    ];
+
+    /// The bakes drawn on the meshes of the system avatar
+    pub const SYSTEM: [BakeType; 6] = [
+        BakeType::Head,
+        BakeType::UpperBody,
+        BakeType::LowerBody,
+        BakeType::Eyes,
+        BakeType::Skirt,
+        BakeType::Hair,
+    ];
+
+    /// The texture slot the finished bake is stored in
+    pub fn baked_slot(&self) -> TextureSlot {
+        match self {
+            BakeType::Head => TextureSlot::HeadBaked,
+            BakeType::UpperBody => TextureSlot::UpperBaked,
+            BakeType::LowerBody => TextureSlot::LowerBaked,
+            BakeType::Eyes => TextureSlot::EyesBaked,
+            BakeType::Skirt => TextureSlot::SkirtBaked,
+            BakeType::Hair => TextureSlot::HairBaked,
+            BakeType::LeftArm => TextureSlot::LeftArmBaked,
+            BakeType::LeftLeg => TextureSlot::LeftLegBaked,
+            BakeType::Aux1 => TextureSlot::Aux1Baked,
+            BakeType::Aux2 => TextureSlot::Aux2Baked,
+            BakeType::Aux3 => TextureSlot::Aux3Baked,
+        }
+    }
+
+    /// The bake stored in a baked texture slot
+    pub fn from_baked_slot(slot: TextureSlot) -> Option<Self> {
+        BakeType::ALL
+            .into_iter()
+            .find(|bake| bake.baked_slot() == slot)
+    }
+
+    /// The texture mesh bodies use for faces that are drawn with the bake
+    pub fn bakes_on_mesh_texture(&self) -> Uuid {
+        match self {
+            BakeType::Head => uuid!("5a9f4a74-30f2-821c-b88d-70499d3e7183"),
+            BakeType::UpperBody => uuid!("ae2de45c-d252-50b8-5c6e-19f39ce79317"),
+            BakeType::LowerBody => uuid!("24daea5f-0539-cfcf-047f-fbc40b2786ba"),
+            BakeType::Eyes => uuid!("52cc6bb6-2ee5-e632-d3ad-50197b1dcb8a"),
+            BakeType::Skirt => uuid!("43529ce8-7faa-ad92-165a-bc4078371687"),
+            BakeType::Hair => uuid!("09aac1fb-6bce-0bee-7d44-caac6dbb6c63"),
+            BakeType::LeftArm => uuid!("ff62763f-d60a-9855-890b-0c96f8f8cd98"),
+            BakeType::LeftLeg => uuid!("8e915e25-31d1-cc95-ae08-d58a47488251"),
+            BakeType::Aux1 => uuid!("9742065b-19b5-297c-858a-29711d539043"),
+            BakeType::Aux2 => uuid!("03642e83-2bd1-4eb9-34b4-4c47ed586d2d"),
+            BakeType::Aux3 => uuid!("edd51b77-fc10-ce7a-4b3d-011dfc349e4f"),
+        }
+    }
+
+    /// The bake a Bakes on Mesh texture stands for, if the texture is one
+    pub fn from_bakes_on_mesh_texture(texture_id: Uuid) -> Option<Self> {
+        BakeType::ALL
+            .into_iter()
+            .find(|bake| bake.bakes_on_mesh_texture() == texture_id)
+    }
+
+    /// The slots that are layered into the bake, from the bottom layer to the top
+    pub fn layers(&self) -> &'static [TextureSlot] {
+        match self {
+            BakeType::Head => &[TextureSlot::HeadBodypaint, TextureSlot::HeadTattoo],
+            BakeType::UpperBody => &[
+                TextureSlot::UpperBodypaint,
+                TextureSlot::UpperTattoo,
+                TextureSlot::UpperUndershirt,
+                TextureSlot::UpperGloves,
+                TextureSlot::UpperShirt,
+                TextureSlot::UpperJacket,
+            ],
+            BakeType::LowerBody => &[
+                TextureSlot::LowerBodypaint,
+                TextureSlot::LowerTattoo,
+                TextureSlot::LowerUnderpants,
+                TextureSlot::LowerSocks,
+                TextureSlot::LowerShoes,
+                TextureSlot::LowerPants,
+                TextureSlot::LowerJacket,
+            ],
+            BakeType::Eyes => &[TextureSlot::EyesIris],
+            BakeType::Skirt => &[TextureSlot::Skirt],
+            BakeType::Hair => &[TextureSlot::Hair],
+            // the extra channels are only baked from universal wearables, which aren't supported
+            _ => &[],
+        }
+    }
+
+    /// The slot whose alpha hides parts of the bake, if it has one
+    pub fn alpha_slot(&self) -> Option<TextureSlot> {
+        match self {
+            BakeType::Head => Some(TextureSlot::HeadAlpha),
+            BakeType::UpperBody => Some(TextureSlot::UpperAlpha),
+            BakeType::LowerBody => Some(TextureSlot::LowerAlpha),
+            BakeType::Eyes => Some(TextureSlot::EyesAlpha),
+            BakeType::Hair => Some(TextureSlot::HairAlpha),
+            _ => None,
+        }
+    }
+
+    /// The width and height of the bake in pixels
+    pub fn size(&self) -> u32 {
+        match self {
+            BakeType::Eyes => 128,
+            _ => 512,
+        }
+    }
+
+    /// If the bake is drawn on skin, which shows through where no layer covers it
+    pub fn is_skin(&self) -> bool {
+        matches!(
+            self,
+            BakeType::Head | BakeType::UpperBody | BakeType::LowerBody
+        )
+    }
+}
@@ -0,0 +1,289 @@
+use crate::errors::ParseError;
+use byteorder::{LittleEndian, ReadBytesExt};
+use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Cursor, Read};
+
+/// The version of the keyframe motion format
+pub const KEYFRAME_MOTION_VERSION: u16 = 1;
+/// The sub version of the keyframe motion format
+pub const KEYFRAME_MOTION_SUB_VERSION: u16 = 0;
+
+/// How far position keys can move a joint from its rest position, in meters
+const MAX_OFFSET: f32 = 5.0;
+/// The length of the collision volume names in constraints
+const VOLUME_NAME_LENGTH: usize = 16;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// A custom animation, such as a dance or gesture, read from an animation asset in the keyframe
+/// motion (.anim) format.
+///
+/// Each animated joint has its own rotation and position keys, which are stored as u16s
+/// quantized over the duration of the animation and the range of the values. They are
+/// dequantized here, so times are in seconds and positions in meters.
+pub struct KeyframeMotion {
+    /// The priority the animation plays at. Animations of a higher priority override the joints
+    /// of lower ones.
+    pub priority: i32,
+    /// The length of the animation in seconds
+    pub duration: f32,
+    /// The name of the facial expression played with the animation, if any
+    pub emote_name: String,
+    /// The time in seconds the animation loops back to
+    pub loop_in_point: f32,
+    /// The time in seconds the animation loops from
+    pub loop_out_point: f32,
+    /// If the animation loops
+    pub looping: bool,
+    /// How long the animation blends in when it starts, in seconds
+    pub ease_in_duration: f32,
+    /// How long the animation blends out when it stops, in seconds
+    pub ease_out_duration: f32,
+    /// The pose of the avatar's hands while the animation plays
+    pub hand_pose: u32,
+    /// The animated joints
+    pub joints: Vec<JointMotion>,
+    /// The constraints that keep parts of the avatar in place while the animation plays
+    pub constraints: Vec<JointConstraint>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// The keys of one animated joint
+pub struct JointMotion {
+    /// The name of the joint, such as mPelvis
+    pub name: String,
+    /// The priority of the joint, or -1 if it plays at the animation's priority
+    pub priority: i32,
+    /// The rotation keys of the joint
+    pub rotation_keys: Vec<RotationKey>,
+    /// The position keys of the joint, as offsets from its rest position
+    pub position_keys: Vec<PositionKey>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// The rotation of a joint at a point in the animation
+pub struct RotationKey {
+    /// The time of the key in seconds
+    pub time: f32,
+    /// The rotation of the joint
+    pub rotation: Quat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// The position of a joint at a point in the animation
+pub struct PositionKey {
+    /// The time of the key in seconds
+    pub time: f32,
+    /// The position of the joint
+    pub position: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// How a constraint holds its source volume to its target
+pub enum ConstraintType {
+    /// The source volume is held to a point
+    Point,
+    /// The source volume is held above a plane, such as the ground
+    Plane,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// A constraint that holds a collision volume of the avatar to a target, such as keeping the
+/// feet on the ground, by bending the joints above it
+pub struct JointConstraint {
+    /// How many joints above the source volume bend to reach the target
+    pub chain_length: u8,
+    /// How the source volume is held to the target
+    pub constraint_type: ConstraintType,
+    /// The collision volume that is held in place
+    pub source_volume: String,
+    /// The offset of the held point from the source volume
+    pub source_offset: Vec3,
+    /// The collision volume the source is held to. Empty if it is held to the ground.
+    pub target_volume: String,
+    /// The offset of the target point from the target volume
+    pub target_offset: Vec3,
+    /// The direction of the target plane
+    pub target_direction: Vec3,
+    /// The time in seconds the constraint starts blending in
+    pub ease_in_start: f32,
+    /// The time in seconds the constraint is fully blended in
+    pub ease_in_stop: f32,
+    /// The time in seconds the constraint starts blending out
+    pub ease_out_start: f32,
+    /// The time in seconds the constraint is fully blended out
+    pub ease_out_stop: f32,
+}
+
+impl KeyframeMotion {
+    /// Parse a keyframe motion from the bytes of an animation asset
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(bytes);
+        let version = cursor.read_u16::<LittleEndian>()?;
+        let sub_version = cursor.read_u16::<LittleEndian>()?;
+        if version != KEYFRAME_MOTION_VERSION || sub_version != KEYFRAME_MOTION_SUB_VERSION {
+            return Err(ParseError::InvalidField(format!(
+                "Unsupported keyframe motion version: {}.{}",
+                version, sub_version
+            )));
+        }
+
+        let priority = cursor.read_i32::<LittleEndian>()?;
+        let duration = cursor.read_f32::<LittleEndian>()?;
+        if !duration.is_finite() || duration < 0.0 {
+            return Err(ParseError::InvalidField(format!(
+                "Invalid animation duration: {}",
+                duration
+            )));
+        }
+        let emote_name = read_string(&mut cursor)?;
+        let loop_in_point = cursor.read_f32::<LittleEndian>()?.clamp(0.0, duration);
+        let loop_out_point = cursor.read_f32::<LittleEndian>()?.clamp(0.0, duration);
+        let looping = cursor.read_i32::<LittleEndian>()? != 0;
+        let ease_in_duration = cursor.read_f32::<LittleEndian>()?;
+        let ease_out_duration = cursor.read_f32::<LittleEndian>()?;
+        let hand_pose = cursor.read_u32::<LittleEndian>()?;
+
+        let joint_count = cursor.read_u32::<LittleEndian>()?;
+        let mut joints = Vec::new();
+        for _ in 0..joint_count {
+            let name = read_string(&mut cursor)?;
+            let priority = cursor.read_i32::<LittleEndian>()?;
+
+            let mut rotation_keys = Vec::new();
+            for _ in 0..cursor.read_i32::<LittleEndian>()?.max(0) {
+                let time = dequantize(cursor.read_u16::<LittleEndian>()?, 0.0, duration);
+                let [x, y, z] = read_quantized(&mut cursor, -1.0, 1.0)?;
+                rotation_keys.push(RotationKey {
+                    time,
+                    rotation: unpack_rotation(Vec3::new(x, y, z)),
+                });
+            }
+
+            let mut position_keys = Vec::new();
+            for _ in 0..cursor.read_i32::<LittleEndian>()?.max(0) {
+                let time = dequantize(cursor.read_u16::<LittleEndian>()?, 0.0, duration);
+                let position = read_quantized(&mut cursor, -MAX_OFFSET, MAX_OFFSET)?;
+                position_keys.push(PositionKey {
+                    time,
+                    position: Vec3::from_array(position),
+                });
+            }
+
+            joints.push(JointMotion {
+                name,
+                priority,
+                rotation_keys,
+                position_keys,
+            });
+        }
+
+        let mut constraints = Vec::new();
+        // older animations end before the constraints
+        if (cursor.position() as usize) < bytes.len() {
+            for _ in 0..cursor.read_i32::<LittleEndian>()?.max(0) {
+                constraints.push(read_constraint(&mut cursor)?);
+            }
+        }
+
+        Ok(KeyframeMotion {
+            priority,
+            duration,
+            emote_name,
+            loop_in_point,
+            loop_out_point,
+            looping,
+            ease_in_duration,
+            ease_out_duration,
+            hand_pose,
+            joints,
+            constraints,
+        })
+    }
+}
+
+fn read_constraint(cursor: &mut Cursor<&[u8]>) -> Result<JointConstraint, ParseError> {
+    let chain_length = cursor.read_u8()?;
+    let constraint_type = match cursor.read_u8()? {
+        0 => ConstraintType::Point,
+        1 => ConstraintType::Plane,
+        other => {
+            return Err(ParseError::InvalidField(format!(
+                "Unknown constraint type: {}",
+                other
+            )));
+        }
+    };
+    let source_volume = read_fixed_string(cursor, VOLUME_NAME_LENGTH)?;
+    let source_offset = read_vec3(cursor)?;
+    let target_volume = read_fixed_string(cursor, VOLUME_NAME_LENGTH)?;
+    let target_offset = read_vec3(cursor)?;
+    let target_direction = read_vec3(cursor)?;
+    Ok(JointConstraint {
+        chain_length,
+        constraint_type,
+        source_volume,
+        source_offset,
+        target_volume,
+        target_offset,
+        target_direction,
+        ease_in_start: cursor.read_f32::<LittleEndian>()?,
+        ease_in_stop: cursor.read_f32::<LittleEndian>()?,
+        ease_out_start: cursor.read_f32::<LittleEndian>()?,
+        ease_out_stop: cursor.read_f32::<LittleEndian>()?,
+    })
+}
+
+/// Convert a quantized u16 back into the range it was stored in. Values within one step of zero
+/// are snapped to zero, so joints that don't move aren't offset by the rounding.
+fn dequantize(value: u16, lower: f32, upper: f32) -> f32 {
+    let step = (upper - lower) / u16::MAX as f32;
+    let value = value as f32 * step + lower;
+    if value.abs() < step { 0.0 } else { value }
+}
+
+fn read_quantized(
+    cursor: &mut Cursor<&[u8]>,
+    lower: f32,
+    upper: f32,
+) -> Result<[f32; 3], ParseError> {
+    let mut values = [0.0; 3];
+    for value in &mut values {
+        *value = dequantize(cursor.read_u16::<LittleEndian>()?, lower, upper);
+    }
+    Ok(values)
+}
+
+/// Rotations are stored as the vector part of a unit quaternion, and W is rebuilt from it
+fn unpack_rotation(vector: Vec3) -> Quat {
+    let w = (1.0 - vector.length_squared()).max(0.0).sqrt();
+    Quat::from_xyzw(vector.x, vector.y, vector.z, w).normalize()
+}
+
+/// Read a null terminated string
+fn read_string(cursor: &mut Cursor<&[u8]>) -> Result<String, ParseError> {
+    let mut bytes = Vec::new();
+    cursor.read_until(0, &mut bytes)?;
+    if bytes.pop() != Some(0) {
+        return Err(ParseError::InvalidField(
+            "Unterminated string in keyframe motion".to_string(),
+        ));
+    }
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// Read a null padded string of a fixed length
+fn read_fixed_string(cursor: &mut Cursor<&[u8]>, length: usize) -> Result<String, ParseError> {
+    let mut bytes = vec![0u8; length];
+    cursor.read_exact(&mut bytes)?;
+    let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(length);
+    Ok(String::from_utf8(bytes[..end].to_vec())?)
+}
+
+fn read_vec3(cursor: &mut Cursor<&[u8]>) -> Result<Vec3, ParseError> {
+    Ok(Vec3::new(
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+        cursor.read_f32::<LittleEndian>()?,
+    ))
+}
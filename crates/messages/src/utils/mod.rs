@@ -1,7 +1,15 @@
 /// global values used for describing agent access levels
 pub mod agent_access;
+/// Meshes of the default system avatar, read from the .llm files shipped with viewers
+pub mod avatar_mesh;
+/// The points on avatars that objects can be attached to, and the joints they follow
+pub mod attachment_point;
+/// The bake textures of system avatars, and the texture slots layered into them
+pub mod bake;
 /// Global values used for describing item metadat, such as name, permissions, etc
 pub mod item_metadata;
+/// Custom animations, read from the keyframe motion (.anim) format
+pub mod keyframe_motion;
 /// Material enum for defining object materials
 /// used for adding textures
 pub mod material;
@@ -15,3 +23,5 @@ pub mod region_flags;
 pub mod sound;
 /// texture information for objects
 pub mod texture_entry;
+/// Visual param definitions from avatar_lad.xml, used to shape avatars from their appearance
+pub mod visual_params;
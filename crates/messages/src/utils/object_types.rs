@@ -220,13 +220,15 @@ impl ObjectType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 /// Types for wearables. Used to determine what article of clothing they are.
 pub enum WearableType {
     /// Shape of the user. Contains body dimensions.
     Shape,
     /// Skin of the user. The texture applied to their body
     Skin,
+    /// Hair of the user. The shape and texture of their system hair.
+    Hair,
     /// Eyes of the user. The texture applied to their eyes.
     Eyes,
 
@@ -248,7 +250,12 @@ pub enum WearableType {
     Underpants,
     /// Skirt
     Skirt,
+    /// Alpha mask, that hides parts of the body
+    Alpha,
+    /// Tattoo, drawn over the skin
+    Tattoo,
     /// Unknown
+    #[default]
     Unknown,
 }
 impl WearableType {
@@ -256,7 +263,9 @@ impl WearableType {
     /// endpoint.
     pub fn category(&self) -> ObjectType {
         match self {
-            WearableType::Shape | WearableType::Skin | WearableType::Eyes => ObjectType::Bodypart,
+            WearableType::Shape | WearableType::Skin | WearableType::Hair | WearableType::Eyes => {
+                ObjectType::Bodypart
+            }
             WearableType::Shirt
             | WearableType::Pants
             | WearableType::Shoes
@@ -265,7 +274,9 @@ impl WearableType {
             | WearableType::Gloves
             | WearableType::Undershirt
             | WearableType::Underpants
-            | WearableType::Skirt => ObjectType::Clothing,
+            | WearableType::Skirt
+            | WearableType::Alpha
+            | WearableType::Tattoo => ObjectType::Clothing,
             WearableType::Unknown => ObjectType::Unknown,
         }
     }
@@ -274,6 +285,7 @@ impl WearableType {
         match bytes {
             0 => WearableType::Shape,
             1 => WearableType::Skin,
+            2 => WearableType::Hair,
             3 => WearableType::Eyes,
 
             4 => WearableType::Shirt,
@@ -285,6 +297,8 @@ impl WearableType {
             10 => WearableType::Undershirt,
             11 => WearableType::Underpants,
             12 => WearableType::Skirt,
+            13 => WearableType::Alpha,
+            14 => WearableType::Tattoo,
             _ => WearableType::Unknown,
         }
     }
@@ -293,6 +307,7 @@ impl WearableType {
         match self {
             WearableType::Shape => 0,
             WearableType::Skin => 1,
+            WearableType::Hair => 2,
             WearableType::Eyes => 3,
 
             WearableType::Shirt => 4,
@@ -304,6 +319,8 @@ impl WearableType {
             WearableType::Undershirt => 10,
             WearableType::Underpants => 11,
             WearableType::Skirt => 12,
+            WearableType::Alpha => 13,
+            WearableType::Tattoo => 14,
             WearableType::Unknown => 99,
         }
     }
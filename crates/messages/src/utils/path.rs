@@ -1,6 +1,6 @@
 use std::io::Cursor;
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -9,9 +9,10 @@ use serde::{Deserialize, Serialize};
 pub struct Path {
     /// This determines the type of path the shape follows.
     /// if it is a straight line, a circle, or etc
-    /// 0x00 is Linear,
-    /// 0x10 is Circular
-    /// 0x20 is a flexible path
+    /// 0x10 is Linear,
+    /// 0x20 is Circular
+    /// 0x30 is an alternate circular path
+    /// 0x80 is a flexible path
     pub curve: u8,
     /// The start point of the path. Controls hwo much of the extrustion is used and cuts off parts
     /// of the shape along the path.
@@ -43,7 +44,11 @@ pub struct Path {
     /// What the shape looks like from profile
     /// 0x00 is a circle
     /// 0x01 is a square
-    /// 0x02 is a triangle
+    /// 0x02 to 0x04 are triangles
+    /// 0x05 is a half circle
+    ///
+    /// The high four bits are the shape of the hollow. 0x00 is the same as the profile, 0x10 is a
+    /// circle, 0x20 is a square and 0x30 is a triangle.
     pub profile_curve: u8,
     /// The start point of the profile. Controls how much extrusion is used and cuts off parts of
     /// the shape horizontally along the profile.
@@ -87,4 +92,29 @@ impl Path {
             profile_shape: None,
         })
     }
+    /// converts a PrimitiveGeometry object into its 23 byte representation
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(23);
+        bytes.push(self.curve);
+        bytes.push(self.profile_curve);
+        bytes.write_u16::<LittleEndian>(self.begin).unwrap();
+        bytes.write_u16::<LittleEndian>(self.end).unwrap();
+        bytes.push(self.scale_x);
+        bytes.push(self.scale_y);
+        bytes.push(self.shear_x);
+        bytes.push(self.shear_y);
+        bytes.write_i8(self.twist_end).unwrap();
+        bytes.write_i8(self.twist_begin).unwrap();
+        bytes.write_i8(self.radius_offset).unwrap();
+        bytes.write_i8(self.taper_x).unwrap();
+        bytes.write_i8(self.taper_y).unwrap();
+        bytes.push(self.revolutions);
+        bytes.write_i8(self.skew).unwrap();
+        bytes.write_u16::<LittleEndian>(self.profile_begin).unwrap();
+        bytes.write_u16::<LittleEndian>(self.profile_end).unwrap();
+        bytes
+            .write_u16::<LittleEndian>((self.profile_hollow * 500.0).round() as u16)
+            .unwrap();
+        bytes
+    }
 }
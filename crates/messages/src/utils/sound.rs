@@ -1,4 +1,4 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 use std::io::{Cursor, Read};
 use uuid::Uuid;
@@ -36,4 +36,14 @@ impl AttachedSound {
             radius: cursor.read_f32::<LittleEndian>()?,
         })
     }
+    /// Convert a Sound object into its 41 byte representation
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(41);
+        bytes.extend_from_slice(self.sound_id.as_bytes());
+        bytes.extend_from_slice(self.owner_id.unwrap_or_default().as_bytes());
+        bytes.write_f32::<LittleEndian>(self.gain).unwrap();
+        bytes.push(self.flags);
+        bytes.write_f32::<LittleEndian>(self.radius).unwrap();
+        bytes
+    }
 }
@@ -1,11 +1,11 @@
 use std::{
-    collections::HashMap,
-    f32::consts::{PI, TAU},
+    collections::BTreeMap,
+    f32::consts::TAU,
     io::{Cursor, Read},
 };
 
 use base64::{Engine, engine::general_purpose};
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use rgb::Rgba;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -33,17 +33,25 @@ pub struct TextureEntry {
     pub media: u8,
     /// glow value of the texture
     pub glow: f32,
-    /// UUID of the texture
+    /// ID of the face's legacy normal and specular material, retrieved from the RenderMaterials
+    /// capability endpoint. Nil if the face has none.
     pub material_id: Uuid,
+    /// Faces that override any of the default values above, by face index.
+    ///
+    /// Each entry holds the full set of values for its face, with the values it doesn't override
+    /// copied from the default. Use [`TextureEntry::face`] to look up a face.
+    #[serde(default)]
+    pub faces: BTreeMap<u32, TextureEntry>,
 }
 
 impl Default for TextureEntry {
+    /// An untinted, unscaled texture, used for anything missing from a received entry
     fn default() -> Self {
         TextureEntry {
             texture_id: Uuid::nil(),
-            rgba: Rgba::new(0, 0, 0, 0),
-            repeat_u: 0.0,
-            repeat_v: 0.0,
+            rgba: Rgba::new(255, 255, 255, 255),
+            repeat_u: 1.0,
+            repeat_v: 1.0,
             offset_u: 0.0,
             offset_v: 0.0,
             rotation: 0.0,
@@ -51,528 +59,272 @@ impl Default for TextureEntry {
             media: 0,
             glow: 0.0,
             material_id: Uuid::nil(),
+            faces: BTreeMap::new(),
         }
     }
 }
 
 impl TextureEntry {
     /// Convert from a b64 byte array to a TextureEntry object.
-    /// Used by SceneObjects
+    /// Used by SceneObjects, which store the same bytes as the ObjectUpdate packets
     pub fn from_b64(b64: &[u8]) -> std::io::Result<Self> {
-        let mut faces: HashMap<u32, TextureEntry> = HashMap::new();
-        let mut texture = TextureEntry::default();
         if b64.len() < 16 {
-            return Ok(texture);
-        }
-        let bytes = general_purpose::STANDARD.decode(b64).unwrap();
-        let mut cursor = Cursor::new(&bytes[..]);
-
-        {
-            let mut id_bytes = [0u8; 16];
-            cursor.read_exact(&mut id_bytes)?;
-            texture.texture_id = Uuid::from_bytes(id_bytes);
-            loop {
-                let (bitfield, size) = read_b64_face_bitfield(&mut cursor)?;
-                if bitfield == 0 {
-                    break;
-                }
-                for face_index in 0..size {
-                    let bit = 1 << face_index;
-                    if bitfield & bit != 0 {
-                        let mut id_bytes = [0u8; 16];
-                        cursor.read_exact(&mut id_bytes)?;
-                        faces.entry(face_index).or_default().texture_id =
-                            Uuid::from_bytes(id_bytes);
-                    }
-                }
-            }
-        }
-
-        {
-            let red = cursor.read_u8()?;
-            let green = cursor.read_u8()?;
-            let blue = cursor.read_u8()?;
-            let alpha = cursor.read_u8()?;
-            texture.rgba = Rgba::new(red, green, blue, alpha);
-            loop {
-                let (bitfield, size) = read_b64_face_bitfield(&mut cursor)?;
-                if bitfield == 0 {
-                    break;
-                }
-                for face_index in 0..size {
-                    let bit = 1 << face_index;
-                    if bitfield & bit != 0 {
-                        let r = cursor.read_u8()?;
-                        let g = cursor.read_u8()?;
-                        let b = cursor.read_u8()?;
-                        let a = cursor.read_u8()?;
-                        faces.entry(face_index).or_default().rgba = Rgba::new(r, g, b, a);
-                    }
-                }
-            }
-        }
-
-        texture.repeat_u = cursor.read_f32::<LittleEndian>()?;
-        loop {
-            let (bitfield, size) = read_b64_face_bitfield(&mut cursor)?;
-            if bitfield == 0 {
-                break;
-            }
-            for face_index in 0..size {
-                let bit = 1 << face_index;
-                if bitfield & bit != 0 {
-                    faces.entry(face_index).or_default().repeat_u =
-                        cursor.read_f32::<LittleEndian>()?;
-                }
-            }
-        }
-
-        texture.repeat_v = cursor.read_f32::<LittleEndian>()?;
-        loop {
-            let (bitfield, size) = read_b64_face_bitfield(&mut cursor)?;
-            if bitfield == 0 {
-                break;
-            }
-            for face_index in 0..size {
-                let bit = 1 << face_index;
-                if bitfield & bit != 0 {
-                    faces.entry(face_index).or_default().repeat_v =
-                        cursor.read_f32::<LittleEndian>()?;
-                }
-            }
-        }
-
-        texture.offset_u = (cursor.read_i16::<LittleEndian>()? as f32) / 32767.0;
-        loop {
-            let (bitfield, size) = read_b64_face_bitfield(&mut cursor)?;
-            if bitfield == 0 {
-                break;
-            }
-            for face_index in 0..size {
-                let bit = 1 << face_index;
-                if bitfield & bit != 0 {
-                    let val = (cursor.read_i16::<LittleEndian>()? as f32) / 32767.0;
-                    faces.entry(face_index).or_default().offset_u = val;
-                }
-            }
-        }
-
-        texture.offset_v = (cursor.read_i16::<LittleEndian>()? as f32) / 32767.0;
-        loop {
-            let (bitfield, size) = read_b64_face_bitfield(&mut cursor)?;
-            if bitfield == 0 {
-                break;
-            }
-            for face_index in 0..size {
-                let bit = 1 << face_index;
-                if bitfield & bit != 0 {
-                    let val = (cursor.read_i16::<LittleEndian>()? as f32) / 32767.0;
-                    faces.entry(face_index).or_default().offset_v = val;
-                }
-            }
-        }
-
-        {
-            let rotation = cursor.read_i16::<LittleEndian>()? as f32;
-            let wrapped = rotation.rem_euclid(TAU);
-            let remainder = if wrapped > PI { wrapped - TAU } else { wrapped };
-            let normalized = (remainder / TAU) * 32768.0 + 0.5;
-            texture.rotation = normalized.round();
-            loop {
-                let (bitfield, size) = read_b64_face_bitfield(&mut cursor)?;
-                if bitfield == 0 {
-                    break;
-                }
-                for face_index in 0..size {
-                    let bit = 1 << face_index;
-                    if bitfield & bit != 0 {
-                        let rotation = cursor.read_i16::<LittleEndian>()? as f32;
-                        let wrapped = rotation.rem_euclid(TAU);
-                        let remainder = if wrapped > PI { wrapped - TAU } else { wrapped };
-                        let normalized = (remainder / TAU) * 32768.0 + 0.5;
-                        faces.entry(face_index).or_default().rotation = normalized.round();
-                    }
-                }
-            }
-        }
-
-        texture.material = cursor.read_u8()?;
-        loop {
-            let (bitfield, size) = read_b64_face_bitfield(&mut cursor)?;
-            if bitfield == 0 {
-                break;
-            }
-            for face_index in 0..size {
-                let bit = 1 << face_index;
-                if bitfield & bit != 0 {
-                    faces.entry(face_index).or_default().material = cursor.read_u8()?;
-                }
-            }
-        }
-
-        texture.media = cursor.read_u8()?;
-        loop {
-            let (bitfield, size) = read_b64_face_bitfield(&mut cursor)?;
-            if bitfield == 0 {
-                break;
-            }
-            for face_index in 0..size {
-                let bit = 1 << face_index;
-                if bitfield & bit != 0 {
-                    faces.entry(face_index).or_default().media = cursor.read_u8()?;
-                }
-            }
-        }
-
-        texture.glow = cursor.read_u8()? as f32 * 255.0;
-        loop {
-            let (bitfield, size) = read_b64_face_bitfield(&mut cursor)?;
-            if bitfield == 0 {
-                break;
-            }
-            for face_index in 0..size {
-                let bit = 1 << face_index;
-                if bitfield & bit != 0 {
-                    faces.entry(face_index).or_default().glow = cursor.read_u8()? as f32 * 255.0;
-                }
-            }
-        }
-
-        {
-            let mut id_bytes = [0u8; 16];
-            cursor.read_exact(&mut id_bytes)?;
-            texture.material_id = Uuid::from_bytes(id_bytes);
-            loop {
-                let (bitfield, size) = read_b64_face_bitfield(&mut cursor)?;
-                if bitfield == 0 {
-                    break;
-                }
-                for face_index in 0..size {
-                    let bit = 1 << face_index;
-                    if bitfield & bit != 0 {
-                        let mut id_bytes = [0u8; 16];
-                        cursor.read_exact(&mut id_bytes)?;
-                        faces.entry(face_index).or_default().material_id =
-                            Uuid::from_bytes(id_bytes);
-                    }
-                }
-            }
+            return Ok(TextureEntry::default());
         }
-
-        // inherit missing fields to faces
-        for face in faces.values_mut() {
-            face.inherit_missing(&texture);
-        }
-
-        Ok(texture)
+        let bytes = general_purpose::STANDARD
+            .decode(b64)
+            .map_err(std::io::Error::other)?;
+        Self::from_bytes(&bytes)
     }
 
     /// Convert from bytes to a TextureEntry
     /// used by ObjectUpdate and ObjectUpdateCompressed packets
+    ///
+    /// Each field is sent as a default value, followed by a list of face bitfields that override
+    /// the value for the faces set in the bitfield.
     pub fn from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
         let mut texture = TextureEntry::default();
-        let mut faces: HashMap<u32, TextureEntry> = HashMap::new();
+        let mut faces = BTreeMap::new();
         if bytes.len() < 16 {
             return Ok(texture);
         }
         let mut cursor = Cursor::new(bytes);
+        let c = &mut cursor;
+
+        read_field(c, &mut texture, &mut faces, 16, read_uuid, |t, v| {
+            t.texture_id = v
+        })?;
+        // colors are sent inverted
+        read_field(
+            c,
+            &mut texture,
+            &mut faces,
+            4,
+            |c| {
+                let mut rgba = [0u8; 4];
+                c.read_exact(&mut rgba)?;
+                Ok(Rgba::from(rgba.map(|c| !c)))
+            },
+            |t, v| t.rgba = v,
+        )?;
+        read_field(c, &mut texture, &mut faces, 4, read_f32, |t, v| {
+            t.repeat_u = v
+        })?;
+        read_field(c, &mut texture, &mut faces, 4, read_f32, |t, v| {
+            t.repeat_v = v
+        })?;
+        read_field(c, &mut texture, &mut faces, 2, read_offset, |t, v| {
+            t.offset_u = v
+        })?;
+        read_field(c, &mut texture, &mut faces, 2, read_offset, |t, v| {
+            t.offset_v = v
+        })?;
+        read_field(
+            c,
+            &mut texture,
+            &mut faces,
+            2,
+            |c| Ok(unpack_rotation(c.read_i16::<LittleEndian>()?)),
+            |t, v| t.rotation = v,
+        )?;
+        read_field(c, &mut texture, &mut faces, 1, read_u8, |t, v| {
+            t.material = v
+        })?;
+        read_field(c, &mut texture, &mut faces, 1, read_u8, |t, v| t.media = v)?;
+        read_field(
+            c,
+            &mut texture,
+            &mut faces,
+            1,
+            |c| Ok(c.read_u8()? as f32 / 255.0),
+            |t, v| t.glow = v,
+        )?;
+        read_field(c, &mut texture, &mut faces, 16, read_uuid, |t, v| {
+            t.material_id = v
+        })?;
+
+        texture.faces = faces;
+        Ok(texture)
+    }
 
-        // two mysterious bytes of padding?
-        cursor.read_u8()?;
-        cursor.read_u8()?;
-
-        fn remaining(cursor: &Cursor<&[u8]>) -> usize {
-            cursor.get_ref().len() - cursor.position() as usize
-        }
-
-        let mut uuid = [0u8; 16];
-        cursor.read_exact(&mut uuid)?;
-        texture.texture_id = Uuid::from_bytes(uuid);
-
-        // Texture IDs per face
-        loop {
-            if remaining(&cursor) < 1 {
-                break;
-            }
-            let mask = read_face_bitfield(&mut cursor)?;
-            if mask == 0 {
-                break;
-            }
-            if remaining(&cursor) < 16 {
-                break;
-            }
-            cursor.read_exact(&mut uuid)?;
-            let id = Uuid::from_bytes(uuid);
-            for_each_face(mask, |f| {
-                faces.entry(f).or_default().texture_id = id;
-            });
-        }
-
-        // RGBA
-        if remaining(&cursor) >= 4 {
-            let mut rgba = [0u8; 4];
-            cursor.read_exact(&mut rgba)?;
-            for c in &mut rgba {
-                *c = !*c;
-            }
-            texture.rgba = Rgba::from(rgba);
-
-            loop {
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let mask = read_face_bitfield(&mut cursor)?;
-                if mask == 0 {
-                    break;
-                }
-                if remaining(&cursor) < 4 {
-                    break;
-                }
-                cursor.read_exact(&mut rgba)?;
-                for c in &mut rgba {
-                    *c = !*c;
-                }
-                let c = Rgba::from(rgba);
-                for_each_face(mask, |f| {
-                    faces.entry(f).or_default().rgba = c;
-                });
-            }
-        }
+    /// Convert a TextureEntry to bytes.
+    /// used by ObjectUpdate and ObjectUpdateCompressed packets. Faces that share an overridden
+    /// value are written with a single bitfield.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_field(
+            &mut bytes,
+            self,
+            |t| t.texture_id,
+            |b, v| b.extend_from_slice(v.as_bytes()),
+        );
+        // colors are sent inverted
+        write_field(
+            &mut bytes,
+            self,
+            |t| t.rgba,
+            |b, v| b.extend([!v.r, !v.g, !v.b, !v.a]),
+        );
+        write_field(
+            &mut bytes,
+            self,
+            |t| t.repeat_u,
+            |b, v| b.write_f32::<LittleEndian>(v).unwrap(),
+        );
+        write_field(
+            &mut bytes,
+            self,
+            |t| t.repeat_v,
+            |b, v| b.write_f32::<LittleEndian>(v).unwrap(),
+        );
+        write_field(
+            &mut bytes,
+            self,
+            |t| t.offset_u,
+            |b, v| {
+                b.write_i16::<LittleEndian>((v * 32767.0).round() as i16)
+                    .unwrap()
+            },
+        );
+        write_field(
+            &mut bytes,
+            self,
+            |t| t.offset_v,
+            |b, v| {
+                b.write_i16::<LittleEndian>((v * 32767.0).round() as i16)
+                    .unwrap()
+            },
+        );
+        write_field(
+            &mut bytes,
+            self,
+            |t| t.rotation,
+            |b, v| b.write_i16::<LittleEndian>(pack_rotation(v)).unwrap(),
+        );
+        write_field(&mut bytes, self, |t| t.material, |b, v| b.push(v));
+        write_field(&mut bytes, self, |t| t.media, |b, v| b.push(v));
+        write_field(
+            &mut bytes,
+            self,
+            |t| t.glow,
+            |b, v| b.push((v * 255.0).round() as u8),
+        );
+        write_field(
+            &mut bytes,
+            self,
+            |t| t.material_id,
+            |b, v| b.extend_from_slice(v.as_bytes()),
+        );
+        bytes
+    }
 
-        // repeat_u
-        if remaining(&cursor) >= 4 {
-            texture.repeat_u = cursor.read_f32::<LittleEndian>()?;
-            loop {
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let mask = read_face_bitfield(&mut cursor)?;
-                if mask == 0 {
-                    break;
-                }
-                if remaining(&cursor) < 4 {
-                    break;
-                }
-                let v = cursor.read_f32::<LittleEndian>()?;
-                for_each_face(mask, |f| {
-                    faces.entry(f).or_default().repeat_u = v;
-                });
-            }
-        }
+    /// Get the texture data of a face. Faces without overrides use the default values.
+    pub fn face(&self, index: u32) -> &TextureEntry {
+        self.faces.get(&index).unwrap_or(self)
+    }
+}
 
-        // repeat_v
-        if remaining(&cursor) >= 4 {
-            texture.repeat_v = cursor.read_f32::<LittleEndian>()?;
-            loop {
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let mask = read_face_bitfield(&mut cursor)?;
-                if mask == 0 {
-                    break;
-                }
-                if remaining(&cursor) < 4 {
-                    break;
-                }
-                let v = cursor.read_f32::<LittleEndian>()?;
-                for_each_face(mask, |f| {
-                    faces.entry(f).or_default().repeat_v = v;
-                });
-            }
-        }
+/// Read the default value of a field, and the list of per-face overrides that follows it.
+/// Reading stops at the first field that is cut off, leaving the rest of the entry as default.
+fn read_field<T: Copy>(
+    cursor: &mut Cursor<&[u8]>,
+    texture: &mut TextureEntry,
+    faces: &mut BTreeMap<u32, TextureEntry>,
+    size: usize,
+    read: impl Fn(&mut Cursor<&[u8]>) -> std::io::Result<T>,
+    set: impl Fn(&mut TextureEntry, T),
+) -> std::io::Result<()> {
+    fn remaining(cursor: &Cursor<&[u8]>) -> usize {
+        cursor
+            .get_ref()
+            .len()
+            .saturating_sub(cursor.position() as usize)
+    }
 
-        // offset_u
-        if remaining(&cursor) >= 2 {
-            texture.offset_u = cursor.read_i16::<LittleEndian>()? as f32 / 32767.0;
-            loop {
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let mask = read_face_bitfield(&mut cursor)?;
-                if mask == 0 {
-                    break;
-                }
-                if remaining(&cursor) < 2 {
-                    break;
-                }
-                let v = cursor.read_i16::<LittleEndian>()? as f32 / 32767.0;
-                for_each_face(mask, |f| {
-                    faces.entry(f).or_default().offset_u = v;
-                });
-            }
-        }
+    if remaining(cursor) < size {
+        return Ok(());
+    }
+    let value = read(cursor)?;
+    set(texture, value);
+    // faces overridden by an earlier field were created before this default was known
+    for face in faces.values_mut() {
+        set(face, value);
+    }
 
-        // offset_v
-        if remaining(&cursor) >= 2 {
-            texture.offset_v = cursor.read_i16::<LittleEndian>()? as f32 / 32767.0;
-            loop {
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let mask = read_face_bitfield(&mut cursor)?;
-                if mask == 0 {
-                    break;
-                }
-                if remaining(&cursor) < 2 {
-                    break;
-                }
-                let v = cursor.read_i16::<LittleEndian>()? as f32 / 32767.0;
-                for_each_face(mask, |f| {
-                    faces.entry(f).or_default().offset_v = v;
-                });
-            }
+    loop {
+        if remaining(cursor) < 1 {
+            break;
         }
-
-        // rotation
-        if remaining(&cursor) >= 2 {
-            texture.rotation = cursor.read_i16::<LittleEndian>()? as f32 * PI / 32767.0;
-            loop {
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let mask = read_face_bitfield(&mut cursor)?;
-                if mask == 0 {
-                    break;
-                }
-                if remaining(&cursor) < 2 {
-                    break;
-                }
-                let r = cursor.read_i16::<LittleEndian>()? as f32 * PI / 32767.0;
-                for_each_face(mask, |f| {
-                    faces.entry(f).or_default().rotation = r;
-                });
-            }
+        let mask = read_face_bitfield(cursor)?;
+        if mask == 0 || remaining(cursor) < size {
+            break;
         }
+        let value = read(cursor)?;
+        for_each_face(mask, |f| {
+            set(faces.entry(f).or_insert_with(|| texture.clone()), value);
+        });
+    }
+    Ok(())
+}
 
-        // material
-        if remaining(&cursor) >= 1 {
-            texture.material = cursor.read_u8()?;
-            loop {
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let mask = read_face_bitfield(&mut cursor)?;
-                if mask == 0 {
-                    break;
-                }
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let v = cursor.read_u8()?;
-                for_each_face(mask, |f| {
-                    faces.entry(f).or_default().material = v;
-                });
-            }
+/// Write the default value of a field, followed by the faces that override it and a terminating
+/// empty bitfield.
+fn write_field<T: Copy + PartialEq>(
+    bytes: &mut Vec<u8>,
+    texture: &TextureEntry,
+    get: impl Fn(&TextureEntry) -> T,
+    write: impl Fn(&mut Vec<u8>, T),
+) {
+    let default = get(texture);
+    write(bytes, default);
+
+    let mut overrides: Vec<(u32, T)> = Vec::new();
+    for (face, entry) in &texture.faces {
+        let value = get(entry);
+        if value == default {
+            continue;
+        }
+        match overrides.iter_mut().find(|(_, v)| *v == value) {
+            Some((mask, _)) => *mask |= 1 << face,
+            None => overrides.push((1 << face, value)),
         }
+    }
+    for (mask, value) in overrides {
+        write_face_bitfield(bytes, mask);
+        write(bytes, value);
+    }
+    bytes.push(0);
+}
 
-        // media
-        if remaining(&cursor) >= 1 {
-            texture.media = cursor.read_u8()?;
-            loop {
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let mask = read_face_bitfield(&mut cursor)?;
-                if mask == 0 {
-                    break;
-                }
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let v = cursor.read_u8()?;
-                for_each_face(mask, |f| {
-                    faces.entry(f).or_default().media = v;
-                });
-            }
-        }
+fn read_uuid(cursor: &mut Cursor<&[u8]>) -> std::io::Result<Uuid> {
+    let mut uuid = [0u8; 16];
+    cursor.read_exact(&mut uuid)?;
+    Ok(Uuid::from_bytes(uuid))
+}
 
-        // glow
-        if remaining(&cursor) >= 1 {
-            texture.glow = cursor.read_u8()? as f32 / 255.0;
-            loop {
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let mask = read_face_bitfield(&mut cursor)?;
-                if mask == 0 {
-                    break;
-                }
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let g = cursor.read_u8()? as f32 / 255.0;
-                for_each_face(mask, |f| {
-                    faces.entry(f).or_default().glow = g;
-                });
-            }
-        }
+fn read_f32(cursor: &mut Cursor<&[u8]>) -> std::io::Result<f32> {
+    cursor.read_f32::<LittleEndian>()
+}
 
-        // material_id
-        if remaining(&cursor) >= 16 {
-            cursor.read_exact(&mut uuid)?;
-            texture.material_id = Uuid::from_bytes(uuid);
-            loop {
-                if remaining(&cursor) < 1 {
-                    break;
-                }
-                let mask = read_face_bitfield(&mut cursor)?;
-                if mask == 0 {
-                    break;
-                }
-                if remaining(&cursor) < 16 {
-                    break;
-                }
-                cursor.read_exact(&mut uuid)?;
-                let id = Uuid::from_bytes(uuid);
-                for_each_face(mask, |f| {
-                    faces.entry(f).or_default().material_id = id;
-                });
-            }
-        }
+fn read_u8(cursor: &mut Cursor<&[u8]>) -> std::io::Result<u8> {
+    cursor.read_u8()
+}
 
-        // inherit missing fields to faces
-        for face in faces.values_mut() {
-            face.inherit_missing(&texture);
-        }
+fn read_offset(cursor: &mut Cursor<&[u8]>) -> std::io::Result<f32> {
+    Ok(cursor.read_i16::<LittleEndian>()? as f32 / 32767.0)
+}
 
-        Ok(texture)
-    }
+/// Rotations are packed as a fraction of a full turn, in radians
+fn unpack_rotation(packed: i16) -> f32 {
+    packed as f32 / 32768.0 * TAU
+}
 
-    fn inherit_missing(&mut self, base: &TextureEntry) {
-        if self.texture_id.is_nil() {
-            self.texture_id = base.texture_id;
-        }
-        if self.rgba == Rgba::default() {
-            self.rgba = base.rgba;
-        }
-        if self.repeat_u == 0.0 {
-            self.repeat_u = base.repeat_u;
-        }
-        if self.repeat_v == 0.0 {
-            self.repeat_v = base.repeat_v;
-        }
-        if self.offset_u == 0.0 {
-            self.offset_u = base.offset_u;
-        }
-        if self.offset_v == 0.0 {
-            self.offset_v = base.offset_v;
-        }
-        if self.rotation == 0.0 {
-            self.rotation = base.rotation;
-        }
-        if self.material == 0 {
-            self.material = base.material;
-        }
-        if self.media == 0 {
-            self.media = base.media;
-        }
-        if self.glow == 0.0 {
-            self.glow = base.glow;
-        }
-        if self.material_id.is_nil() {
-            self.material_id = base.material_id;
-        }
-    }
+fn pack_rotation(rotation: f32) -> i16 {
+    // wrap into -PI..PI so the value fits the packed range
+    let wrapped = rotation - TAU * (rotation / TAU).round();
+    (wrapped / TAU * 32768.0)
+        .round()
+        .clamp(i16::MIN as f32, i16::MAX as f32) as i16
 }
 
 #[inline]
@@ -584,38 +336,31 @@ fn for_each_face(mask: u32, mut f: impl FnMut(u32)) {
     }
 }
 
+/// Face bitfields are written as big endian groups of 7 bits, where the high bit of each byte
+/// marks that another byte follows.
 fn read_face_bitfield<R: Read>(r: &mut R) -> std::io::Result<u32> {
-    let first = r.read_u8()?;
-    if first == 0 {
-        return Ok(0);
-    }
-    if first & 0x80 == 0 {
-        return Ok(first as u32);
-    }
-    let second = r.read_u8()?;
-    let mut value = ((first as u32 & 0x7F) << 7) | (second as u32 & 0x7F);
-    if second & 0x80 == 0 {
-        return Ok(value);
+    let mut value = 0u32;
+    loop {
+        let byte = r.read_u8()?;
+        value = (value << 7) | (byte & 0x7F) as u32;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
     }
-    let third = r.read_u8()?;
-    let fourth = r.read_u8()?;
-    value |= (third as u32) << 14;
-    value |= (fourth as u32) << 22;
-    Ok(value)
 }
 
-fn read_b64_face_bitfield(cursor: &mut Cursor<&[u8]>) -> std::io::Result<(u32, u32)> {
-    let mut face_bits = 0u32;
-    let mut bitfield_size = 0u32;
+fn write_face_bitfield(bytes: &mut Vec<u8>, mask: u32) {
+    let mut groups = Vec::new();
+    let mut remaining = mask;
     loop {
-        let mut b = [0u8];
-        cursor.read_exact(&mut b)?;
-        let b = b[0];
-        face_bits = (face_bits << 7) | (b & 0x7F) as u32;
-        bitfield_size += 7;
-        if b & 0x80 == 0 {
+        groups.push((remaining & 0x7F) as u8);
+        remaining >>= 7;
+        if remaining == 0 {
             break;
         }
     }
-    Ok((face_bits, bitfield_size))
+    let last = groups.len() - 1;
+    for (i, group) in groups.iter().rev().enumerate() {
+        bytes.push(if i < last { group | 0x80 } else { *group });
+    }
 }
@@ -0,0 +1,410 @@
+use crate::errors::ParseError;
+use benthic_protocol::skeleton::JointName;
+use glam::Vec3;
+use quick_xml::{
+    Reader,
+    events::{BytesStart, Event},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+/// The ID of the param that sets the sex of the avatar. Params that only apply to one sex are
+/// skipped for avatars of the other.
+pub const SEX_PARAM_ID: i32 = 80;
+
+#[derive(Debug, Clone, Default)]
+/// The definitions of the visual params that make up the shape of an avatar, read from the
+/// avatar_lad.xml file shipped with every viewer.
+///
+/// The AvatarAppearance packet only contains one byte per param, in order of the params' IDs.
+/// The definitions are needed to know which params were sent, what range each byte covers, and
+/// what each param does to the avatar.
+pub struct AvatarLad {
+    /// The params, by ID
+    pub params: BTreeMap<i32, VisualParam>,
+}
+
+#[derive(Debug, Clone)]
+/// A single visual param definition
+pub struct VisualParam {
+    /// ID of the param
+    pub id: i32,
+    /// The group of the param. Params in groups 0 and 3 are sent to other viewers.
+    pub group: u8,
+    /// Name of the param. Morph params use this to find their morph in the avatar mesh.
+    pub name: String,
+    /// Which sex the param applies to
+    pub sex: ParamSex,
+    /// The weight of the param when its byte is 0
+    pub value_min: f32,
+    /// The weight of the param when its byte is 255
+    pub value_max: f32,
+    /// The weight of the param when it hasn't been set
+    pub value_default: f32,
+    /// What the param does to the avatar
+    pub kind: VisualParamKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The sex of the avatars a param applies to
+pub enum ParamSex {
+    /// Applies to all avatars
+    #[default]
+    Both,
+    /// Only applies to male avatars
+    Male,
+    /// Only applies to female avatars
+    Female,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The effect a visual param has on the avatar
+pub enum VisualParamKind {
+    /// Scales and moves joints of the skeleton
+    Skeleton(Vec<BoneDeformation>),
+    /// Blends a morph of the avatar mesh, named after the param
+    Morph,
+    /// Sets the weights of other params
+    Driver(Vec<DrivenParam>),
+    /// Colors, alpha masks and other effects on the avatar's bake textures
+    #[default]
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// How a skeleton param deforms one joint at a weight of 1
+pub struct BoneDeformation {
+    /// The joint that is deformed
+    pub joint: JointName,
+    /// Added to the scale of the joint
+    pub scale: Vec3,
+    /// Added to the position of the joint, relative to its parent
+    pub offset: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// A param set by a driver param.
+///
+/// The driven param ramps from its minimum to its maximum as the driver goes from min1 to max1,
+/// stays at its maximum until max2, and ramps back down to its minimum by min2.
+pub struct DrivenParam {
+    /// ID of the driven param
+    pub id: i32,
+    /// The driver weight where the driven param starts to rise
+    pub min1: f32,
+    /// The driver weight where the driven param reaches its maximum
+    pub max1: f32,
+    /// The driver weight where the driven param starts to fall
+    pub max2: f32,
+    /// The driver weight where the driven param reaches its minimum again
+    pub min2: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// The shape of an avatar, calculated from its visual params
+pub struct AvatarShape {
+    /// How each joint is deformed from the default skeleton
+    pub joints: BTreeMap<JointName, JointDeformation>,
+    /// The weights of the avatar mesh's morphs, by name. Morphs with no weight are left out.
+    pub morphs: BTreeMap<String, f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// How a joint is deformed from the default skeleton
+pub struct JointDeformation {
+    /// The scale of the joint
+    pub scale: Vec3,
+    /// How far the joint is moved from its default position, relative to its parent
+    pub offset: Vec3,
+}
+
+impl Default for JointDeformation {
+    fn default() -> Self {
+        JointDeformation {
+            scale: Vec3::ONE,
+            offset: Vec3::ZERO,
+        }
+    }
+}
+
+impl AvatarLad {
+    /// Read the param definitions from the contents of avatar_lad.xml.
+    ///
+    /// Params that are defined more than once, such as morphs shared between the levels of detail
+    /// of a mesh, keep their first definition. Bones that aren't joints of the skeleton, like the
+    /// collision volumes, are skipped.
+    pub fn from_xml(bytes: &[u8]) -> Result<Self, ParseError> {
+        let mut params = BTreeMap::new();
+        let mut reader = Reader::from_reader(bytes);
+        let mut buf = Vec::new();
+        let mut current: Option<VisualParam> = None;
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(ref e) if e.name().as_ref() == b"param" => {
+                    current = Some(VisualParam::from_attributes(e)?);
+                }
+                Event::Start(ref e) | Event::Empty(ref e) => {
+                    if let Some(param) = current.as_mut() {
+                        param.read_child(e)?;
+                    }
+                }
+                Event::End(ref e) if e.name().as_ref() == b"param" => {
+                    if let Some(param) = current.take() {
+                        params.entry(param.id).or_insert(param);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(AvatarLad { params })
+    }
+
+    /// The params that are sent in the AvatarAppearance packet, in the order they are sent
+    pub fn transmitted(&self) -> impl Iterator<Item = &VisualParam> {
+        self.params
+            .values()
+            .filter(|param| param.group == 0 || param.group == 3)
+    }
+
+    /// The weight of every param, from the bytes of an AvatarAppearance packet. Params that
+    /// weren't sent keep their default, unless a driver param sets them.
+    pub fn weights(&self, visual_params: &[u8]) -> HashMap<i32, f32> {
+        let mut weights: HashMap<i32, f32> = self
+            .params
+            .values()
+            .map(|param| (param.id, param.value_default))
+            .collect();
+        for (param, byte) in self.transmitted().zip(visual_params) {
+            weights.insert(param.id, param.weight_from_u8(*byte));
+        }
+        for param in self.transmitted() {
+            let VisualParamKind::Driver(driven) = &param.kind else {
+                continue;
+            };
+            let weight = weights[&param.id];
+            for entry in driven {
+                if let Some(driven_param) = self.params.get(&entry.id) {
+                    weights.insert(entry.id, param.driven_weight(entry, driven_param, weight));
+                }
+            }
+        }
+        weights
+    }
+
+    /// Calculate the shape of an avatar from the bytes of an AvatarAppearance packet
+    pub fn shape(&self, visual_params: &[u8]) -> AvatarShape {
+        let weights = self.weights(visual_params);
+        let sex = match weights.get(&SEX_PARAM_ID) {
+            Some(weight) if *weight > 0.5 => ParamSex::Male,
+            _ => ParamSex::Female,
+        };
+
+        let mut shape = AvatarShape::default();
+        for param in self.params.values() {
+            if param.sex != ParamSex::Both && param.sex != sex {
+                continue;
+            }
+            let weight = weights[&param.id];
+            match &param.kind {
+                VisualParamKind::Skeleton(bones) => {
+                    for bone in bones {
+                        let joint = shape.joints.entry(bone.joint).or_default();
+                        joint.scale += bone.scale * weight;
+                        joint.offset += bone.offset * weight;
+                    }
+                }
+                VisualParamKind::Morph if weight != 0.0 => {
+                    shape.morphs.insert(param.name.clone(), weight);
+                }
+                _ => {}
+            }
+        }
+        shape
+    }
+
+    /// The bytes of an AvatarAppearance packet for the given param weights, such as the params of
+    /// an avatar's wearables. Params that aren't given are sent at their default.
+    pub fn visual_params(&self, weights: &HashMap<i32, f32>) -> Vec<u8> {
+        self.transmitted()
+            .map(|param| {
+                param.weight_to_u8(
+                    weights
+                        .get(&param.id)
+                        .copied()
+                        .unwrap_or(param.value_default),
+                )
+            })
+            .collect()
+    }
+}
+
+impl VisualParam {
+    /// Convert a byte from an AvatarAppearance packet to the weight of the param. Weights within
+    /// one step of zero are snapped to zero, so params centered on zero can be turned off.
+    pub fn weight_from_u8(&self, byte: u8) -> f32 {
+        let range = self.value_max - self.value_min;
+        let weight = self.value_min + (byte as f32 / 255.0) * range;
+        if weight.abs() < range / 255.0 {
+            0.0
+        } else {
+            weight
+        }
+    }
+
+    /// Convert the weight of the param to the byte it is sent as in an AvatarAppearance packet
+    pub fn weight_to_u8(&self, weight: f32) -> u8 {
+        let range = self.value_max - self.value_min;
+        if range <= 0.0 {
+            return 0;
+        }
+        (((weight - self.value_min) / range).clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+
+    /// The weight a driver param at the given weight sets a driven param to
+    fn driven_weight(&self, entry: &DrivenParam, driven: &VisualParam, weight: f32) -> f32 {
+        let (driven_min, driven_max) = (driven.value_min, driven.value_max);
+        if weight <= entry.min1 {
+            if entry.min1 == entry.max1 && entry.min1 <= self.value_min {
+                driven_max
+            } else {
+                driven_min
+            }
+        } else if weight <= entry.max1 {
+            let t = (weight - entry.min1) / (entry.max1 - entry.min1);
+            driven_min + t * (driven_max - driven_min)
+        } else if weight <= entry.max2 {
+            driven_max
+        } else if weight <= entry.min2 {
+            let t = (weight - entry.max2) / (entry.min2 - entry.max2);
+            driven_max + t * (driven_min - driven_max)
+        } else if entry.max2 >= self.value_max {
+            driven_max
+        } else {
+            driven_min
+        }
+    }
+
+    fn from_attributes(e: &BytesStart) -> Result<Self, ParseError> {
+        let mut param = VisualParam {
+            id: 0,
+            group: 0,
+            name: String::new(),
+            sex: ParamSex::Both,
+            value_min: 0.0,
+            value_max: 1.0,
+            value_default: 0.0,
+            kind: VisualParamKind::Other,
+        };
+        let mut has_default = false;
+        for attribute in e.attributes() {
+            let attribute = attribute.map_err(quick_xml::Error::from)?;
+            let value = attribute.unescape_value()?;
+            match attribute.key.as_ref() {
+                b"id" => param.id = value.trim().parse()?,
+                b"group" => param.group = value.trim().parse()?,
+                b"name" => param.name = value.into_owned(),
+                b"sex" => {
+                    param.sex = match value.as_ref() {
+                        "male" => ParamSex::Male,
+                        "female" => ParamSex::Female,
+                        _ => ParamSex::Both,
+                    }
+                }
+                b"value_min" => param.value_min = value.trim().parse()?,
+                b"value_max" => param.value_max = value.trim().parse()?,
+                b"value_default" => {
+                    param.value_default = value.trim().parse()?;
+                    has_default = true;
+                }
+                _ => {}
+            }
+        }
+        // params without a default start at the nearest end of their range to zero
+        if !has_default {
+            param.value_default = 0.0_f32.clamp(param.value_min, param.value_max);
+        }
+        Ok(param)
+    }
+
+    fn read_child(&mut self, e: &BytesStart) -> Result<(), ParseError> {
+        match e.name().as_ref() {
+            b"param_skeleton" => self.kind = VisualParamKind::Skeleton(Vec::new()),
+            b"param_morph" => self.kind = VisualParamKind::Morph,
+            b"param_driver" => self.kind = VisualParamKind::Driver(Vec::new()),
+            b"bone" => {
+                let VisualParamKind::Skeleton(bones) = &mut self.kind else {
+                    return Ok(());
+                };
+                let mut name = String::new();
+                let mut scale = Vec3::ZERO;
+                let mut offset = Vec3::ZERO;
+                for attribute in e.attributes() {
+                    let attribute = attribute.map_err(quick_xml::Error::from)?;
+                    let value = attribute.unescape_value()?;
+                    match attribute.key.as_ref() {
+                        b"name" => name = value.into_owned(),
+                        b"scale" => scale = parse_vec3(&value)?,
+                        b"offset" => offset = parse_vec3(&value)?,
+                        _ => {}
+                    }
+                }
+                if let Ok(joint) = JointName::from_str(&name) {
+                    bones.push(BoneDeformation {
+                        joint,
+                        scale,
+                        offset,
+                    });
+                }
+            }
+            b"driven" => {
+                let (value_min, value_max) = (self.value_min, self.value_max);
+                let VisualParamKind::Driver(driven) = &mut self.kind else {
+                    return Ok(());
+                };
+                // without the second ramp, the driven param stays at its maximum
+                let mut entry = DrivenParam {
+                    id: 0,
+                    min1: value_min,
+                    max1: value_max,
+                    max2: value_max,
+                    min2: value_max,
+                };
+                for attribute in e.attributes() {
+                    let attribute = attribute.map_err(quick_xml::Error::from)?;
+                    let value = attribute.unescape_value()?;
+                    match attribute.key.as_ref() {
+                        b"id" => entry.id = value.trim().parse()?,
+                        b"min1" => entry.min1 = value.trim().parse()?,
+                        b"max1" => entry.max1 = value.trim().parse()?,
+                        b"max2" => entry.max2 = value.trim().parse()?,
+                        b"min2" => entry.min2 = value.trim().parse()?,
+                        _ => {}
+                    }
+                }
+                driven.push(entry);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Parse a vector written as three space separated floats, like "0 -.05 0"
+fn parse_vec3(value: &str) -> Result<Vec3, ParseError> {
+    let mut components = [0.0; 3];
+    for (component, text) in components.iter_mut().zip(value.split_whitespace()) {
+        *component = text.parse()?;
+    }
+    Ok(Vec3::from_array(components))
+}
+
+impl AvatarShape {
+    /// The deformation of a joint. Joints no param touches are left as they are.
+    pub fn joint(&self, joint: &JointName) -> JointDeformation {
+        self.joints.get(joint).copied().unwrap_or_default()
+    }
+}
@@ -0,0 +1,89 @@
+use metaverse_messages::http::gltf_material::{AlphaMode, GltfMaterial, OVERRIDE_NULL_TEXTURE};
+use serde_llsd_benthic::LLSDValue;
+use std::collections::HashMap;
+use uuid::uuid;
+
+const MATERIAL: &str = r#"{
+    "asset": {"version": "2.0"},
+    "images": [
+        {"uri": "0e41a2e3-5cf4-4b9c-9b6e-0f3c1d0a7a11"},
+        {"uri": "b4c1a8f0-27e2-4e7d-8d4a-9a8f5e3e6c22"}
+    ],
+    "textures": [{"source": 1}, {"source": 0}],
+    "materials": [{
+        "pbrMetallicRoughness": {
+            "baseColorFactor": [1.0, 0.5, 0.25, 1.0],
+            "baseColorTexture": {"index": 1},
+            "metallicFactor": 0.0,
+            "roughnessFactor": 0.75,
+            "metallicRoughnessTexture": {"index": 0}
+        },
+        "normalTexture": {"index": 0},
+        "emissiveFactor": [0.0, 0.0, 1.0],
+        "alphaMode": "MASK",
+        "alphaCutoff": 0.25,
+        "doubleSided": true
+    }]
+}"#;
+
+#[test]
+fn test_gltf_material() {
+    let material = GltfMaterial::from_json(MATERIAL).unwrap();
+    let first = uuid!("0e41a2e3-5cf4-4b9c-9b6e-0f3c1d0a7a11");
+    let second = uuid!("b4c1a8f0-27e2-4e7d-8d4a-9a8f5e3e6c22");
+    assert_eq!(material.base_color_texture, Some(first));
+    assert_eq!(material.metallic_roughness_texture, Some(second));
+    assert_eq!(material.normal_texture, Some(second));
+    assert_eq!(material.emissive_texture, None);
+    assert_eq!(material.base_color, [1.0, 0.5, 0.25, 1.0]);
+    assert_eq!(material.emissive, [0.0, 0.0, 1.0]);
+    assert_eq!(material.metallic, 0.0);
+    assert_eq!(material.roughness, 0.75);
+    assert_eq!(material.alpha_mode, AlphaMode::Mask);
+    assert_eq!(material.alpha_cutoff, 0.25);
+    assert!(material.double_sided);
+    assert_eq!(material.textures().count(), 3);
+
+    // values that are left out use the GLTF defaults
+    let empty = GltfMaterial::from_json(r#"{"materials": [{}]}"#).unwrap();
+    assert_eq!(empty, GltfMaterial::default());
+    assert!(GltfMaterial::from_json(r#"{"materials": []}"#).is_err());
+}
+
+#[test]
+fn test_gltf_material_override() {
+    let mut material = GltfMaterial::from_json(MATERIAL).unwrap();
+    let replacement = uuid!("8dcd4a48-2d37-4909-9f78-f7a9eb4ef903");
+    material.apply_override(&LLSDValue::Map(HashMap::from([
+        (
+            "tex".to_string(),
+            LLSDValue::Array(vec![
+                LLSDValue::UUID(replacement),
+                LLSDValue::UUID(OVERRIDE_NULL_TEXTURE),
+            ]),
+        ),
+        (
+            "ec".to_string(),
+            LLSDValue::Array(vec![
+                LLSDValue::Real(1.0),
+                LLSDValue::Real(1.0),
+                LLSDValue::Real(0.0),
+            ]),
+        ),
+        ("rf".to_string(), LLSDValue::Real(0.5)),
+        ("am".to_string(), LLSDValue::Integer(1)),
+        ("ds".to_string(), LLSDValue::Boolean(false)),
+    ])));
+
+    assert_eq!(material.base_color_texture, Some(replacement));
+    assert_eq!(material.normal_texture, None);
+    assert_eq!(
+        material.metallic_roughness_texture,
+        Some(uuid!("b4c1a8f0-27e2-4e7d-8d4a-9a8f5e3e6c22"))
+    );
+    assert_eq!(material.emissive, [1.0, 1.0, 0.0]);
+    assert_eq!(material.roughness, 0.5);
+    assert_eq!(material.metallic, 0.0);
+    assert_eq!(material.alpha_mode, AlphaMode::Blend);
+    assert!(!material.double_sided);
+}
@@ -1,4 +1,5 @@
-use metaverse_messages::http::item::ItemData;
+use metaverse_messages::http::item::{ItemData, TextureSlot};
+use metaverse_messages::utils::object_types::WearableType;
 use std::{fs::File, io::Read};
 
 #[test]
@@ -7,6 +8,16 @@ fn parse_login_response() {
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer).unwrap();
 
-    let llsd = ItemData::from_bytes(&buffer);
-    assert_eq!(llsd.unwrap().version, 22);
+    let item = ItemData::from_bytes(&buffer).unwrap();
+    assert_eq!(item.version, 22);
+    assert_eq!(item.wearable_type, WearableType::Pants);
+}
+
+#[test]
+fn test_texture_slots() {
+    // wearables and the avatar's texture entry index their slots the same way
+    assert_eq!("2".parse(), Ok(TextureSlot::LowerPants));
+    assert_eq!(TextureSlot::from_index(20), TextureSlot::HairBaked);
+    assert_eq!(TextureSlot::Aux3Baked.index(), 44);
+    assert_eq!("35".parse(), Ok(TextureSlot::Unknown));
 }
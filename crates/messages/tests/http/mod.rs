@@ -1,6 +1,8 @@
+pub mod gltf_material;
 pub mod item;
 pub mod login_response;
 pub mod parse_environment;
 pub mod parse_mesh_data;
+pub mod render_materials;
 pub mod scenegroup;
 pub mod simulator_login_protocol;
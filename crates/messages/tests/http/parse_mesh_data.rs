@@ -1,6 +1,6 @@
 use std::{fs::File, io::Read};
 
-use metaverse_messages::http::mesh::Mesh;
+use metaverse_messages::http::mesh::{Mesh, MeshGeometry, MeshHeader, Skin};
 
 #[test]
 fn handle_mesh_data() {
@@ -10,6 +10,38 @@ fn handle_mesh_data() {
 
     let mesh = Mesh::from_bytes(&buffer).unwrap();
     assert!(mesh.skin.is_some());
+    assert!(!mesh.high_level_of_detail.is_empty());
+    assert!(
+        mesh.high_level_of_detail
+            .iter()
+            .any(|submesh| !submesh.no_geometry && !submesh.indices.is_empty())
+    );
 
     println!("{:?}", mesh);
 }
+
+#[test]
+fn handle_mesh_header_prefix() {
+    let mut file = File::open("tests/data/mesh_data.txt").unwrap();
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer).unwrap();
+
+    // the header can be read without the compressed sections that follow it
+    let header = MeshHeader::from_bytes(&buffer).unwrap();
+    let prefix = MeshHeader::from_bytes(&buffer[..header.size + 2]).unwrap();
+    assert_eq!(prefix.size, header.size);
+    assert_eq!(prefix.high_lod, header.high_lod);
+    assert_eq!(prefix.lowest_lod, header.lowest_lod);
+    assert_eq!(prefix.skin, header.skin);
+
+    // sections can be decoded on their own
+    let mesh = Mesh::from_bytes(&buffer).unwrap();
+    let skin = Skin::from_compressed(&buffer[header.skin.unwrap().range(header.size)]).unwrap();
+    let high = MeshGeometry::from_compressed(
+        &buffer[header.high_lod.unwrap().range(header.size)],
+        &Some(skin),
+    )
+    .unwrap();
+    assert_eq!(high.len(), mesh.high_level_of_detail.len());
+    assert_eq!(high[0].indices, mesh.high_level_of_detail[0].indices);
+}
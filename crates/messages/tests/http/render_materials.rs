@@ -0,0 +1,74 @@
+use flate2::{Compression, write::ZlibEncoder};
+use metaverse_messages::http::render_materials::LegacyMaterial;
+use rgb::Rgba;
+use serde_llsd_benthic::{LLSDValue, ser::xml};
+use std::{collections::HashMap, io::Write};
+use uuid::uuid;
+
+fn binary_llsd_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.push(b'k');
+    bytes.extend((value.len() as u32).to_be_bytes());
+    bytes.extend(value.as_bytes());
+}
+
+#[test]
+fn test_render_materials_response() {
+    let material_id = uuid!("0e41a2e3-5cf4-4b9c-9b6e-0f3c1d0a7a11");
+    let normal_map = uuid!("8dcd4a48-2d37-4909-9f78-f7a9eb4ef903");
+
+    // [{ID: binary, Material: {NormMap: uuid, SpecExp: 200, SpecColor: [255, 0, 0, 128]}}]
+    let mut entries = b"[".to_vec();
+    entries.extend(1u32.to_be_bytes());
+    entries.push(b'{');
+    entries.extend(2u32.to_be_bytes());
+    binary_llsd_string(&mut entries, "ID");
+    entries.push(b'b');
+    entries.extend(16u32.to_be_bytes());
+    entries.extend(material_id.as_bytes());
+    binary_llsd_string(&mut entries, "Material");
+    entries.push(b'{');
+    entries.extend(3u32.to_be_bytes());
+    binary_llsd_string(&mut entries, "NormMap");
+    entries.push(b'u');
+    entries.extend(normal_map.as_bytes());
+    binary_llsd_string(&mut entries, "SpecExp");
+    entries.push(b'i');
+    entries.extend(200u32.to_be_bytes());
+    binary_llsd_string(&mut entries, "SpecColor");
+    entries.push(b'[');
+    entries.extend(4u32.to_be_bytes());
+    for channel in [255u32, 0, 0, 128] {
+        entries.push(b'i');
+        entries.extend(channel.to_be_bytes());
+    }
+    entries.extend(b"]}}]");
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&entries).unwrap();
+    let response = xml::to_string(
+        &LLSDValue::Map(HashMap::from([(
+            "Zipped".to_string(),
+            LLSDValue::Binary(encoder.finish().unwrap()),
+        )])),
+        false,
+    )
+    .unwrap();
+
+    let materials = LegacyMaterial::response_from_llsd(response.as_bytes()).unwrap();
+    let material = &materials[&material_id];
+    assert_eq!(material.normal_map, normal_map);
+    assert!(material.specular_map.is_nil());
+    assert_eq!(material.specular_exponent, 200);
+    assert_eq!(material.specular_color, Rgba::new(255, 0, 0, 128));
+    assert_eq!(
+        material.diffuse_alpha_mode,
+        LegacyMaterial::default().diffuse_alpha_mode
+    );
+}
+
+#[test]
+fn test_render_materials_request() {
+    let body =
+        LegacyMaterial::request_body(&[uuid!("0e41a2e3-5cf4-4b9c-9b6e-0f3c1d0a7a11")]).unwrap();
+    assert!(body.contains("Zipped"));
+}
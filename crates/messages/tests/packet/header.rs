@@ -45,3 +45,27 @@ fn test_header_to_from_bytes() {
     let header_back_to_bytes = header_from_bytes.to_bytes();
     assert!(header_bytes == header_back_to_bytes);
 }
+
+#[test]
+fn test_header_appended_acks() {
+    // reliable with appended acks. StartPingCheck with two acks appended to the end.
+    let bytes: [u8; 21] = [
+        0x50, 0, 0, 0, 7, 0, // flags, sequence number and extra byte
+        1, 3, 0, 0, 0, 0, // ID and body
+        0, 0, 0, 5, 0, 0, 1, 0, // acks 5 and 256, big-endian
+        2, // ack count
+    ];
+    let test_header = Header::try_from_bytes(&bytes).unwrap();
+    assert!(test_header.appended_acks);
+    assert!(test_header.reliable);
+    assert_eq!(test_header.sequence_number, 7);
+    assert_eq!(test_header.ack_list, Some(vec![5, 256]));
+    assert_eq!(test_header.appended_acks_len(), 9);
+}
+
+#[test]
+fn test_header_appended_acks_too_short() {
+    // claims 200 acks, but the packet is nowhere near long enough
+    let bytes: [u8; 8] = [0x10, 0, 0, 0, 0, 0, 1, 200];
+    assert!(Header::try_from_bytes(&bytes).is_err());
+}
@@ -1 +1,6 @@
 pub mod header;
+pub mod packet_protocol;
+pub mod zerocode;
+pub mod template;
+pub mod round_trip;
+pub mod samples;
@@ -0,0 +1,41 @@
+use metaverse_messages::{
+    packet::{packet_protocol::Packet, packet_types::PacketType},
+    udp::core::start_ping_check::StartPingCheck,
+};
+
+#[test]
+fn test_packet_appended_acks_round_trip() {
+    let mut packet = Packet::new_start_ping_check(StartPingCheck {
+        ping_id: 3,
+        oldest_unacked: 12,
+    });
+    packet.header.ack_list = Some(vec![1, 2, 70000]);
+
+    let bytes = packet.to_bytes();
+    let parsed = Packet::from_bytes(&bytes).unwrap();
+
+    assert!(parsed.header.appended_acks);
+    assert_eq!(parsed.header.ack_list, Some(vec![1, 2, 70000]));
+    match parsed.body {
+        PacketType::StartPingCheck(data) => {
+            assert_eq!(data.ping_id, 3);
+            assert_eq!(data.oldest_unacked, 12);
+        }
+        other => panic!("parsed wrong packet type {:?}", other),
+    }
+}
+
+#[test]
+fn test_packet_without_acks_has_no_ack_block() {
+    let packet = Packet::new_start_ping_check(StartPingCheck {
+        ping_id: 1,
+        oldest_unacked: 0,
+    });
+    let bytes = packet.to_bytes();
+    // 6 byte header, 1 byte ID, 5 byte body
+    assert_eq!(bytes.len(), 12);
+
+    let parsed = Packet::from_bytes(&bytes).unwrap();
+    assert!(!parsed.header.appended_acks);
+    assert_eq!(parsed.header.ack_list, None);
+}
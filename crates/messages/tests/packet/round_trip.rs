@@ -0,0 +1,695 @@
+use benthic_protocol::messages::utils::chat_types::{Audible, ChatType, SourceType};
+use glam::{Quat, Vec3, Vec4};
+use metaverse_messages::{
+    legacy::udp::{
+        agent_wearables_request::AgentWearablesRequest,
+        agent_wearables_update::{AgentWearablesUpdate, Wearable},
+    },
+    packet::{packet_protocol::Packet, packet_types::PacketType},
+    udp::{
+        agent::{
+            agent_update::AgentUpdate,
+            avatar_animation::{AnimationEntry, AvatarAnimation},
+            avatar_appearance::{AppearanceAttachment, AppearanceData, AvatarAppearance},
+            coarse_location_update::{CoarseLocationUpdate, MinimapEntities},
+        },
+        chat::{chat_from_simulator::ChatFromSimulator, chat_from_viewer::ChatFromViewer},
+        core::{
+            agent_movement_complete::AgentMovementComplete,
+            agent_throttle::{AgentThrottle, ThrottleData},
+            circuit_code::CircuitCode,
+            complete_agent_movement::CompleteAgentMovementData,
+            complete_ping_check::CompletePingCheck,
+            disable_simulator::DisableSimulator,
+            enable_simulator::EnableSimulator,
+            logout_request::LogoutRequest,
+            packet_ack::PacketAck,
+            parcel_overlay::ParcelOverlay,
+            region_handshake::RegionHandshake,
+            region_handshake_reply::RegionHandshakeReply,
+            sim_stats::{SimStats, Stat},
+            simulator_viewer_time_message::SimulatorViewerTimeMessage,
+            start_ping_check::StartPingCheck,
+            test_packet::TestPacket,
+            viewer_effect::{Effect, ViewerEffect},
+        },
+        environment::layer_data::{LayerData, LayerType},
+        object::{
+            improved_terse_object_update::{ImprovedTerseObjectUpdate, TerseObjectData},
+            kill_object::KillObject,
+            multiple_object_update::{MultipleObjectUpdate, ObjectUpdateData},
+            object_update_cached::{CachedObjectData, ObjectUpdateCached},
+            request_multiple_objects::{CacheMissType, RequestMultipleObjects},
+        },
+        teleport::{
+            teleport_request::TeleportRequest,
+            teleport_start::{TeleportFlag, TeleportStart},
+        },
+    },
+    utils::{
+        agent_access::AgentAccess, attachment_point::AttachmentPoint, object_types::WearableType,
+        texture_entry::TextureEntry,
+    },
+};
+use proptest::prelude::*;
+use std::net::Ipv4Addr;
+use uuid::Uuid;
+
+/// serialize the packet, parse it back, and check that the parsed body serializes to the same bytes
+fn round_trip(packet: Packet) -> PacketType {
+    let bytes = packet.to_bytes();
+    let parsed = Packet::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed.header.id, packet.header.id);
+    assert_eq!(parsed.header.frequency, packet.header.frequency);
+    assert_eq!(parsed.body.to_bytes(), packet.body.to_bytes());
+    parsed.body
+}
+
+fn id(n: u8) -> Uuid {
+    Uuid::from_bytes([n; 16])
+}
+
+#[test]
+fn test_start_ping_check_round_trip() {
+    let packet = Packet::new_start_ping_check(StartPingCheck {
+        ping_id: 7,
+        oldest_unacked: 1234,
+    });
+    let PacketType::StartPingCheck(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.ping_id, 7);
+    assert_eq!(data.oldest_unacked, 1234);
+}
+
+#[test]
+fn test_complete_ping_check_round_trip() {
+    let packet = Packet::new_complete_ping_check(CompletePingCheck { ping_id: 7 });
+    let PacketType::CompletePingCheck(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.ping_id, 7);
+}
+
+#[test]
+fn test_agent_update_round_trip() {
+    let packet = Packet::new_agent_update(AgentUpdate {
+        agent_id: id(1),
+        session_id: id(2),
+        body_rotation: Quat::from_xyzw(0.0, 0.0, 1.0, 0.0),
+        head_rotation: Quat::IDENTITY,
+        camera_center: Vec3::new(128.0, 64.0, 22.5),
+        far: 128.0,
+        ..Default::default()
+    });
+    let PacketType::AgentUpdate(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.agent_id, id(1));
+    assert_eq!(data.session_id, id(2));
+    assert_eq!(data.body_rotation, Quat::from_xyzw(0.0, 0.0, 1.0, 0.0));
+    assert_eq!(data.head_rotation, Quat::IDENTITY);
+    assert_eq!(data.camera_center, Vec3::new(128.0, 64.0, 22.5));
+    assert_eq!(data.far, 128.0);
+}
+
+#[test]
+fn test_layer_data_round_trip() {
+    let packet = Packet::new_layer_data(LayerData {
+        layer_type: LayerType::Land,
+        stride: 264,
+        patch_size: 16,
+        layer_content: vec![1, 2, 3, 4, 5],
+    });
+    let PacketType::LayerData(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.stride, 264);
+    assert_eq!(data.patch_size, 16);
+    assert_eq!(data.layer_content, vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_object_update_cached_round_trip() {
+    let packet = Packet::new_object_update_cached(ObjectUpdateCached {
+        region_handle: 1099511628032000,
+        time_dilation: 65535,
+        objects: vec![
+            CachedObjectData {
+                id: 10,
+                crc: 99,
+                flags: vec![],
+            },
+            CachedObjectData {
+                id: 11,
+                crc: 100,
+                flags: vec![],
+            },
+        ],
+    });
+    let PacketType::ObjectUpdateCached(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.region_handle, 1099511628032000);
+    assert_eq!(data.objects.len(), 2);
+    assert_eq!(data.objects[1].id, 11);
+    assert_eq!(data.objects[1].crc, 100);
+}
+
+#[test]
+fn test_improved_terse_object_update_round_trip() {
+    let object = TerseObjectData {
+        local_id: 42,
+        state: 0,
+        avatar: false,
+        collision_plane: None,
+        position: Vec3::new(12.5, 200.25, 30.0),
+        velocity: Vec3::ZERO,
+        acceleration: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        angular_velocity: Vec3::ZERO,
+        texture_entry: vec![],
+    };
+    let avatar = TerseObjectData {
+        local_id: 43,
+        avatar: true,
+        collision_plane: Some(Vec4::new(0.0, 0.0, 1.0, 21.0)),
+        texture_entry: vec![1, 2, 3],
+        ..object.clone()
+    };
+    let packet = Packet::new_improved_terse_object_update(ImprovedTerseObjectUpdate {
+        region_handle: 1099511628032000,
+        time_dilation: 65535,
+        objects: vec![object, avatar],
+    });
+    let PacketType::ImprovedTerseObjectUpdate(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.objects.len(), 2);
+    assert_eq!(data.objects[0].local_id, 42);
+    assert_eq!(data.objects[0].position, Vec3::new(12.5, 200.25, 30.0));
+    assert!(data.objects[1].avatar);
+    assert_eq!(
+        data.objects[1].collision_plane,
+        Some(Vec4::new(0.0, 0.0, 1.0, 21.0))
+    );
+    assert_eq!(data.objects[1].texture_entry, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_avatar_animation_round_trip() {
+    let packet = Packet::new_avatar_animation(AvatarAnimation {
+        sender_id: id(1),
+        animations: vec![
+            AnimationEntry {
+                anim_id: id(2),
+                sequence_id: 1,
+            },
+            AnimationEntry {
+                anim_id: id(3),
+                sequence_id: 2,
+            },
+        ],
+        sources: vec![id(4)],
+    });
+    let PacketType::AvatarAnimation(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.sender_id, id(1));
+    assert_eq!(data.animations.len(), 2);
+    assert_eq!(data.animations[1].anim_id, id(3));
+    assert_eq!(data.sources, vec![id(4)]);
+}
+
+#[test]
+fn test_kill_object_round_trip() {
+    let packet = Packet::new_kill_object(KillObject {
+        object_ids: vec![1, 2, 70000],
+    });
+    let PacketType::KillObject(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.object_ids, vec![1, 2, 70000]);
+}
+
+#[test]
+fn test_multiple_object_update_round_trip() {
+    let packet = Packet::new_multiple_object_update(MultipleObjectUpdate {
+        agent_id: id(1),
+        session_id: id(2),
+        updates: vec![ObjectUpdateData {
+            local_id: 5,
+            update_type: 1,
+            data: vec![0; 12],
+        }],
+    });
+    let PacketType::MultipleObjectUpdate(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.updates.len(), 1);
+    assert_eq!(data.updates[0].local_id, 5);
+    assert_eq!(data.updates[0].data, vec![0; 12]);
+}
+
+#[test]
+fn test_request_multiple_objects_round_trip() {
+    let packet = Packet::new_request_multiple_objects(RequestMultipleObjects {
+        agent_id: id(1),
+        session_id: id(2),
+        requests: vec![(CacheMissType::Normal, 5), (CacheMissType::Attachment, 6)],
+    });
+    let PacketType::RequestMultipleObjects(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.requests.len(), 2);
+    assert_eq!(data.requests[1].1, 6);
+}
+
+#[test]
+fn test_coarse_location_update_round_trip() {
+    let packet = Packet::new_coarse_location_update(CoarseLocationUpdate {
+        locations: vec![
+            MinimapEntities { x: 1, y: 2, z: 3 },
+            MinimapEntities { x: 4, y: 5, z: 6 },
+        ],
+        you: 0,
+        prey: -1,
+        agent_ids: vec![id(1), id(2)],
+    });
+    let PacketType::CoarseLocationUpdate(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.locations.len(), 2);
+    assert_eq!(data.locations[1].z, 6);
+    assert_eq!(data.prey, -1);
+    assert_eq!(data.agent_ids, vec![id(1), id(2)]);
+}
+
+#[test]
+fn test_viewer_effect_round_trip() {
+    let packet = Packet::new_viewer_effect(ViewerEffect {
+        agent_id: id(1),
+        session_id: id(2),
+        effects: vec![Effect {
+            id: id(3),
+            agent_id: id(1),
+            effect_type: 9,
+            duration: 0.5,
+            color: [255, 0, 0, 255],
+            type_data: vec![7; 56],
+        }],
+    });
+    let PacketType::ViewerEffect(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.effects.len(), 1);
+    assert_eq!(data.effects[0].effect_type, 9);
+    assert_eq!(data.effects[0].type_data, vec![7; 56]);
+}
+
+#[test]
+fn test_test_packet_round_trip() {
+    let packet = Packet::new_test_packet(TestPacket {
+        test1: 5,
+        neighbor_block: [[1, 2, 3], [4, 5, 6], [7, 8, 9], [10, 11, 12]],
+    });
+    let PacketType::TestPacket(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.test1, 5);
+    assert_eq!(data.neighbor_block[3], [10, 11, 12]);
+}
+
+#[test]
+fn test_circuit_code_round_trip() {
+    let packet = Packet::new_circuit_code(CircuitCode {
+        code: 697482820,
+        session_id: id(1),
+        id: id(2),
+    });
+    let PacketType::CircuitCode(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.code, 697482820);
+    assert_eq!(data.session_id, id(1));
+    assert_eq!(data.id, id(2));
+}
+
+#[test]
+fn test_teleport_request_round_trip() {
+    let packet = Packet::new_teleport_request(TeleportRequest {
+        agent_id: id(1),
+        session_id: id(2),
+        region_id: id(3),
+        position: Vec3::new(128.0, 128.0, 25.0),
+        look_at: Vec3::X,
+    });
+    let PacketType::TeleportRequest(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.region_id, id(3));
+    assert_eq!(data.position, Vec3::new(128.0, 128.0, 25.0));
+}
+
+#[test]
+fn test_teleport_start_round_trip() {
+    let packet = Packet::new_teleport_start(TeleportStart {
+        flags: vec![TeleportFlag::Lure, TeleportFlag::IsFlying],
+    });
+    let PacketType::TeleportStart(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.flags.len(), 2);
+}
+
+#[test]
+fn test_chat_from_viewer_round_trip() {
+    let packet = Packet::new_chat_from_viewer(ChatFromViewer {
+        agent_id: id(1),
+        session_id: id(2),
+        message: "hello".to_string(),
+        message_type: ChatType::from_bytes(1),
+        channel: 0,
+    });
+    let PacketType::ChatFromViewer(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.message, "hello");
+    assert_eq!(data.channel, 0);
+}
+
+#[test]
+fn test_agent_throttle_round_trip() {
+    let packet = Packet::new_agent_throttle(AgentThrottle {
+        agent_id: id(1),
+        session_id: id(2),
+        circuit_code: 5,
+        gen_counter: 0,
+        throttles: ThrottleData::default(),
+    });
+    let PacketType::AgentThrottle(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.circuit_code, 5);
+    assert_eq!(data.throttles.texture, ThrottleData::default().texture);
+}
+
+#[test]
+fn test_chat_from_simulator_round_trip() {
+    let packet = Packet::new_chat_from_simulator(ChatFromSimulator {
+        from_name: "Resident".to_string(),
+        source_id: id(1),
+        owner_id: id(1),
+        source_type: SourceType::from_bytes(1),
+        chat_type: ChatType::from_bytes(1),
+        audible: Audible::from_bytes(1),
+        position: Vec3::new(1.0, 2.0, 3.0),
+        message: "hello".to_string(),
+    });
+    let PacketType::ChatFromSimulator(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.from_name, "Resident");
+    assert_eq!(data.source_id, id(1));
+    assert_eq!(data.position, Vec3::new(1.0, 2.0, 3.0));
+    assert_eq!(data.message, "hello");
+}
+
+#[test]
+fn test_sim_stats_round_trip() {
+    let packet = Packet::new_sim_stats(SimStats {
+        region_x: 1000,
+        region_y: 1001,
+        region_flags: 4,
+        object_capacity: 15000,
+        stats: vec![Stat { id: 0, value: 1.0 }, Stat { id: 1, value: 45.0 }],
+        pid: 1234,
+        region_flags_extended: vec![4],
+    });
+    let PacketType::SimStats(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.region_x, 1000);
+    assert_eq!(data.stats.len(), 2);
+    assert_eq!(data.stats[1].value, 45.0);
+    assert_eq!(data.pid, 1234);
+    assert_eq!(data.region_flags_extended, vec![4]);
+}
+
+#[test]
+fn test_region_handshake_round_trip() {
+    let packet = Packet::new_region_handshake(RegionHandshake {
+        region_flags: 1,
+        sim_access: AgentAccess::General,
+        sim_name: "Sandbox".to_string(),
+        sim_owner: id(1),
+        is_estate_manager: true,
+        water_height: 20.0,
+        billable_factor: 1.0,
+        cache_id: id(2),
+        terrain_base_0: id(3),
+        terrain_base_1: id(4),
+        terrain_base_2: id(5),
+        terrain_base_3: id(6),
+        terrain_detail_0: id(7),
+        terrain_detail_1: id(8),
+        terrain_detail_2: id(9),
+        terrain_detail_3: id(10),
+        terrain_start_height_0: 10.0,
+        terrain_start_height_1: 10.0,
+        terrain_start_height_2: 10.0,
+        terrain_start_height_3: 10.0,
+        terrain_height_range_0: 60.0,
+        terrain_height_range_1: 60.0,
+        terrain_height_range_2: 60.0,
+        terrain_height_range_3: 60.0,
+    });
+    let PacketType::RegionHandshake(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.sim_name, "Sandbox");
+    assert!(data.is_estate_manager);
+    assert_eq!(data.water_height, 20.0);
+    assert_eq!(data.terrain_detail_3, id(10));
+}
+
+#[test]
+fn test_region_handshake_reply_round_trip() {
+    let packet = Packet::new_region_handshake_reply(RegionHandshakeReply {
+        agent_id: id(1),
+        session_id: id(2),
+        flags: 5,
+    });
+    let PacketType::RegionHandshakeReply(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.agent_id, id(1));
+    assert_eq!(data.flags, 5);
+}
+
+#[test]
+fn test_simulator_viewer_time_message_round_trip() {
+    let packet = Packet::new_simulator_viewer_time_message(SimulatorViewerTimeMessage {
+        seconds_since_start: 1_000_000,
+        seconds_per_day: 14400,
+        seconds_per_year: 1_000_000,
+        sun_direction: Vec3::Z,
+        sun_phase: 1.5,
+        sun_angle_velocity: Vec3::new(0.0, 0.001, 0.0),
+    });
+    let PacketType::SimulatorViewerTimeMessage(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.seconds_per_day, 14400);
+    assert_eq!(data.sun_direction, Vec3::Z);
+}
+
+#[test]
+fn test_disable_simulator_round_trip() {
+    let packet = Packet::new_disable_simulator(DisableSimulator {});
+    let PacketType::DisableSimulator(_) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+}
+
+#[test]
+fn test_enable_simulator_round_trip() {
+    let packet = Packet::new_enable_simulator(EnableSimulator {
+        handle: 1099511628032000,
+        ip: Ipv4Addr::new(127, 0, 0, 1),
+        port: 9000,
+    });
+    let PacketType::EnableSimulator(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.handle, 1099511628032000);
+    assert_eq!(data.ip, Ipv4Addr::new(127, 0, 0, 1));
+    assert_eq!(data.port, 9000);
+}
+
+#[test]
+fn test_avatar_appearance_round_trip() {
+    let texture_entry = TextureEntry {
+        texture_id: id(3),
+        ..Default::default()
+    };
+    let packet = Packet::new_avatar_appearance(AvatarAppearance {
+        id: id(1),
+        is_trial: false,
+        texture_entry,
+        visual_params: vec![127; 218],
+        appearance_data: Some(AppearanceData {
+            appearance_version: 1,
+            cof_version: 42,
+            flags: 0,
+        }),
+        hover_height: Some(Vec3::new(0.0, 0.0, -0.25)),
+        attachments: vec![AppearanceAttachment {
+            id: id(2),
+            attachment_point: AttachmentPoint::Skull,
+        }],
+    });
+    let PacketType::AvatarAppearance(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.id, id(1));
+    assert_eq!(data.texture_entry.texture_id, id(3));
+    assert_eq!(data.visual_params, vec![127; 218]);
+    assert_eq!(
+        data.appearance_data,
+        Some(AppearanceData {
+            appearance_version: 1,
+            cof_version: 42,
+            flags: 0,
+        })
+    );
+    assert_eq!(data.hover_height, Some(Vec3::new(0.0, 0.0, -0.25)));
+    assert_eq!(
+        data.attachments,
+        vec![AppearanceAttachment {
+            id: id(2),
+            attachment_point: AttachmentPoint::Skull,
+        }]
+    );
+}
+
+#[test]
+fn test_parcel_overlay_round_trip() {
+    let packet = Packet::new_parcel_overlay(ParcelOverlay {
+        sequence_id: 3,
+        data: vec![0x42; 1024],
+    });
+    let PacketType::ParcelOverlay(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.sequence_id, 3);
+    assert_eq!(data.data, vec![0x42; 1024]);
+}
+
+#[test]
+fn test_complete_agent_movement_round_trip() {
+    let packet = Packet::new_complete_agent_movement(CompleteAgentMovementData {
+        agent_id: id(1),
+        session_id: id(2),
+        circuit_code: 3,
+    });
+    let PacketType::CompleteAgentMovementData(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.agent_id, id(1));
+    assert_eq!(data.session_id, id(2));
+    assert_eq!(data.circuit_code, 3);
+}
+
+#[test]
+fn test_agent_movement_complete_round_trip() {
+    let packet = Packet::new_agent_movement_complete(AgentMovementComplete {
+        agent_id: id(1),
+        session_id: id(2),
+        position: Vec3::new(128.0, 128.0, 25.0),
+        look_at: Vec3::X,
+        region_handle: 1099511628032000,
+        timestamp: 1700000000,
+        channel_version: "Simulator 1.0".to_string(),
+    });
+    let PacketType::AgentMovementComplete(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.position, Vec3::new(128.0, 128.0, 25.0));
+    assert_eq!(data.timestamp, 1700000000);
+    assert_eq!(data.channel_version, "Simulator 1.0");
+}
+
+#[test]
+fn test_logout_request_round_trip() {
+    let packet = Packet::new_logout_request(LogoutRequest {
+        agent_id: id(1),
+        session_id: id(2),
+    });
+    let PacketType::LogoutRequest(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.agent_id, id(1));
+    assert_eq!(data.session_id, id(2));
+}
+
+#[test]
+fn test_packet_ack_round_trip() {
+    let packet = Packet::new_packet_ack(PacketAck {
+        packet_ids: vec![1, 2, 3],
+    });
+    let PacketType::PacketAck(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.packet_ids, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_agent_wearables_update_round_trip() {
+    let packet = Packet::new_agent_wearables_update(AgentWearablesUpdate {
+        agent_id: id(1),
+        session_id: id(2),
+        serial_number: 4,
+        wearables: vec![Wearable {
+            item_id: id(3),
+            asset_id: id(4),
+            wearable_type: WearableType::Shape,
+        }],
+    });
+    let PacketType::AgentWearablesUpdate(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.serial_number, 4);
+    assert_eq!(data.wearables.len(), 1);
+    assert_eq!(data.wearables[0].asset_id, id(4));
+}
+
+#[test]
+fn test_agent_wearables_request_round_trip() {
+    let packet = Packet::new_agent_wearables_request(AgentWearablesRequest {
+        agent_id: id(1),
+        session_id: id(2),
+    });
+    let PacketType::AgentWearablesRequest(data) = round_trip(packet) else {
+        panic!("parsed wrong packet type");
+    };
+    assert_eq!(data.agent_id, id(1));
+}
+
+proptest! {
+    #[test]
+    fn prop_parsing_arbitrary_bodies_never_panics(
+        index in 0..PacketType::REGISTERED.len(),
+        body in prop::collection::vec(any::<u8>(), 0..512),
+    ) {
+        let (id, frequency, _) = PacketType::REGISTERED[index];
+        let _ = PacketType::from_id(id, frequency, &body);
+    }
+
+    #[test]
+    fn prop_kill_object_round_trip(object_ids in prop::collection::vec(any::<u32>(), 0..255)) {
+        let packet = Packet::new_kill_object(KillObject { object_ids: object_ids.clone() });
+        let parsed = Packet::from_bytes(&packet.to_bytes()).unwrap();
+        prop_assert_eq!(parsed.body.to_bytes(), packet.body.to_bytes());
+    }
+}
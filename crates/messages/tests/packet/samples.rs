@@ -0,0 +1,346 @@
+use benthic_protocol::messages::utils::chat_types::{Audible, ChatType, SourceType};
+use glam::{Quat, Vec3, Vec4};
+use metaverse_messages::{
+    legacy::udp::{
+        agent_wearables_request::AgentWearablesRequest,
+        agent_wearables_update::{AgentWearablesUpdate, Wearable},
+    },
+    packet::{
+        packet_protocol::{Packet, PacketData},
+        packet_types::PacketType,
+    },
+    udp::{
+        agent::{
+            agent_update::AgentUpdate,
+            avatar_animation::{AnimationEntry, AvatarAnimation},
+            avatar_appearance::{AppearanceAttachment, AppearanceData, AvatarAppearance},
+            coarse_location_update::{CoarseLocationUpdate, MinimapEntities},
+        },
+        chat::{chat_from_simulator::ChatFromSimulator, chat_from_viewer::ChatFromViewer},
+        core::{
+            agent_movement_complete::AgentMovementComplete,
+            agent_throttle::{AgentThrottle, ThrottleData},
+            circuit_code::CircuitCode,
+            complete_agent_movement::CompleteAgentMovementData,
+            complete_ping_check::CompletePingCheck,
+            disable_simulator::DisableSimulator,
+            enable_simulator::EnableSimulator,
+            logout_request::LogoutRequest,
+            packet_ack::PacketAck,
+            parcel_overlay::ParcelOverlay,
+            region_handshake::RegionHandshake,
+            region_handshake_reply::RegionHandshakeReply,
+            sim_stats::{SimStats, Stat},
+            simulator_viewer_time_message::SimulatorViewerTimeMessage,
+            start_ping_check::StartPingCheck,
+            test_packet::TestPacket,
+            viewer_effect::{Effect, ViewerEffect},
+        },
+        environment::layer_data::{LayerData, LayerType},
+        object::{
+            generic_streaming_message::{GenericStreamingMessage, METHOD_MATERIAL_OVERRIDE},
+            improved_terse_object_update::{ImprovedTerseObjectUpdate, TerseObjectData},
+            kill_object::KillObject,
+            multiple_object_update::{MultipleObjectUpdate, ObjectUpdateData},
+            object_update::ObjectUpdate,
+            object_update_cached::{CachedObjectData, ObjectUpdateCached},
+            object_update_compressed::ObjectUpdateCompressed,
+            request_multiple_objects::{CacheMissType, RequestMultipleObjects},
+        },
+        teleport::{
+            teleport_request::TeleportRequest,
+            teleport_start::{TeleportFlag, TeleportStart},
+        },
+    },
+    utils::{
+        agent_access::AgentAccess, attachment_point::AttachmentPoint, object_types::WearableType,
+        texture_entry::TextureEntry,
+    },
+};
+use std::net::Ipv4Addr;
+use uuid::Uuid;
+
+use crate::udp::object::{object_update::NON_ATTACH, object_update_compressed::PACKET_BYTES_2};
+
+fn id(n: u8) -> Uuid {
+    Uuid::from_bytes([n; 16])
+}
+
+/// A packet of every type registered in define_packets!, for tests that run over all of them
+pub fn packets() -> Vec<Packet> {
+    let terse_object = TerseObjectData {
+        local_id: 42,
+        state: 0,
+        avatar: false,
+        collision_plane: None,
+        position: Vec3::new(12.5, 200.25, 30.0),
+        velocity: Vec3::ZERO,
+        acceleration: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        angular_velocity: Vec3::ZERO,
+        texture_entry: vec![],
+    };
+    let terse_avatar = TerseObjectData {
+        local_id: 43,
+        avatar: true,
+        collision_plane: Some(Vec4::new(0.0, 0.0, 1.0, 21.0)),
+        texture_entry: vec![1, 2, 3],
+        ..terse_object.clone()
+    };
+
+    vec![
+        Packet::new_start_ping_check(StartPingCheck {
+            ping_id: 7,
+            oldest_unacked: 1234,
+        }),
+        Packet::new_complete_ping_check(CompletePingCheck { ping_id: 7 }),
+        Packet::new_agent_update(AgentUpdate {
+            agent_id: id(1),
+            session_id: id(2),
+            camera_center: Vec3::new(128.0, 64.0, 22.5),
+            far: 128.0,
+            ..Default::default()
+        }),
+        Packet::new_layer_data(LayerData {
+            layer_type: LayerType::Land,
+            stride: 264,
+            patch_size: 16,
+            layer_content: vec![1, 2, 3, 4, 5],
+        }),
+        Packet::new_object_update(ObjectUpdate::from_bytes(&NON_ATTACH).unwrap()),
+        Packet::new_object_update_compressed(
+            ObjectUpdateCompressed::from_bytes(&PACKET_BYTES_2).unwrap(),
+        ),
+        Packet::new_object_update_cached(ObjectUpdateCached {
+            region_handle: 1099511628032000,
+            time_dilation: 65535,
+            objects: vec![CachedObjectData {
+                id: 10,
+                crc: 99,
+                flags: vec![],
+            }],
+        }),
+        Packet::new_improved_terse_object_update(ImprovedTerseObjectUpdate {
+            region_handle: 1099511628032000,
+            time_dilation: 65535,
+            objects: vec![terse_object, terse_avatar],
+        }),
+        Packet::new_avatar_animation(AvatarAnimation {
+            sender_id: id(1),
+            animations: vec![AnimationEntry {
+                anim_id: id(2),
+                sequence_id: 1,
+            }],
+            sources: vec![id(3)],
+        }),
+        Packet::new_kill_object(KillObject {
+            object_ids: vec![1, 2, 70000],
+        }),
+        Packet::new_generic_streaming_message(GenericStreamingMessage {
+            method: METHOD_MATERIAL_OVERRIDE,
+            data: b"{'id':i1234,'te':[i0],'od':[{'mf':r0}]}".to_vec(),
+        }),
+        Packet::new_multiple_object_update(MultipleObjectUpdate {
+            agent_id: id(1),
+            session_id: id(2),
+            updates: vec![ObjectUpdateData {
+                local_id: 5,
+                update_type: 1,
+                data: vec![0; 12],
+            }],
+        }),
+        Packet::new_request_multiple_objects(RequestMultipleObjects {
+            agent_id: id(1),
+            session_id: id(2),
+            requests: vec![(CacheMissType::Normal, 5), (CacheMissType::Attachment, 6)],
+        }),
+        Packet::new_coarse_location_update(CoarseLocationUpdate {
+            locations: vec![MinimapEntities { x: 1, y: 2, z: 3 }],
+            you: 0,
+            prey: -1,
+            agent_ids: vec![id(1)],
+        }),
+        Packet::new_viewer_effect(ViewerEffect {
+            agent_id: id(1),
+            session_id: id(2),
+            effects: vec![Effect {
+                id: id(3),
+                agent_id: id(1),
+                effect_type: 9,
+                duration: 0.5,
+                color: [255, 0, 0, 255],
+                type_data: vec![7; 56],
+            }],
+        }),
+        Packet::new_test_packet(TestPacket {
+            test1: 5,
+            neighbor_block: [[1, 2, 3], [4, 5, 6], [7, 8, 9], [10, 11, 12]],
+        }),
+        Packet::new_circuit_code(CircuitCode {
+            code: 697482820,
+            session_id: id(1),
+            id: id(2),
+        }),
+        Packet::new_teleport_request(TeleportRequest {
+            agent_id: id(1),
+            session_id: id(2),
+            region_id: id(3),
+            position: Vec3::new(128.0, 128.0, 25.0),
+            look_at: Vec3::X,
+        }),
+        Packet::new_teleport_start(TeleportStart {
+            flags: vec![TeleportFlag::Lure, TeleportFlag::IsFlying],
+        }),
+        Packet::new_chat_from_viewer(ChatFromViewer {
+            agent_id: id(1),
+            session_id: id(2),
+            message: "hello".to_string(),
+            message_type: ChatType::from_bytes(1),
+            channel: 0,
+        }),
+        Packet::new_agent_throttle(AgentThrottle {
+            agent_id: id(1),
+            session_id: id(2),
+            circuit_code: 5,
+            gen_counter: 0,
+            throttles: ThrottleData::default(),
+        }),
+        Packet::new_chat_from_simulator(ChatFromSimulator {
+            from_name: "Resident".to_string(),
+            source_id: id(1),
+            owner_id: id(1),
+            source_type: SourceType::from_bytes(1),
+            chat_type: ChatType::from_bytes(1),
+            audible: Audible::from_bytes(1),
+            position: Vec3::new(1.0, 2.0, 3.0),
+            message: "hello".to_string(),
+        }),
+        Packet::new_sim_stats(SimStats {
+            region_x: 1000,
+            region_y: 1001,
+            region_flags: 4,
+            object_capacity: 15000,
+            stats: vec![Stat { id: 0, value: 1.0 }],
+            pid: 1234,
+            region_flags_extended: vec![4],
+        }),
+        Packet::new_region_handshake(RegionHandshake {
+            region_flags: 1,
+            sim_access: AgentAccess::General,
+            sim_name: "Sandbox".to_string(),
+            sim_owner: id(1),
+            is_estate_manager: true,
+            water_height: 20.0,
+            billable_factor: 1.0,
+            cache_id: id(2),
+            terrain_base_0: id(3),
+            terrain_base_1: id(4),
+            terrain_base_2: id(5),
+            terrain_base_3: id(6),
+            terrain_detail_0: id(7),
+            terrain_detail_1: id(8),
+            terrain_detail_2: id(9),
+            terrain_detail_3: id(10),
+            terrain_start_height_0: 10.0,
+            terrain_start_height_1: 10.0,
+            terrain_start_height_2: 10.0,
+            terrain_start_height_3: 10.0,
+            terrain_height_range_0: 60.0,
+            terrain_height_range_1: 60.0,
+            terrain_height_range_2: 60.0,
+            terrain_height_range_3: 60.0,
+        }),
+        Packet::new_region_handshake_reply(RegionHandshakeReply {
+            agent_id: id(1),
+            session_id: id(2),
+            flags: 5,
+        }),
+        Packet::new_simulator_viewer_time_message(SimulatorViewerTimeMessage {
+            seconds_since_start: 1_000_000,
+            seconds_per_day: 14400,
+            seconds_per_year: 1_000_000,
+            sun_direction: Vec3::Z,
+            sun_phase: 1.5,
+            sun_angle_velocity: Vec3::new(0.0, 0.001, 0.0),
+        }),
+        Packet::new_disable_simulator(DisableSimulator {}),
+        Packet::new_enable_simulator(EnableSimulator {
+            handle: 1099511628032000,
+            ip: Ipv4Addr::new(127, 0, 0, 1),
+            port: 9000,
+        }),
+        Packet::new_avatar_appearance(AvatarAppearance {
+            id: id(1),
+            is_trial: false,
+            texture_entry: TextureEntry {
+                texture_id: id(3),
+                ..Default::default()
+            },
+            visual_params: vec![127; 218],
+            appearance_data: Some(AppearanceData {
+                appearance_version: 1,
+                cof_version: 42,
+                flags: 0,
+            }),
+            hover_height: Some(Vec3::new(0.0, 0.0, -0.25)),
+            attachments: vec![AppearanceAttachment {
+                id: id(2),
+                attachment_point: AttachmentPoint::Skull,
+            }],
+        }),
+        Packet::new_parcel_overlay(ParcelOverlay {
+            sequence_id: 3,
+            data: vec![0x42; 1024],
+        }),
+        Packet::new_complete_agent_movement(CompleteAgentMovementData {
+            agent_id: id(1),
+            session_id: id(2),
+            circuit_code: 3,
+        }),
+        Packet::new_agent_movement_complete(AgentMovementComplete {
+            agent_id: id(1),
+            session_id: id(2),
+            position: Vec3::new(128.0, 128.0, 25.0),
+            look_at: Vec3::X,
+            region_handle: 1099511628032000,
+            timestamp: 1700000000,
+            channel_version: "Simulator 1.0".to_string(),
+        }),
+        Packet::new_logout_request(LogoutRequest {
+            agent_id: id(1),
+            session_id: id(2),
+        }),
+        Packet::new_packet_ack(PacketAck {
+            packet_ids: vec![1, 2, 3],
+        }),
+        Packet::new_agent_wearables_update(AgentWearablesUpdate {
+            agent_id: id(1),
+            session_id: id(2),
+            serial_number: 4,
+            wearables: vec![Wearable {
+                item_id: id(3),
+                asset_id: id(4),
+                wearable_type: WearableType::Shape,
+            }],
+        }),
+        Packet::new_agent_wearables_request(AgentWearablesRequest {
+            agent_id: id(1),
+            session_id: id(2),
+        }),
+    ]
+}
+
+#[test]
+fn test_samples_cover_every_packet() {
+    let packets = packets();
+    assert_eq!(packets.len(), PacketType::REGISTERED.len());
+    for (id, frequency, name) in PacketType::REGISTERED {
+        assert!(
+            packets
+                .iter()
+                .any(|packet| packet.header.id == *id && packet.header.frequency == *frequency),
+            "no sample of {}",
+            name
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use metaverse_messages::packet::{
+    header::PacketFrequency,
+    message_template::{
+        ImprovedInstantMessage, ImprovedInstantMessageAgentData,
+        ImprovedInstantMessageMessageBlock, LogoutReply, LogoutReplyAgentData,
+        LogoutReplyInventoryData, MESSAGES, MessageInfo, TemplateMessage, UUIDNameReply,
+        UUIDNameReplyUUIDNameBlock,
+    },
+    packet_protocol::{Packet, PacketData},
+    packet_types::PacketType,
+};
+use metaverse_messages::udp::core::logout_request::LogoutRequest;
+use uuid::Uuid;
+
+// the hand written packets in define_packets!, with the name of their template message
+const HAND_WRITTEN: [(&str, u16, PacketFrequency); 11] = [
+    ("StartPingCheck", 1, PacketFrequency::High),
+    ("AgentUpdate", 4, PacketFrequency::High),
+    ("KillObject", 16, PacketFrequency::High),
+    ("GenericStreamingMessage", 31, PacketFrequency::High),
+    ("ViewerEffect", 17, PacketFrequency::Medium),
+    ("TestMessage", 1, PacketFrequency::Low),
+    ("UseCircuitCode", 3, PacketFrequency::Low),
+    ("CompleteAgentMovement", 249, PacketFrequency::Low),
+    ("LogoutRequest", 252, PacketFrequency::Low),
+    ("PacketAck", 251, PacketFrequency::Fixed),
+    ("AgentWearablesUpdate", 382, PacketFrequency::Low),
+];
+
+#[test]
+fn test_template_matches_hand_written_ids() {
+    for (name, id, frequency) in HAND_WRITTEN {
+        let info = MessageInfo::from_name(name).unwrap();
+        assert_eq!(info.id, id, "{}", name);
+        assert_eq!(info.frequency, frequency, "{}", name);
+    }
+}
+
+// hand written packets that are named differently from their template message
+const RENAMED: [(&str, &str); 3] = [
+    ("TestPacket", "TestMessage"),
+    ("CircuitCode", "UseCircuitCode"),
+    ("CompleteAgentMovementData", "CompleteAgentMovement"),
+];
+
+#[test]
+fn test_registered_packets_match_template() {
+    for (id, frequency, name) in PacketType::REGISTERED {
+        let template_name = RENAMED
+            .iter()
+            .find(|(variant, _)| variant == name)
+            .map_or(*name, |(_, template_name)| *template_name);
+        let info = MessageInfo::from_id(*id, *frequency).unwrap();
+        assert_eq!(info.name, template_name, "{}", name);
+    }
+}
+
+#[test]
+fn test_sample_headers_match_template() {
+    // the headers of hand written packets are written out in each packet's constructor, so they
+    // must parse back to the packet registered for their template message
+    for packet in super::samples::packets() {
+        let info = MessageInfo::from_id(packet.header.id, packet.header.frequency).unwrap();
+        let parsed = Packet::from_bytes(&packet.to_bytes()).unwrap();
+        let (_, _, name) = PacketType::REGISTERED
+            .iter()
+            .find(|(id, frequency, _)| *id == info.id && *frequency == info.frequency)
+            .unwrap();
+        assert!(
+            format!("{:?}", parsed.body).starts_with(&format!("{}(", name)),
+            "{} parsed as {:?}",
+            info.name,
+            parsed.body
+        );
+    }
+}
+
+#[test]
+fn test_template_metadata() {
+    let info = MessageInfo::from_id(253, PacketFrequency::Low).unwrap();
+    assert_eq!(info.name, "LogoutReply");
+    assert!(info.trusted);
+    assert!(info.zerocoded);
+    assert!(!info.deprecated);
+
+    let info = MessageInfo::from_name("AgentWearablesRequest").unwrap();
+    assert!(info.deprecated);
+
+    // fixed message numbers are identified by their last byte
+    let info = MessageInfo::from_name("CloseCircuit").unwrap();
+    assert_eq!(info.id, 0xFD);
+    assert_eq!(info.frequency, PacketFrequency::Fixed);
+
+    // every message has a unique ID within its frequency
+    for (i, a) in MESSAGES.iter().enumerate() {
+        for b in &MESSAGES[i + 1..] {
+            assert!(
+                a.id != b.id || a.frequency != b.frequency,
+                "{} {}",
+                a.name,
+                b.name
+            );
+        }
+    }
+}
+
+#[test]
+fn test_generated_message_round_trip() {
+    let reply = UUIDNameReply {
+        uuid_name_block: vec![
+            UUIDNameReplyUUIDNameBlock {
+                id: Uuid::new_v4(),
+                first_name: b"Benthic".to_vec(),
+                last_name: b"Resident".to_vec(),
+            },
+            UUIDNameReplyUUIDNameBlock::default(),
+        ],
+    };
+    let bytes = reply.to_bytes();
+    // count, then 16 byte id and two length prefixed names per block
+    assert_eq!(bytes.len(), 1 + (16 + 1 + 7 + 1 + 8) + (16 + 1 + 1));
+    assert_eq!(UUIDNameReply::from_bytes(&bytes).unwrap(), reply);
+}
+
+#[test]
+fn test_trailing_variable_block_can_be_omitted() {
+    let agent_data = LogoutReplyAgentData {
+        agent_id: Uuid::new_v4(),
+        session_id: Uuid::new_v4(),
+    };
+    let reply = LogoutReply {
+        agent_data: agent_data.clone(),
+        inventory_data: Vec::new(),
+    };
+    let bytes = reply.to_bytes();
+    assert_eq!(LogoutReply::from_bytes(&bytes[..32]).unwrap(), reply);
+
+    let reply = LogoutReply {
+        agent_data,
+        inventory_data: vec![LogoutReplyInventoryData {
+            item_id: Uuid::new_v4(),
+        }],
+    };
+    assert_eq!(LogoutReply::from_bytes(&reply.to_bytes()).unwrap(), reply);
+}
+
+#[test]
+fn test_unknown_packet_falls_back_to_template() {
+    let message = ImprovedInstantMessage {
+        agent_data: ImprovedInstantMessageAgentData {
+            agent_id: Uuid::new_v4(),
+            session_id: Uuid::new_v4(),
+        },
+        message_block: ImprovedInstantMessageMessageBlock {
+            from_group: true,
+            dialog: 19,
+            timestamp: 1234,
+            from_agent_name: b"Second Life".to_vec(),
+            message: b"hello".to_vec(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let packet = Packet::new_template(TemplateMessage::ImprovedInstantMessage(Box::new(
+        message.clone(),
+    )));
+    assert_eq!(packet.header.id, 254);
+    assert!(packet.header.zerocoded);
+    assert!(packet.header.reliable);
+
+    let parsed = Packet::from_bytes(&packet.to_bytes()).unwrap();
+    match parsed.body {
+        PacketType::Template(data) => match *data {
+            TemplateMessage::ImprovedInstantMessage(data) => assert_eq!(*data, message),
+            other => panic!("parsed wrong message {:?}", other),
+        },
+        other => panic!("parsed wrong packet type {:?}", other),
+    }
+}
+
+#[test]
+fn test_hand_written_packets_are_not_generated() {
+    // hand written packets are only parsed by their own implementation
+    for (id, frequency, name) in PacketType::REGISTERED {
+        assert!(
+            TemplateMessage::from_id(*id, *frequency, &[0; 32]).is_none(),
+            "{}",
+            name
+        );
+    }
+    // but their template definition is still known
+    assert_eq!(LogoutRequest::INFO.name, "LogoutRequest");
+    assert_eq!(
+        MessageInfo::from_id(252, PacketFrequency::Low),
+        Some(&LogoutRequest::INFO)
+    );
+
+    let packet = Packet::new_logout_request(LogoutRequest {
+        agent_id: Uuid::new_v4(),
+        session_id: Uuid::new_v4(),
+    });
+    let parsed = Packet::from_bytes(&packet.to_bytes()).unwrap();
+    assert!(matches!(parsed.body, PacketType::LogoutRequest(_)));
+}
@@ -0,0 +1,181 @@
+use metaverse_messages::{
+    packet::{
+        header::{Header, PacketFrequency},
+        packet_protocol::{Packet, zero_decode, zero_encode},
+        packet_types::PacketType,
+    },
+    udp::{
+        agent::agent_update::AgentUpdate,
+        core::{
+            circuit_code::CircuitCode, complete_ping_check::CompletePingCheck,
+            packet_ack::PacketAck, start_ping_check::StartPingCheck,
+        },
+    },
+};
+use proptest::prelude::*;
+
+use super::samples;
+use uuid::Uuid;
+
+// bytes that are mostly zero, with long runs, like real packet bodies
+fn zero_heavy_bytes() -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(
+        prop_oneof![
+            3 => prop::collection::vec(Just(0u8), 0..600),
+            1 => prop::collection::vec(any::<u8>(), 0..8),
+        ],
+        0..6,
+    )
+    .prop_map(|chunks| chunks.concat())
+}
+
+#[test]
+fn test_zero_encode_splits_long_runs() {
+    assert_eq!(zero_encode(&[0; 255]), vec![0, 255]);
+    assert_eq!(zero_encode(&[0; 256]), vec![0, 255, 0, 1]);
+    assert_eq!(zero_encode(&[0; 600]), vec![0, 255, 0, 255, 0, 90]);
+
+    let mut bytes = vec![7];
+    bytes.extend([0; 300]);
+    bytes.push(9);
+    assert_eq!(zero_encode(&bytes), vec![7, 0, 255, 0, 45, 9]);
+}
+
+#[test]
+fn test_zero_decode_long_runs() {
+    assert_eq!(zero_decode(&[0, 255, 0, 1]), vec![0; 256]);
+    assert_eq!(zero_decode(&[1, 0, 3, 2]), vec![1, 0, 0, 0, 2]);
+}
+
+#[test]
+fn test_header_zerocoded_low_id() {
+    // CircuitCode, ID 3. The high byte of the ID is zero, so it is zerocoded.
+    let bytes = [0x80, 0, 0, 0, 1, 0, 0xff, 0xff, 0, 1, 3, 5];
+    let header = Header::try_from_bytes(&bytes).unwrap();
+    assert_eq!(header.frequency, PacketFrequency::Low);
+    assert_eq!(header.id, 3);
+    assert_eq!(header.size, Some(10));
+}
+
+#[test]
+fn test_header_zerocoded_run_into_body() {
+    // ID 256, followed by a body starting with three zeros. The low byte of the ID and the zeros
+    // of the body are zerocoded as a single run.
+    let bytes = [0x80, 0, 0, 0, 1, 0, 0xff, 0xff, 1, 0, 4, 7];
+    let header = Header::try_from_bytes(&bytes).unwrap();
+    assert_eq!(header.frequency, PacketFrequency::Low);
+    assert_eq!(header.id, 256);
+
+    let data = zero_decode(&bytes[6..]);
+    assert_eq!(&data[header.size.unwrap() - 6..], &[0, 0, 0, 7]);
+}
+
+#[test]
+fn test_zerocoded_agent_update_round_trip() {
+    // identity quaternions and zero vectors make the body mostly zeros
+    let mut packet = Packet::new_agent_update(AgentUpdate::default());
+    packet.header.zerocoded = true;
+    let bytes = packet.to_bytes();
+    assert!(bytes.len() < packet.body.to_bytes().len());
+
+    let parsed = Packet::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed.header.id, 4);
+    assert_eq!(parsed.body.to_bytes(), packet.body.to_bytes());
+    match parsed.body {
+        PacketType::AgentUpdate(data) => {
+            assert_eq!(data.agent_id, Uuid::nil());
+            assert_eq!(data.far, 200.0);
+        }
+        other => panic!("parsed wrong packet type {:?}", other),
+    }
+}
+
+#[test]
+fn test_zerocoded_circuit_code_round_trip() {
+    let mut packet = Packet::new_circuit_code(CircuitCode {
+        code: 0,
+        session_id: Uuid::nil(),
+        id: Uuid::nil(),
+    });
+    packet.header.zerocoded = true;
+    let bytes = packet.to_bytes();
+
+    let parsed = Packet::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed.header.id, 3);
+    assert_eq!(parsed.header.frequency, PacketFrequency::Low);
+    assert_eq!(parsed.body.to_bytes(), packet.body.to_bytes());
+}
+
+proptest! {
+    #[test]
+    fn prop_zero_decode_inverts_encode(bytes in zero_heavy_bytes()) {
+        prop_assert_eq!(zero_decode(&zero_encode(&bytes)), bytes);
+    }
+
+    #[test]
+    fn prop_zero_encode_never_writes_empty_runs(bytes in zero_heavy_bytes()) {
+        let encoded = zero_encode(&bytes);
+        for pair in encoded.windows(2) {
+            prop_assert!(!(pair[0] == 0 && pair[1] == 0));
+        }
+    }
+
+    #[test]
+    fn prop_header_round_trip_every_packet_id(
+        index in 0..PacketType::REGISTERED.len(),
+        zerocoded in any::<bool>(),
+        sequence_number in any::<u32>(),
+        body in zero_heavy_bytes(),
+    ) {
+        let (id, frequency, _) = PacketType::REGISTERED[index];
+        let header = Header {
+            id,
+            frequency,
+            zerocoded,
+            sequence_number,
+            ..Default::default()
+        };
+        let mut bytes = header.to_bytes();
+        bytes.extend(&body);
+        if zerocoded {
+            let encoded = zero_encode(&bytes[6..]);
+            bytes.truncate(6);
+            bytes.extend(encoded);
+        }
+
+        let parsed = Header::try_from_bytes(&bytes).unwrap();
+        prop_assert_eq!(parsed.id, id);
+        prop_assert_eq!(parsed.frequency, frequency);
+        prop_assert_eq!(parsed.sequence_number, sequence_number);
+        prop_assert_eq!(parsed.zerocoded, zerocoded);
+
+        let data = if zerocoded { zero_decode(&bytes[6..]) } else { bytes[6..].to_vec() };
+        prop_assert_eq!(&data[parsed.size.unwrap() - 6..], body.as_slice());
+    }
+
+    #[test]
+    fn prop_zerocoded_packet_round_trip(
+        ping_id in any::<u8>(),
+        oldest_unacked in prop_oneof![Just(0u32), any::<u32>()],
+        packet_ids in prop::collection::vec(prop_oneof![Just(0u32), any::<u32>()], 0..20),
+        acks in prop::collection::vec(any::<u32>(), 0..4),
+    ) {
+        let packets = [
+            Packet::new_start_ping_check(StartPingCheck { ping_id, oldest_unacked }),
+            Packet::new_complete_ping_check(CompletePingCheck { ping_id }),
+            Packet::new_packet_ack(PacketAck { packet_ids }),
+        ];
+        // along with a packet of every other registered type
+        for mut packet in packets.into_iter().chain(samples::packets()) {
+            packet.header.zerocoded = true;
+            packet.header.ack_list = Some(acks.clone());
+            let bytes = packet.to_bytes();
+
+            let parsed = Packet::from_bytes(&bytes).unwrap();
+            prop_assert_eq!(parsed.header.id, packet.header.id);
+            prop_assert_eq!(parsed.header.frequency, packet.header.frequency);
+            prop_assert_eq!(parsed.body.to_bytes(), packet.body.to_bytes());
+            prop_assert_eq!(parsed.to_bytes(), bytes);
+        }
+    }
+}
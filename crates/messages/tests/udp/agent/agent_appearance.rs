@@ -1,4 +1,13 @@
-use metaverse_messages::packet_protocol::{packet::Packet, packet_types::PacketType};
+use glam::Vec3;
+use metaverse_messages::{
+    http::item::TextureSlot,
+    packet::packet_protocol::PacketData,
+    udp::agent::avatar_appearance::AvatarAppearance,
+    utils::{
+        bake::{BakeType, DEFAULT_AVATAR_TEXTURE},
+        texture_entry::TextureEntry,
+    },
+};
 use uuid::{Uuid, uuid};
 
 const DEFAULT_USER_APPEARANCE: [u8; 326] = [
@@ -39,23 +48,64 @@ const TEST_USER_APPEARANCE: [u8; 361] = [
 const TEST_USER_ID: Uuid = uuid!("f96360e2883b4eadb86a41bcd3e21550");
 const DEFAULT_USER_ID: Uuid = uuid!("9dc18bb1044f4c68906b2cb608b2e197");
 
-/// ensures the AvatarAppearance packet parses correctly. The AvatarAppearance packet is currently
-/// unused.
+/// The captured packets were logged after they were zero-decoded, so their bodies are parsed
+/// without the header, which still has the zerocoded flag set.
+const HEADER_SIZE: usize = 10;
+
+/// ensures the AvatarAppearance packet parses correctly, including the hover height block sent
+/// after the visual params
 #[test]
 pub fn test_parse_avatar_appearance() {
-    let default_user = Packet::from_bytes(&DEFAULT_USER_APPEARANCE).unwrap();
-    let test_user = Packet::from_bytes(&TEST_USER_APPEARANCE).unwrap();
-    match default_user.body {
-        PacketType::AvatarAppearance(avatar) => {
-            assert_eq!(avatar.id, DEFAULT_USER_ID);
-        }
-        _ => assert!(false),
-    }
+    let default_user =
+        AvatarAppearance::from_bytes(&DEFAULT_USER_APPEARANCE[HEADER_SIZE..]).unwrap();
+    assert_eq!(default_user.id, DEFAULT_USER_ID);
+    assert_eq!(default_user.visual_params.len(), 218);
+    assert_eq!(default_user.appearance_data, None);
+    assert_eq!(default_user.hover_height, Some(Vec3::ZERO));
+    assert!(default_user.attachments.is_empty());
+
+    let test_user = AvatarAppearance::from_bytes(&TEST_USER_APPEARANCE[HEADER_SIZE..]).unwrap();
+    assert_eq!(test_user.id, TEST_USER_ID);
+    assert_eq!(test_user.visual_params.len(), 253);
+    assert_eq!(test_user.hover_height, Some(Vec3::ZERO));
+    // the texture entry survives being written back out
+    let texture_entry = TextureEntry::from_bytes(&test_user.texture_entry.to_bytes()).unwrap();
+    assert_eq!(texture_entry.texture_id, test_user.texture_entry.texture_id);
+}
+
+/// ensures the baked textures of an appearance are read from the baked slots of its texture entry
+#[test]
+pub fn test_baked_textures() {
+    // the default user hasn't been baked, so every slot has the default avatar texture
+    let default_user =
+        AvatarAppearance::from_bytes(&DEFAULT_USER_APPEARANCE[HEADER_SIZE..]).unwrap();
+    assert!(default_user.baked_textures().is_empty());
 
-    match test_user.body {
-        PacketType::AvatarAppearance(avatar) => {
-            assert_eq!(avatar.id, TEST_USER_ID);
-        }
-        _ => assert!(false),
+    let head = uuid!("11111111-2222-3333-4444-555555555555");
+    let left_arm = uuid!("66666666-7777-8888-9999-000000000000");
+    let mut appearance = AvatarAppearance::default();
+    appearance.texture_entry.texture_id = DEFAULT_AVATAR_TEXTURE;
+    for (slot, texture_id) in [
+        (TextureSlot::HeadBaked, head),
+        (TextureSlot::LeftArmBaked, left_arm),
+        // layers of wearables aren't bakes
+        (TextureSlot::UpperShirt, head),
+    ] {
+        appearance.texture_entry.faces.insert(
+            slot.index() as u32,
+            TextureEntry {
+                texture_id,
+                ..Default::default()
+            },
+        );
     }
+    let bakes = appearance.baked_textures();
+    assert_eq!(bakes.len(), 2);
+    assert_eq!(bakes[&BakeType::Head], head);
+    assert_eq!(bakes[&BakeType::LeftArm], left_arm);
+    assert_eq!(
+        BakeType::from_bakes_on_mesh_texture(BakeType::LeftArm.bakes_on_mesh_texture()),
+        Some(BakeType::LeftArm)
+    );
+    assert_eq!(BakeType::from_bakes_on_mesh_texture(head), None);
 }
@@ -0,0 +1,41 @@
+use metaverse_messages::packet::packet_protocol::PacketData;
+use metaverse_messages::{
+    http::gltf_material::GltfMaterial,
+    udp::object::generic_streaming_message::{GenericStreamingMessage, MaterialOverride},
+};
+use uuid::uuid;
+
+#[test]
+fn test_material_override() {
+    let data = "{'id':i1234,'te':[i0,i2],'od':[{'bc':[r1,r0,r0,r0.5],'mf':r0},\
+                {'tex':[u00000000-0000-0000-0000-000000000000,u8dcd4a48-2d37-4909-9f78-f7a9eb4ef903]}]}";
+    let message = GenericStreamingMessage {
+        method: 0x4175,
+        data: data.as_bytes().to_vec(),
+    };
+    let parsed = GenericStreamingMessage::from_bytes(&message.to_bytes()).unwrap();
+    assert_eq!(parsed.method, 0x4175);
+
+    let material_override = MaterialOverride::from_bytes(&parsed.data).unwrap();
+    assert_eq!(material_override.local_id, 1234);
+    assert_eq!(material_override.faces.len(), 2);
+
+    let mut red = GltfMaterial::default();
+    red.apply_override(&material_override.faces[&0]);
+    assert_eq!(red.base_color, [1.0, 0.0, 0.0, 0.5]);
+    assert_eq!(red.metallic, 0.0);
+    assert_eq!(red.roughness, 1.0);
+
+    // nil textures leave the material's own texture in place
+    let base_color = uuid!("0e41a2e3-5cf4-4b9c-9b6e-0f3c1d0a7a11");
+    let mut textured = GltfMaterial {
+        base_color_texture: Some(base_color),
+        ..Default::default()
+    };
+    textured.apply_override(&material_override.faces[&2]);
+    assert_eq!(textured.base_color_texture, Some(base_color));
+    assert_eq!(
+        textured.normal_texture,
+        Some(uuid!("8dcd4a48-2d37-4909-9f78-f7a9eb4ef903"))
+    );
+}
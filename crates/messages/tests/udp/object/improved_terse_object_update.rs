@@ -1,5 +1,6 @@
+use glam::{Quat, Vec3};
 use metaverse_messages::{
-    packet::packet::PacketData,
+    packet::packet_protocol::PacketData,
     udp::object::improved_terse_object_update::ImprovedTerseObjectUpdate,
 };
 
@@ -12,5 +13,41 @@ const BODY_BYTES: [u8; 74] = [
 
 #[test]
 fn test_improved_terse_object_update() {
-    ImprovedTerseObjectUpdate::from_bytes(&BODY_BYTES).unwrap();
+    let update = ImprovedTerseObjectUpdate::from_bytes(&BODY_BYTES).unwrap();
+    assert_eq!(update.objects.len(), 1);
+
+    let object = &update.objects[0];
+    assert_eq!(object.local_id, 901940275);
+    assert!(object.avatar);
+    assert!(object.collision_plane.is_some());
+    assert!(
+        object
+            .position
+            .abs_diff_eq(Vec3::new(128.25476, 124.7452, 25.473778), 1e-4)
+    );
+
+    // a standing avatar. The quantized zeros are snapped back to zero.
+    assert_eq!(object.velocity, Vec3::ZERO);
+    assert_eq!(object.acceleration, Vec3::ZERO);
+    assert_eq!(object.angular_velocity, Vec3::ZERO);
+    assert!(object.rotation.is_normalized());
+    assert!(
+        object
+            .rotation
+            .abs_diff_eq(Quat::from_rotation_z(0.04233), 1e-4)
+    );
+    assert!(object.texture_entry.is_empty());
+}
+
+#[test]
+fn test_improved_terse_object_update_round_trip() {
+    // the simulator encodes zero as 32767 and we encode it as 32768, so compare after one re-encode
+    let update = ImprovedTerseObjectUpdate::from_bytes(&BODY_BYTES).unwrap();
+    let bytes = update.to_bytes();
+    assert_eq!(bytes.len(), BODY_BYTES.len());
+
+    let parsed = ImprovedTerseObjectUpdate::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed.to_bytes(), bytes);
+    assert_eq!(parsed.objects[0].position, update.objects[0].position);
+    assert_eq!(parsed.objects[0].velocity, Vec3::ZERO);
 }
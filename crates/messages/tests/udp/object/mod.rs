@@ -1,3 +1,4 @@
+pub mod generic_streaming_message;
 pub mod improved_terse_object_update;
 pub mod object_update;
 pub mod object_update_cached;
@@ -1,7 +1,16 @@
 use metaverse_messages::{
-    packet_protocol::{packet::Packet, packet::PacketData, packet_types::PacketType},
-    udp::object::object_update::ObjectUpdate,
+    packet::{
+        packet_protocol::{Packet, PacketData},
+        packet_types::PacketType,
+    },
+    http::scene::SculptType,
+    udp::object::object_update::{
+        FlexiData, LightData, MaterialsData, MeshFlagsData, ObjectUpdate, ProjectionData,
+        ReflectionProbeData, SculptData,
+    },
 };
+use glam::Vec3;
+use rgb::Rgb;
 use uuid::{Uuid, uuid};
 const PACKET: [u8; 169] = [
     192, 0, 3, 4, 0, 1, 12, 0, 1, 232, 3, 0, 2, 232, 3, 0, 1, 255, 255, 1, 246, 81, 208, 22, 0, 1,
@@ -109,7 +118,7 @@ const ATTACH_ITEM: [u8; 1467] = [
     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
 ];
 
-const NON_ATTACH: [u8; 312] = [
+pub const NON_ATTACH: [u8; 312] = [
     0, 232, 3, 0, 0, 232, 3, 0, 255, 255, 1, 91, 125, 142, 43, 80, 105, 119, 196, 91, 88, 167, 66,
     72, 188, 179, 187, 159, 248, 10, 141, 250, 95, 174, 255, 130, 9, 3, 0, 241, 102, 202, 61, 96,
     219, 32, 62, 72, 65, 209, 61, 60, 0, 214, 6, 63, 0, 240, 159, 188, 64, 177, 183, 62, 0, 0, 0,
@@ -144,14 +153,12 @@ pub fn test_object_update() {
         }
         _ => assert!(false),
     }
-    let object_update = ObjectUpdate::from_bytes(&ATTACH_ITEM).unwrap();
-    let object_update = ObjectUpdate::from_bytes(&NON_ATTACH).unwrap();
+    ObjectUpdate::from_bytes(&ATTACH_ITEM).unwrap();
+    ObjectUpdate::from_bytes(&NON_ATTACH).unwrap();
 }
 
 #[test]
-// this test WILL FAIL. this is a known bug. This test is for debugging it.
-// the issue is related to the NameValue being read as 4099 bytes long. this is of course not the
-// right size.
+// this packet used to fail, because the text length was read as two bytes instead of one.
 pub fn test_failing_object() {
     let object_update = Packet::from_bytes(&PACKET_3).unwrap();
     match object_update.body {
@@ -164,3 +171,154 @@ pub fn test_failing_object() {
         _ => assert!(false),
     }
 }
+
+#[test]
+pub fn test_object_update_round_trip() {
+    for bytes in [&PACKET[..], &PACKET2[..], &MORE_PACKET[..], &PACKET_3[..]] {
+        let packet = Packet::from_bytes(bytes).unwrap();
+        let parsed = Packet::from_bytes(&packet.to_bytes()).unwrap();
+        match (&packet.body, &parsed.body) {
+            (PacketType::ObjectUpdate(object), PacketType::ObjectUpdate(parsed)) => {
+                assert_eq!(object.full_id, parsed.full_id);
+                assert_eq!(object.name_value, parsed.name_value);
+                assert_eq!(object.motion_data.position, parsed.motion_data.position);
+                assert_eq!(object.to_bytes(), parsed.to_bytes());
+            }
+            _ => panic!("expected ObjectUpdate"),
+        }
+    }
+    for bytes in [&ATTACH_ITEM[..], &NON_ATTACH[..]] {
+        let object = ObjectUpdate::from_bytes(bytes).unwrap();
+        let parsed = ObjectUpdate::from_bytes(&object.to_bytes()).unwrap();
+        assert_eq!(object.id, parsed.id);
+        assert_eq!(object.to_bytes(), parsed.to_bytes());
+    }
+}
+
+#[test]
+pub fn test_sculpt_flags() {
+    let texture_id = uuid!("8dcd4a48-2d37-4909-9f78-f7a9eb4ef903");
+    let mut bytes = texture_id.as_bytes().to_vec();
+    // an inverted and mirrored sphere
+    bytes.push(0x01 | 0x40 | 0x80);
+
+    let sculpt = SculptData::from_bytes(&bytes).unwrap();
+    assert_eq!(sculpt.texture_id, texture_id);
+    assert!(matches!(sculpt.sculpt_type, SculptType::Sphere));
+    assert!(sculpt.invert);
+    assert!(sculpt.mirror);
+    assert_eq!(sculpt.to_bytes(), bytes);
+}
+
+#[test]
+pub fn test_flexi_params() {
+    let mut bytes = vec![
+        // the softness is split across the high bits of the tension and drag
+        0x80 | 10,
+        0x80 | 20,
+        103,
+        5,
+    ];
+    bytes.extend(0.0f32.to_le_bytes());
+    bytes.extend(0.0f32.to_le_bytes());
+    bytes.extend((-1.0f32).to_le_bytes());
+
+    let flexi = FlexiData::from_bytes(&bytes).unwrap();
+    assert_eq!(flexi.softness, 3);
+    assert_eq!(flexi.tension, 1.0);
+    assert_eq!(flexi.drag, 2.0);
+    assert!((flexi.gravity - 0.3).abs() < 1e-5);
+    assert_eq!(flexi.wind, 0.5);
+    assert_eq!(flexi.force, Vec3::new(0.0, 0.0, -1.0));
+    assert_eq!(flexi.to_bytes(), bytes);
+}
+
+#[test]
+pub fn test_flexi_params_without_force() {
+    let flexi = FlexiData::from_bytes(&[0x80 | 10, 20, 103, 0]).unwrap();
+    assert_eq!(flexi.softness, 2);
+    assert_eq!(flexi.force, Vec3::ZERO);
+    assert_eq!(flexi.to_bytes()[..4], [0x80 | 10, 20, 103, 0]);
+}
+
+#[test]
+pub fn test_light_params() {
+    // an orange light at half intensity
+    let mut bytes = vec![255, 128, 0, 128];
+    bytes.extend(5.0f32.to_le_bytes());
+    bytes.extend(0.0f32.to_le_bytes());
+    bytes.extend(1.5f32.to_le_bytes());
+
+    let light = LightData::from_bytes(&bytes).unwrap();
+    assert_eq!(light.color, Rgb::new(255, 128, 0));
+    assert!((light.intensity - 128.0 / 255.0).abs() < 1e-6);
+    assert_eq!(light.radius, 5.0);
+    assert_eq!(light.cutoff, 0.0);
+    assert_eq!(light.falloff, 1.5);
+    assert_eq!(light.to_bytes(), bytes);
+}
+
+#[test]
+pub fn test_projection_params() {
+    let texture_id = uuid!("8dcd4a48-2d37-4909-9f78-f7a9eb4ef903");
+    let mut bytes = texture_id.as_bytes().to_vec();
+    bytes.extend(1.0f32.to_le_bytes());
+    bytes.extend((-2.0f32).to_le_bytes());
+    bytes.extend(0.25f32.to_le_bytes());
+
+    let projection = ProjectionData::from_bytes(&bytes).unwrap();
+    assert_eq!(projection.texture_id, texture_id);
+    assert_eq!(projection.fov, 1.0);
+    assert_eq!(projection.focus, -2.0);
+    assert_eq!(projection.ambiance, 0.25);
+    assert_eq!(projection.to_bytes(), bytes);
+}
+
+#[test]
+pub fn test_materials_params() {
+    let base = uuid!("0e41a2e3-5cf4-4b9c-9b6e-0f3c1d0a7a11");
+    let trim = uuid!("b4c1a8f0-27e2-4e7d-8d4a-9a8f5e3e6c22");
+    let mut bytes = vec![2, 0];
+    bytes.extend(base.as_bytes());
+    bytes.push(3);
+    bytes.extend(trim.as_bytes());
+
+    let materials = MaterialsData::from_bytes(&bytes).unwrap();
+    assert_eq!(materials.materials.len(), 2);
+    assert_eq!(materials.materials[&0], base);
+    assert_eq!(materials.materials[&3], trim);
+    assert_eq!(materials.to_bytes(), bytes);
+
+    // a count without its entries is an error
+    assert!(MaterialsData::from_bytes(&[1, 0]).is_err());
+}
+
+#[test]
+pub fn test_mesh_flags() {
+    let bytes = 1u32.to_le_bytes().to_vec();
+    let flags = MeshFlagsData::from_bytes(&bytes).unwrap();
+    assert!(flags.animated_mesh);
+    assert_eq!(flags.to_bytes(), bytes);
+
+    let flags = MeshFlagsData::from_bytes(&[0, 0, 0, 0]).unwrap();
+    assert!(!flags.animated_mesh);
+    assert!(MeshFlagsData::from_bytes(&[1]).is_err());
+}
+
+#[test]
+pub fn test_reflection_probe() {
+    let mut bytes = Vec::new();
+    bytes.extend(0.5f32.to_le_bytes());
+    bytes.extend(2.0f32.to_le_bytes());
+    // a dynamic box probe
+    bytes.push(0x01 | 0x02);
+
+    let probe = ReflectionProbeData::from_bytes(&bytes).unwrap();
+    assert_eq!(probe.ambiance, 0.5);
+    assert_eq!(probe.clip_distance, 2.0);
+    assert!(probe.box_volume);
+    assert!(probe.dynamic);
+    assert!(!probe.mirror);
+    assert_eq!(probe.to_bytes(), bytes);
+    assert!(ReflectionProbeData::from_bytes(&bytes[..8]).is_err());
+}
@@ -1,11 +1,6 @@
 use metaverse_messages::{
-    packet_protocol::{
-        packet_protocol::{Packet, PacketData},
-        packet_types::PacketType,
-    },
-    udp::object::{
-        object_update_cached::ObjectUpdateCached, object_update_compressed::ObjectUpdateCompressed,
-    },
+    packet::packet_protocol::PacketData,
+    udp::object::object_update_compressed::ObjectUpdateCompressed,
 };
 
 const PACKET_BYTES: [u8; 1526] = [
@@ -69,7 +64,7 @@ const PACKET_BYTES: [u8; 1526] = [
     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
 ];
 
-const PACKET_BYTES_2: [u8; 839] = [
+pub const PACKET_BYTES_2: [u8; 839] = [
     0, 232, 3, 0, 0, 232, 3, 0, 255, 255, 4, 60, 9, 2, 16, 199, 0, 67, 78, 41, 56, 105, 23, 69, 20,
     166, 119, 216, 149, 226, 139, 49, 22, 80, 5, 119, 46, 9, 0, 44, 230, 253, 175, 3, 0, 153, 153,
     153, 62, 23, 98, 21, 61, 100, 12, 128, 62, 167, 59, 255, 66, 208, 68, 253, 66, 78, 233, 202,
@@ -109,3 +104,20 @@ const PACKET_BYTES_2: [u8; 839] = [
 fn test_object_update_compressed() {
     ObjectUpdateCompressed::from_bytes(&PACKET_BYTES_2).unwrap();
 }
+
+#[test]
+fn test_object_update_compressed_round_trip() {
+    for bytes in [&PACKET_BYTES[..], &PACKET_BYTES_2[..]] {
+        let update = ObjectUpdateCompressed::from_bytes(bytes).unwrap();
+        let parsed = ObjectUpdateCompressed::from_bytes(&update.to_bytes()).unwrap();
+
+        assert_eq!(update.object_data.len(), parsed.object_data.len());
+        for (object, parsed) in update.object_data.iter().zip(&parsed.object_data) {
+            assert_eq!(object.full_id, parsed.full_id);
+            assert_eq!(object.local_id, parsed.local_id);
+            assert_eq!(object.parent_id, parsed.parent_id);
+            assert_eq!(object.position, parsed.position);
+        }
+        assert_eq!(update.to_bytes(), parsed.to_bytes());
+    }
+}
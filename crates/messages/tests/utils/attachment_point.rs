@@ -0,0 +1,46 @@
+use glam::Vec2;
+use metaverse_messages::utils::attachment_point::AttachmentPoint;
+
+#[test]
+fn test_attachment_point_from_state() {
+    // the state stores the point's ID with its nibbles swapped
+    assert_eq!(AttachmentPoint::from_state(0x20), AttachmentPoint::Skull);
+    assert_eq!(
+        AttachmentPoint::from_state(0x32),
+        AttachmentPoint::HudCenter
+    );
+    assert_eq!(
+        AttachmentPoint::from_state(0x73),
+        AttachmentPoint::RightHindFoot
+    );
+    assert_eq!(AttachmentPoint::from_state(0x00), AttachmentPoint::Default);
+    assert_eq!(
+        AttachmentPoint::from_state(0xFF),
+        AttachmentPoint::Unknown(0xFF)
+    );
+
+    for id in 0..=u8::MAX {
+        assert_eq!(AttachmentPoint::from_u8(id).to_u8(), id);
+    }
+}
+
+#[test]
+fn test_attachment_point_joints() {
+    assert_eq!(AttachmentPoint::Skull.joint_name(), Some("mHead"));
+    assert_eq!(AttachmentPoint::LeftHand.joint_name(), Some("mWristLeft"));
+    assert_eq!(
+        AttachmentPoint::Tongue.joint_name(),
+        Some("mFaceTongueBase")
+    );
+    assert_eq!(AttachmentPoint::Default.joint_name(), None);
+
+    // HUD points are on the screen instead of a joint
+    assert!(AttachmentPoint::HudTopLeft.is_hud());
+    assert_eq!(AttachmentPoint::HudTopLeft.joint_name(), None);
+    assert_eq!(
+        AttachmentPoint::HudTopLeft.hud_anchor(),
+        Some(Vec2::new(-1.0, 1.0))
+    );
+    assert!(!AttachmentPoint::Chest.is_hud());
+    assert_eq!(AttachmentPoint::Chest.hud_anchor(), None);
+}
@@ -0,0 +1,101 @@
+use benthic_protocol::skeleton::JointName;
+use glam::Vec3;
+use metaverse_messages::utils::avatar_mesh::{AVATAR_MESH_HEADER, AvatarMesh};
+use std::collections::BTreeMap;
+
+fn name(bytes: &mut Vec<u8>, name: &str, length: usize) {
+    let mut padded = name.as_bytes().to_vec();
+    padded.resize(length, 0);
+    bytes.extend(padded);
+}
+
+fn floats(bytes: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        bytes.extend(value.to_le_bytes());
+    }
+}
+
+/// A skinned triangle with one morph that lifts its first vertex, and a third vertex that shares
+/// the first one's position along a seam
+fn triangle() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    name(&mut bytes, AVATAR_MESH_HEADER, 24);
+    // weights, no detail texture coordinates
+    bytes.extend([1, 0]);
+    // position, rotation, rotation order, scale
+    floats(&mut bytes, &[0.0; 6]);
+    bytes.push(0);
+    floats(&mut bytes, &[1.0; 3]);
+
+    bytes.extend(3u16.to_le_bytes());
+    // positions
+    floats(&mut bytes, &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+    // normals
+    floats(&mut bytes, &[0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+    // binormals
+    floats(&mut bytes, &[1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+    // texture coordinates
+    floats(&mut bytes, &[0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+    // weights
+    floats(&mut bytes, &[1.0, 1.5, 2.0]);
+
+    bytes.extend(1u16.to_le_bytes());
+    for index in [0i16, 1, 2] {
+        bytes.extend(index.to_le_bytes());
+    }
+
+    bytes.extend(2u16.to_le_bytes());
+    name(&mut bytes, "mPelvis", 64);
+    name(&mut bytes, "mTorso", 64);
+
+    name(&mut bytes, "Big_Brow", 64);
+    bytes.extend(1i32.to_le_bytes());
+    bytes.extend(0u32.to_le_bytes());
+    floats(&mut bytes, &[0.0, 0.0, 0.5]);
+    floats(&mut bytes, &[0.0; 3]);
+    floats(&mut bytes, &[0.0; 3]);
+    floats(&mut bytes, &[0.0; 2]);
+    name(&mut bytes, "End Morphs", 64);
+
+    bytes.extend(1i32.to_le_bytes());
+    bytes.extend(2i32.to_le_bytes());
+    bytes.extend(0i32.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn test_parse_avatar_mesh() {
+    let mesh = AvatarMesh::from_bytes(&triangle()).unwrap();
+    assert_eq!(mesh.vertices.len(), 3);
+    assert_eq!(mesh.vertices[1], Vec3::X);
+    assert_eq!(mesh.normals[2], Vec3::Z);
+    assert_eq!(mesh.uvs[2].y, 1.0);
+    assert_eq!(mesh.weights, vec![1.0, 1.5, 2.0]);
+    assert_eq!(mesh.indices, vec![0, 1, 2]);
+    assert_eq!(
+        mesh.skin_joints,
+        vec![JointName::MPelvis, JointName::MTorso]
+    );
+    assert_eq!(mesh.morphs["Big_Brow"].len(), 1);
+    assert_eq!(mesh.shared_vertices, vec![(2, 0)]);
+
+    assert!(AvatarMesh::from_bytes(b"Linden Binary Mesh 2.0").is_err());
+}
+
+#[test]
+fn test_morph_avatar_mesh() {
+    let mesh = AvatarMesh::from_bytes(&triangle()).unwrap();
+
+    let weights = BTreeMap::from([
+        ("Big_Brow".to_string(), 2.0),
+        ("Not_A_Morph".to_string(), 1.0),
+    ]);
+    let vertices = mesh.morphed_vertices(&weights);
+    assert_eq!(vertices[0], Vec3::Z);
+    assert_eq!(vertices[1], Vec3::X);
+    // the seam vertex follows the vertex it shares
+    assert_eq!(vertices[2], Vec3::Z);
+
+    // without weights the mesh is in its bind pose
+    assert_eq!(mesh.morphed_vertices(&BTreeMap::new()), mesh.vertices);
+}
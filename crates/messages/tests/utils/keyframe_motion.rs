@@ -0,0 +1,126 @@
+use glam::{Quat, Vec3};
+use metaverse_messages::utils::keyframe_motion::{ConstraintType, KeyframeMotion};
+
+fn floats(bytes: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        bytes.extend(value.to_le_bytes());
+    }
+}
+
+fn quantized(bytes: &mut Vec<u8>, values: &[u16]) {
+    for value in values {
+        bytes.extend(value.to_le_bytes());
+    }
+}
+
+/// A looping two second animation that turns the pelvis half way around and lifts it, with a
+/// constraint that keeps the left foot on the ground
+fn turn() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    quantized(&mut bytes, &[1, 0]);
+    bytes.extend(4i32.to_le_bytes());
+    floats(&mut bytes, &[2.0]);
+    bytes.extend(b"express_smile\0");
+    // loop in and out, with a loop out past the end of the animation
+    floats(&mut bytes, &[0.5, 3.0]);
+    bytes.extend(1i32.to_le_bytes());
+    // ease in and out
+    floats(&mut bytes, &[0.25, 0.5]);
+    bytes.extend(3u32.to_le_bytes());
+
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend(b"mPelvis\0");
+    bytes.extend((-1i32).to_le_bytes());
+    bytes.extend(2i32.to_le_bytes());
+    // no rotation at the start, and half a turn around Z at the end
+    quantized(&mut bytes, &[0, 32767, 32767, 32767]);
+    quantized(&mut bytes, &[65535, 32767, 32767, 65535]);
+    bytes.extend(1i32.to_le_bytes());
+    // lifted 5 meters half way through
+    quantized(&mut bytes, &[32767, 32767, 32767, 65535]);
+
+    bytes.extend(1i32.to_le_bytes());
+    bytes.extend([2, 1]);
+    let mut volume = b"L_FOOT".to_vec();
+    volume.resize(16, 0);
+    bytes.extend(volume);
+    floats(&mut bytes, &[0.0, 0.0, -0.1]);
+    bytes.extend([0; 16]);
+    floats(&mut bytes, &[0.0; 3]);
+    floats(&mut bytes, &[0.0, 0.0, 1.0]);
+    floats(&mut bytes, &[0.0, 0.1, 1.9, 2.0]);
+    bytes
+}
+
+#[test]
+fn test_parse_keyframe_motion() {
+    let motion = KeyframeMotion::from_bytes(&turn()).unwrap();
+    assert_eq!(motion.priority, 4);
+    assert_eq!(motion.duration, 2.0);
+    assert_eq!(motion.emote_name, "express_smile");
+    assert_eq!(motion.loop_in_point, 0.5);
+    assert_eq!(motion.loop_out_point, 2.0);
+    assert!(motion.looping);
+    assert_eq!(motion.ease_in_duration, 0.25);
+    assert_eq!(motion.ease_out_duration, 0.5);
+    assert_eq!(motion.hand_pose, 3);
+
+    assert_eq!(motion.joints.len(), 1);
+    let pelvis = &motion.joints[0];
+    assert_eq!(pelvis.name, "mPelvis");
+    assert_eq!(pelvis.priority, -1);
+
+    // values near zero are snapped to it
+    assert_eq!(pelvis.rotation_keys[0].time, 0.0);
+    assert_eq!(pelvis.rotation_keys[0].rotation, Quat::IDENTITY);
+    assert!((pelvis.rotation_keys[1].time - 2.0).abs() < 1e-4);
+    assert!(
+        pelvis.rotation_keys[1]
+            .rotation
+            .abs_diff_eq(Quat::from_rotation_z(std::f32::consts::PI), 1e-4)
+    );
+
+    assert!((pelvis.position_keys[0].time - 1.0).abs() < 1e-4);
+    assert!(
+        pelvis.position_keys[0]
+            .position
+            .abs_diff_eq(Vec3::new(0.0, 0.0, 5.0), 1e-4)
+    );
+
+    assert_eq!(motion.constraints.len(), 1);
+    let constraint = &motion.constraints[0];
+    assert_eq!(constraint.chain_length, 2);
+    assert_eq!(constraint.constraint_type, ConstraintType::Plane);
+    assert_eq!(constraint.source_volume, "L_FOOT");
+    assert_eq!(constraint.target_volume, "");
+    assert_eq!(constraint.target_direction, Vec3::Z);
+    assert_eq!(constraint.ease_out_stop, 2.0);
+}
+
+#[test]
+fn test_keyframe_motion_without_constraints() {
+    let mut bytes = turn();
+    // older animations end after their joints
+    bytes.truncate(bytes.len() - 90);
+    let motion = KeyframeMotion::from_bytes(&bytes).unwrap();
+    assert_eq!(motion.joints.len(), 1);
+    assert!(motion.constraints.is_empty());
+}
+
+#[test]
+fn test_invalid_keyframe_motion() {
+    let mut bytes = turn();
+    bytes[0] = 2;
+    assert!(KeyframeMotion::from_bytes(&bytes).is_err());
+    assert!(KeyframeMotion::from_bytes(&turn()[..40]).is_err());
+}
+
+#[test]
+fn test_keyframe_motion_json() {
+    let motion = KeyframeMotion::from_bytes(&turn()).unwrap();
+    let json = serde_json::to_string(&motion).unwrap();
+    assert_eq!(
+        serde_json::from_str::<KeyframeMotion>(&json).unwrap(),
+        motion
+    );
+}
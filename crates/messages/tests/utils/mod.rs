@@ -1 +1,5 @@
-
+pub mod attachment_point;
+pub mod avatar_mesh;
+pub mod keyframe_motion;
+pub mod texture_entry;
+pub mod visual_params;
@@ -0,0 +1,104 @@
+use std::f32::consts::FRAC_PI_2;
+
+use metaverse_messages::utils::texture_entry::TextureEntry;
+use rgb::Rgba;
+use uuid::Uuid;
+
+/// Build texture entry bytes with a default texture, a texture override on faces 1 and 3, and a
+/// color override on face 3
+fn face_override_bytes(default: Uuid, overridden: Uuid) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(default.as_bytes());
+    bytes.push(0b0000_1010);
+    bytes.extend_from_slice(overridden.as_bytes());
+    bytes.push(0);
+
+    // colors are inverted, so this is opaque white
+    bytes.extend([0, 0, 0, 0]);
+    bytes.push(0b0000_1000);
+    // opaque red
+    bytes.extend([0, 255, 255, 0]);
+    bytes.push(0);
+
+    bytes.extend(1.0f32.to_le_bytes());
+    bytes.push(0);
+    bytes.extend(1.0f32.to_le_bytes());
+    bytes.push(0);
+    bytes
+}
+
+#[test]
+fn test_face_overrides() {
+    let default = Uuid::new_v4();
+    let overridden = Uuid::new_v4();
+    let entry = TextureEntry::from_bytes(&face_override_bytes(default, overridden)).unwrap();
+
+    assert_eq!(entry.texture_id, default);
+    assert_eq!(entry.rgba, Rgba::new(255, 255, 255, 255));
+    assert_eq!(entry.faces.len(), 2);
+
+    assert_eq!(entry.face(0).texture_id, default);
+    assert_eq!(entry.face(1).texture_id, overridden);
+    assert_eq!(entry.face(3).texture_id, overridden);
+
+    // face 1 only overrides the texture, so it keeps the default color and repeats
+    assert_eq!(entry.face(1).rgba, Rgba::new(255, 255, 255, 255));
+    assert_eq!(entry.face(1).repeat_u, 1.0);
+    assert_eq!(entry.face(3).rgba, Rgba::new(255, 0, 0, 255));
+    assert_eq!(entry.face(3).repeat_v, 1.0);
+}
+
+#[test]
+fn test_face_override_round_trip() {
+    let mut entry = TextureEntry {
+        texture_id: Uuid::new_v4(),
+        rgba: Rgba::new(255, 255, 255, 255),
+        repeat_u: 1.0,
+        repeat_v: 1.0,
+        rotation: FRAC_PI_2,
+        glow: 0.2,
+        ..Default::default()
+    };
+    let mut face = entry.clone();
+    face.texture_id = Uuid::new_v4();
+    face.rgba = Rgba::new(0, 128, 255, 128);
+    face.repeat_u = 4.0;
+    face.offset_v = 0.5;
+    face.glow = 1.0;
+    // faces past the first 7 need a multi byte bitfield
+    entry.faces.insert(2, face.clone());
+    entry.faces.insert(9, face);
+
+    let parsed = TextureEntry::from_bytes(&entry.to_bytes()).unwrap();
+    assert_eq!(parsed.texture_id, entry.texture_id);
+    assert!((parsed.rotation - FRAC_PI_2).abs() < 1e-3);
+    assert!((parsed.glow - 0.2).abs() < 1e-2);
+    assert_eq!(parsed.faces.keys().copied().collect::<Vec<_>>(), vec![2, 9]);
+
+    for index in [2, 9] {
+        let face = parsed.face(index);
+        assert_eq!(face.texture_id, entry.faces[&index].texture_id);
+        assert_eq!(face.rgba, Rgba::new(0, 128, 255, 128));
+        assert_eq!(face.repeat_u, 4.0);
+        assert_eq!(face.repeat_v, 1.0);
+        assert!((face.offset_v - 0.5).abs() < 1e-4);
+        assert!((face.rotation - FRAC_PI_2).abs() < 1e-3);
+        assert_eq!(face.glow, 1.0);
+    }
+    assert_eq!(parsed.face(5).texture_id, entry.texture_id);
+}
+
+#[test]
+fn test_from_b64() {
+    let b64 =
+        b"ykDj72I5Saqn4j4n0DoKrQAAAAAAAAAAgD8AAACAPwAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA==";
+    let entry = TextureEntry::from_b64(b64).unwrap();
+    assert_eq!(
+        entry.texture_id,
+        Uuid::parse_str("ca40e3ef-6239-49aa-a7e2-3e27d03a0aad").unwrap()
+    );
+    assert_eq!(entry.rgba, Rgba::new(255, 255, 255, 255));
+    assert_eq!(entry.repeat_u, 1.0);
+    assert_eq!(entry.repeat_v, 1.0);
+    assert!(entry.faces.is_empty());
+}
@@ -0,0 +1,123 @@
+use benthic_protocol::skeleton::JointName;
+use glam::Vec3;
+use metaverse_messages::utils::visual_params::{AvatarLad, VisualParamKind};
+use std::collections::HashMap;
+
+/// A cut down avatar_lad.xml, with a skeleton param, a morph, a driver and a param that isn't
+/// sent to other viewers
+const AVATAR_LAD: &str = r#"<?xml version="1.0" encoding="US-ASCII" standalone="yes"?>
+<linden_avatar version="2.0" wearable_definition_version="22">
+  <skeleton file_name="avatar_skeleton.xml">
+    <param id="33" group="1" name="Height" value_min="-2.3" value_max="2">
+      <param_skeleton>
+        <bone name="mPelvis" scale="0 0 .02" offset="0 0 .02" />
+        <bone name="PELVIS" scale="0 0 .1" />
+        <bone name="mHead" scale=".1 .1 .1" />
+      </param_skeleton>
+    </param>
+  </skeleton>
+  <mesh type="headMesh" lod="0" file_name="avatar_head.llm">
+    <param id="1" group="1" name="Big_Brow" value_min="-.3" value_max="2">
+      <param_morph />
+    </param>
+  </mesh>
+  <mesh type="headMesh" lod="1" file_name="avatar_head_1.llm">
+    <param id="1" group="1" name="Big_Brow_Lod1" shared="1" value_min="-.3" value_max="2">
+      <param_morph />
+    </param>
+  </mesh>
+  <driver_parameters>
+    <param id="80" group="0" name="male" value_min="0" value_max="1">
+      <param_driver />
+    </param>
+    <param id="25" group="0" name="Body Height" value_min="-2.3" value_max="2">
+      <param_driver>
+        <driven id="33" />
+      </param_driver>
+    </param>
+    <param id="50" group="3" name="Brow Size" value_min="0" value_max="1">
+      <param_driver>
+        <driven id="1" min1="0" max1=".5" max2=".5" min2="1" />
+      </param_driver>
+    </param>
+    <param id="70" group="2" name="Not Sent" value_min="0" value_max="1" value_default=".5">
+      <param_driver />
+    </param>
+  </driver_parameters>
+</linden_avatar>"#;
+
+#[test]
+fn test_parse_avatar_lad() {
+    let lad = AvatarLad::from_xml(AVATAR_LAD.as_bytes()).unwrap();
+    assert_eq!(lad.params.len(), 6);
+
+    // shared params keep their first definition
+    assert_eq!(lad.params[&1].name, "Big_Brow");
+    assert!(matches!(lad.params[&1].kind, VisualParamKind::Morph));
+
+    // collision volumes aren't joints of the skeleton
+    let VisualParamKind::Skeleton(bones) = &lad.params[&33].kind else {
+        panic!("height is not a skeleton param");
+    };
+    assert_eq!(bones.len(), 2);
+    assert_eq!(bones[0].joint, JointName::MPelvis);
+    assert!(bones[0].scale.abs_diff_eq(Vec3::new(0.0, 0.0, 0.02), 1e-6));
+
+    // params are sent in order of their IDs, and only from groups 0 and 3
+    let transmitted: Vec<i32> = lad.transmitted().map(|param| param.id).collect();
+    assert_eq!(transmitted, vec![25, 50, 80]);
+    assert_eq!(lad.params[&70].value_default, 0.5);
+}
+
+#[test]
+fn test_avatar_shape() {
+    let lad = AvatarLad::from_xml(AVATAR_LAD.as_bytes()).unwrap();
+
+    // the byte covers the param's range, and values within a step of zero are snapped to it
+    assert!((lad.params[&25].weight_from_u8(0) + 2.3).abs() < 1e-5);
+    assert!((lad.params[&25].weight_from_u8(255) - 2.0).abs() < 1e-5);
+    assert_eq!(lad.params[&80].weight_from_u8(0), 0.0);
+
+    // full body height, and the brow driver at the top of its ramp
+    let weights = lad.weights(&[255, 128, 255]);
+    assert!((weights[&33] - 2.0).abs() < 1e-5);
+    assert!((weights[&1] - 2.0).abs() < 0.02);
+    assert_eq!(weights[&70], 0.5);
+
+    // past max2 the driven param ramps back down
+    let weights = lad.weights(&[255, 255, 255]);
+    assert!((weights[&1] + 0.3).abs() < 1e-5);
+
+    let shape = lad.shape(&[255, 128, 255]);
+    let pelvis = shape.joint(&JointName::MPelvis);
+    assert!(pelvis.scale.abs_diff_eq(Vec3::new(1.0, 1.0, 1.04), 1e-5));
+    assert!(pelvis.offset.abs_diff_eq(Vec3::new(0.0, 0.0, 0.04), 1e-5));
+    let head = shape.joint(&JointName::MHead);
+    assert!(head.scale.abs_diff_eq(Vec3::splat(1.2), 1e-5));
+    assert_eq!(shape.joint(&JointName::MTorso), Default::default());
+    assert!(shape.morphs.contains_key("Big_Brow"));
+
+    // missing bytes leave the params at their defaults, which drivers still apply
+    let shape = lad.shape(&[]);
+    assert!(
+        shape
+            .joints
+            .values()
+            .all(|joint| *joint == Default::default())
+    );
+    assert!((shape.morphs["Big_Brow"] + 0.3).abs() < 1e-5);
+}
+
+#[test]
+fn test_visual_params_from_weights() {
+    let lad = AvatarLad::from_xml(AVATAR_LAD.as_bytes()).unwrap();
+
+    // missing params are sent at their defaults, and weights are clamped to the param's range
+    let weights = HashMap::from([(25, 2.0), (50, 4.0)]);
+    let visual_params = lad.visual_params(&weights);
+    assert_eq!(visual_params, vec![255, 255, 0]);
+    assert_eq!(lad.params[&25].weight_to_u8(-2.3), 0);
+
+    let weights = lad.weights(&visual_params);
+    assert!((weights[&33] - 2.0).abs() < 1e-5);
+}
@@ -1,9 +1,9 @@
 use crate::render::AgentID;
 use bevy::ecs::component::Component;
 use bevy::ecs::entity::Entity;
+use bevy::ecs::hierarchy::Children;
 use bevy::ecs::observer::On;
 use bevy::prelude::Res;
-use log::warn;
 use bevy::scene::SceneInstanceReady;
 use bevy::{
     animation::{
@@ -24,6 +24,9 @@ use uuid::Uuid;
 #[derive(Resource)]
 pub struct AnimationQueue {
     pub pending: HashMap<Uuid, AnimationPath>,
+    /// The animation each agent is playing, which is queued again when the agent's model is
+    /// replaced
+    pub playing: HashMap<Uuid, AnimationPath>,
 }
 
 pub struct AnimationPath {
@@ -40,14 +43,27 @@ pub struct AgentAnimationPlayer {
 pub fn scene_instance_ready(
     trigger: On<SceneInstanceReady>,
     mut commands: Commands,
+    mut animation_queue: ResMut<AnimationQueue>,
     animation_players: Query<(Entity, &AnimationPlayer)>,
     agent_id_query: Query<(Entity, &AgentID)>,
+    children: Query<&Children>,
 ) {
     if let Some((_, agent_id)) = agent_id_query
         .iter()
         .find(|(entity, _)| *entity == trigger.entity)
     {
-        for (player_entity, _) in animation_players.iter() {
+        // a newer animation may already be waiting for the new model
+        if let Some(animation) = animation_queue.playing.remove(&agent_id.id) {
+            animation_queue
+                .pending
+                .entry(agent_id.id)
+                .or_insert(animation);
+        }
+        // only the players of this agent's model, since every avatar has its own
+        for player_entity in children
+            .iter_descendants(trigger.entity)
+            .filter(|entity| animation_players.contains(*entity))
+        {
             commands.entity(player_entity).insert(AgentAnimationPlayer {
                 agent_id: agent_id.id,
             });
@@ -89,12 +105,12 @@ pub fn update_animations(
                   
                 println!("Playing animation for {:?} at node {:?}", agent_id, node_index);  
                 done.push(*agent_id);  
-            } else {  
-                warn!("Failed to find player entity for animation");  
             }  
         }  
     }
     for id in done {
-        animation_queue.pending.remove(&id);
+        if let Some(animation) = animation_queue.pending.remove(&id) {
+            animation_queue.playing.insert(id, animation);
+        }
     }
 }
@@ -0,0 +1,168 @@
+use crate::plugin::SessionData;
+use crate::render::{AgentID, KillObjectEvent, MainCamera, SceneIDMap};
+use benthic_protocol::messages::ui::attachment_update::AttachmentUpdate;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+/// How far in front of the camera HUDs are drawn. They are scaled down to fit the screen at this
+/// distance, so they stay in front of the scene without being clipped by the near plane.
+const HUD_DEPTH: f32 = 0.2;
+
+#[derive(Message)]
+pub struct AttachmentUpdateEvent {
+    pub value: AttachmentUpdate,
+}
+
+/// Attachments whose object or avatar haven't been spawned yet, and the frames HUDs are placed in
+#[derive(Resource, Default)]
+pub struct AttachmentQueue {
+    pub pending: HashMap<u32, AttachmentUpdate>,
+    pub hud_frames: HashMap<u32, Entity>,
+}
+
+/// An attachment worn on an avatar's joint. If the avatar's model is replaced, the attachment is
+/// detached from it and attached again to the new model.
+#[derive(Component)]
+pub struct Worn {
+    pub attachment: AttachmentUpdate,
+}
+
+/// The frame of a HUD attachment, which follows a corner or edge of the screen
+#[derive(Component)]
+pub struct HudFrame {
+    pub anchor: Vec2,
+}
+
+/// Parent attachments to the joint of the avatar wearing them, or to the screen if they are HUDs.
+///
+/// Attachments are hidden until their avatar's skeleton has loaded, so they aren't drawn in the
+/// middle of the region.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_attachment_update(
+    mut ev_kill_object: MessageReader<KillObjectEvent>,
+    mut ev_attachment_update: MessageReader<AttachmentUpdateEvent>,
+    mut attachment_queue: ResMut<AttachmentQueue>,
+    mut commands: Commands,
+    scene_id_map: Res<SceneIDMap>,
+    session_data: Res<SessionData>,
+    agents: Query<(Entity, &AgentID)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    cameras: Query<Entity, With<MainCamera>>,
+    detached: Query<(Entity, &Worn), Without<ChildOf>>,
+) {
+    for update in ev_attachment_update.read() {
+        attachment_queue
+            .pending
+            .insert(update.value.scene_id, update.value.clone());
+    }
+    for (object, worn) in &detached {
+        commands.entity(object).try_remove::<Worn>();
+        attachment_queue
+            .pending
+            .insert(worn.attachment.scene_id, worn.attachment.clone());
+    }
+
+    for kill in ev_kill_object.read() {
+        let AttachmentQueue {
+            pending,
+            hud_frames,
+        } = &mut *attachment_queue;
+        for scene_id in &kill.value.scene_ids {
+            pending.remove(scene_id);
+            if let Some(frame) = hud_frames.remove(scene_id) {
+                commands.entity(frame).try_despawn();
+            }
+        }
+    }
+
+    let AttachmentQueue {
+        pending,
+        hud_frames,
+    } = &mut *attachment_queue;
+    pending.retain(|scene_id, attachment| {
+        let Some(object) = scene_id_map.entities.get(scene_id).copied() else {
+            return true;
+        };
+
+        if let Some(anchor) = attachment.hud_anchor {
+            // the server only sends the user's own HUDs, but they are never drawn for others
+            let own = session_data
+                .login_response
+                .as_ref()
+                .is_some_and(|login| login.agent_id == attachment.agent_id);
+            if !own {
+                commands.entity(object).insert(Visibility::Hidden);
+                return false;
+            }
+            let Ok(camera) = cameras.single() else {
+                return true;
+            };
+            let frame = commands
+                .spawn((
+                    Transform::default(),
+                    Visibility::default(),
+                    Name::new("HudFrame"),
+                    HudFrame { anchor },
+                    ChildOf(camera),
+                ))
+                .id();
+            commands.entity(frame).add_child(object);
+            hud_frames.insert(*scene_id, frame);
+            return false;
+        }
+
+        let joint = attachment.joint.map(|joint| joint.to_string());
+        let parent = agents
+            .iter()
+            .filter(|(_, agent)| agent.id == attachment.agent_id)
+            .find_map(|(agent, _)| match &joint {
+                Some(joint) => children.iter_descendants(agent).find(|entity| {
+                    names
+                        .get(*entity)
+                        .is_ok_and(|name| name.as_str() == joint.as_str())
+                }),
+                // attachments to points this viewer doesn't know follow the avatar itself
+                None => Some(agent),
+            });
+        match parent {
+            Some(parent) => {
+                commands.entity(parent).add_child(object);
+                commands.entity(object).insert((
+                    Visibility::Inherited,
+                    Worn {
+                        attachment: attachment.clone(),
+                    },
+                ));
+                false
+            }
+            None => {
+                commands.entity(object).insert(Visibility::Hidden);
+                true
+            }
+        }
+    });
+}
+
+/// Place HUD frames on their corner or edge of the screen, scaled so one unit is the height of the
+/// screen. This follows the camera's field of view and aspect ratio as the window is resized.
+pub fn place_hud_frames(
+    cameras: Query<&Projection, With<MainCamera>>,
+    mut frames: Query<(&mut Transform, &HudFrame)>,
+) {
+    let Ok(Projection::Perspective(perspective)) = cameras.single() else {
+        return;
+    };
+    let half_height = HUD_DEPTH * (perspective.fov * 0.5).tan();
+    // HUD positions are forward, left and up, where the camera looks down negative Z with Y up
+    let rotation = Quat::from_mat3(&Mat3::from_cols(Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y));
+    for (mut transform, frame) in &mut frames {
+        transform.translation = Vec3::new(
+            frame.anchor.x * half_height * perspective.aspect_ratio,
+            frame.anchor.y * half_height,
+            -HUD_DEPTH,
+        );
+        transform.rotation = rotation;
+        transform.scale = Vec3::splat(half_height * 2.0);
+    }
+}
@@ -1,5 +1,5 @@
 use benthic_protocol::messages::ui::land_update::{LandData, LandUpdate};
-use benthic_protocol::messages::ui::mesh_update::MeshType;
+use benthic_protocol::messages::ui::mesh_update::{LevelOfDetail, MeshType};
 use benthic_protocol::messages::ui::skybox_update::SkyboxUpdate;
 use benthic_protocol::messages::ui::water_update::WaterUpdate;
 use bevy::anti_alias::fxaa::Fxaa;
@@ -189,6 +189,8 @@ pub fn handle_land_update(
                         parent: None,
                         mesh_type: MeshType::Land,
                         id: None,
+                        scene_id: None,
+                        lod: LevelOfDetail::High,
                     })
                 }
                 Err(err) => error!("Failed to deserialize JSON: {}", err),
@@ -0,0 +1,142 @@
+use crate::render::{KillObjectEvent, SceneIDMap};
+use benthic_protocol::messages::ui::flexi_update::FlexiUpdate;
+use bevy::camera::visibility::NoFrustumCulling;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use metaverse_core::flexi::FlexiChain;
+
+#[derive(Message)]
+pub struct FlexiUpdateEvent {
+    pub value: FlexiUpdate,
+}
+
+/// Flexible parameters of objects that haven't been spawned yet
+#[derive(Resource, Default)]
+pub struct FlexiQueue {
+    pub pending: HashMap<u32, FlexiUpdate>,
+}
+
+/// An object that bends like hair, flags and skirts
+#[derive(Component)]
+pub struct Flexible {
+    pub chain: FlexiChain,
+}
+
+/// The vertices of a flexible object's mesh before it is bent
+#[derive(Component)]
+pub struct FlexiRestMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Option<Vec<[f32; 3]>>,
+}
+
+pub fn handle_flexi_update(
+    mut ev_kill_object: MessageReader<KillObjectEvent>,
+    mut ev_flexi_update: MessageReader<FlexiUpdateEvent>,
+    mut flexi_queue: ResMut<FlexiQueue>,
+    mut commands: Commands,
+    scene_id_map: Res<SceneIDMap>,
+    transforms: Query<&Transform>,
+) {
+    for update in ev_flexi_update.read() {
+        flexi_queue
+            .pending
+            .insert(update.value.scene_id, update.value.clone());
+    }
+
+    for kill in ev_kill_object.read() {
+        flexi_queue
+            .pending
+            .retain(|scene_id, _| !kill.value.scene_ids.contains(scene_id));
+    }
+
+    // objects are usually still loading when their flexible parameters arrive
+    flexi_queue.pending.retain(|scene_id, flexi| {
+        let Some(entity) = scene_id_map.entities.get(scene_id) else {
+            return true;
+        };
+        let Ok(transform) = transforms.get(*entity) else {
+            return true;
+        };
+        commands.entity(*entity).insert(Flexible {
+            chain: FlexiChain::new(
+                flexi.clone(),
+                transform.translation,
+                transform.rotation,
+                transform.scale,
+            ),
+        });
+        false
+    });
+}
+
+/// Bend flexible objects, and move the vertices of their meshes along with the bend.
+pub fn simulate_flexi(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut objects: Query<(Entity, &Transform, &mut Flexible)>,
+    children: Query<&Children>,
+    mut mesh_entities: Query<(&mut Mesh3d, Option<&FlexiRestMesh>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (entity, transform, mut flexible) in &mut objects {
+        // the region's wind isn't received yet, so objects only bend from gravity, their own force
+        // and their own motion
+        flexible.chain.step(
+            time.delta_secs(),
+            transform.translation,
+            transform.rotation,
+            transform.scale,
+            Vec3::ZERO,
+        );
+
+        for descendant in children.iter_descendants(entity) {
+            let Ok((mut mesh_3d, rest)) = mesh_entities.get_mut(descendant) else {
+                continue;
+            };
+            let Some(rest) = rest else {
+                // meshes loaded from the same file are shared, so each object bends its own copy
+                let Some(mesh) = meshes.get(&mesh_3d.0) else {
+                    continue;
+                };
+                let Some(positions) = mesh
+                    .attribute(Mesh::ATTRIBUTE_POSITION)
+                    .and_then(|positions| positions.as_float3())
+                else {
+                    continue;
+                };
+                let rest = FlexiRestMesh {
+                    positions: positions.to_vec(),
+                    normals: mesh
+                        .attribute(Mesh::ATTRIBUTE_NORMAL)
+                        .and_then(|normals| normals.as_float3())
+                        .map(|normals| normals.to_vec()),
+                };
+                let mesh = mesh.clone();
+                mesh_3d.0 = meshes.add(mesh);
+                // the bounds of the mesh are not updated as it bends
+                commands.entity(descendant).insert((rest, NoFrustumCulling));
+                continue;
+            };
+
+            let Some(mesh) = meshes.get_mut(&mesh_3d.0) else {
+                continue;
+            };
+            let mut positions = Vec::with_capacity(rest.positions.len());
+            let mut bends = Vec::with_capacity(rest.positions.len());
+            for position in &rest.positions {
+                let (position, bend) = flexible.chain.deform(Vec3::from_array(*position));
+                positions.push(position.to_array());
+                bends.push(bend);
+            }
+            mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+            if let Some(normals) = &rest.normals {
+                let normals: Vec<[f32; 3]> = normals
+                    .iter()
+                    .zip(bends)
+                    .map(|(normal, bend)| (bend * Vec3::from_array(*normal)).to_array())
+                    .collect();
+                mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+            }
+        }
+    }
+}
@@ -1,9 +1,13 @@
 pub mod animation;
+pub mod attachments;
 pub mod chat;
 pub mod environment;
 pub mod errors;
+pub mod flexi;
+pub mod lights;
 pub mod loading;
 pub mod login;
+pub mod network;
 pub mod plugin;
 pub mod render;
 pub mod subscriber;
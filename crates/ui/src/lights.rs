@@ -0,0 +1,96 @@
+use crate::render::{KillObjectEvent, SceneIDMap};
+use benthic_protocol::messages::ui::light_update::LightUpdate;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use std::f32::consts::FRAC_PI_2;
+
+/// How bright a light at full intensity is, in lumens. This is about a dozen household bulbs,
+/// which is enough to light up a room at night without washing out the day.
+const FULL_INTENSITY_LUMENS: f32 = 20_000.0;
+
+#[derive(Message)]
+pub struct LightUpdateEvent {
+    pub value: LightUpdate,
+}
+
+/// Lights of objects that haven't been spawned yet
+#[derive(Resource, Default)]
+pub struct LightQueue {
+    pub pending: HashMap<u32, LightUpdate>,
+}
+
+/// The light entity spawned for an object that is a light
+#[derive(Component)]
+pub struct ObjectLight {
+    pub entity: Entity,
+}
+
+/// Spawn the lights of objects as children of their objects, so they move with them.
+///
+/// Lights without a projector are point lights. Projectors are spot lights shining along the
+/// object's negative Z axis, which is also the direction Bevy's spot lights face.
+pub fn handle_light_update(
+    mut ev_kill_object: MessageReader<KillObjectEvent>,
+    mut ev_light_update: MessageReader<LightUpdateEvent>,
+    mut light_queue: ResMut<LightQueue>,
+    mut commands: Commands,
+    scene_id_map: Res<SceneIDMap>,
+    object_lights: Query<&ObjectLight>,
+) {
+    for update in ev_light_update.read() {
+        light_queue
+            .pending
+            .insert(update.value.scene_id, update.value.clone());
+    }
+
+    for kill in ev_kill_object.read() {
+        light_queue
+            .pending
+            .retain(|scene_id, _| !kill.value.scene_ids.contains(scene_id));
+    }
+
+    // objects are usually still loading when their lights arrive
+    light_queue.pending.retain(|scene_id, light| {
+        let Some(object) = scene_id_map.entities.get(scene_id) else {
+            return true;
+        };
+        if let Ok(previous) = object_lights.get(*object) {
+            commands.entity(previous.entity).despawn();
+        }
+
+        let [r, g, b] = light.color;
+        let color = Color::srgb(r, g, b);
+        let intensity = light.intensity * FULL_INTENSITY_LUMENS;
+        // Bevy lights always fall off with the square of the distance, so the falloff of the
+        // light is not used
+        let entity = match &light.projector {
+            Some(projector) => {
+                let outer_angle = (projector.fov * 0.5).clamp(0.0, FRAC_PI_2);
+                commands
+                    .spawn(SpotLight {
+                        color,
+                        intensity,
+                        range: light.radius,
+                        outer_angle,
+                        inner_angle: outer_angle * 0.5,
+                        shadows_enabled: true,
+                        ..default()
+                    })
+                    .id()
+            }
+            None => commands
+                .spawn(PointLight {
+                    color,
+                    intensity,
+                    range: light.radius,
+                    ..default()
+                })
+                .id(),
+        };
+        commands
+            .entity(*object)
+            .add_child(entity)
+            .insert(ObjectLight { entity });
+        false
+    });
+}
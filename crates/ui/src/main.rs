@@ -1,6 +1,7 @@
 use benthic_ui::chat::chat_screen;
 use benthic_ui::loading::loading_screen;
 use benthic_ui::login::login_screen;
+use benthic_ui::network::network_screen;
 use benthic_ui::plugin::MetaversePlugin;
 use benthic_ui::plugin::ViewerState;
 use bevy::app::TerminalCtrlCHandlerPlugin;
@@ -42,5 +43,9 @@ fn main() {
             EguiPrimaryContextPass,
             chat_screen.run_if(in_state(ViewerState::Chat)),
         )
+        .add_systems(
+            EguiPrimaryContextPass,
+            network_screen.run_if(in_state(ViewerState::Chat)),
+        )
         .run();
 }
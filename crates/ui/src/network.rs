@@ -0,0 +1,29 @@
+use crate::plugin::CircuitLatency;
+use bevy::ecs::error::Result;
+use bevy::ecs::system::Res;
+use bevy_egui::{egui, EguiContexts};
+
+pub fn network_screen(mut contexts: EguiContexts, circuit_latency: Res<CircuitLatency>) -> Result {
+    let ctx = contexts.ctx_mut()?;
+
+    egui::Window::new("Network")
+        .default_open(false)
+        .resizable(false)
+        .collapsible(true)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "Round trip time: {} ms",
+                circuit_latency.round_trip_time.as_millis()
+            ));
+            for (circuit, stats) in circuit_latency.circuits.iter() {
+                ui.separator();
+                ui.label(circuit);
+                ui.label(format!("Received: {}", stats.received));
+                ui.label(format!("Dropped: {}", stats.dropped));
+                ui.label(format!("Duplicated: {}", stats.duplicated));
+                ui.label(format!("Reordered: {}", stats.reordered));
+                ui.label(format!("Resets: {}", stats.resets));
+            }
+        });
+    Ok(())
+}
@@ -1,12 +1,19 @@
 use crate::animation::{scene_instance_ready, update_animations, AnimationPath, AnimationQueue};
+use crate::attachments::{
+    handle_attachment_update, place_hud_frames, AttachmentQueue, AttachmentUpdateEvent,
+};
 use crate::environment::{
     handle_land_update, handle_skybox_update, handle_water_update, setup_environment, update_sun,
     LandUpdateEvent, SkyboxUpdateEvent, SunState, Water, WaterUpdateEvent,
 };
 use crate::errors::{NotLoggedIn, PacketSendError, PortError, ShareDirError};
+use crate::flexi::{handle_flexi_update, simulate_flexi, FlexiQueue, FlexiUpdateEvent};
+use crate::lights::{handle_light_update, LightQueue, LightUpdateEvent};
 use crate::render::{
-    extract_gltf_meshes, follow_gltf_with_offset, handle_camera_update, handle_mesh_update,
-    AgentIDMap, MeshQueue, MeshUpdateEvent, SceneIDMap,
+    extract_gltf_meshes, follow_gltf_with_offset, handle_camera_update, handle_kill_object,
+    handle_mesh_update, handle_transform_update, interpolate_motion, replace_avatar_models,
+    update_link_frames, update_mesh_lods, AgentIDMap, KillObjectEvent, MeshQueue,
+    MeshUpdateEvent, SceneIDMap, TransformUpdateEvent,
 };
 use crate::subscriber::listen_for_core_events;
 use crate::textures::environment::HeightMaterial;
@@ -14,6 +21,7 @@ use crate::{chat, login};
 use actix_rt::System;
 use benthic_protocol::messages::ui::agent_update::AgentUpdate;
 use benthic_protocol::messages::ui::camera_position::CameraPosition;
+use benthic_protocol::messages::ui::circuit_stats_update::CircuitStatsUpdate;
 use benthic_protocol::messages::ui::coarse_location_update::CoarseLocationUpdate;
 use benthic_protocol::messages::ui::errors::SessionError;
 use benthic_protocol::messages::ui::login_error::LoginError;
@@ -21,6 +29,7 @@ use benthic_protocol::messages::ui::login_response::LoginResponse;
 use benthic_protocol::messages::ui::play_animation::PlayAnimation;
 use benthic_protocol::messages::ui::ui_messages::{UIMessage, UIResponse};
 use bevy::app::App;
+use bevy::ecs::system::SystemParam;
 use bevy::mesh::skinning::SkinnedMesh;
 use bevy::pbr::{DefaultOpaqueRendererMethod, ExtendedMaterial};
 use bevy::platform::collections::HashMap;
@@ -35,6 +44,7 @@ use portpicker::pick_unused_port;
 use std::fs::create_dir_all;
 use std::net::UdpSocket;
 use std::path::PathBuf;
+use std::time::Duration;
 
 pub const VIEWER_NAME: &str = "benthic";
 
@@ -69,6 +79,13 @@ pub struct ChatMessages {
     pub messages: Vec<ChatFromClientMessage>,
 }
 
+#[derive(Resource, Default)]
+pub struct CircuitLatency {
+    pub round_trip_time: Duration,
+    /// the most recent stats of each circuit, by the simulator's address
+    pub circuits: HashMap<String, CircuitStatsUpdate>,
+}
+
 #[derive(Resource)]
 pub struct ShareDir {
     pub _path: PathBuf,
@@ -179,20 +196,34 @@ impl Plugin for MetaversePlugin {
             })
             .insert_resource(SceneIDMap {
                 entities: HashMap::new(),
+                link_frames: HashMap::new(),
             })
             .insert_resource(AnimationQueue {
                 pending: HashMap::new(),
+                playing: HashMap::new(),
             })
             .insert_resource(SunState {
                 current_phase: 0.0,
                 target_phase: 0.0,
             })
             .insert_resource(Assets::<ExtendedMaterial<StandardMaterial, Water>>::default())
-            .insert_resource(MeshQueue { pending: vec![] })
+            .insert_resource(MeshQueue {
+                pending: vec![],
+                waiting: HashMap::new(),
+            })
+            .insert_resource(FlexiQueue::default())
+            .insert_resource(LightQueue::default())
+            .insert_resource(AttachmentQueue::default())
+            .insert_resource(CircuitLatency::default())
             .add_message::<LoginResponseEvent>()
             .add_message::<CameraUpdateEvent>()
             .add_message::<CoarseLocationUpdateEvent>()
             .add_message::<MeshUpdateEvent>()
+            .add_message::<KillObjectEvent>()
+            .add_message::<TransformUpdateEvent>()
+            .add_message::<FlexiUpdateEvent>()
+            .add_message::<LightUpdateEvent>()
+            .add_message::<AttachmentUpdateEvent>()
             .add_message::<LandUpdateEvent>()
             .add_message::<WaterUpdateEvent>()
             .add_message::<SkyboxUpdateEvent>()
@@ -218,12 +249,23 @@ impl Plugin for MetaversePlugin {
             .add_systems(Startup, setup_environment)
             .add_systems(Startup, start_core)
             .add_systems(Update, extract_gltf_meshes)
+            .add_systems(Update, replace_avatar_models)
             .add_systems(Update, handle_window_close)
             .add_systems(Update, handle_logout)
             .add_systems(Update, handle_queue)
             .add_systems(Update, handle_login_response)
             .add_systems(Update, handle_disconnect)
             .add_systems(Update, handle_mesh_update)
+            .add_systems(Update, handle_kill_object)
+            .add_systems(Update, handle_transform_update)
+            .add_systems(Update, interpolate_motion)
+            .add_systems(Update, update_mesh_lods)
+            .add_systems(Update, update_link_frames)
+            .add_systems(Update, handle_flexi_update)
+            .add_systems(Update, simulate_flexi.after(interpolate_motion))
+            .add_systems(Update, handle_light_update)
+            .add_systems(Update, handle_attachment_update)
+            .add_systems(Update, place_hud_frames)
             .add_systems(Update, handle_land_update)
             .add_systems(Update, handle_water_update)
             .add_systems(Update, handle_skybox_update)
@@ -293,6 +335,17 @@ fn handle_disconnect(
     }
 }
 
+/// Writers for the object events, grouped to keep handle_queue under bevy's system parameter
+/// limit
+#[derive(SystemParam)]
+struct ObjectWriters<'w> {
+    kill_object: MessageWriter<'w, KillObjectEvent>,
+    transform_update: MessageWriter<'w, TransformUpdateEvent>,
+    flexi_update: MessageWriter<'w, FlexiUpdateEvent>,
+    light_update: MessageWriter<'w, LightUpdateEvent>,
+    attachment_update: MessageWriter<'w, AttachmentUpdateEvent>,
+}
+
 // Handle all of the core events that are received from the listener.
 #[allow(clippy::all)]
 fn handle_queue(
@@ -301,12 +354,14 @@ fn handle_queue(
     mut ev_coarselocationupdate: MessageWriter<CoarseLocationUpdateEvent>,
     mut ev_disable_simulator: MessageWriter<DisableSimulatorEvent>,
     mut ev_mesh_update: MessageWriter<MeshUpdateEvent>,
+    mut object_writers: ObjectWriters,
     mut ev_land_update: MessageWriter<LandUpdateEvent>,
     mut ev_camera_update: MessageWriter<CameraUpdateEvent>,
     mut ev_water_update: MessageWriter<WaterUpdateEvent>,
     mut ev_skybox_update: MessageWriter<SkyboxUpdateEvent>,
     mut chat_messages: ResMut<ChatMessages>,
     mut animation_queue: ResMut<AnimationQueue>,
+    mut circuit_latency: ResMut<CircuitLatency>,
     asset_server: Res<AssetServer>,
 ) {
     // Check for events in the channel
@@ -324,6 +379,29 @@ fn handle_queue(
             UIMessage::MeshUpdate(mesh_update) => {
                 ev_mesh_update.write(MeshUpdateEvent { value: mesh_update });
             }
+            UIMessage::KillObject(kill_object) => {
+                object_writers.kill_object.write(KillObjectEvent { value: kill_object });
+            }
+            UIMessage::TransformUpdate(transform_update) => {
+                object_writers.transform_update.write(TransformUpdateEvent {
+                    value: transform_update,
+                });
+            }
+            UIMessage::FlexiUpdate(flexi_update) => {
+                object_writers.flexi_update.write(FlexiUpdateEvent {
+                    value: flexi_update,
+                });
+            }
+            UIMessage::LightUpdate(light_update) => {
+                object_writers.light_update.write(LightUpdateEvent {
+                    value: light_update,
+                });
+            }
+            UIMessage::AttachmentUpdate(attachment_update) => {
+                object_writers.attachment_update.write(AttachmentUpdateEvent {
+                    value: attachment_update,
+                });
+            }
             UIMessage::PlayAnimation(play_animation) => {
                 let gltf_handle: Handle<Gltf> =
                     asset_server.load(play_animation.animation_path.clone());
@@ -349,6 +427,16 @@ fn handle_queue(
             UIMessage::DisableSimulator(_) => {
                 ev_disable_simulator.write(DisableSimulatorEvent {});
             }
+            UIMessage::LatencyUpdate(data) => {
+                circuit_latency.round_trip_time = data.round_trip_time;
+            }
+            UIMessage::CircuitStatsUpdate(data) => {
+                circuit_latency.circuits.insert(data.circuit.clone(), data);
+            }
+            UIMessage::CircuitDead(_) => {
+                warn!("Lost connection to the simulator");
+                ev_disable_simulator.write(DisableSimulatorEvent {});
+            }
             UIMessage::CameraPosition(data) => {
                 ev_camera_update.write(CameraUpdateEvent { value: data });
             }
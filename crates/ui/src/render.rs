@@ -1,21 +1,34 @@
+use crate::attachments::Worn;
 use crate::plugin::{CameraUpdateEvent, SessionData};
 use crate::textures::environment::HeightMaterial;
+use benthic_protocol::messages::ui::kill_object::KillObject;
 use benthic_protocol::messages::ui::land_update::LandUpdate;
-use benthic_protocol::messages::ui::mesh_update::{MeshType, MeshUpdate};
+use benthic_protocol::messages::ui::mesh_update::{LevelOfDetail, MeshType, MeshUpdate};
+use benthic_protocol::messages::ui::transform_update::TransformUpdate;
 use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
+use bevy::scene::InstanceId;
 use bevy_gltf::{Gltf, GltfLoaderSettings};
 use bevy_panorbit_camera::PanOrbitCamera;
+use metaverse_core::lod::{closest_available, select_level_of_detail, DEFAULT_LOD_FACTOR};
+use metaverse_core::motion::{MotionSmoother, MotionState};
 
 use std::path::PathBuf;
+use std::time::Duration;
 use uuid::Uuid;
 
+/// How long a child object waits for its parent to spawn before it is spawned on its own
+const PARENT_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Component)]
 pub struct WaterPlane;
 
 #[derive(Resource)]
 pub struct SceneIDMap {
     pub entities: HashMap<u32, Entity>,
+    /// The entities the children of each object are parented to. These undo the scale of the
+    /// object, because children don't inherit the scale of their parent.
+    pub link_frames: HashMap<u32, Entity>,
 }
 
 #[derive(Resource)]
@@ -39,6 +52,16 @@ pub struct LandUpdateEvent {
     pub value: LandUpdate,
 }
 
+#[derive(Message)]
+pub struct KillObjectEvent {
+    pub value: KillObject,
+}
+
+#[derive(Message)]
+pub struct TransformUpdateEvent {
+    pub value: TransformUpdate,
+}
+
 pub enum RenderableHandle {
     Gltf(Handle<Gltf>),
     Mesh(Handle<Mesh>),
@@ -51,13 +74,21 @@ pub struct Renderable {
     pub parent: Option<u32>,
     pub mesh_type: MeshType,
     pub id: Option<Uuid>,
+    pub scene_id: Option<u32>,
+    pub lod: LevelOfDetail,
 }
 
 #[derive(Resource)]
 pub struct MeshQueue {
     pub pending: Vec<Renderable>,
+    /// When each child object started waiting for its parent to spawn
+    pub waiting: HashMap<u32, Duration>,
 }
 
+/// The entity the children of an object are parented to, which undoes the scale of the object
+#[derive(Component)]
+pub struct LinkFrame;
+
 #[derive(Component, Debug)]
 pub struct AgentID {
     pub id: Uuid,
@@ -66,6 +97,31 @@ pub struct AgentID {
 #[derive(Component)]
 pub struct MainCamera;
 
+/// The levels of detail of an object that have finished loading, and the one that is spawned
+/// under it
+#[derive(Component, Default)]
+pub struct MeshLods {
+    pub loaded: HashMap<LevelOfDetail, Handle<Gltf>>,
+    pub shown: Option<(Handle<Gltf>, InstanceId)>,
+}
+
+/// Smooths the movement of an object between transform updates from the server
+#[derive(Component)]
+pub struct Motion {
+    pub smoother: MotionSmoother,
+}
+
+impl Motion {
+    fn at_rest(transform: &Transform) -> Self {
+        Motion {
+            smoother: MotionSmoother::new(MotionState::at_rest(
+                transform.translation,
+                transform.rotation,
+            )),
+        }
+    }
+}
+
 pub fn handle_camera_update(
     mut ev_camera_update: MessageReader<CameraUpdateEvent>,
     mut query: Query<&mut PanOrbitCamera, With<MainCamera>>,
@@ -118,14 +174,17 @@ pub fn handle_mesh_update(
         if renderable.value.mesh_type == MeshType::Avatar {
             let agent_root = commands.spawn((Name::new("AgentRoot"), transform)).id();
 
-            agent_id_map.entities.insert(
+            // avatars are rebuilt when their outfit changes
+            if let Some(previous) = agent_id_map.entities.insert(
                 renderable.value.id.unwrap(),
                 AgentEntity {
                     entity: agent_root,
                     skeleton: agent_root,
                     animation: None,
                 },
-            );
+            ) {
+                commands.entity(previous.entity).try_despawn();
+            }
         }
 
         mesh_queue.pending.push(Renderable {
@@ -134,38 +193,127 @@ pub fn handle_mesh_update(
             parent: renderable.value.parent,
             mesh_type: renderable.value.mesh_type.clone(),
             id: renderable.value.id,
+            scene_id: renderable.value.scene_id,
+            lod: renderable.value.lod,
         });
     }
 }
 
 pub fn extract_gltf_meshes(
     mut commands: Commands,
+    time: Res<Time>,
     mut queue: ResMut<MeshQueue>,
     gltfs: Res<Assets<Gltf>>,
     mut scene_spawner: ResMut<SceneSpawner>,
     mut standard_materials: ResMut<Assets<StandardMaterial>>,
     _height_materials: ResMut<Assets<HeightMaterial>>,
     _asset_server: Res<AssetServer>,
+    mut scene_id_map: ResMut<SceneIDMap>,
 ) {
     let mut ready = vec![];
+    let queue = &mut *queue;
 
     for (i, item) in queue.pending.iter().enumerate() {
         match &item.handle {
             RenderableHandle::Gltf(gltf_handle) => {
                 let Some(gltf) = gltfs.get(gltf_handle) else { continue };
+
+                // every level of detail of an object shares one root, and update_mesh_lods
+                // chooses which of them is spawned under it
+                if item.id.is_none()
+                    && let Some(scene_id) = item.scene_id
+                {
+                    // children are positioned relative to their parent, so they wait for it. A
+                    // parent that never spawns, such as one that failed to generate, would hold
+                    // them forever, so they are spawned on their own after a while.
+                    let link_frame = match item.parent {
+                        Some(parent) => match scene_id_map.link_frames.get(&parent) {
+                            Some(link_frame) => Some(*link_frame),
+                            None => {
+                                let since =
+                                    *queue.waiting.entry(scene_id).or_insert(time.elapsed());
+                                if time.elapsed() - since < PARENT_TIMEOUT {
+                                    continue;
+                                }
+                                warn!(
+                                    "Parent {} of {} never spawned. Spawning it on its own.",
+                                    parent, scene_id
+                                );
+                                None
+                            }
+                        },
+                        None => None,
+                    };
+                    queue.waiting.remove(&scene_id);
+                    let (lod, handle) = (item.lod, gltf_handle.clone());
+                    match scene_id_map.entities.get(&scene_id) {
+                        Some(scene_root) => {
+                            // regenerated meshes carry the object's new scale, which its link
+                            // frame follows
+                            let scale = item.transform.scale;
+                            commands
+                                .entity(*scene_root)
+                                .entry::<MeshLods>()
+                                .or_default()
+                                .and_modify(move |mut lods| {
+                                    lods.loaded.insert(lod, handle);
+                                });
+                            commands
+                                .entity(*scene_root)
+                                .entry::<Transform>()
+                                .and_modify(move |mut transform| transform.scale = scale);
+                        }
+                        None => {
+                            let mut lods = MeshLods::default();
+                            lods.loaded.insert(lod, handle);
+                            let scene_root = commands
+                                .spawn((
+                                    item.transform,
+                                    Name::new("SceneRoot"),
+                                    Motion::at_rest(&item.transform),
+                                    lods,
+                                ))
+                                .id();
+                            if let Some(link_frame) = link_frame {
+                                commands.entity(link_frame).add_child(scene_root);
+                            }
+                            let link_frame = commands
+                                .spawn((
+                                    Transform::from_scale(item.transform.scale.recip()),
+                                    Name::new("LinkFrame"),
+                                    LinkFrame,
+                                    ChildOf(scene_root),
+                                ))
+                                .id();
+                            scene_id_map.entities.insert(scene_id, scene_root);
+                            scene_id_map.link_frames.insert(scene_id, link_frame);
+                        }
+                    }
+                    ready.push(i);
+                    continue;
+                }
+
                 let scene_root = if let Some(agent_id) = item.id {
                     commands
                         .spawn((
                             item.transform,
                             Name::new("SceneRoot"),
                             AgentID { id: agent_id },
+                            Motion::at_rest(&item.transform),
                         ))
                         .id()
                 } else {
                     commands
-                        .spawn((item.transform, Name::new("SceneRoot")))
+                        .spawn((
+                            item.transform,
+                            Name::new("SceneRoot"),
+                            Motion::at_rest(&item.transform),
+                        ))
                         .id()
                 };
+                if let Some(scene_id) = item.scene_id {
+                    scene_id_map.entities.insert(scene_id, scene_root);
+                }
                 let instance = scene_spawner.spawn_as_child(gltf.scenes[0].clone(), scene_root);
 
                 for entity in scene_spawner.iter_instance_entities(instance) {
@@ -208,11 +356,17 @@ pub fn extract_gltf_meshes(
                         };
                         let mat_handle = standard_materials.add(standard_mat);
 
-                        commands.spawn((
-                            Mesh3d(mesh_handle.clone()),
-                            item.transform,
-                            MeshMaterial3d::from(mat_handle),
-                        ));
+                        let entity = commands
+                            .spawn((
+                                Mesh3d(mesh_handle.clone()),
+                                item.transform,
+                                MeshMaterial3d::from(mat_handle),
+                                Motion::at_rest(&item.transform),
+                            ))
+                            .id();
+                        if let Some(scene_id) = item.scene_id {
+                            scene_id_map.entities.insert(scene_id, entity);
+                        }
                     }
                 }
 
@@ -225,3 +379,200 @@ pub fn extract_gltf_meshes(
         queue.pending.remove(i);
     }
 }
+
+/// Despawn the previous model of an avatar once its rebuilt model has been spawned. Objects worn
+/// on the previous model are detached first, so they can be attached to the new one.
+pub fn replace_avatar_models(
+    mut commands: Commands,
+    added: Query<(Entity, &AgentID), Added<AgentID>>,
+    agents: Query<(Entity, &AgentID)>,
+    children: Query<&Children>,
+    worn: Query<(), With<Worn>>,
+) {
+    for (model, agent) in &added {
+        for (previous, _) in agents
+            .iter()
+            .filter(|(entity, other)| *entity != model && other.id == agent.id)
+        {
+            for entity in children.iter_descendants(previous) {
+                if worn.contains(entity) {
+                    commands.entity(entity).try_remove::<ChildOf>();
+                }
+            }
+            commands.entity(previous).try_despawn();
+        }
+    }
+}
+
+pub fn handle_kill_object(
+    mut ev_kill_object: MessageReader<KillObjectEvent>,
+    mut commands: Commands,
+    mut mesh_queue: ResMut<MeshQueue>,
+    mut scene_id_map: ResMut<SceneIDMap>,
+    mut agent_id_map: ResMut<AgentIDMap>,
+    agents: Query<(Entity, &AgentID)>,
+) {
+    let mesh_queue = &mut *mesh_queue;
+    for kill in ev_kill_object.read() {
+        let scene_ids = &kill.value.scene_ids;
+        let agent_ids = &kill.value.agent_ids;
+
+        // objects that are still loading are dropped before they are spawned, along with the
+        // children waiting for them
+        mesh_queue.pending.retain(|item| {
+            !item.scene_id.is_some_and(|id| scene_ids.contains(&id))
+                && !item.parent.is_some_and(|id| scene_ids.contains(&id))
+                && !item.id.is_some_and(|id| agent_ids.contains(&id))
+        });
+        let pending = &mesh_queue.pending;
+        mesh_queue
+            .waiting
+            .retain(|scene_id, _| pending.iter().any(|item| item.scene_id == Some(*scene_id)));
+
+        // despawning an object despawns its children, which may also be killed
+        for scene_id in scene_ids {
+            scene_id_map.link_frames.remove(scene_id);
+            if let Some(entity) = scene_id_map.entities.remove(scene_id) {
+                commands.entity(entity).try_despawn();
+            }
+        }
+
+        for agent_id in agent_ids {
+            if let Some(agent) = agent_id_map.entities.remove(agent_id) {
+                commands.entity(agent.entity).try_despawn();
+            }
+        }
+        for (entity, agent) in &agents {
+            if agent_ids.contains(&agent.id) {
+                commands.entity(entity).try_despawn();
+            }
+        }
+    }
+}
+
+/// Keep the link frames of objects undoing their scale when it changes
+pub fn update_link_frames(
+    mut link_frames: Query<(&mut Transform, &ChildOf), With<LinkFrame>>,
+    objects: Query<Ref<Transform>, Without<LinkFrame>>,
+) {
+    for (mut transform, child_of) in &mut link_frames {
+        let Ok(object) = objects.get(child_of.parent()) else { continue };
+        if object.is_changed() {
+            transform.scale = object.scale.recip();
+        }
+    }
+}
+
+/// Spawn each object at the level of detail that suits its size on screen, switching levels as the
+/// camera moves and as more detailed levels finish loading.
+pub fn update_mesh_lods(
+    cameras: Query<&GlobalTransform, With<MainCamera>>,
+    mut objects: Query<(Entity, &GlobalTransform, &mut MeshLods)>,
+    gltfs: Res<Assets<Gltf>>,
+    mut scene_spawner: ResMut<SceneSpawner>,
+) {
+    let Ok(camera) = cameras.single() else { return };
+    for (entity, transform, mut lods) in &mut objects {
+        let (scale, _, position) = transform.to_scale_rotation_translation();
+        // the bounding sphere of the object's unit cube
+        let radius = scale.length() * 0.5;
+        let distance = camera.translation().distance(position);
+        let desired = select_level_of_detail(radius, distance, DEFAULT_LOD_FACTOR);
+        let Some(lod) = closest_available(desired, |lod| lods.loaded.contains_key(&lod)) else {
+            continue;
+        };
+
+        let handle = &lods.loaded[&lod];
+        if lods
+            .shown
+            .as_ref()
+            .is_some_and(|(shown, _)| shown == handle)
+        {
+            continue;
+        }
+        let Some(gltf) = gltfs.get(handle) else { continue };
+        let handle = handle.clone();
+        let instance = scene_spawner.spawn_as_child(gltf.scenes[0].clone(), entity);
+        if let Some((_, previous)) = lods.shown.replace((handle, instance)) {
+            scene_spawner.despawn_instance(previous);
+        }
+    }
+}
+
+/// Transform updates don't move objects directly. They replace the motion that
+/// `interpolate_motion` moves the object along, so the object glides to its new position instead
+/// of jumping to it.
+pub fn handle_transform_update(
+    mut ev_transform_update: MessageReader<TransformUpdateEvent>,
+    mut commands: Commands,
+    mut mesh_queue: ResMut<MeshQueue>,
+    scene_id_map: Res<SceneIDMap>,
+    agent_id_map: Res<AgentIDMap>,
+    agents: Query<(Entity, &AgentID)>,
+    mut transforms: Query<(&mut Transform, Option<&mut Motion>)>,
+) {
+    for update in ev_transform_update.read() {
+        let update = &update.value;
+        let state = MotionState {
+            position: update.position,
+            velocity: update.velocity,
+            acceleration: update.acceleration,
+            rotation: update.rotation,
+            angular_velocity: update.angular_velocity,
+        };
+
+        // objects that are still loading are spawned at their latest position
+        for item in mesh_queue.pending.iter_mut() {
+            let matches = match update.id {
+                Some(id) => item.id == Some(id),
+                None => item.scene_id == Some(update.scene_id),
+            };
+            if matches {
+                item.transform.translation = update.position;
+                item.transform.rotation = update.rotation;
+            }
+        }
+
+        let entities: Vec<Entity> = match update.id {
+            Some(id) => agents
+                .iter()
+                .filter(|(_, agent)| agent.id == id)
+                .map(|(entity, _)| entity)
+                .chain(agent_id_map.entities.get(&id).map(|agent| agent.entity))
+                .collect(),
+            None => scene_id_map
+                .entities
+                .get(&update.scene_id)
+                .copied()
+                .into_iter()
+                .collect(),
+        };
+        for entity in entities {
+            let Ok((mut transform, motion)) = transforms.get_mut(entity) else { continue };
+            match motion {
+                Some(mut motion) => motion.smoother.update(state),
+                None => {
+                    transform.translation = update.position;
+                    transform.rotation = update.rotation;
+                    commands.entity(entity).insert(Motion {
+                        smoother: MotionSmoother::new(state),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Move objects along their last reported motion, and ease out the difference when a new update
+/// disagrees with where the object was predicted to be.
+pub fn interpolate_motion(time: Res<Time>, mut objects: Query<(&mut Transform, &mut Motion)>) {
+    for (mut transform, mut motion) in &mut objects {
+        // skip objects at rest, so their transforms aren't marked as changed every frame
+        if motion.smoother.is_settled() {
+            continue;
+        }
+        let (position, rotation) = motion.smoother.advance(time.delta_secs());
+        transform.translation = position;
+        transform.rotation = rotation;
+    }
+}